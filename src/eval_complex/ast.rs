@@ -1,5 +1,7 @@
 use num_complex::Complex;
-use std::error;
+use std::sync::Arc;
+
+use crate::utils::{Context, EvalError, Function};
 
 #[derive(Debug, Clone, PartialEq)]
 pub enum Node {
@@ -7,11 +9,27 @@ pub enum Node {
     Subtract(Box<Node>, Box<Node>),
     Multiply(Box<Node>, Box<Node>),
     Divide(Box<Node>, Box<Node>),
+    Modulo(Box<Node>, Box<Node>),
     Root(Box<Node>, Box<Node>),
     Pow(Box<Node>, Box<Node>),
     Log(Box<Node>, Box<Node>),
+    Polar(Box<Node>, Box<Node>),
     Negative(Box<Node>),
+    Factorial(Box<Node>),
     Abs(Box<Node>),
+    Floor(Box<Node>),
+    Ceil(Box<Node>),
+    Round(Box<Node>),
+    Truncate(Box<Node>),
+    Min(Arc<Vec<Node>>),
+    Max(Arc<Vec<Node>>),
+    Avg(Arc<Vec<Node>>),
+    Med(Arc<Vec<Node>>),
+    Re(Box<Node>),
+    Im(Box<Node>),
+    Arg(Box<Node>),
+    Conj(Box<Node>),
+    Cis(Box<Node>),
     Sin(Box<Node>),
     Cos(Box<Node>),
     Tan(Box<Node>),
@@ -30,38 +48,284 @@ pub enum Node {
     Exp(Box<Node>),
     Exp2(Box<Node>),
     Number(Complex<f64>),
+    CustomFunction(Function<Complex<f64>>, Arc<Vec<Node>>),
+    Equal(Box<Node>, Box<Node>),
+    NotEqual(Box<Node>, Box<Node>),
+    Less(Box<Node>, Box<Node>),
+    LessEqual(Box<Node>, Box<Node>),
+    Greater(Box<Node>, Box<Node>),
+    GreaterEqual(Box<Node>, Box<Node>),
+    LogicalAnd(Box<Node>, Box<Node>),
+    LogicalOr(Box<Node>, Box<Node>),
+    LogicalNot(Box<Node>),
+    Conditional(Box<Node>, Box<Node>, Box<Node>),
+    Variable(String),
+    Ans,
+}
+
+/// The point of the unit circle at this angle, `e^(iθ)`.
+fn cis(angle: Complex<f64>) -> Complex<f64> {
+    (Complex::<f64>::i() * angle).exp()
+}
+
+/// Apply a rounding function to the real and imaginary parts.
+fn component_wise(value: Complex<f64>, function: fn(f64) -> f64) -> Complex<f64> {
+    Complex::new(function(value.re), function(value.im))
+}
+
+/// The value of an argument of a function only defined on real numbers.
+fn real(function: &str, value: Complex<f64>) -> Result<f64, EvalError> {
+    if value.im == 0.0 {
+        Ok(value.re)
+    } else {
+        Err(EvalError::Domain(function.to_string()))
+    }
+}
+
+fn real_arguments(
+    function: &str,
+    args: &[Node],
+    placeholder: Complex<f64>,
+    context: &dyn Context<Complex<f64>>,
+) -> Result<Vec<f64>, EvalError> {
+    let mut values = vec![];
+    for arg in args.iter() {
+        values.push(real(function, eval(arg, placeholder, context)?)?);
+    }
+    Ok(values)
+}
+
+/// The coefficients of the same approximation of the gamma function as `eval_f64`.
+const GAMMA_COEFFICIENTS: [f64; 10] = [
+    1.051_423_785_817_219_7,
+    -3.456_870_972_220_162_5,
+    4.512_277_094_668_948,
+    -2.982_852_253_235_766_4,
+    1.056_397_115_771_267,
+    -1.954_287_731_916_458_7e-1,
+    1.709_705_434_044_412e-2,
+    -5.719_261_174_043_057e-4,
+    4.633_994_733_599_057e-6,
+    -2.719_949_084_886_077_2e-9,
+];
+
+fn gamma(a: Complex<f64>) -> Complex<f64> {
+    if a.re < 0.5 {
+        // Euler's reflection formula.
+        return std::f64::consts::PI / ((std::f64::consts::PI * a).sin() * gamma(1.0 - a));
+    }
+    let mut s = Complex::new(2.485_740_891_387_535_5e-5, 0.0);
+    for (k, coefficient) in GAMMA_COEFFICIENTS.iter().enumerate() {
+        s += *coefficient / (a + k as f64);
+    }
+    s * 1.860_382_734_205_265_7 * ((a + 10.400511) / std::f64::consts::E).powc(a - 0.5)
 }
 
-pub fn eval(expr: Node) -> Result<Complex<f64>, Box<dyn error::Error>> {
+/// 1 when the condition holds and 0 otherwise.
+fn truth(condition: bool) -> Complex<f64> {
+    if condition {
+        Complex::new(1.0, 0.0)
+    } else {
+        Complex::new(0.0, 0.0)
+    }
+}
+
+pub fn eval(
+    expr: &Node,
+    placeholder: Complex<f64>,
+    context: &dyn Context<Complex<f64>>,
+) -> Result<Complex<f64>, EvalError> {
     use self::Node::*;
     match expr {
-        Number(i) => Ok(i),
-        Add(expr1, expr2) => Ok(eval(*expr1)? + eval(*expr2)?),
-        Subtract(expr1, expr2) => Ok(eval(*expr1)? - eval(*expr2)?),
-        Multiply(expr1, expr2) => Ok(eval(*expr1)? * eval(*expr2)?),
-        Divide(expr1, expr2) => Ok(eval(*expr1)? / eval(*expr2)?),
-        Negative(expr1) => Ok(-(eval(*expr1)?)),
-        Pow(expr1, expr2) => Ok(eval(*expr1)?.powc(eval(*expr2)?)),
-        Root(n_th_expr, x_expr) => Ok(eval(*x_expr)?.powc(1.0 / eval(*n_th_expr)?)),
-        Abs(sub_expr) => Ok(Complex::new(eval(*sub_expr)?.norm(), 0.0)),
-        Sin(sub_expr) => Ok(eval(*sub_expr)?.sin()),
-        Cos(sub_expr) => Ok(eval(*sub_expr)?.cos()),
-        Tan(sub_expr) => Ok(eval(*sub_expr)?.tan()),
-        Sinh(sub_expr) => Ok(eval(*sub_expr)?.sinh()),
-        Cosh(sub_expr) => Ok(eval(*sub_expr)?.cosh()),
-        Tanh(sub_expr) => Ok(eval(*sub_expr)?.tanh()),
-        Asin(sub_expr) => Ok(eval(*sub_expr)?.asin()),
-        Acos(sub_expr) => Ok(eval(*sub_expr)?.acos()),
-        Atan(sub_expr) => Ok(eval(*sub_expr)?.atan()),
-        Arsinh(sub_expr) => Ok(eval(*sub_expr)?.asinh()),
-        Arcosh(sub_expr) => Ok(eval(*sub_expr)?.acosh()),
-        Artanh(sub_expr) => Ok(eval(*sub_expr)?.atanh()),
-        Sqrt(sub_expr) => Ok(eval(*sub_expr)?.sqrt()),
-        Ln(sub_expr) => Ok(eval(*sub_expr)?.ln()),
-        Lb(sub_expr) => Ok(eval(*sub_expr)?.log(2.0)),
-        Exp(sub_expr) => Ok(eval(*sub_expr)?.exp()),
-        Exp2(sub_expr) => Ok(eval(*sub_expr)?.exp2()),
-        Log(expr1, expr2) => Ok(eval(*expr1)?.ln() / eval(*expr2)?.ln()),
+        Number(i) => Ok(*i),
+        Ans => Ok(placeholder),
+        CustomFunction(function, args) => {
+            let mut values = vec![];
+            for arg in args.iter() {
+                values.push(eval(arg, placeholder, context)?);
+            }
+            function.call(&values)
+        }
+        Variable(name) => match context.get(name) {
+            Some(value) => Ok(value),
+            None => Err(EvalError::UndefinedVariable(name.clone())),
+        },
+        Add(expr1, expr2) => {
+            Ok(eval(expr1, placeholder, context)? + eval(expr2, placeholder, context)?)
+        }
+        Subtract(expr1, expr2) => {
+            Ok(eval(expr1, placeholder, context)? - eval(expr2, placeholder, context)?)
+        }
+        Multiply(expr1, expr2) => {
+            Ok(eval(expr1, placeholder, context)? * eval(expr2, placeholder, context)?)
+        }
+        Divide(expr1, expr2) => {
+            let dividend = eval(expr1, placeholder, context)?;
+            let divisor = eval(expr2, placeholder, context)?;
+            if divisor == Complex::new(0.0, 0.0) {
+                return Err(EvalError::DivisionByZero);
+            }
+            Ok(dividend / divisor)
+        }
+        Modulo(expr1, expr2) => {
+            let dividend = eval(expr1, placeholder, context)?;
+            let divisor = eval(expr2, placeholder, context)?;
+            if divisor == Complex::new(0.0, 0.0) {
+                return Err(EvalError::DivisionByZero);
+            }
+            // The remainder of the quotient truncated toward zero, as `%` is for real numbers.
+            Ok(dividend - divisor * component_wise(dividend / divisor, f64::trunc))
+        }
+        Equal(expr1, expr2) => Ok(truth(
+            eval(expr1, placeholder, context)? == eval(expr2, placeholder, context)?,
+        )),
+        NotEqual(expr1, expr2) => Ok(truth(
+            eval(expr1, placeholder, context)? != eval(expr2, placeholder, context)?,
+        )),
+        Less(expr1, expr2) => Ok(truth(
+            real("<", eval(expr1, placeholder, context)?)?
+                < real("<", eval(expr2, placeholder, context)?)?,
+        )),
+        LessEqual(expr1, expr2) => Ok(truth(
+            real("<=", eval(expr1, placeholder, context)?)?
+                <= real("<=", eval(expr2, placeholder, context)?)?,
+        )),
+        Greater(expr1, expr2) => Ok(truth(
+            real(">", eval(expr1, placeholder, context)?)?
+                > real(">", eval(expr2, placeholder, context)?)?,
+        )),
+        GreaterEqual(expr1, expr2) => Ok(truth(
+            real(">=", eval(expr1, placeholder, context)?)?
+                >= real(">=", eval(expr2, placeholder, context)?)?,
+        )),
+        LogicalAnd(expr1, expr2) => Ok(truth(
+            eval(expr1, placeholder, context)? != Complex::new(0.0, 0.0)
+                && eval(expr2, placeholder, context)? != Complex::new(0.0, 0.0),
+        )),
+        LogicalOr(expr1, expr2) => Ok(truth(
+            eval(expr1, placeholder, context)? != Complex::new(0.0, 0.0)
+                || eval(expr2, placeholder, context)? != Complex::new(0.0, 0.0),
+        )),
+        LogicalNot(expr1) => Ok(truth(
+            eval(expr1, placeholder, context)? == Complex::new(0.0, 0.0),
+        )),
+        Conditional(condition, expr1, expr2) => {
+            if eval(condition, placeholder, context)? != Complex::new(0.0, 0.0) {
+                eval(expr1, placeholder, context)
+            } else {
+                eval(expr2, placeholder, context)
+            }
+        }
+        Negative(expr1) => Ok(-(eval(expr1, placeholder, context)?)),
+        Factorial(sub_expr) => {
+            let sub_result = eval(sub_expr, placeholder, context)?;
+            if sub_result.im != 0.0 || sub_result.re.fract() != 0.0 {
+                Ok(gamma(sub_result + 1.0))
+            } else if sub_result.re < 0.0 {
+                Err(EvalError::NegativeFactorial)
+            } else {
+                let mut factorial_result = 1.0;
+                for i in 2..=(sub_result.re as usize) {
+                    factorial_result *= i as f64;
+                    if factorial_result.is_infinite() {
+                        break;
+                    }
+                }
+                Ok(Complex::new(factorial_result, 0.0))
+            }
+        }
+        Floor(sub_expr) => Ok(component_wise(
+            eval(sub_expr, placeholder, context)?,
+            f64::floor,
+        )),
+        Ceil(sub_expr) => Ok(component_wise(
+            eval(sub_expr, placeholder, context)?,
+            f64::ceil,
+        )),
+        Round(sub_expr) => Ok(component_wise(
+            eval(sub_expr, placeholder, context)?,
+            f64::round,
+        )),
+        Truncate(sub_expr) => Ok(component_wise(
+            eval(sub_expr, placeholder, context)?,
+            f64::trunc,
+        )),
+        Min(args) => {
+            let values = real_arguments("min", args, placeholder, context)?;
+            Ok(Complex::new(
+                values.into_iter().fold(f64::INFINITY, f64::min),
+                0.0,
+            ))
+        }
+        Max(args) => {
+            let values = real_arguments("max", args, placeholder, context)?;
+            Ok(Complex::new(
+                values.into_iter().fold(f64::NEG_INFINITY, f64::max),
+                0.0,
+            ))
+        }
+        Avg(args) => {
+            let mut result = Complex::new(0.0, 0.0);
+            for arg in args.iter() {
+                result += eval(arg, placeholder, context)?;
+            }
+            Ok(result / args.len() as f64)
+        }
+        Med(args) => {
+            let mut results = real_arguments("median", args, placeholder, context)?;
+            results.sort_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
+            let len = results.len();
+            if len % 2 == 0 {
+                Ok(Complex::new(
+                    (results[len >> 1] + results[(len >> 1) - 1]) / 2.0,
+                    0.0,
+                ))
+            } else {
+                Ok(Complex::new(results[len >> 1], 0.0))
+            }
+        }
+        Pow(expr1, expr2) => {
+            Ok(eval(expr1, placeholder, context)?.powc(eval(expr2, placeholder, context)?))
+        }
+        Root(n_th_expr, x_expr) => {
+            Ok(eval(x_expr, placeholder, context)?
+                .powc(1.0 / eval(n_th_expr, placeholder, context)?))
+        }
+        Abs(sub_expr) => Ok(Complex::new(
+            eval(sub_expr, placeholder, context)?.norm(),
+            0.0,
+        )),
+        Re(sub_expr) => Ok(Complex::new(eval(sub_expr, placeholder, context)?.re, 0.0)),
+        Im(sub_expr) => Ok(Complex::new(eval(sub_expr, placeholder, context)?.im, 0.0)),
+        Arg(sub_expr) => Ok(Complex::new(
+            eval(sub_expr, placeholder, context)?.arg(),
+            0.0,
+        )),
+        Conj(sub_expr) => Ok(eval(sub_expr, placeholder, context)?.conj()),
+        Cis(sub_expr) => Ok(cis(eval(sub_expr, placeholder, context)?)),
+        Polar(r_expr, angle_expr) => {
+            Ok(eval(r_expr, placeholder, context)? * cis(eval(angle_expr, placeholder, context)?))
+        }
+        Sin(sub_expr) => Ok(eval(sub_expr, placeholder, context)?.sin()),
+        Cos(sub_expr) => Ok(eval(sub_expr, placeholder, context)?.cos()),
+        Tan(sub_expr) => Ok(eval(sub_expr, placeholder, context)?.tan()),
+        Sinh(sub_expr) => Ok(eval(sub_expr, placeholder, context)?.sinh()),
+        Cosh(sub_expr) => Ok(eval(sub_expr, placeholder, context)?.cosh()),
+        Tanh(sub_expr) => Ok(eval(sub_expr, placeholder, context)?.tanh()),
+        Asin(sub_expr) => Ok(eval(sub_expr, placeholder, context)?.asin()),
+        Acos(sub_expr) => Ok(eval(sub_expr, placeholder, context)?.acos()),
+        Atan(sub_expr) => Ok(eval(sub_expr, placeholder, context)?.atan()),
+        Arsinh(sub_expr) => Ok(eval(sub_expr, placeholder, context)?.asinh()),
+        Arcosh(sub_expr) => Ok(eval(sub_expr, placeholder, context)?.acosh()),
+        Artanh(sub_expr) => Ok(eval(sub_expr, placeholder, context)?.atanh()),
+        Sqrt(sub_expr) => Ok(eval(sub_expr, placeholder, context)?.sqrt()),
+        Ln(sub_expr) => Ok(eval(sub_expr, placeholder, context)?.ln()),
+        Lb(sub_expr) => Ok(eval(sub_expr, placeholder, context)?.log(2.0)),
+        Exp(sub_expr) => Ok(eval(sub_expr, placeholder, context)?.exp()),
+        Exp2(sub_expr) => Ok(eval(sub_expr, placeholder, context)?.exp2()),
+        Log(expr1, expr2) => {
+            Ok(eval(expr1, placeholder, context)?.ln() / eval(expr2, placeholder, context)?.ln())
+        }
     }
 }
 
@@ -69,43 +333,188 @@ pub fn eval(expr: Node) -> Result<Complex<f64>, Box<dyn error::Error>> {
 #[cfg(test)]
 mod tests {
     use num_complex::Complex;
+    use std::collections::HashMap;
 
     use crate::eval_complex::{ast::eval, parser::Parser};
+    use crate::utils::EvalError;
 
     #[test]
     fn test_expr1() {
-        let ast = Parser::new("1+2-3", None).unwrap().parse().unwrap();
-        let value = eval(ast).unwrap();
+        let ast = Parser::new("1+2-3").unwrap().parse().unwrap();
+        let value = eval(&ast, Complex::new(0.0, 0.0), &HashMap::new()).unwrap();
         assert_eq!(value, Complex::new(0.0, 0.0));
     }
     #[test]
     fn test_expr2() {
-        let ast = Parser::new("3+2-1*5/4", None).unwrap().parse().unwrap();
-        let value = eval(ast).unwrap();
+        let ast = Parser::new("3+2-1*5/4").unwrap().parse().unwrap();
+        let value = eval(&ast, Complex::new(0.0, 0.0), &HashMap::new()).unwrap();
         assert_eq!(value, Complex::new(3.75, 0.0));
     }
     #[test]
     fn test_expr3() {
-        let ast = Parser::new("5+(2*7)*3", None).unwrap().parse().unwrap();
-        let value = eval(ast).unwrap();
+        let ast = Parser::new("5+(2*7)*3").unwrap().parse().unwrap();
+        let value = eval(&ast, Complex::new(0.0, 0.0), &HashMap::new()).unwrap();
         assert_eq!(value, Complex::new(47.0, 0.0));
     }
     #[test]
     fn test_expr4() {
-        let ast = Parser::new("3*2^3*3", None).unwrap().parse().unwrap();
-        let value = eval(ast).unwrap();
+        let ast = Parser::new("3*2^3*3").unwrap().parse().unwrap();
+        let value = eval(&ast, Complex::new(0.0, 0.0), &HashMap::new()).unwrap();
         assert_eq!(value, Complex::new(71.99999999999997, 0.0));
     }
     #[test]
     fn test_expr5() {
-        let ast = Parser::new("-i", None).unwrap().parse().unwrap();
-        let value = eval(ast).unwrap();
+        let ast = Parser::new("-i").unwrap().parse().unwrap();
+        let value = eval(&ast, Complex::new(0.0, 0.0), &HashMap::new()).unwrap();
         assert_eq!(value, Complex::new(0.0, -1.0));
     }
     #[test]
     fn test_expr6() {
-        let ast = Parser::new("i²", None).unwrap().parse().unwrap();
-        let value = eval(ast).unwrap();
+        let ast = Parser::new("i²").unwrap().parse().unwrap();
+        let value = eval(&ast, Complex::new(0.0, 0.0), &HashMap::new()).unwrap();
         assert_eq!(value, Complex::new(-1.0, 1.2246467991473532e-16));
     }
+    #[test]
+    fn test_components() {
+        let cases = [
+            ("re(3+4i)", Complex::new(3.0, 0.0)),
+            ("im(3+4i)", Complex::new(4.0, 0.0)),
+            ("arg(-i)", Complex::new(-std::f64::consts::FRAC_PI_2, 0.0)),
+            ("conj(3+4i)", Complex::new(3.0, -4.0)),
+            ("norm(3+4i)", Complex::new(5.0, 0.0)),
+            ("cis(0)", Complex::new(1.0, 0.0)),
+            ("polar(2,0)", Complex::new(2.0, 0.0)),
+        ];
+        for (formula, expected) in cases {
+            let ast = Parser::new(formula).unwrap().parse().unwrap();
+            let value = eval(&ast, Complex::new(0.0, 0.0), &HashMap::new()).unwrap();
+            assert_eq!(value, expected, "{}", formula);
+        }
+    }
+    #[test]
+    fn test_polar() {
+        let ast = Parser::new("10∠30°").unwrap().parse().unwrap();
+        let value = eval(&ast, Complex::new(0.0, 0.0), &HashMap::new()).unwrap();
+        assert!((value - Complex::from_polar(10.0, std::f64::consts::PI / 6.0)).norm() < 1e-12);
+        let ast = Parser::new("polar(2,pi/2)-2∠pi/2")
+            .unwrap()
+            .parse()
+            .unwrap();
+        let value = eval(&ast, Complex::new(0.0, 0.0), &HashMap::new()).unwrap();
+        assert_eq!(value, Complex::new(0.0, 0.0));
+        let ast = Parser::new("abs(@∠1)-abs(@)").unwrap().parse().unwrap();
+        let value = eval(&ast, Complex::new(3.0, 4.0), &HashMap::new()).unwrap();
+        assert!(value.norm() < 1e-12);
+    }
+    #[test]
+    fn test_rounding() {
+        for (formula, expected) in [
+            ("⌊2.5-1.5i⌋", Complex::new(2.0, -2.0)),
+            ("⌈2.5-1.5i⌉", Complex::new(3.0, -1.0)),
+            ("round(2.5-1.5i)", Complex::new(3.0, -2.0)),
+            ("trunc(-2.5+1.5i)", Complex::new(-2.0, 1.0)),
+            ("7%3", Complex::new(1.0, 0.0)),
+            ("-7%3", Complex::new(-1.0, 0.0)),
+            ("(5+3i)%(2+i)", Complex::new(1.0, 1.0)),
+            ("mod(5+3i,2)", Complex::new(1.0, 1.0)),
+            ("5!", Complex::new(120.0, 0.0)),
+            ("min(3,-1,2)", Complex::new(-1.0, 0.0)),
+            ("max(3,-1,2)", Complex::new(3.0, 0.0)),
+            ("avg(1+i,2-3i)", Complex::new(1.5, -1.0)),
+            ("med(5,2,8,9)", Complex::new(6.5, 0.0)),
+        ] {
+            let ast = Parser::new(formula).unwrap().parse().unwrap();
+            let value = eval(&ast, Complex::new(0.0, 0.0), &HashMap::new()).unwrap();
+            assert_eq!(value, expected, "{}", formula);
+        }
+    }
+    #[test]
+    fn test_gamma() {
+        let ast = Parser::new("0.5!").unwrap().parse().unwrap();
+        let value = eval(&ast, Complex::new(0.0, 0.0), &HashMap::new()).unwrap();
+        assert!((value - Complex::new(0.886226925452758, 0.0)).norm() < 1e-9);
+        let ast = Parser::new("(-1.5)!").unwrap().parse().unwrap();
+        let value = eval(&ast, Complex::new(0.0, 0.0), &HashMap::new()).unwrap();
+        assert!((value - Complex::new(-3.544907701811032, 0.0)).norm() < 1e-9);
+        // Γ(1+i) = i!
+        let ast = Parser::new("i!").unwrap().parse().unwrap();
+        let value = eval(&ast, Complex::new(0.0, 0.0), &HashMap::new()).unwrap();
+        assert!((value - Complex::new(0.498015668118356, -0.154949828301811)).norm() < 1e-9);
+    }
+    #[test]
+    fn test_eval_errors() {
+        let ast = Parser::new("1/0").unwrap().parse().unwrap();
+        assert_eq!(
+            eval(&ast, Complex::new(0.0, 0.0), &HashMap::new()),
+            Err(EvalError::DivisionByZero)
+        );
+        let ast = Parser::new("i/(i-i)").unwrap().parse().unwrap();
+        assert_eq!(
+            eval(&ast, Complex::new(0.0, 0.0), &HashMap::new()),
+            Err(EvalError::DivisionByZero)
+        );
+        let ast = Parser::new("i%0").unwrap().parse().unwrap();
+        assert_eq!(
+            eval(&ast, Complex::new(0.0, 0.0), &HashMap::new()),
+            Err(EvalError::DivisionByZero)
+        );
+        let ast = Parser::new("(-2)!").unwrap().parse().unwrap();
+        assert_eq!(
+            eval(&ast, Complex::new(0.0, 0.0), &HashMap::new()),
+            Err(EvalError::NegativeFactorial)
+        );
+        let ast = Parser::new("max(1,i)").unwrap().parse().unwrap();
+        assert_eq!(
+            eval(&ast, Complex::new(0.0, 0.0), &HashMap::new()),
+            Err(EvalError::Domain("max".to_string()))
+        );
+        let ast = Parser::new("med(1,2,i)").unwrap().parse().unwrap();
+        assert_eq!(
+            eval(&ast, Complex::new(0.0, 0.0), &HashMap::new()),
+            Err(EvalError::Domain("median".to_string()))
+        );
+    }
+    #[test]
+    fn test_logic() {
+        for (formula, expected) in [
+            ("2<3", 1),
+            ("3<=2", 0),
+            ("2==2", 1),
+            ("2!=2", 0),
+            ("3>2", 1),
+            ("2>=3", 0),
+            ("1&&0", 0),
+            ("0||2", 1),
+            ("!0", 1),
+            ("!5", 0),
+            ("1<2?10:20", 10),
+            ("if(0,10,20)", 20),
+            ("0?1:0?2:3", 3),
+            ("0&&x", 0),
+            ("1||x", 1),
+            ("1?2:x", 2),
+            ("if(0,x,3)", 3),
+            ("i==i", 1),
+            ("i!=1", 1),
+            ("i&&1", 1),
+        ] {
+            let ast = Parser::new(formula).unwrap().parse().unwrap();
+            assert_eq!(
+                eval(&ast, Complex::new(0.0, 0.0), &HashMap::new()),
+                Ok(Complex::new(expected as f64, 0.0)),
+                "{}",
+                formula
+            );
+        }
+        for (formula, error) in [
+            ("1?x:2", EvalError::UndefinedVariable("x".to_string())),
+            ("i<1", EvalError::Domain("<".to_string())),
+        ] {
+            let ast = Parser::new(formula).unwrap().parse().unwrap();
+            assert_eq!(
+                eval(&ast, Complex::new(0.0, 0.0), &HashMap::new()),
+                Err(error)
+            );
+        }
+    }
 }
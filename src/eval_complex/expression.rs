@@ -0,0 +1,153 @@
+use super::ast::{eval, Node};
+use super::parser::Parser;
+use super::simplify::simplify;
+use super::tokenizer::RESERVED_WORDS;
+use crate::utils::{latex, mathml, Context, EvalError, Formula, Functions, ParseError};
+use num_complex::Complex;
+use std::{collections::HashMap, fmt};
+
+/// A formula parsed once into a reusable AST and evaluated into Complex<f64> as many times as needed.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExpressionComplex {
+    ast: Node,
+}
+
+impl ExpressionComplex {
+    /// Parse a formula without evaluating it.
+    pub fn new(expr: String) -> Result<Self, ParseError> {
+        let mut math_parser = Parser::new(&expr)?;
+        let ast = math_parser.parse()?;
+        Ok(ExpressionComplex { ast })
+    }
+    /// Parse a formula that can call the given user-defined functions.
+    pub fn with_functions(
+        expr: String,
+        functions: &Functions<Complex<f64>>,
+    ) -> Result<Self, ParseError> {
+        let mut math_parser = Parser::with_functions(&expr, functions)?;
+        let ast = math_parser.parse()?;
+        Ok(ExpressionComplex { ast })
+    }
+    /// Evaluate the parsed formula, `@` being replaced by the placeholder.
+    pub fn eval(&self, placeholder: Complex<f64>) -> Result<Complex<f64>, EvalError> {
+        self.eval_with_context(placeholder, &HashMap::<String, Complex<f64>>::new())
+    }
+    /// Evaluate the parsed formula, resolving its named variables with the context.
+    pub fn eval_with_context(
+        &self,
+        placeholder: Complex<f64>,
+        context: &dyn Context<Complex<f64>>,
+    ) -> Result<Complex<f64>, EvalError> {
+        eval(&self.ast, placeholder, context)
+    }
+    /// Simplify the parsed formula, folding its constant parts and combining its like terms, so
+    /// that it evaluates faster.
+    pub fn simplify(&self) -> Self {
+        ExpressionComplex {
+            ast: simplify(&self.ast),
+        }
+    }
+    /// Write the parsed formula as LaTeX math, without the surrounding `$`.
+    pub fn to_latex(&self) -> String {
+        latex(&self.ast)
+    }
+    /// Write the parsed formula as a MathML `math` element.
+    pub fn to_mathml(&self) -> String {
+        mathml(&self.ast)
+    }
+}
+
+/// Writes the parsed formula back with as few parentheses as possible, as text that parses into
+/// the same formula.
+impl fmt::Display for ExpressionComplex {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.ast)
+    }
+}
+
+impl Formula for ExpressionComplex {
+    type Value = Complex<f64>;
+
+    fn parse(
+        expr: String,
+        functions: Option<&Functions<Complex<f64>>>,
+    ) -> Result<Self, ParseError> {
+        match functions {
+            Some(functions) => ExpressionComplex::with_functions(expr, functions),
+            None => ExpressionComplex::new(expr),
+        }
+    }
+    fn evaluate(
+        &self,
+        placeholder: Complex<f64>,
+        context: &dyn Context<Complex<f64>>,
+    ) -> Result<Complex<f64>, EvalError> {
+        self.eval_with_context(placeholder, context)
+    }
+    fn is_reserved(name: &str) -> bool {
+        RESERVED_WORDS.contains(&name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::Arity;
+    use crate::utils::ParseErrorKind;
+
+    #[test]
+    fn test_reuse() {
+        let expr = ExpressionComplex::new("@ * i + 1".to_string()).unwrap();
+        assert_eq!(
+            expr.eval(Complex::new(2.0, 0.0)).unwrap(),
+            Complex::new(1.0, 2.0)
+        );
+        assert_eq!(
+            expr.eval(Complex::new(0.0, 1.0)).unwrap(),
+            Complex::new(0.0, 0.0)
+        );
+    }
+    #[test]
+    fn test_invalid_expression() {
+        assert!(ExpressionComplex::new("*i".to_string()).is_err());
+    }
+    #[test]
+    fn test_error_span() {
+        let error = ExpressionComplex::new("2i + bar(i)".to_string()).unwrap_err();
+        assert_eq!(error.kind(), ParseErrorKind::UnknownFunction);
+        assert_eq!(error.span(), 5..8);
+        let error = ExpressionComplex::new("sqrt(4, 2)".to_string()).unwrap_err();
+        assert_eq!(error.kind(), ParseErrorKind::WrongArity);
+        assert_eq!(error.span(), 0..4);
+        let error = ExpressionComplex::new("1e3.5i".to_string()).unwrap_err();
+        assert_eq!(error.kind(), ParseErrorKind::InvalidNumber);
+        assert_eq!(error.span(), 0..5);
+    }
+    #[test]
+    fn test_context() {
+        let expr = ExpressionComplex::new("z * i".to_string()).unwrap();
+        let mut context = HashMap::new();
+        context.insert("z".to_string(), Complex::new(1.0, 2.0));
+        assert_eq!(
+            expr.eval_with_context(Complex::new(0.0, 0.0), &context)
+                .unwrap(),
+            Complex::new(-2.0, 1.0)
+        );
+    }
+    #[test]
+    fn test_unbound_variable() {
+        let expr = ExpressionComplex::new("z".to_string()).unwrap();
+        assert!(expr.eval(Complex::new(0.0, 0.0)).is_err());
+    }
+    #[test]
+    fn test_custom_functions() {
+        let mut functions = Functions::<Complex<f64>>::new();
+        functions.register("conjugate", Arity::Exactly(1), |args| Ok(args[0].conj()));
+        let expr =
+            ExpressionComplex::with_functions("conjugate(@)".to_string(), &functions).unwrap();
+        assert_eq!(
+            expr.eval(Complex::new(1.0, 2.0)).unwrap(),
+            Complex::new(1.0, -2.0)
+        );
+    }
+}
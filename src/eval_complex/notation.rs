@@ -0,0 +1,158 @@
+use super::ast::Node;
+use crate::utils::{real_text, write_infix, Notation, Operator, OperatorCategory, Term, Unary};
+use num_complex::Complex;
+use std::fmt;
+
+/// Write a complex number as the literals the tokenizer reads, `2i` being imaginary.
+fn literal<'a>(value: &Complex<f64>) -> Term<'a, Node> {
+    let imaginary = |im: f64| {
+        if im == 1.0 {
+            "i".to_string()
+        } else {
+            format!("{}i", real_text(im))
+        }
+    };
+    if value.im == 0.0 {
+        Term::number(real_text(value.re))
+    } else if value.re == 0.0 {
+        Term::number(imaginary(value.im))
+    } else {
+        let (sign, im) = if value.im < 0.0 {
+            ('-', -value.im)
+        } else {
+            ('+', value.im)
+        };
+        let text = format!("{} {} {}", real_text(value.re), sign, imaginary(im));
+        Term::Literal(text, OperatorCategory::Additive)
+    }
+}
+
+impl Notation for Node {
+    fn term(&self) -> Term<'_, Self> {
+        use self::Node::*;
+        match self {
+            Number(value) => literal(value),
+            Variable(name) => Term::Variable(name),
+            Ans => Term::Ans,
+            Add(left, right) => Term::Binary(Operator::Add, left, right),
+            Subtract(left, right) => Term::Binary(Operator::Subtract, left, right),
+            Multiply(left, right) => Term::Binary(Operator::Multiply, left, right),
+            Divide(left, right) => Term::Binary(Operator::Divide, left, right),
+            Modulo(left, right) => Term::Binary(Operator::Modulo, left, right),
+            Root(left, right) => Term::Function("root", vec![left, right]),
+            Pow(left, right) => Term::Binary(Operator::Pow, left, right),
+            Log(left, right) => Term::Function("log", vec![left, right]),
+            Polar(left, right) => Term::Function("polar", vec![left, right]),
+            Negative(expr) => Term::Unary(Unary::Negative, expr),
+            Abs(expr) => Term::Function("abs", vec![expr]),
+            Floor(expr) => Term::Function("floor", vec![expr]),
+            Ceil(expr) => Term::Function("ceil", vec![expr]),
+            Round(expr) => Term::Function("round", vec![expr]),
+            Truncate(expr) => Term::Function("trunc", vec![expr]),
+            Min(args) => Term::Function("min", args.iter().collect()),
+            Max(args) => Term::Function("max", args.iter().collect()),
+            Avg(args) => Term::Function("avg", args.iter().collect()),
+            Med(args) => Term::Function("med", args.iter().collect()),
+            Re(expr) => Term::Function("re", vec![expr]),
+            Im(expr) => Term::Function("im", vec![expr]),
+            Arg(expr) => Term::Function("arg", vec![expr]),
+            Conj(expr) => Term::Function("conj", vec![expr]),
+            Cis(expr) => Term::Function("cis", vec![expr]),
+            Sin(expr) => Term::Function("sin", vec![expr]),
+            Cos(expr) => Term::Function("cos", vec![expr]),
+            Tan(expr) => Term::Function("tan", vec![expr]),
+            Sinh(expr) => Term::Function("sinh", vec![expr]),
+            Cosh(expr) => Term::Function("cosh", vec![expr]),
+            Tanh(expr) => Term::Function("tanh", vec![expr]),
+            Arsinh(expr) => Term::Function("arsinh", vec![expr]),
+            Arcosh(expr) => Term::Function("arcosh", vec![expr]),
+            Artanh(expr) => Term::Function("artanh", vec![expr]),
+            Asin(expr) => Term::Function("asin", vec![expr]),
+            Acos(expr) => Term::Function("acos", vec![expr]),
+            Atan(expr) => Term::Function("atan", vec![expr]),
+            Sqrt(expr) => Term::Function("sqrt", vec![expr]),
+            Ln(expr) => Term::Function("ln", vec![expr]),
+            Lb(expr) => Term::Function("lb", vec![expr]),
+            Exp(expr) => Term::Function("exp", vec![expr]),
+            Exp2(expr) => Term::Function("exp2", vec![expr]),
+            Equal(left, right) => Term::Binary(Operator::Equal, left, right),
+            NotEqual(left, right) => Term::Binary(Operator::NotEqual, left, right),
+            Less(left, right) => Term::Binary(Operator::Less, left, right),
+            LessEqual(left, right) => Term::Binary(Operator::LessEqual, left, right),
+            Greater(left, right) => Term::Binary(Operator::Greater, left, right),
+            GreaterEqual(left, right) => Term::Binary(Operator::GreaterEqual, left, right),
+            LogicalAnd(left, right) => Term::Binary(Operator::LogicalAnd, left, right),
+            LogicalOr(left, right) => Term::Binary(Operator::LogicalOr, left, right),
+            LogicalNot(expr) => Term::Unary(Unary::LogicalNot, expr),
+            Factorial(expr) => Term::Factorial(expr),
+            Conditional(condition, then, otherwise) => {
+                Term::Conditional(condition, then, otherwise)
+            }
+            CustomFunction(function, args) => {
+                Term::Function(function.name(), args.iter().collect())
+            }
+        }
+    }
+}
+
+impl fmt::Display for Node {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write_infix(self, f)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::eval_complex::ast::Node;
+    use crate::eval_complex::parser::Parser;
+    use crate::utils::{latex, mathml};
+    use num_complex::Complex;
+
+    #[test]
+    fn test_literals() {
+        let number = |re, im| Node::Number(Complex::new(re, im));
+        assert_eq!(number(0.0, 1.0).to_string(), "i");
+        assert_eq!(number(0.0, -2.5).to_string(), "-2.5i");
+        let product = Node::Multiply(Box::new(number(1.0, -2.0)), Box::new(number(3.0, 0.0)));
+        assert_eq!(product.to_string(), "(1 - 2i) * 3");
+        assert_eq!(number(f64::NAN, 0.0).to_string(), "0 * 1e999");
+        assert_eq!(number(2.0, f64::NEG_INFINITY).to_string(), "2 - 1e999i");
+    }
+    #[test]
+    fn test_latex_and_mathml() {
+        let number = |re, im| Node::Number(Complex::new(re, im));
+        assert_eq!(latex(&number(2.0, f64::INFINITY)), "2 + \\infty i");
+        assert_eq!(latex(&number(0.0, -3.0)), "-3i");
+        assert_eq!(
+            mathml(&number(2.0, -1.0)),
+            "<math xmlns=\"http://www.w3.org/1998/Math/MathML\"><mrow><mn>2</mn>\
+             <mo>&#x2212;</mo><mi>i</mi></mrow></math>"
+        );
+        assert_eq!(
+            mathml(&number(0.0, 2.5)),
+            "<math xmlns=\"http://www.w3.org/1998/Math/MathML\"><mrow><mn>2.5</mn>\
+             <mi>i</mi></mrow></math>"
+        );
+    }
+    #[test]
+    fn test_round_trip() {
+        let formulas = [
+            "(3+2i)*i^2-3.5i/x",
+            "re(x)+im(x)+arg(x)+conj(x)+cis(x)+polar(1,x)+abs(x)+sqrt(x)+root(3,x)",
+            "sin(x)+cos(x)+tan(x)+sinh(x)+cosh(x)+tanh(x)+asin(x)+acos(x)+atan(x)",
+            "arsinh(x)+arcosh(x)+artanh(x)+ln(x)+lb(x)+exp(x)+exp2(x)+log(x,2)+x%2",
+            "floor(x)+ceil(x)+round(x)+trunc(x)+min(x,1)+max(x,1)+avg(x,1)+med(x,1)+3!",
+            "x==1||x!=2&&!(x<i)?1:2",
+        ];
+        for formula in formulas {
+            let ast = Parser::new(formula).unwrap().parse().unwrap();
+            let text = ast.to_string();
+            assert_eq!(
+                Parser::new(&text).unwrap().parse().unwrap(),
+                ast,
+                "{}",
+                text
+            );
+        }
+    }
+}
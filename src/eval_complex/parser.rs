@@ -1,59 +1,96 @@
 use num_complex::Complex;
+use std::{ops::Range, sync::Arc};
 
 use super::ast::Node;
 use super::token::{NativeFunction, Token};
 use super::tokenizer::Tokenizer;
-use crate::utils::{OperatorCategory, ParseError};
+use crate::utils::{
+    too_deep, Arity, Functions, OperatorCategory, ParseError, ParseErrorKind, MAX_DEPTH,
+};
 
 pub struct Parser<'a> {
     tokenizer: Tokenizer<'a>,
     current_token: Token,
+    current_span: Range<usize>,
     previous_token: Option<Token>,
-    placeholder: Complex<f64>,
+    depth: usize,
 }
 
 impl<'a> Parser<'a> {
-    pub fn new(expr: &'a str, placeholder: Option<Complex<f64>>) -> Result<Self, ParseError> {
-        let mut lexer = Tokenizer::new(expr);
+    pub fn new(expr: &'a str) -> Result<Self, ParseError> {
+        Parser::from_tokenizer(Tokenizer::new(expr))
+    }
+    pub fn with_functions(
+        expr: &'a str,
+        functions: &'a Functions<Complex<f64>>,
+    ) -> Result<Self, ParseError> {
+        Parser::from_tokenizer(Tokenizer::with_functions(expr, functions))
+    }
+    fn from_tokenizer(mut lexer: Tokenizer<'a>) -> Result<Self, ParseError> {
+        lexer.skip_whitespace();
+        let start = lexer.position();
         let cur_token = match lexer.next() {
             Some(token) => token,
-            None => return Err(ParseError::InvalidOperator("Invalid character".into())),
+            None => return Err(lexer.error(start)),
         };
         Ok(Parser {
+            current_span: start..lexer.position(),
             tokenizer: lexer,
             current_token: cur_token,
             previous_token: None,
-            placeholder: placeholder.unwrap_or_default(),
+            depth: 0,
         })
     }
     pub fn parse(&mut self) -> Result<Node, ParseError> {
-        let ast = self.generate_ast(OperatorCategory::DefaultZero);
-        match ast {
-            Ok(ast) => Ok(ast),
-            Err(e) => Err(e),
+        let ast = self.generate_ast(OperatorCategory::DefaultZero)?;
+        if self.current_token == Token::Eof {
+            Ok(ast)
+        } else if self.current_token.is_closing_bracket() {
+            Err(ParseError::new(
+                ParseErrorKind::UnbalancedBracket,
+                self.current_span.clone(),
+                format!("Unmatched {}", self.current_token),
+            ))
+        } else {
+            Err(self.unexpected_token(&["an operator", "end of input"]))
         }
     }
     fn get_next_token(&mut self) -> Result<(), ParseError> {
+        self.tokenizer.skip_whitespace();
+        let start = self.tokenizer.position();
         let next_token = match self.tokenizer.next() {
             Some(token) => token,
-            None => return Err(ParseError::InvalidOperator("Invalid character".into())),
+            None => return Err(self.tokenizer.error(start)),
         };
         self.previous_token = Some(self.current_token.clone());
         self.current_token = next_token;
+        self.current_span = start..self.tokenizer.position();
         Ok(())
     }
     fn generate_ast(&mut self, oper_prec: OperatorCategory) -> Result<Node, ParseError> {
+        let depth = self.depth;
+        self.nest()?;
         let mut left_expr = self.parse_number()?;
         while oper_prec < self.current_token.get_oper_prec() {
             if self.current_token == Token::Eof {
                 break;
             }
-            let right_expr = self.convert_token_to_node(left_expr.clone())?;
-            left_expr = right_expr;
+            self.nest()?;
+            left_expr = self.convert_token_to_node(left_expr)?;
         }
+        self.depth = depth;
         Ok(left_expr)
     }
-    fn function_static_arguments(&mut self, n: i32) -> Result<Vec<Node>, ParseError> {
+    fn nest(&mut self) -> Result<(), ParseError> {
+        self.depth += 1;
+        if self.depth > MAX_DEPTH {
+            return Err(too_deep(self.current_span.clone()));
+        }
+        Ok(())
+    }
+    fn function_static_arguments(&mut self, n: usize) -> Result<Vec<Node>, ParseError> {
+        let function = self.current_token.clone();
+        let function_span = self.current_span.clone();
         self.get_next_token()?;
         self.check_paren(Token::LeftParen)?;
         let mut args = Vec::new();
@@ -61,24 +98,164 @@ impl<'a> Parser<'a> {
             let arg_expr = self.generate_ast(OperatorCategory::DefaultZero)?;
             args.push(arg_expr);
             if i < n - 1 {
+                if self.current_token == Token::RightParen {
+                    return Err(wrong_arity(&function, function_span, n));
+                }
                 self.check_paren(Token::Comma)?;
             }
         }
+        if self.current_token == Token::Comma {
+            return Err(wrong_arity(&function, function_span, n));
+        }
         self.check_paren(Token::RightParen)?;
         Ok(args)
     }
+    fn function_arguments(&mut self) -> Result<Vec<Node>, ParseError> {
+        self.find_item_list(
+            Token::LeftParen,
+            Token::RightParen,
+            OperatorCategory::DefaultZero,
+        )
+    }
+    fn find_item_list(
+        &mut self,
+        start_token: Token,
+        end_token: Token,
+        oper_prec: OperatorCategory,
+    ) -> Result<Vec<Node>, ParseError> {
+        self.get_next_token()?;
+        self.check_paren(start_token)?;
+        let mut args = Vec::new();
+        loop {
+            if args.is_empty() && (end_token == self.current_token) {
+                self.get_next_token()?;
+                break;
+            }
+            let arg_expr = self.generate_ast(oper_prec.clone())?;
+            args.push(arg_expr);
+            if Token::Comma == self.current_token {
+                self.get_next_token()?;
+            } else if end_token == self.current_token {
+                self.get_next_token()?;
+                break;
+            } else {
+                return Err(self.missing_token(&[Token::Comma, end_token]));
+            }
+        }
+        Ok(args)
+    }
     fn parse_number(&mut self) -> Result<Node, ParseError> {
         let token = self.current_token.clone();
         match token {
             Token::Ans => {
                 self.get_next_token()?;
-                Ok(Node::Number(self.placeholder))
+                Ok(Node::Ans)
+            }
+            Token::Identifier(name) => {
+                self.get_next_token()?;
+                self.implicit_multiply(Node::Variable(name))
+            }
+            Token::CustomFunction(function) => {
+                let function_span = self.current_span.clone();
+                let args = match function.arity() {
+                    Arity::Exactly(n) => self.function_static_arguments(n)?,
+                    Arity::AtLeast(n) => {
+                        let args = self.function_arguments()?;
+                        if args.len() < n {
+                            return Err(ParseError::new(
+                                ParseErrorKind::WrongArity,
+                                function_span,
+                                format!(
+                                    "The {} function takes at least {} arguments",
+                                    function.name(),
+                                    n
+                                ),
+                            ));
+                        }
+                        args
+                    }
+                };
+                self.implicit_multiply(Node::CustomFunction(function, Arc::new(args)))
             }
             Token::ExplicitFunction(current_function) => {
+                let function_span = self.current_span.clone();
                 let current_function = match current_function {
                     NativeFunction::Abs => {
                         Node::Abs(Box::new(self.function_static_arguments(1)?[0].clone()))
                     }
+                    NativeFunction::Mod => {
+                        let args = self.function_static_arguments(2)?;
+                        Node::Modulo(Box::new(args[0].clone()), Box::new(args[1].clone()))
+                    }
+                    NativeFunction::Min => {
+                        let args = self.function_arguments()?;
+                        if args.is_empty() {
+                            return Err(ParseError::new(
+                                ParseErrorKind::WrongArity,
+                                function_span,
+                                "There's no arguments in the min function".to_string(),
+                            ));
+                        }
+                        Node::Min(Arc::new(args))
+                    }
+                    NativeFunction::Max => {
+                        let args = self.function_arguments()?;
+                        if args.is_empty() {
+                            return Err(ParseError::new(
+                                ParseErrorKind::WrongArity,
+                                function_span,
+                                "There's no arguments in the max function".to_string(),
+                            ));
+                        }
+                        Node::Max(Arc::new(args))
+                    }
+                    NativeFunction::Avg => {
+                        let args = self.function_arguments()?;
+                        if args.is_empty() {
+                            Node::Number(Complex::new(0.0, 0.0))
+                        } else {
+                            Node::Avg(Arc::new(args))
+                        }
+                    }
+                    NativeFunction::Med => {
+                        let args = self.function_arguments()?;
+                        if args.is_empty() {
+                            return Err(ParseError::new(
+                                ParseErrorKind::WrongArity,
+                                function_span,
+                                "Cannot compute the median of no arguments".to_string(),
+                            ));
+                        } else {
+                            Node::Med(Arc::new(args))
+                        }
+                    }
+                    NativeFunction::Floor => {
+                        Node::Floor(Box::new(self.function_static_arguments(1)?[0].clone()))
+                    }
+                    NativeFunction::Ceil => {
+                        Node::Ceil(Box::new(self.function_static_arguments(1)?[0].clone()))
+                    }
+                    NativeFunction::Round => {
+                        Node::Round(Box::new(self.function_static_arguments(1)?[0].clone()))
+                    }
+                    NativeFunction::Truncate => {
+                        Node::Truncate(Box::new(self.function_static_arguments(1)?[0].clone()))
+                    }
+                    NativeFunction::Re => {
+                        Node::Re(Box::new(self.function_static_arguments(1)?[0].clone()))
+                    }
+                    NativeFunction::Im => {
+                        Node::Im(Box::new(self.function_static_arguments(1)?[0].clone()))
+                    }
+                    NativeFunction::Arg => {
+                        Node::Arg(Box::new(self.function_static_arguments(1)?[0].clone()))
+                    }
+                    NativeFunction::Conj => {
+                        Node::Conj(Box::new(self.function_static_arguments(1)?[0].clone()))
+                    }
+                    NativeFunction::Cis => {
+                        Node::Cis(Box::new(self.function_static_arguments(1)?[0].clone()))
+                    }
                     NativeFunction::Sin => {
                         Node::Sin(Box::new(self.function_static_arguments(1)?[0].clone()))
                     }
@@ -142,6 +319,18 @@ impl<'a> Parser<'a> {
                         let args = self.function_static_arguments(2)?;
                         Node::Log(Box::new(args[0].clone()), Box::new(args[1].clone()))
                     }
+                    NativeFunction::Polar => {
+                        let args = self.function_static_arguments(2)?;
+                        Node::Polar(Box::new(args[0].clone()), Box::new(args[1].clone()))
+                    }
+                    NativeFunction::If => {
+                        let args = self.function_static_arguments(3)?;
+                        Node::Conditional(
+                            Box::new(args[0].clone()),
+                            Box::new(args[1].clone()),
+                            Box::new(args[2].clone()),
+                        )
+                    }
                 };
                 self.implicit_multiply(current_function)
             }
@@ -155,32 +344,58 @@ impl<'a> Parser<'a> {
                 let expr = self.generate_ast(OperatorCategory::Negative)?;
                 Ok(expr)
             }
+            Token::ExclamationMark => {
+                self.get_next_token()?;
+                let expr = self.generate_ast(OperatorCategory::Negative)?;
+                Ok(Node::LogicalNot(Box::new(expr)))
+            }
             Token::Num(i) => {
+                let end = self.current_span.end;
                 self.get_next_token()?;
+                if let Token::Num(_) = self.current_token {
+                    if self.current_span.start > end {
+                        // Numbers separated by whitespace are not multiplied.
+                        return Err(self.unexpected_token(&["an operator"]));
+                    }
+                }
                 self.implicit_multiply(Node::Number(i))
             }
             Token::Pi => {
                 self.get_next_token()?;
-                Ok(Node::Number(Complex::new(std::f64::consts::PI, 0.0)))
+                self.implicit_multiply(Node::Number(Complex::new(std::f64::consts::PI, 0.0)))
             }
             Token::E => {
                 self.get_next_token()?;
-                Ok(Node::Number(Complex::new(std::f64::consts::E, 0.0)))
+                self.implicit_multiply(Node::Number(Complex::new(std::f64::consts::E, 0.0)))
             }
             Token::LeftParen => self.get_enclosed_elements_with_impl_mult(
                 OperatorCategory::DefaultZero,
                 Token::RightParen,
                 |expr| expr,
             ),
-            _ => Err(ParseError::UnableToParse(
-                "Unknown parsing token for parsing number".to_string(),
-            )),
+            Token::LeftFloor => self.get_enclosed_elements_with_impl_mult(
+                OperatorCategory::DefaultZero,
+                Token::RightFloor,
+                |expr| Node::Floor(Box::new(expr)),
+            ),
+            Token::LeftCeiling => self.get_enclosed_elements_with_impl_mult(
+                OperatorCategory::DefaultZero,
+                Token::RightCeiling,
+                |expr| Node::Ceil(Box::new(expr)),
+            ),
+            _ => Err(self.unexpected_token(&["a number", "a variable", "a function", "("])),
         }
     }
     fn implicit_multiply(&mut self, node: Node) -> Result<Node, ParseError> {
         if (self.current_token == Token::LeftParen)
+            || (self.current_token == Token::LeftCeiling)
+            || (self.current_token == Token::LeftFloor)
             || matches!(self.current_token, Token::ExplicitFunction(_))
+            || matches!(self.current_token, Token::CustomFunction(_))
             || matches!(self.current_token, Token::Num(_))
+            || matches!(self.current_token, Token::Identifier(_))
+            || self.current_token == Token::Pi
+            || self.current_token == Token::E
         {
             let right = self.generate_ast(OperatorCategory::Multiplicative)?;
             return Ok(Node::Multiply(Box::new(node), Box::new(right)));
@@ -198,15 +413,32 @@ impl<'a> Parser<'a> {
         self.check_paren(end_token)?;
         self.implicit_multiply(get_node(expr))
     }
+    fn unexpected_token<S: ToString>(&self, expected: &[S]) -> ParseError {
+        ParseError::new(
+            ParseErrorKind::UnexpectedToken,
+            self.current_span.clone(),
+            format!("Unexpected {}", self.current_token),
+        )
+        .with_expected(expected)
+    }
+    fn missing_token(&self, expected: &[Token]) -> ParseError {
+        let unclosed = self.current_token == Token::Eof || self.current_token.is_closing_bracket();
+        if !(unclosed && expected.iter().any(Token::is_closing_bracket)) {
+            return self.unexpected_token(expected);
+        }
+        ParseError::new(
+            ParseErrorKind::UnbalancedBracket,
+            self.current_span.clone(),
+            format!("Unexpected {}", self.current_token),
+        )
+        .with_expected(expected)
+    }
     fn check_paren(&mut self, expected: Token) -> Result<(), ParseError> {
         if expected == self.current_token {
             self.get_next_token()?;
             Ok(())
         } else {
-            Err(ParseError::InvalidOperator(format!(
-                "Expected {:?}, got {:?}",
-                expected, self.current_token
-            )))
+            Err(self.missing_token(&[expected]))
         }
     }
     fn convert_token_to_node(&mut self, left_expr: Node) -> Result<Node, ParseError> {
@@ -233,9 +465,14 @@ impl<'a> Parser<'a> {
             }
             Token::Caret => {
                 self.get_next_token()?;
-                let right_expr = self.generate_ast(OperatorCategory::Power)?;
+                // Right-associative: the exponent is itself a power, optionally negated.
+                let right_expr = self.generate_ast(OperatorCategory::Negative)?;
                 Ok(Node::Pow(Box::new(left_expr), Box::new(right_expr)))
             }
+            Token::ExclamationMark => {
+                self.get_next_token()?;
+                self.implicit_multiply(Node::Factorial(Box::new(left_expr)))
+            }
             Token::DegToRad => {
                 self.get_next_token()?;
                 Ok(Node::Multiply(
@@ -250,6 +487,12 @@ impl<'a> Parser<'a> {
                     Box::new(Node::Number(Complex::new(57.2957795131, 0.0))),
                 ))
             }
+            Token::Angle => {
+                self.get_next_token()?;
+                // The angle is the whole product that follows, `10∠pi/2` being `10∠(pi/2)`.
+                let angle = self.generate_ast(OperatorCategory::Additive)?;
+                Ok(Node::Polar(Box::new(left_expr), Box::new(angle)))
+            }
             Token::Superscript(script) => {
                 self.get_next_token()?;
                 Ok(Node::Pow(
@@ -257,14 +500,79 @@ impl<'a> Parser<'a> {
                     Box::new(Node::Number(script)),
                 ))
             }
-            _ => Err(ParseError::InvalidOperator(format!(
-                "Please enter a valid operator {:?}",
-                self.current_token
-            ))),
+            Token::Modulo => {
+                self.get_next_token()?;
+                let right_expr = self.generate_ast(OperatorCategory::Multiplicative)?;
+                Ok(Node::Modulo(Box::new(left_expr), Box::new(right_expr)))
+            }
+            Token::Equal => {
+                self.get_next_token()?;
+                let right_expr = self.generate_ast(OperatorCategory::Comparison)?;
+                Ok(Node::Equal(Box::new(left_expr), Box::new(right_expr)))
+            }
+            Token::NotEqual => {
+                self.get_next_token()?;
+                let right_expr = self.generate_ast(OperatorCategory::Comparison)?;
+                Ok(Node::NotEqual(Box::new(left_expr), Box::new(right_expr)))
+            }
+            Token::Less => {
+                self.get_next_token()?;
+                let right_expr = self.generate_ast(OperatorCategory::Comparison)?;
+                Ok(Node::Less(Box::new(left_expr), Box::new(right_expr)))
+            }
+            Token::LessEqual => {
+                self.get_next_token()?;
+                let right_expr = self.generate_ast(OperatorCategory::Comparison)?;
+                Ok(Node::LessEqual(Box::new(left_expr), Box::new(right_expr)))
+            }
+            Token::Greater => {
+                self.get_next_token()?;
+                let right_expr = self.generate_ast(OperatorCategory::Comparison)?;
+                Ok(Node::Greater(Box::new(left_expr), Box::new(right_expr)))
+            }
+            Token::GreaterEqual => {
+                self.get_next_token()?;
+                let right_expr = self.generate_ast(OperatorCategory::Comparison)?;
+                Ok(Node::GreaterEqual(
+                    Box::new(left_expr),
+                    Box::new(right_expr),
+                ))
+            }
+            Token::DoubleAmpersand => {
+                self.get_next_token()?;
+                let right_expr = self.generate_ast(OperatorCategory::LogicalAnd)?;
+                Ok(Node::LogicalAnd(Box::new(left_expr), Box::new(right_expr)))
+            }
+            Token::DoubleBar => {
+                self.get_next_token()?;
+                let right_expr = self.generate_ast(OperatorCategory::LogicalOr)?;
+                Ok(Node::LogicalOr(Box::new(left_expr), Box::new(right_expr)))
+            }
+            Token::QuestionMark => {
+                self.get_next_token()?;
+                let then_expr = self.generate_ast(OperatorCategory::DefaultZero)?;
+                self.check_paren(Token::Colon)?;
+                // Right-associative: the else branch can itself be a conditional.
+                let else_expr = self.generate_ast(OperatorCategory::DefaultZero)?;
+                Ok(Node::Conditional(
+                    Box::new(left_expr),
+                    Box::new(then_expr),
+                    Box::new(else_expr),
+                ))
+            }
+            _ => Err(self.unexpected_token(&["an operator"])),
         }
     }
 }
 
+fn wrong_arity(function: &Token, function_span: Range<usize>, n: usize) -> ParseError {
+    ParseError::new(
+        ParseErrorKind::WrongArity,
+        function_span,
+        format!("The {} function takes {} arguments", function, n),
+    )
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -272,25 +580,25 @@ mod tests {
 
     #[test]
     fn test_pi() {
-        let mut parser = Parser::new("pi", None).unwrap();
+        let mut parser = Parser::new("pi").unwrap();
         let expected = Number(Complex::new(std::f64::consts::PI, 0.0));
         assert_eq!(parser.parse().unwrap(), expected);
     }
     #[test]
     fn test_e() {
-        let mut parser = Parser::new("e", None).unwrap();
+        let mut parser = Parser::new("e").unwrap();
         let expected = Number(Complex::new(std::f64::consts::E, 0.0));
         assert_eq!(parser.parse().unwrap(), expected);
     }
     #[test]
     fn test_negative() {
-        let mut parser = Parser::new("-1", None).unwrap();
+        let mut parser = Parser::new("-1").unwrap();
         let expected = Negative(Box::new(Number(Complex::new(1.0, 0.0))));
         assert_eq!(parser.parse().unwrap(), expected);
     }
     #[test]
     fn test_add() {
-        let mut parser = Parser::new("1+2", None).unwrap();
+        let mut parser = Parser::new("1+2").unwrap();
         let expected = Add(
             Box::new(Number(Complex::new(1.0, 0.0))),
             Box::new(Number(Complex::new(2.0, 0.0))),
@@ -299,7 +607,7 @@ mod tests {
     }
     #[test]
     fn test_subtract() {
-        let mut parser = Parser::new("1-2", None).unwrap();
+        let mut parser = Parser::new("1-2").unwrap();
         let expected = Subtract(
             Box::new(Number(Complex::new(1.0, 0.0))),
             Box::new(Number(Complex::new(2.0, 0.0))),
@@ -308,7 +616,7 @@ mod tests {
     }
     #[test]
     fn test_multiply() {
-        let mut parser = Parser::new("1*2", None).unwrap();
+        let mut parser = Parser::new("1*2").unwrap();
         let expected = Multiply(
             Box::new(Number(Complex::new(1.0, 0.0))),
             Box::new(Number(Complex::new(2.0, 0.0))),
@@ -317,7 +625,7 @@ mod tests {
     }
     #[test]
     fn test_divide() {
-        let mut parser = Parser::new("1/2", None).unwrap();
+        let mut parser = Parser::new("1/2").unwrap();
         let expected = Divide(
             Box::new(Number(Complex::new(1.0, 0.0))),
             Box::new(Number(Complex::new(2.0, 0.0))),
@@ -326,7 +634,7 @@ mod tests {
     }
     #[test]
     fn test_caret() {
-        let mut parser = Parser::new("1^2", None).unwrap();
+        let mut parser = Parser::new("1^2").unwrap();
         let expected = Pow(
             Box::new(Number(Complex::new(1.0, 0.0))),
             Box::new(Number(Complex::new(2.0, 0.0))),
@@ -334,8 +642,45 @@ mod tests {
         assert_eq!(parser.parse().unwrap(), expected);
     }
     #[test]
+    fn test_caret_right_associative() {
+        let mut parser = Parser::new("2^3^2").unwrap();
+        let expected = Pow(
+            Box::new(Number(Complex::new(2.0, 0.0))),
+            Box::new(Pow(
+                Box::new(Number(Complex::new(3.0, 0.0))),
+                Box::new(Number(Complex::new(2.0, 0.0))),
+            )),
+        );
+        assert_eq!(parser.parse().unwrap(), expected);
+        let mut parser = Parser::new("2^3²").unwrap();
+        assert_eq!(parser.parse().unwrap(), expected);
+    }
+    #[test]
+    fn test_negative_power() {
+        let mut parser = Parser::new("-2^2").unwrap();
+        let expected = Negative(Box::new(Pow(
+            Box::new(Number(Complex::new(2.0, 0.0))),
+            Box::new(Number(Complex::new(2.0, 0.0))),
+        )));
+        assert_eq!(parser.parse().unwrap(), expected);
+        let mut parser = Parser::new("-2²").unwrap();
+        assert_eq!(parser.parse().unwrap(), expected);
+        let mut parser = Parser::new("2^-2").unwrap();
+        let expected = Pow(
+            Box::new(Number(Complex::new(2.0, 0.0))),
+            Box::new(Negative(Box::new(Number(Complex::new(2.0, 0.0))))),
+        );
+        assert_eq!(parser.parse().unwrap(), expected);
+        let mut parser = Parser::new("-2*3").unwrap();
+        let expected = Multiply(
+            Box::new(Negative(Box::new(Number(Complex::new(2.0, 0.0))))),
+            Box::new(Number(Complex::new(3.0, 0.0))),
+        );
+        assert_eq!(parser.parse().unwrap(), expected);
+    }
+    #[test]
     fn test_deg_to_rad() {
-        let mut parser = Parser::new("1°", None).unwrap();
+        let mut parser = Parser::new("1°").unwrap();
         let expected = Multiply(
             Box::new(Number(Complex::new(1.0, 0.0))),
             Box::new(Number(Complex::new(0.017453292519943295, 0.0))),
@@ -343,8 +688,41 @@ mod tests {
         assert_eq!(parser.parse().unwrap(), expected);
     }
     #[test]
+    fn test_angle() {
+        let mut parser = Parser::new("10∠30°").unwrap();
+        let expected = Polar(
+            Box::new(Number(Complex::new(10.0, 0.0))),
+            Box::new(Multiply(
+                Box::new(Number(Complex::new(30.0, 0.0))),
+                Box::new(Number(Complex::new(0.017453292519943295, 0.0))),
+            )),
+        );
+        assert_eq!(parser.parse().unwrap(), expected);
+        let mut parser = Parser::new("1+2∠3/4").unwrap();
+        let expected = Add(
+            Box::new(Number(Complex::new(1.0, 0.0))),
+            Box::new(Polar(
+                Box::new(Number(Complex::new(2.0, 0.0))),
+                Box::new(Divide(
+                    Box::new(Number(Complex::new(3.0, 0.0))),
+                    Box::new(Number(Complex::new(4.0, 0.0))),
+                )),
+            )),
+        );
+        assert_eq!(parser.parse().unwrap(), expected);
+    }
+    #[test]
+    fn test_polar_function() {
+        let mut parser = Parser::new("polar(2,3)").unwrap();
+        let expected = Polar(
+            Box::new(Number(Complex::new(2.0, 0.0))),
+            Box::new(Number(Complex::new(3.0, 0.0))),
+        );
+        assert_eq!(parser.parse().unwrap(), expected);
+    }
+    #[test]
     fn test_rad_to_deg() {
-        let mut parser = Parser::new("1rad", None).unwrap();
+        let mut parser = Parser::new("1rad").unwrap();
         let expected = Multiply(
             Box::new(Number(Complex::new(1.0, 0.0))),
             Box::new(Number(Complex::new(57.2957795131, 0.0))),
@@ -353,7 +731,7 @@ mod tests {
     }
     #[test]
     fn test_pow_function() {
-        let mut parser = Parser::new("pow(3,2)", None).unwrap();
+        let mut parser = Parser::new("pow(3,2)").unwrap();
         let expected = Pow(
             Box::new(Number(Complex::new(3.0, 0.0))),
             Box::new(Number(Complex::new(2.0, 0.0))),
@@ -362,7 +740,7 @@ mod tests {
     }
     #[test]
     fn test_log_function() {
-        let mut parser = Parser::new("log(3,2)", None).unwrap();
+        let mut parser = Parser::new("log(3,2)").unwrap();
         let expected = Log(
             Box::new(Number(Complex::new(3.0, 0.0))),
             Box::new(Number(Complex::new(2.0, 0.0))),
@@ -371,119 +749,204 @@ mod tests {
     }
     #[test]
     fn test_abs_function() {
-        let mut parser = Parser::new("abs(5.25)", None).unwrap();
+        let mut parser = Parser::new("abs(5.25)").unwrap();
         let expected = Abs(Box::new(Number(Complex::new(5.25, 0.0))));
         assert_eq!(parser.parse().unwrap(), expected);
     }
     #[test]
+    fn test_floor() {
+        let mut parser = Parser::new("2⌊5.25⌋").unwrap();
+        let expected = Multiply(
+            Box::new(Number(Complex::new(2.0, 0.0))),
+            Box::new(Floor(Box::new(Number(Complex::new(5.25, 0.0))))),
+        );
+        assert_eq!(parser.parse().unwrap(), expected);
+    }
+    #[test]
+    fn test_factorial_and_modulo() {
+        let mut parser = Parser::new("3!%2").unwrap();
+        let expected = Modulo(
+            Box::new(Factorial(Box::new(Number(Complex::new(3.0, 0.0))))),
+            Box::new(Number(Complex::new(2.0, 0.0))),
+        );
+        assert_eq!(parser.parse().unwrap(), expected);
+    }
+    #[test]
+    fn test_med_function() {
+        let mut parser = Parser::new("med()").unwrap();
+        assert_eq!(
+            parser.parse().unwrap_err().kind(),
+            crate::utils::ParseErrorKind::WrongArity
+        );
+    }
+    #[test]
     fn test_sqrt_function() {
-        let mut parser = Parser::new("sqrt(5.25)", None).unwrap();
+        let mut parser = Parser::new("sqrt(5.25)").unwrap();
         let expected = Sqrt(Box::new(Number(Complex::new(5.25, 0.0))));
         assert_eq!(parser.parse().unwrap(), expected);
     }
     #[test]
     fn test_exp_function() {
-        let mut parser = Parser::new("exp(5.25)", None).unwrap();
+        let mut parser = Parser::new("exp(5.25)").unwrap();
         let expected = Exp(Box::new(Number(Complex::new(5.25, 0.0))));
         assert_eq!(parser.parse().unwrap(), expected);
     }
     #[test]
     fn test_exp2_function() {
-        let mut parser = Parser::new("exp2(5.25)", None).unwrap();
+        let mut parser = Parser::new("exp2(5.25)").unwrap();
         let expected = Exp2(Box::new(Number(Complex::new(5.25, 0.0))));
         assert_eq!(parser.parse().unwrap(), expected);
     }
     #[test]
     fn test_ln_function() {
-        let mut parser = Parser::new("ln(5.25)", None).unwrap();
+        let mut parser = Parser::new("ln(5.25)").unwrap();
         let expected = Ln(Box::new(Number(Complex::new(5.25, 0.0))));
         assert_eq!(parser.parse().unwrap(), expected);
     }
     #[test]
     fn test_lb_function() {
-        let mut parser = Parser::new("lb(5.25)", None).unwrap();
+        let mut parser = Parser::new("lb(5.25)").unwrap();
         let expected = Lb(Box::new(Number(Complex::new(5.25, 0.0))));
         assert_eq!(parser.parse().unwrap(), expected);
     }
     #[test]
     fn test_sin_function() {
-        let mut parser = Parser::new("sin(5.25)", None).unwrap();
+        let mut parser = Parser::new("sin(5.25)").unwrap();
         let expected = Sin(Box::new(Number(Complex::new(5.25, 0.0))));
         assert_eq!(parser.parse().unwrap(), expected);
     }
     #[test]
     fn test_cos_function() {
-        let mut parser = Parser::new("cos(5.25)", None).unwrap();
+        let mut parser = Parser::new("cos(5.25)").unwrap();
         let expected = Cos(Box::new(Number(Complex::new(5.25, 0.0))));
         assert_eq!(parser.parse().unwrap(), expected);
     }
     #[test]
     fn test_tan_function() {
-        let mut parser = Parser::new("tan(5.25)", None).unwrap();
+        let mut parser = Parser::new("tan(5.25)").unwrap();
         let expected = Tan(Box::new(Number(Complex::new(5.25, 0.0))));
         assert_eq!(parser.parse().unwrap(), expected);
     }
     #[test]
     fn test_sinh_function() {
-        let mut parser = Parser::new("sinh(5.25)", None).unwrap();
+        let mut parser = Parser::new("sinh(5.25)").unwrap();
         let expected = Sinh(Box::new(Number(Complex::new(5.25, 0.0))));
         assert_eq!(parser.parse().unwrap(), expected);
     }
     #[test]
     fn test_cosh_function() {
-        let mut parser = Parser::new("cosh(5.25)", None).unwrap();
+        let mut parser = Parser::new("cosh(5.25)").unwrap();
         let expected = Cosh(Box::new(Number(Complex::new(5.25, 0.0))));
         assert_eq!(parser.parse().unwrap(), expected);
     }
     #[test]
     fn test_tanh_function() {
-        let mut parser = Parser::new("tanh(5.25)", None).unwrap();
+        let mut parser = Parser::new("tanh(5.25)").unwrap();
         let expected = Tanh(Box::new(Number(Complex::new(5.25, 0.0))));
         assert_eq!(parser.parse().unwrap(), expected);
     }
     #[test]
     fn test_asin_function() {
-        let mut parser = Parser::new("asin(5.25)", None).unwrap();
+        let mut parser = Parser::new("asin(5.25)").unwrap();
         let expected = Asin(Box::new(Number(Complex::new(5.25, 0.0))));
         assert_eq!(parser.parse().unwrap(), expected);
     }
     #[test]
     fn test_acos_function() {
-        let mut parser = Parser::new("acos(5.25)", None).unwrap();
+        let mut parser = Parser::new("acos(5.25)").unwrap();
         let expected = Acos(Box::new(Number(Complex::new(5.25, 0.0))));
         assert_eq!(parser.parse().unwrap(), expected);
     }
     #[test]
     fn test_atan_function() {
-        let mut parser = Parser::new("atan(5.25)", None).unwrap();
+        let mut parser = Parser::new("atan(5.25)").unwrap();
         let expected = Atan(Box::new(Number(Complex::new(5.25, 0.0))));
         assert_eq!(parser.parse().unwrap(), expected);
     }
     #[test]
     fn test_arsinh_function() {
-        let mut parser = Parser::new("arsinh(5.25)", None).unwrap();
+        let mut parser = Parser::new("arsinh(5.25)").unwrap();
         let expected = Arsinh(Box::new(Number(Complex::new(5.25, 0.0))));
         assert_eq!(parser.parse().unwrap(), expected);
     }
     #[test]
     fn test_arcosh_function() {
-        let mut parser = Parser::new("arcosh(5.25)", None).unwrap();
+        let mut parser = Parser::new("arcosh(5.25)").unwrap();
         let expected = Arcosh(Box::new(Number(Complex::new(5.25, 0.0))));
         assert_eq!(parser.parse().unwrap(), expected);
     }
     #[test]
     fn test_artanh_function() {
-        let mut parser = Parser::new("artanh(5.25)", None).unwrap();
+        let mut parser = Parser::new("artanh(5.25)").unwrap();
         let expected = Artanh(Box::new(Number(Complex::new(5.25, 0.0))));
         assert_eq!(parser.parse().unwrap(), expected);
     }
     #[test]
     fn test_implicit_mul_prts() {
-        let mut parser = Parser::new("(2)(3)", None).unwrap();
+        let mut parser = Parser::new("(2)(3)").unwrap();
         let expected = Multiply(
             Box::new(Number(Complex::new(2.0, 0.0))),
             Box::new(Number(Complex::new(3.0, 0.0))),
         );
         assert_eq!(parser.parse().unwrap(), expected);
     }
+    #[test]
+    fn test_variable() {
+        let mut parser = Parser::new("x").unwrap();
+        let expected = Variable("x".to_string());
+        assert_eq!(parser.parse().unwrap(), expected);
+    }
+    #[test]
+    fn test_implicit_mul_variable() {
+        let mut parser = Parser::new("2x").unwrap();
+        let expected = Multiply(
+            Box::new(Number(Complex::new(2.0, 0.0))),
+            Box::new(Variable("x".to_string())),
+        );
+        assert_eq!(parser.parse().unwrap(), expected);
+    }
+    #[test]
+    fn test_conditional() {
+        let mut parser = Parser::new("1+1<3&&!0?4:5").unwrap();
+        let condition = LogicalAnd(
+            Box::new(Less(
+                Box::new(Add(
+                    Box::new(Number(Complex::new(1.0, 0.0))),
+                    Box::new(Number(Complex::new(1.0, 0.0))),
+                )),
+                Box::new(Number(Complex::new(3.0, 0.0))),
+            )),
+            Box::new(LogicalNot(Box::new(Number(Complex::new(0.0, 0.0))))),
+        );
+        let expected = Conditional(
+            Box::new(condition),
+            Box::new(Number(Complex::new(4.0, 0.0))),
+            Box::new(Number(Complex::new(5.0, 0.0))),
+        );
+        assert_eq!(parser.parse().unwrap(), expected);
+        let mut parser = Parser::new("1?2:3?4:5").unwrap();
+        let expected = Conditional(
+            Box::new(Number(Complex::new(1.0, 0.0))),
+            Box::new(Number(Complex::new(2.0, 0.0))),
+            Box::new(Conditional(
+                Box::new(Number(Complex::new(3.0, 0.0))),
+                Box::new(Number(Complex::new(4.0, 0.0))),
+                Box::new(Number(Complex::new(5.0, 0.0))),
+            )),
+        );
+        assert_eq!(parser.parse().unwrap(), expected);
+        let mut parser = Parser::new("if(0||1,2,3)").unwrap();
+        let expected = Conditional(
+            Box::new(LogicalOr(
+                Box::new(Number(Complex::new(0.0, 0.0))),
+                Box::new(Number(Complex::new(1.0, 0.0))),
+            )),
+            Box::new(Number(Complex::new(2.0, 0.0))),
+            Box::new(Number(Complex::new(3.0, 0.0))),
+        );
+        assert_eq!(parser.parse().unwrap(), expected);
+        let error = Parser::new("1?2").unwrap().parse().unwrap_err();
+        assert_eq!(error.kind(), ParseErrorKind::UnexpectedToken);
+        assert_eq!(error.expected(), [":"]);
+    }
 }
@@ -1,6 +1,7 @@
 use num_complex::Complex;
 
-use crate::utils::OperatorCategory;
+use crate::utils::{Function, OperatorCategory};
+use std::fmt;
 
 #[derive(Debug, PartialEq, Clone)]
 pub enum NativeFunction {
@@ -25,6 +26,22 @@ pub enum NativeFunction {
     Exp,
     Exp2,
     Abs,
+    Floor,
+    Ceil,
+    Round,
+    Truncate,
+    Mod,
+    Min,
+    Max,
+    Avg,
+    Med,
+    Re,
+    Im,
+    Arg,
+    Conj,
+    Polar,
+    Cis,
+    If,
 }
 
 #[derive(Debug, PartialEq, Clone)]
@@ -34,16 +51,35 @@ pub enum Token {
     Multiply,
     Divide,
     Caret,
+    ExclamationMark,
+    Modulo,
+    Equal,
+    NotEqual,
+    Less,
+    LessEqual,
+    Greater,
+    GreaterEqual,
+    DoubleAmpersand,
+    DoubleBar,
+    QuestionMark,
+    Colon,
     LeftParen,
     RightParen,
+    LeftFloor,
+    RightFloor,
+    LeftCeiling,
+    RightCeiling,
     E,
     Pi,
     Comma,
     DegToRad,
     RadToDeg,
+    Angle,
     ExplicitFunction(NativeFunction),
+    CustomFunction(Function<Complex<f64>>),
     Superscript(Complex<f64>),
     Num(Complex<f64>),
+    Identifier(String),
     Ans,
     Eof,
 }
@@ -52,11 +88,71 @@ impl Token {
     pub fn get_oper_prec(&self) -> OperatorCategory {
         use self::Token::*;
         match *self {
+            QuestionMark => OperatorCategory::Conditional,
+            DoubleBar => OperatorCategory::LogicalOr,
+            DoubleAmpersand => OperatorCategory::LogicalAnd,
+            Equal | NotEqual | Less | LessEqual | Greater | GreaterEqual => {
+                OperatorCategory::Comparison
+            }
             Add | Subtract => OperatorCategory::Additive,
-            Multiply | Divide | DegToRad | RadToDeg => OperatorCategory::Multiplicative,
+            Multiply | Divide | Modulo | DegToRad | RadToDeg | Angle => {
+                OperatorCategory::Multiplicative
+            }
             Caret | Superscript(_) => OperatorCategory::Power,
-            ExplicitFunction(_) => OperatorCategory::Functional,
+            ExclamationMark | ExplicitFunction(_) | CustomFunction(_) => {
+                OperatorCategory::Functional
+            }
             _ => OperatorCategory::DefaultZero,
         }
     }
+    pub fn is_closing_bracket(&self) -> bool {
+        use self::Token::*;
+        matches!(self, RightParen | RightFloor | RightCeiling)
+    }
+}
+
+impl fmt::Display for Token {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        use self::Token::*;
+        match self {
+            Add => write!(f, "+"),
+            Subtract => write!(f, "-"),
+            Multiply => write!(f, "*"),
+            Divide => write!(f, "/"),
+            Caret => write!(f, "^"),
+            ExclamationMark => write!(f, "!"),
+            Modulo => write!(f, "%"),
+            Equal => write!(f, "=="),
+            NotEqual => write!(f, "!="),
+            Less => write!(f, "<"),
+            LessEqual => write!(f, "<="),
+            Greater => write!(f, ">"),
+            GreaterEqual => write!(f, ">="),
+            DoubleAmpersand => write!(f, "&&"),
+            DoubleBar => write!(f, "||"),
+            QuestionMark => write!(f, "?"),
+            Colon => write!(f, ":"),
+            LeftParen => write!(f, "("),
+            RightParen => write!(f, ")"),
+            LeftFloor => write!(f, "⌊"),
+            RightFloor => write!(f, "⌋"),
+            LeftCeiling => write!(f, "⌈"),
+            RightCeiling => write!(f, "⌉"),
+            E => write!(f, "e"),
+            Pi => write!(f, "π"),
+            Comma => write!(f, ","),
+            DegToRad => write!(f, "°"),
+            RadToDeg => write!(f, "rad"),
+            Angle => write!(f, "∠"),
+            ExplicitFunction(function) => {
+                write!(f, "{}", format!("{:?}", function).to_lowercase())
+            }
+            CustomFunction(function) => write!(f, "{}", function.name()),
+            Superscript(value) => write!(f, "^{}", value),
+            Num(value) => write!(f, "{}", value),
+            Identifier(name) => write!(f, "{}", name),
+            Ans => write!(f, "@"),
+            Eof => write!(f, "end of input"),
+        }
+    }
 }
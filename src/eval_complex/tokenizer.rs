@@ -1,26 +1,45 @@
 use num_complex::Complex;
 
 use super::token::{NativeFunction, Token};
-use crate::utils::deserialize_superscript_number;
-use std::iter::Peekable;
-use std::str::Chars;
+use crate::utils::{
+    deserialize_number, deserialize_superscript_number, peek_identifier, unrecognized_token,
+    Cursor, Functions, ParseError,
+};
+
+/// The names read as constants or units rather than variables.
+pub const RESERVED_WORDS: [&str; 4] = ["e", "pi", "rad", "i"];
 
 pub struct Tokenizer<'a> {
-    expr: Peekable<Chars<'a>>,
+    expr: Cursor<'a>,
+    functions: Option<&'a Functions<Complex<f64>>>,
 }
 
 impl<'a> Tokenizer<'a> {
     pub fn new(new_expr: &'a str) -> Self {
         Tokenizer {
-            expr: new_expr.chars().peekable(),
+            expr: Cursor::new(new_expr),
+            functions: None,
         }
     }
-}
-
-impl<'a> Iterator for Tokenizer<'a> {
-    type Item = Token;
-
-    fn next(&mut self) -> Option<Token> {
+    pub fn with_functions(new_expr: &'a str, functions: &'a Functions<Complex<f64>>) -> Self {
+        Tokenizer {
+            expr: Cursor::new(new_expr),
+            functions: Some(functions),
+        }
+    }
+    /// The byte offset of the next token.
+    pub fn position(&self) -> usize {
+        self.expr.position()
+    }
+    /// Consume the whitespaces before the next token, which only separate tokens.
+    pub fn skip_whitespace(&mut self) {
+        self.expr.skip_whitespace()
+    }
+    /// The error explaining why no token could be read at the given byte offset.
+    pub fn error(&self, start: usize) -> ParseError {
+        unrecognized_token(self.expr.source(), start)
+    }
+    fn native_token(&mut self) -> Option<Token> {
         let current_char = self.expr.next();
 
         match current_char {
@@ -32,32 +51,75 @@ impl<'a> Iterator for Tokenizer<'a> {
             Some('^') => Some(Token::Caret),
             Some('(') => Some(Token::LeftParen),
             Some(')') => Some(Token::RightParen),
+            Some('!') => {
+                if let Some('=') = self.expr.peek() {
+                    self.expr.next();
+                    Some(Token::NotEqual)
+                } else {
+                    Some(Token::ExclamationMark)
+                }
+            }
+            Some('=') => {
+                if let Some('=') = self.expr.peek() {
+                    self.expr.next();
+                    Some(Token::Equal)
+                } else {
+                    None
+                }
+            }
+            Some('<') => {
+                if let Some('=') = self.expr.peek() {
+                    self.expr.next();
+                    Some(Token::LessEqual)
+                } else {
+                    Some(Token::Less)
+                }
+            }
+            Some('>') => {
+                if let Some('=') = self.expr.peek() {
+                    self.expr.next();
+                    Some(Token::GreaterEqual)
+                } else {
+                    Some(Token::Greater)
+                }
+            }
+            Some('&') => {
+                if let Some('&') = self.expr.peek() {
+                    self.expr.next();
+                    Some(Token::DoubleAmpersand)
+                } else {
+                    None
+                }
+            }
+            Some('|') => {
+                if let Some('|') = self.expr.peek() {
+                    self.expr.next();
+                    Some(Token::DoubleBar)
+                } else {
+                    None
+                }
+            }
+            Some('?') => Some(Token::QuestionMark),
+            Some(':') => Some(Token::Colon),
             Some(',') => Some(Token::Comma),
+            Some('%') => Some(Token::Modulo),
             Some('π') => Some(Token::Pi),
+            Some('⌊') => Some(Token::LeftFloor),
+            Some('⌋') => Some(Token::RightFloor),
+            Some('⌈') => Some(Token::LeftCeiling),
+            Some('⌉') => Some(Token::RightCeiling),
             Some('°') => Some(Token::DegToRad),
+            Some('∠') => Some(Token::Angle),
             Some('.') => {
                 let next_char = self.expr.peek()?;
                 if next_char.is_ascii_digit() {
-                    let mut number = "0".to_string();
-                    number.push(current_char?);
-                    while let Some(next_char) = self.expr.peek() {
-                        if next_char.is_ascii_digit() {
-                            number.push(self.expr.next()?);
-                        } else {
-                            break;
-                        }
-                    }
+                    let (mut number, exponent) = deserialize_number('.', &mut self.expr)?;
+                    number.push_str(&exponent);
                     if let Some('i') = self.expr.peek() {
                         self.expr.next()?;
-                        Some(Token::Num(Complex::new(
-                            0.0,
-                            number.parse::<f64>().unwrap(),
-                        )))
+                        Some(Token::Num(Complex::new(0.0, number.parse::<f64>().ok()?)))
                     } else {
-                        Some(Token::Num(Complex::new(
-                            number.parse::<f64>().unwrap(),
-                            0.0,
-                        )))
+                        Some(Token::Num(Complex::new(number.parse::<f64>().ok()?, 0.0)))
                     }
                 } else {
                     None
@@ -66,83 +128,71 @@ impl<'a> Iterator for Tokenizer<'a> {
             Some('⁰') => Some(Token::Superscript(Complex::new(
                 deserialize_superscript_number(&current_char?, &mut self.expr)
                     .parse::<f64>()
-                    .unwrap(),
+                    .ok()?,
                 0.0,
             ))),
             Some('¹') => Some(Token::Superscript(Complex::new(
                 deserialize_superscript_number(&current_char?, &mut self.expr)
                     .parse::<f64>()
-                    .unwrap(),
+                    .ok()?,
                 0.0,
             ))),
             Some('²') => Some(Token::Superscript(Complex::new(
                 deserialize_superscript_number(&current_char?, &mut self.expr)
                     .parse::<f64>()
-                    .unwrap(),
+                    .ok()?,
                 0.0,
             ))),
             Some('³') => Some(Token::Superscript(Complex::new(
                 deserialize_superscript_number(&current_char?, &mut self.expr)
                     .parse::<f64>()
-                    .unwrap(),
+                    .ok()?,
                 0.0,
             ))),
             Some('⁴') => Some(Token::Superscript(Complex::new(
                 deserialize_superscript_number(&current_char?, &mut self.expr)
                     .parse::<f64>()
-                    .unwrap(),
+                    .ok()?,
                 0.0,
             ))),
             Some('⁵') => Some(Token::Superscript(Complex::new(
                 deserialize_superscript_number(&current_char?, &mut self.expr)
                     .parse::<f64>()
-                    .unwrap(),
+                    .ok()?,
                 0.0,
             ))),
             Some('⁶') => Some(Token::Superscript(Complex::new(
                 deserialize_superscript_number(&current_char?, &mut self.expr)
                     .parse::<f64>()
-                    .unwrap(),
+                    .ok()?,
                 0.0,
             ))),
             Some('⁷') => Some(Token::Superscript(Complex::new(
                 deserialize_superscript_number(&current_char?, &mut self.expr)
                     .parse::<f64>()
-                    .unwrap(),
+                    .ok()?,
                 0.0,
             ))),
             Some('⁸') => Some(Token::Superscript(Complex::new(
                 deserialize_superscript_number(&current_char?, &mut self.expr)
                     .parse::<f64>()
-                    .unwrap(),
+                    .ok()?,
                 0.0,
             ))),
             Some('⁹') => Some(Token::Superscript(Complex::new(
                 deserialize_superscript_number(&current_char?, &mut self.expr)
                     .parse::<f64>()
-                    .unwrap(),
+                    .ok()?,
                 0.0,
             ))),
             Some('0'..='9') => {
-                let mut number = current_char?.to_string();
-                while let Some(next_char) = self.expr.peek() {
-                    if next_char.is_ascii_digit() || next_char == &'.' {
-                        number.push(self.expr.next()?);
-                    } else {
-                        break;
-                    }
-                }
+                let (mut number, exponent) = deserialize_number(current_char?, &mut self.expr)?;
+                number.push_str(&exponent);
                 if let Some('i') = self.expr.peek() {
                     self.expr.next()?;
-                    Some(Token::Num(Complex::new(
-                        0.0,
-                        number.parse::<f64>().unwrap(),
-                    )))
+                    Some(Token::Num(Complex::new(0.0, number.parse::<f64>().ok()?)))
                 } else {
-                    Some(Token::Num(Complex::new(
-                        number.parse::<f64>().unwrap(),
-                        0.0,
-                    )))
+                    Some(Token::Num(Complex::new(number.parse::<f64>().ok()?, 0.0)))
                 }
             }
             Some('a') => match self.expr.clone().take(6).collect::<String>().as_str() {
@@ -189,21 +239,41 @@ impl<'a> Iterator for Tokenizer<'a> {
                                 self.expr.by_ref().take(2).for_each(drop);
                                 Some(Token::ExplicitFunction(NativeFunction::Abs))
                             }
+                            "rg(" => {
+                                self.expr.by_ref().take(2).for_each(drop);
+                                Some(Token::ExplicitFunction(NativeFunction::Arg))
+                            }
+                            "vg(" => {
+                                self.expr.by_ref().take(2).for_each(drop);
+                                Some(Token::ExplicitFunction(NativeFunction::Avg))
+                            }
                             _ => None,
                         },
                     },
                 },
             },
             Some('c') => match self.expr.clone().take(4).collect::<String>().as_str() {
+                "eil(" => {
+                    self.expr.by_ref().take(3).for_each(drop);
+                    Some(Token::ExplicitFunction(NativeFunction::Ceil))
+                }
                 "osh(" => {
                     self.expr.by_ref().take(3).for_each(drop);
                     Some(Token::ExplicitFunction(NativeFunction::Cosh))
                 }
+                "onj(" => {
+                    self.expr.by_ref().take(3).for_each(drop);
+                    Some(Token::ExplicitFunction(NativeFunction::Conj))
+                }
                 _ => match self.expr.clone().take(3).collect::<String>().as_str() {
                     "os(" => {
                         self.expr.by_ref().take(2).for_each(drop);
                         Some(Token::ExplicitFunction(NativeFunction::Cos))
                     }
+                    "is(" => {
+                        self.expr.by_ref().take(2).for_each(drop);
+                        Some(Token::ExplicitFunction(NativeFunction::Cis))
+                    }
                     _ => None,
                 },
             },
@@ -218,7 +288,25 @@ impl<'a> Iterator for Tokenizer<'a> {
                     Some(Token::E)
                 }
             }
-            Some('i') => Some(Token::Num(Complex::new(0.0, 1.0))),
+            Some('f') => {
+                if self.expr.clone().take(5).collect::<String>() == "loor(" {
+                    self.expr.by_ref().take(4).for_each(drop);
+                    Some(Token::ExplicitFunction(NativeFunction::Floor))
+                } else {
+                    None
+                }
+            }
+            Some('i') => {
+                if self.expr.clone().take(2).collect::<String>() == "m(" {
+                    self.expr.by_ref().take(1).for_each(drop);
+                    Some(Token::ExplicitFunction(NativeFunction::Im))
+                } else if self.expr.clone().take(2).collect::<String>() == "f(" {
+                    self.expr.by_ref().take(1).for_each(drop);
+                    Some(Token::ExplicitFunction(NativeFunction::If))
+                } else {
+                    Some(Token::Num(Complex::new(0.0, 1.0)))
+                }
+            }
             Some('l') => {
                 if self.expr.clone().take(3).collect::<String>() == "og(" {
                     self.expr.by_ref().take(2).for_each(drop);
@@ -237,8 +325,44 @@ impl<'a> Iterator for Tokenizer<'a> {
                     }
                 }
             }
+            Some('m') => match self.expr.clone().take(6).collect::<String>().as_str() {
+                "edian(" => {
+                    self.expr.by_ref().take(5).for_each(drop);
+                    Some(Token::ExplicitFunction(NativeFunction::Med))
+                }
+                _ => match self.expr.clone().take(3).collect::<String>().as_str() {
+                    "in(" => {
+                        self.expr.by_ref().take(2).for_each(drop);
+                        Some(Token::ExplicitFunction(NativeFunction::Min))
+                    }
+                    "ax(" => {
+                        self.expr.by_ref().take(2).for_each(drop);
+                        Some(Token::ExplicitFunction(NativeFunction::Max))
+                    }
+                    "od(" => {
+                        self.expr.by_ref().take(2).for_each(drop);
+                        Some(Token::ExplicitFunction(NativeFunction::Mod))
+                    }
+                    "ed(" => {
+                        self.expr.by_ref().take(2).for_each(drop);
+                        Some(Token::ExplicitFunction(NativeFunction::Med))
+                    }
+                    _ => None,
+                },
+            },
+            Some('n') => {
+                if self.expr.clone().take(4).collect::<String>() == "orm(" {
+                    self.expr.by_ref().take(3).for_each(drop);
+                    Some(Token::ExplicitFunction(NativeFunction::Abs))
+                } else {
+                    None
+                }
+            }
             Some('p') => {
-                if self.expr.clone().take(3).collect::<String>() == "ow(" {
+                if self.expr.clone().take(5).collect::<String>() == "olar(" {
+                    self.expr.by_ref().take(4).for_each(drop);
+                    Some(Token::ExplicitFunction(NativeFunction::Polar))
+                } else if self.expr.clone().take(3).collect::<String>() == "ow(" {
                     self.expr.by_ref().take(2).for_each(drop);
                     Some(Token::ExplicitFunction(NativeFunction::Pow))
                 } else if self.expr.clone().take(1).collect::<String>() == "i" {
@@ -249,7 +373,13 @@ impl<'a> Iterator for Tokenizer<'a> {
                 }
             }
             Some('r') => {
-                if self.expr.clone().take(4).collect::<String>() == "oot(" {
+                if self.expr.clone().take(5).collect::<String>() == "ound(" {
+                    self.expr.by_ref().take(4).for_each(drop);
+                    Some(Token::ExplicitFunction(NativeFunction::Round))
+                } else if self.expr.clone().take(2).collect::<String>() == "e(" {
+                    self.expr.by_ref().take(1).for_each(drop);
+                    Some(Token::ExplicitFunction(NativeFunction::Re))
+                } else if self.expr.clone().take(4).collect::<String>() == "oot(" {
                     self.expr.by_ref().take(3).for_each(drop);
                     Some(Token::ExplicitFunction(NativeFunction::Root))
                 } else if self.expr.clone().take(2).collect::<String>() == "ad" {
@@ -277,7 +407,13 @@ impl<'a> Iterator for Tokenizer<'a> {
                 },
             },
             Some('t') => {
-                if self.expr.clone().take(4).collect::<String>() == "anh(" {
+                if self.expr.clone().take(8).collect::<String>() == "runcate(" {
+                    self.expr.by_ref().take(7).for_each(drop);
+                    Some(Token::ExplicitFunction(NativeFunction::Truncate))
+                } else if self.expr.clone().take(5).collect::<String>() == "runc(" {
+                    self.expr.by_ref().take(4).for_each(drop);
+                    Some(Token::ExplicitFunction(NativeFunction::Truncate))
+                } else if self.expr.clone().take(4).collect::<String>() == "anh(" {
                     self.expr.by_ref().take(3).for_each(drop);
                     Some(Token::ExplicitFunction(NativeFunction::Tanh))
                 } else if self.expr.clone().take(3).collect::<String>() == "an(" {
@@ -293,6 +429,44 @@ impl<'a> Iterator for Tokenizer<'a> {
     }
 }
 
+impl<'a> Iterator for Tokenizer<'a> {
+    type Item = Token;
+
+    fn next(&mut self) -> Option<Token> {
+        self.expr.skip_whitespace();
+        let (identifier, next_char) = peek_identifier(&self.expr);
+        if !identifier.is_empty() {
+            if next_char == Some('(') {
+                if let Some(function) = self
+                    .functions
+                    .and_then(|functions| functions.get(&identifier))
+                {
+                    let function = function.clone();
+                    self.expr.by_ref().take(identifier.len()).for_each(drop);
+                    return Some(Token::CustomFunction(function));
+                }
+            } else if !RESERVED_WORDS.contains(&identifier.as_str()) {
+                self.expr.by_ref().take(identifier.len()).for_each(drop);
+                return Some(Token::Identifier(identifier));
+            }
+        }
+        if next_char == Some('(') && !identifier.is_empty() {
+            // The native functions are matched along with their `(`, which whitespace may set
+            // apart from the name.
+            let call = format!("{}(", identifier);
+            let mut native = Tokenizer::new(&call);
+            let token = native.native_token()?;
+            if native.position() != identifier.len() {
+                // The word before the arguments is not a native function either.
+                return None;
+            }
+            self.expr.by_ref().take(identifier.len()).for_each(drop);
+            return Some(token);
+        }
+        self.native_token()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -362,6 +536,34 @@ mod tests {
         )
     }
     #[test]
+    fn test_scientific_number() {
+        let mut tokenizer = Tokenizer::new("1.5e-3");
+        assert_eq!(
+            tokenizer.next().unwrap(),
+            Token::Num(Complex::new(0.0015, 0.0))
+        );
+        let mut tokenizer = Tokenizer::new("6.022E23i");
+        assert_eq!(
+            tokenizer.next().unwrap(),
+            Token::Num(Complex::new(0.0, 6.022e23))
+        );
+        let mut tokenizer = Tokenizer::new(".5e+2");
+        assert_eq!(
+            tokenizer.next().unwrap(),
+            Token::Num(Complex::new(50.0, 0.0))
+        );
+    }
+    #[test]
+    fn test_e_const_after_number() {
+        let mut tokenizer = Tokenizer::new("2e-x");
+        assert_eq!(
+            tokenizer.next().unwrap(),
+            Token::Num(Complex::new(2.0, 0.0))
+        );
+        assert_eq!(tokenizer.next().unwrap(), Token::E);
+        assert_eq!(tokenizer.next().unwrap(), Token::Subtract);
+    }
+    #[test]
     fn test_e_const() {
         let mut tokenizer = Tokenizer::new("e");
         assert_eq!(tokenizer.next().unwrap(), Token::E)
@@ -407,6 +609,11 @@ mod tests {
         assert_eq!(tokenizer.next().unwrap(), Token::RadToDeg)
     }
     #[test]
+    fn test_angle_operator() {
+        let mut tokenizer = Tokenizer::new("∠");
+        assert_eq!(tokenizer.next().unwrap(), Token::Angle)
+    }
+    #[test]
     fn test_add_operator() {
         let mut tokenizer = Tokenizer::new("+");
         assert_eq!(tokenizer.next().unwrap(), Token::Add)
@@ -623,4 +830,137 @@ mod tests {
             Token::ExplicitFunction(NativeFunction::Abs)
         )
     }
+    #[test]
+    fn test_re_function() {
+        let mut tokenizer = Tokenizer::new("re(i)");
+        assert_eq!(
+            tokenizer.next().unwrap(),
+            Token::ExplicitFunction(NativeFunction::Re)
+        )
+    }
+    #[test]
+    fn test_im_function() {
+        let mut tokenizer = Tokenizer::new("im(i)");
+        assert_eq!(
+            tokenizer.next().unwrap(),
+            Token::ExplicitFunction(NativeFunction::Im)
+        )
+    }
+    #[test]
+    fn test_arg_function() {
+        let mut tokenizer = Tokenizer::new("arg(i)");
+        assert_eq!(
+            tokenizer.next().unwrap(),
+            Token::ExplicitFunction(NativeFunction::Arg)
+        )
+    }
+    #[test]
+    fn test_conj_function() {
+        let mut tokenizer = Tokenizer::new("conj(i)");
+        assert_eq!(
+            tokenizer.next().unwrap(),
+            Token::ExplicitFunction(NativeFunction::Conj)
+        )
+    }
+    #[test]
+    fn test_norm_function() {
+        let mut tokenizer = Tokenizer::new("norm(i)");
+        assert_eq!(
+            tokenizer.next().unwrap(),
+            Token::ExplicitFunction(NativeFunction::Abs)
+        )
+    }
+    #[test]
+    fn test_polar_function() {
+        let mut tokenizer = Tokenizer::new("polar(1,pi)");
+        assert_eq!(
+            tokenizer.next().unwrap(),
+            Token::ExplicitFunction(NativeFunction::Polar)
+        )
+    }
+    #[test]
+    fn test_cis_function() {
+        let mut tokenizer = Tokenizer::new("cis(pi)");
+        assert_eq!(
+            tokenizer.next().unwrap(),
+            Token::ExplicitFunction(NativeFunction::Cis)
+        )
+    }
+    #[test]
+    fn test_rounding_operators() {
+        let mut tokenizer = Tokenizer::new("⌊⌋⌈⌉!%");
+        assert_eq!(tokenizer.next().unwrap(), Token::LeftFloor);
+        assert_eq!(tokenizer.next().unwrap(), Token::RightFloor);
+        assert_eq!(tokenizer.next().unwrap(), Token::LeftCeiling);
+        assert_eq!(tokenizer.next().unwrap(), Token::RightCeiling);
+        assert_eq!(tokenizer.next().unwrap(), Token::ExclamationMark);
+        assert_eq!(tokenizer.next().unwrap(), Token::Modulo);
+    }
+    #[test]
+    fn test_real_functions() {
+        let mut tokenizer =
+            Tokenizer::new("floor(ceil(round(trunc(truncate(mod(min(max(avg(med(median(");
+        for function in [
+            NativeFunction::Floor,
+            NativeFunction::Ceil,
+            NativeFunction::Round,
+            NativeFunction::Truncate,
+            NativeFunction::Truncate,
+            NativeFunction::Mod,
+            NativeFunction::Min,
+            NativeFunction::Max,
+            NativeFunction::Avg,
+            NativeFunction::Med,
+            NativeFunction::Med,
+        ] {
+            assert_eq!(tokenizer.next().unwrap(), Token::ExplicitFunction(function));
+            assert_eq!(tokenizer.next().unwrap(), Token::LeftParen);
+        }
+    }
+    #[test]
+    fn test_identifier() {
+        let mut tokenizer = Tokenizer::new("price_2*x");
+        assert_eq!(
+            tokenizer.next().unwrap(),
+            Token::Identifier("price_2".to_string())
+        );
+        assert_eq!(tokenizer.next().unwrap(), Token::Multiply);
+        assert_eq!(
+            tokenizer.next().unwrap(),
+            Token::Identifier("x".to_string())
+        );
+    }
+    #[test]
+    fn test_identifier_starting_with_reserved_word() {
+        let mut tokenizer = Tokenizer::new("impedance");
+        assert_eq!(
+            tokenizer.next().unwrap(),
+            Token::Identifier("impedance".to_string())
+        )
+    }
+    #[test]
+    fn test_logical_operators() {
+        let mut tokenizer = Tokenizer::new("==!=<=<>=>&&||!?:if(i");
+        for token in [
+            Token::Equal,
+            Token::NotEqual,
+            Token::LessEqual,
+            Token::Less,
+            Token::GreaterEqual,
+            Token::Greater,
+            Token::DoubleAmpersand,
+            Token::DoubleBar,
+            Token::ExclamationMark,
+            Token::QuestionMark,
+            Token::Colon,
+            Token::ExplicitFunction(NativeFunction::If),
+        ] {
+            assert_eq!(tokenizer.next().unwrap(), token);
+        }
+        assert_eq!(tokenizer.next().unwrap(), Token::LeftParen);
+        assert_eq!(
+            tokenizer.next().unwrap(),
+            Token::Num(Complex::new(0.0, 1.0))
+        );
+    }
 }
@@ -1,7 +1,9 @@
-use std::{error, sync::Arc};
+use std::sync::Arc;
 
 use rust_decimal::prelude::*;
 
+use crate::utils::{Context, EvalError, Function};
+
 #[derive(Debug, Clone, PartialEq)]
 pub enum Node {
     Add(Box<Node>, Box<Node>),
@@ -13,6 +15,19 @@ pub enum Node {
     Factorial(Box<Node>),
     Abs(Box<Node>),
     Sign(Box<Node>),
+    Sin(Box<Node>),
+    Cos(Box<Node>),
+    Tan(Box<Node>),
+    Sinh(Box<Node>),
+    Cosh(Box<Node>),
+    Tanh(Box<Node>),
+    Arsinh(Box<Node>),
+    Arcosh(Box<Node>),
+    Artanh(Box<Node>),
+    Asin(Box<Node>),
+    Acos(Box<Node>),
+    Atan(Box<Node>),
+    Atan2(Box<Node>, Box<Node>),
     Min(Arc<Vec<Node>>),
     Max(Arc<Vec<Node>>),
     Avg(Arc<Vec<Node>>),
@@ -20,6 +35,7 @@ pub enum Node {
     Floor(Box<Node>),
     Ceil(Box<Node>),
     Round(Box<Node>),
+    RoundTo(Box<Node>, Box<Node>),
     Truncate(Box<Node>),
     Sqrt(Box<Node>),
     Root(Box<Node>, Box<Node>),
@@ -32,127 +48,514 @@ pub enum Node {
     Pow(Box<Node>, Box<Node>),
     Log(Box<Node>, Box<Node>),
     Number(Decimal),
+    CustomFunction(Function<Decimal>, Arc<Vec<Node>>),
+    Equal(Box<Node>, Box<Node>),
+    NotEqual(Box<Node>, Box<Node>),
+    Less(Box<Node>, Box<Node>),
+    LessEqual(Box<Node>, Box<Node>),
+    Greater(Box<Node>, Box<Node>),
+    GreaterEqual(Box<Node>, Box<Node>),
+    LogicalAnd(Box<Node>, Box<Node>),
+    LogicalOr(Box<Node>, Box<Node>),
+    LogicalNot(Box<Node>),
+    Conditional(Box<Node>, Box<Node>, Box<Node>),
+    Variable(String),
+    Ans,
 }
 
-fn gamma(a: Decimal) -> Decimal {
-    let mut s = Decimal::new(2485740891387535655, 27);
+fn gamma(a: Decimal) -> Option<Decimal> {
+    let mut s = Decimal::new(2485740891387535655, 23);
     if a < Decimal::new(5, 1) {
-        s += Decimal::new(1051423785817219742, 20) / (Decimal::new(1, 0) - a);
-        s += Decimal::new(-3456870972220162354, 22) / (Decimal::new(2, 0) - a);
-        s += Decimal::new(4512277094668948237, 20) / (Decimal::new(3, 0) - a);
-        s += Decimal::new(-2982852253235766557, 22) / (Decimal::new(4, 0) - a);
-        s += Decimal::new(1056397115771267131, 22) / (Decimal::new(5, 0) - a);
-        s += Decimal::new(-1954287731916458696, 23) / (Decimal::new(6, 0) - a);
-        s += Decimal::new(1709705434044412243, 24) / (Decimal::new(7, 0) - a);
-        s += Decimal::new(-5719261174043057813, 24) / (Decimal::new(8, 0) - a);
-        s += Decimal::new(4633994733599056367, 28) / (Decimal::new(9, 0) - a);
-        s += Decimal::new(-2719949084886077039, 31) / (Decimal::new(10, 0) - a);
-        let compute_sin = (Decimal::new(3141592653589793238, 18) * a).sin(); // 3.14159265358979323846264338327950288419716939937510582
+        s += Decimal::new(1051423785817219742, 18).checked_div(Decimal::new(1, 0) - a)?;
+        s += Decimal::new(-3456870972220162354, 18).checked_div(Decimal::new(2, 0) - a)?;
+        s += Decimal::new(4512277094668948237, 18).checked_div(Decimal::new(3, 0) - a)?;
+        s += Decimal::new(-2982852253235766557, 18).checked_div(Decimal::new(4, 0) - a)?;
+        s += Decimal::new(1056397115771267131, 18).checked_div(Decimal::new(5, 0) - a)?;
+        s += Decimal::new(-1954287731916458696, 19).checked_div(Decimal::new(6, 0) - a)?;
+        s += Decimal::new(1709705434044412243, 20).checked_div(Decimal::new(7, 0) - a)?;
+        s += Decimal::new(-5719261174043057813, 22).checked_div(Decimal::new(8, 0) - a)?;
+        s += Decimal::new(4633994733599056367, 24).checked_div(Decimal::new(9, 0) - a)?;
+        s += Decimal::new(-2719949084886077039, 27).checked_div(Decimal::new(10, 0) - a)?;
+        let compute_sin = Decimal::new(3141592653589793238, 18)
+            .checked_mul(a)?
+            .checked_sin()?; // 3.14159265358979323846264338327950288419716939937510582
         let compute_pow = ((a - Decimal::new(10400511, 6)) / Decimal::new(2718281828459045235, 18))
-            .powd(Decimal::new(5, 1) - a);
-        Decimal::new(3141592653589793238, 18)
-            / (compute_sin * s * Decimal::new(1860382734205265717, 18) * compute_pow)
+            .checked_powd(Decimal::new(5, 1) - a)?;
+        Decimal::new(3141592653589793238, 18).checked_div(
+            compute_sin
+                .checked_mul(s)?
+                .checked_mul(Decimal::new(1860382734205265717, 18))?
+                .checked_mul(compute_pow)?,
+        )
+    } else {
+        s += Decimal::new(1051423785817219742, 18).checked_div(a)?;
+        s += Decimal::new(-3456870972220162354, 18).checked_div(a + Decimal::new(1, 0))?;
+        s += Decimal::new(4512277094668948237, 18).checked_div(a + Decimal::new(2, 0))?;
+        s += Decimal::new(-2982852253235766557, 18).checked_div(a + Decimal::new(3, 0))?;
+        s += Decimal::new(1056397115771267131, 18).checked_div(a + Decimal::new(4, 0))?;
+        s += Decimal::new(-1954287731916458696, 19).checked_div(a + Decimal::new(5, 0))?;
+        s += Decimal::new(1709705434044412243, 20).checked_div(a + Decimal::new(6, 0))?;
+        s += Decimal::new(-5719261174043057813, 22).checked_div(a + Decimal::new(7, 0))?;
+        s += Decimal::new(4633994733599056367, 24).checked_div(a + Decimal::new(8, 0))?;
+        s += Decimal::new(-2719949084886077039, 27).checked_div(a + Decimal::new(9, 0))?;
+        let compute_pow = (a.checked_add(Decimal::new(10400511, 6))?
+            / Decimal::new(2718281828459045235, 18))
+        .checked_powd(a - Decimal::new(5, 1))?;
+        s.checked_mul(Decimal::new(1860382734205265717, 18))?
+            .checked_mul(compute_pow)
+    }
+}
+
+fn ln(function: &str, value: Decimal) -> Result<Decimal, EvalError> {
+    value
+        .checked_ln()
+        .ok_or_else(|| EvalError::Domain(function.to_string()))
+}
+
+/// Sum a power series whose successive terms are produced by `next_term`, until they stop mattering.
+fn series(first: Decimal, mut next_term: impl FnMut(Decimal, i64) -> Decimal) -> Decimal {
+    let mut term = first;
+    let mut sum = first;
+    for n in 1.. {
+        term = next_term(term, n);
+        let next = sum + term;
+        if next == sum {
+            break;
+        }
+        sum = next;
+    }
+    sum
+}
+
+fn tan(x: Decimal) -> Result<Decimal, EvalError> {
+    // `checked_tan` is only accurate to about ten digits, unlike `checked_sin` and `checked_cos`.
+    let cos = x.checked_cos().ok_or(EvalError::Overflow)?;
+    if cos.is_zero() {
+        return Err(EvalError::Domain("tan".to_string()));
+    }
+    x.checked_sin()
+        .ok_or(EvalError::Overflow)?
+        .checked_div(cos)
+        .ok_or(EvalError::Overflow)
+}
+
+fn atan(x: Decimal) -> Decimal {
+    if x.abs() > Decimal::ONE {
+        return x.signum() * Decimal::HALF_PI - atan(Decimal::ONE / x);
+    }
+    // atan(x) = 2 atan(x / (1 + sqrt(1 + x²))) shrinks the argument until the series converges fast.
+    let mut x = x;
+    let mut scale = Decimal::ONE;
+    while x.abs() > Decimal::new(125, 3) {
+        x /= Decimal::ONE + (Decimal::ONE + x * x).sqrt().unwrap_or(Decimal::ONE);
+        scale *= Decimal::TWO;
+    }
+    let square = x * x;
+    let sum = series(x, |term, n| {
+        -term * square * Decimal::new(2 * n - 1, 0) / Decimal::new(2 * n + 1, 0)
+    });
+    sum * scale
+}
+
+fn atan2(y: Decimal, x: Decimal) -> Decimal {
+    if y.abs() > x.abs() {
+        y.signum() * Decimal::HALF_PI - atan(x / y)
+    } else if x > Decimal::ZERO {
+        atan(y / x)
+    } else if x.is_zero() {
+        Decimal::ZERO
+    } else if y < Decimal::ZERO {
+        atan(y / x) - Decimal::PI
+    } else {
+        atan(y / x) + Decimal::PI
+    }
+}
+
+fn asin(x: Decimal) -> Result<Decimal, EvalError> {
+    if x.abs() > Decimal::ONE {
+        return Err(EvalError::Domain("asin".to_string()));
+    }
+    match (Decimal::ONE - x * x).sqrt() {
+        Some(cos) if !cos.is_zero() => Ok(atan(x / cos)),
+        _ => Ok(x.signum() * Decimal::HALF_PI),
+    }
+}
+
+fn sinh(x: Decimal) -> Result<Decimal, EvalError> {
+    if x.is_sign_negative() {
+        return Ok(-sinh(-x)?);
+    }
+    if x < Decimal::ONE {
+        // Avoid the cancellation in (e^x - e^-x) / 2 near zero.
+        let square = x * x;
+        return Ok(series(x, |term, n| {
+            term * square / Decimal::new(2 * n * (2 * n + 1), 0)
+        }));
+    }
+    let exp = x.checked_exp().ok_or(EvalError::Overflow)?;
+    Ok((exp - Decimal::ONE / exp) / Decimal::TWO)
+}
+
+fn cosh(x: Decimal) -> Result<Decimal, EvalError> {
+    let exp = x.abs().checked_exp().ok_or(EvalError::Overflow)?;
+    Ok((exp + Decimal::ONE / exp) / Decimal::TWO)
+}
+
+fn tanh(x: Decimal) -> Result<Decimal, EvalError> {
+    // Past this point tanh(x) rounds to ±1 and e^x would overflow.
+    if x.abs() > Decimal::new(33, 0) {
+        return Ok(x.signum());
+    }
+    Ok(sinh(x)? / cosh(x)?)
+}
+
+/// The logarithm of `x + sqrt(x² + offset)`, falling back to `ln(2x)` where `x²` would overflow.
+fn ln_hypot(function: &str, x: Decimal, offset: Decimal) -> Result<Decimal, EvalError> {
+    if x > Decimal::new(100_000_000_000_000, 0) {
+        return Ok(ln(function, x)? + Decimal::TWO.ln());
+    }
+    match (x * x + offset).sqrt() {
+        Some(root) => ln(function, x + root),
+        None => Err(EvalError::Domain(function.to_string())),
+    }
+}
+
+/// 1 when the condition holds and 0 otherwise.
+fn truth(condition: bool) -> Decimal {
+    if condition {
+        Decimal::ONE
     } else {
-        s += Decimal::new(1051423785817219742, 20) / a;
-        s += Decimal::new(-3456870972220162354, 22) / (a + Decimal::new(1, 0));
-        s += Decimal::new(4512277094668948237, 20) / (a + Decimal::new(2, 0));
-        s += Decimal::new(-2982852253235766557, 22) / (a + Decimal::new(3, 0));
-        s += Decimal::new(1056397115771267131, 22) / (a + Decimal::new(4, 0));
-        s += Decimal::new(-1954287731916458696, 23) / (a + Decimal::new(5, 0));
-        s += Decimal::new(1709705434044412243, 24) / (a + Decimal::new(6, 0));
-        s += Decimal::new(-5719261174043057813, 24) / (a + Decimal::new(7, 0));
-        s += Decimal::new(4633994733599056367, 28) / (a + Decimal::new(8, 0));
-        s += Decimal::new(-2719949084886077039, 31) / (a + Decimal::new(9, 0));
-        let compute_pow = ((a + Decimal::new(10400511, 6)) / Decimal::new(2718281828459045235, 18))
-            .powd(a - Decimal::new(5, 1));
-        s * Decimal::new(1860382734205265717, 18) * compute_pow
-    }
-}
-
-pub fn eval(expr: Node) -> Result<Decimal, Box<dyn error::Error>> {
+        Decimal::ZERO
+    }
+}
+
+pub fn eval(
+    expr: &Node,
+    placeholder: Decimal,
+    context: &dyn Context<Decimal>,
+) -> Result<Decimal, EvalError> {
+    eval_with_strategy(
+        expr,
+        placeholder,
+        context,
+        RoundingStrategy::MidpointNearestEven,
+    )
+}
+
+pub fn eval_with_strategy(
+    expr: &Node,
+    placeholder: Decimal,
+    context: &dyn Context<Decimal>,
+    strategy: RoundingStrategy,
+) -> Result<Decimal, EvalError> {
     use self::Node::*;
     match expr {
-        Number(i) => Ok(i),
-        Add(expr1, expr2) => Ok(eval(*expr1)? + eval(*expr2)?),
-        Subtract(expr1, expr2) => Ok(eval(*expr1)? - eval(*expr2)?),
-        Multiply(expr1, expr2) => Ok(eval(*expr1)? * eval(*expr2)?),
-        Divide(expr1, expr2) => Ok(eval(*expr1)? / eval(*expr2)?),
-        Modulo(expr1, expr2) => Ok(eval(*expr1)? % eval(*expr2)?),
-        Negative(expr1) => Ok(-(eval(*expr1)?)),
-        Abs(sub_expr) => Ok(eval(*sub_expr)?.abs()),
-        Floor(sub_expr) => Ok(eval(*sub_expr)?.floor()),
-        Ceil(sub_expr) => Ok(eval(*sub_expr)?.ceil()),
-        Round(sub_expr) => Ok(eval(*sub_expr)?.round()),
-        Truncate(sub_expr) => Ok(eval(*sub_expr)?.trunc()),
-        Sign(sub_expr) => Ok(eval(*sub_expr)?.signum()),
-        Ln(sub_expr) => Ok(eval(*sub_expr)?.ln()),
-        Lb(sub_expr) => Ok(eval(*sub_expr)?.ln() / Decimal::new(2, 0).ln()),
-        Exp(sub_expr) => Ok(eval(*sub_expr)?.exp()),
-        Exp2(sub_expr) => Ok(Decimal::new(2, 0).powd(eval(*sub_expr)?)),
-        Pow(expr1, expr2) => Ok(eval(*expr1)?.powd(eval(*expr2)?)),
-        Log(expr1, expr2) => Ok(eval(*expr1)?.ln() / eval(*expr2)?.ln()),
+        Number(i) => Ok(*i),
+        Ans => Ok(placeholder),
+        CustomFunction(function, args) => {
+            let mut values = vec![];
+            for arg in args.iter() {
+                values.push(eval_with_strategy(arg, placeholder, context, strategy)?);
+            }
+            function.call(&values)
+        }
+        Variable(name) => match context.get(name) {
+            Some(value) => Ok(value),
+            None => Err(EvalError::UndefinedVariable(name.clone())),
+        },
+        Add(expr1, expr2) => eval_with_strategy(expr1, placeholder, context, strategy)?
+            .checked_add(eval_with_strategy(expr2, placeholder, context, strategy)?)
+            .ok_or(EvalError::Overflow),
+        Subtract(expr1, expr2) => eval_with_strategy(expr1, placeholder, context, strategy)?
+            .checked_sub(eval_with_strategy(expr2, placeholder, context, strategy)?)
+            .ok_or(EvalError::Overflow),
+        Multiply(expr1, expr2) => eval_with_strategy(expr1, placeholder, context, strategy)?
+            .checked_mul(eval_with_strategy(expr2, placeholder, context, strategy)?)
+            .ok_or(EvalError::Overflow),
+        Divide(expr1, expr2) => {
+            let dividend = eval_with_strategy(expr1, placeholder, context, strategy)?;
+            let divisor = eval_with_strategy(expr2, placeholder, context, strategy)?;
+            if divisor.is_zero() {
+                return Err(EvalError::DivisionByZero);
+            }
+            dividend.checked_div(divisor).ok_or(EvalError::Overflow)
+        }
+        Modulo(expr1, expr2) => {
+            let dividend = eval_with_strategy(expr1, placeholder, context, strategy)?;
+            let divisor = eval_with_strategy(expr2, placeholder, context, strategy)?;
+            if divisor.is_zero() {
+                return Err(EvalError::DivisionByZero);
+            }
+            dividend.checked_rem(divisor).ok_or(EvalError::Overflow)
+        }
+        Equal(expr1, expr2) => Ok(truth(
+            eval_with_strategy(expr1, placeholder, context, strategy)?
+                == eval_with_strategy(expr2, placeholder, context, strategy)?,
+        )),
+        NotEqual(expr1, expr2) => Ok(truth(
+            eval_with_strategy(expr1, placeholder, context, strategy)?
+                != eval_with_strategy(expr2, placeholder, context, strategy)?,
+        )),
+        Less(expr1, expr2) => Ok(truth(
+            eval_with_strategy(expr1, placeholder, context, strategy)?
+                < eval_with_strategy(expr2, placeholder, context, strategy)?,
+        )),
+        LessEqual(expr1, expr2) => Ok(truth(
+            eval_with_strategy(expr1, placeholder, context, strategy)?
+                <= eval_with_strategy(expr2, placeholder, context, strategy)?,
+        )),
+        Greater(expr1, expr2) => Ok(truth(
+            eval_with_strategy(expr1, placeholder, context, strategy)?
+                > eval_with_strategy(expr2, placeholder, context, strategy)?,
+        )),
+        GreaterEqual(expr1, expr2) => Ok(truth(
+            eval_with_strategy(expr1, placeholder, context, strategy)?
+                >= eval_with_strategy(expr2, placeholder, context, strategy)?,
+        )),
+        LogicalAnd(expr1, expr2) => Ok(truth(
+            !eval_with_strategy(expr1, placeholder, context, strategy)?.is_zero()
+                && !eval_with_strategy(expr2, placeholder, context, strategy)?.is_zero(),
+        )),
+        LogicalOr(expr1, expr2) => Ok(truth(
+            !eval_with_strategy(expr1, placeholder, context, strategy)?.is_zero()
+                || !eval_with_strategy(expr2, placeholder, context, strategy)?.is_zero(),
+        )),
+        LogicalNot(expr1) => Ok(truth(
+            eval_with_strategy(expr1, placeholder, context, strategy)?.is_zero(),
+        )),
+        Conditional(condition, expr1, expr2) => {
+            if !eval_with_strategy(condition, placeholder, context, strategy)?.is_zero() {
+                eval_with_strategy(expr1, placeholder, context, strategy)
+            } else {
+                eval_with_strategy(expr2, placeholder, context, strategy)
+            }
+        }
+        Negative(expr1) => Ok(-(eval_with_strategy(expr1, placeholder, context, strategy)?)),
+        Abs(sub_expr) => Ok(eval_with_strategy(sub_expr, placeholder, context, strategy)?.abs()),
+        Floor(sub_expr) => {
+            Ok(eval_with_strategy(sub_expr, placeholder, context, strategy)?.floor())
+        }
+        Ceil(sub_expr) => Ok(eval_with_strategy(sub_expr, placeholder, context, strategy)?.ceil()),
+        Round(sub_expr) => Ok(
+            eval_with_strategy(sub_expr, placeholder, context, strategy)?
+                .round_dp_with_strategy(0, strategy),
+        ),
+        RoundTo(expr1, expr2) => {
+            let value = eval_with_strategy(expr1, placeholder, context, strategy)?;
+            let digits = eval_with_strategy(expr2, placeholder, context, strategy)?;
+            if !digits.fract().is_zero() || digits < Decimal::ZERO {
+                return Err(EvalError::Domain("round".to_string()));
+            }
+            let digits = digits.to_u32().unwrap_or(u32::MAX);
+            Ok(value.round_dp_with_strategy(digits, strategy))
+        }
+        Truncate(sub_expr) => {
+            Ok(eval_with_strategy(sub_expr, placeholder, context, strategy)?.trunc())
+        }
+        Sign(sub_expr) => {
+            Ok(eval_with_strategy(sub_expr, placeholder, context, strategy)?.signum())
+        }
+        Sin(sub_expr) => eval_with_strategy(sub_expr, placeholder, context, strategy)?
+            .checked_sin()
+            .ok_or(EvalError::Overflow),
+        Cos(sub_expr) => eval_with_strategy(sub_expr, placeholder, context, strategy)?
+            .checked_cos()
+            .ok_or(EvalError::Overflow),
+        Tan(sub_expr) => tan(eval_with_strategy(
+            sub_expr,
+            placeholder,
+            context,
+            strategy,
+        )?),
+        Sinh(sub_expr) => sinh(eval_with_strategy(
+            sub_expr,
+            placeholder,
+            context,
+            strategy,
+        )?),
+        Cosh(sub_expr) => cosh(eval_with_strategy(
+            sub_expr,
+            placeholder,
+            context,
+            strategy,
+        )?),
+        Tanh(sub_expr) => tanh(eval_with_strategy(
+            sub_expr,
+            placeholder,
+            context,
+            strategy,
+        )?),
+        Asin(sub_expr) => asin(eval_with_strategy(
+            sub_expr,
+            placeholder,
+            context,
+            strategy,
+        )?),
+        Acos(sub_expr) => Ok(Decimal::HALF_PI
+            - asin(eval_with_strategy(
+                sub_expr,
+                placeholder,
+                context,
+                strategy,
+            )?)
+            .map_err(|_| EvalError::Domain("acos".to_string()))?),
+        Atan(sub_expr) => Ok(atan(eval_with_strategy(
+            sub_expr,
+            placeholder,
+            context,
+            strategy,
+        )?)),
+        Atan2(expr1, expr2) => Ok(atan2(
+            eval_with_strategy(expr1, placeholder, context, strategy)?,
+            eval_with_strategy(expr2, placeholder, context, strategy)?,
+        )),
+        Arsinh(sub_expr) => {
+            let value = eval_with_strategy(sub_expr, placeholder, context, strategy)?;
+            Ok(value.signum() * ln_hypot("arsinh", value.abs(), Decimal::ONE)?)
+        }
+        Arcosh(sub_expr) => {
+            let value = eval_with_strategy(sub_expr, placeholder, context, strategy)?;
+            if value < Decimal::ONE {
+                return Err(EvalError::Domain("arcosh".to_string()));
+            }
+            ln_hypot("arcosh", value, Decimal::NEGATIVE_ONE)
+        }
+        Artanh(sub_expr) => {
+            let value = eval_with_strategy(sub_expr, placeholder, context, strategy)?;
+            if value.abs() >= Decimal::ONE {
+                return Err(EvalError::Domain("artanh".to_string()));
+            }
+            Ok(ln("artanh", (Decimal::ONE + value) / (Decimal::ONE - value))? / Decimal::TWO)
+        }
+        Ln(sub_expr) => ln(
+            "ln",
+            eval_with_strategy(sub_expr, placeholder, context, strategy)?,
+        ),
+        Lb(sub_expr) => Ok(ln(
+            "lb",
+            eval_with_strategy(sub_expr, placeholder, context, strategy)?,
+        )? / Decimal::new(2, 0).ln()),
+        Exp(sub_expr) => eval_with_strategy(sub_expr, placeholder, context, strategy)?
+            .checked_exp()
+            .ok_or(EvalError::Overflow),
+        Exp2(sub_expr) => Decimal::new(2, 0)
+            .checked_powd(eval_with_strategy(
+                sub_expr,
+                placeholder,
+                context,
+                strategy,
+            )?)
+            .ok_or(EvalError::Overflow),
+        Pow(expr1, expr2) => eval_with_strategy(expr1, placeholder, context, strategy)?
+            .checked_powd(eval_with_strategy(expr2, placeholder, context, strategy)?)
+            .ok_or(EvalError::Overflow),
+        Log(expr1, expr2) => {
+            let value = ln(
+                "log",
+                eval_with_strategy(expr1, placeholder, context, strategy)?,
+            )?;
+            let base = ln(
+                "log",
+                eval_with_strategy(expr2, placeholder, context, strategy)?,
+            )?;
+            if base.is_zero() {
+                return Err(EvalError::Domain("log".to_string()));
+            }
+            Ok(value / base)
+        }
         Factorial(sub_expr) => {
-            let sub_result = eval(*sub_expr)?;
+            let sub_result = eval_with_strategy(sub_expr, placeholder, context, strategy)?;
             if sub_result >= Decimal::ZERO {
                 if (sub_result % Decimal::new(1, 0)) > Decimal::ZERO {
-                    Ok(gamma(sub_result + Decimal::new(1, 0)))
+                    gamma(sub_result + Decimal::new(1, 0)).ok_or(EvalError::Overflow)
                 } else {
                     let mut factorial_result = Decimal::new(1, 0);
-                    for i in 2..=sub_result.to_i64().unwrap() {
-                        factorial_result *= Decimal::new(i, 0);
+                    for i in 2..=sub_result.to_i64().ok_or(EvalError::Overflow)? {
+                        factorial_result = factorial_result
+                            .checked_mul(Decimal::new(i, 0))
+                            .ok_or(EvalError::Overflow)?;
                     }
                     Ok(factorial_result)
                 }
             } else if (sub_result % Decimal::new(1, 0)) == Decimal::ZERO {
-                return Err("The factorial function is not defined for {}.".into());
+                Err(EvalError::NegativeFactorial)
             } else {
-                Ok(gamma(sub_result + Decimal::new(1, 0)))
+                gamma(sub_result + Decimal::new(1, 0)).ok_or(EvalError::Overflow)
             }
         }
         LambertW(expr) => {
-            let sub_expr = eval(*expr)?;
+            let sub_expr = eval_with_strategy(expr, placeholder, context, strategy)?;
             if sub_expr < -Decimal::new(-1, 0).exp() {
-                return Err("The Lambert W function is not defined for {}.".into());
+                return Err(EvalError::Domain("lambertw".to_string()));
             }
             let iterations = (Decimal::new(4, 0))
-                .max((sub_expr.log10() / Decimal::new(3, 0)).ceil())
+                .max(
+                    sub_expr
+                        .checked_log10()
+                        .map_or(Decimal::ZERO, |log| (log / Decimal::new(3, 0)).ceil()),
+                )
                 .to_i32()
                 .unwrap_or(4);
+            // One step of Halley's method, or `None` when it leaves the range of Decimal.
+            let step = |w: Decimal| -> Option<Decimal> {
+                let exp_w = w.checked_exp()?;
+                let residual = w.checked_mul(exp_w)?.checked_sub(sub_expr)?;
+                let slope = exp_w.checked_mul(w.checked_add(Decimal::ONE)?)?;
+                let correction = (w.checked_add(Decimal::TWO)?)
+                    .checked_mul(residual)?
+                    .checked_div(Decimal::TWO.checked_mul(w)?.checked_add(Decimal::TWO)?)?;
+                w.checked_sub(residual.checked_div(slope.checked_sub(correction)?)?)
+            };
             let mut w = Decimal::ZERO;
             for _ in 0..iterations {
-                let exp_w = w.exp();
-                w -= (w * exp_w - sub_expr)
-                    / (exp_w * (w + Decimal::new(1, 0))
-                        - (w + Decimal::new(2, 0)) * (w * exp_w - sub_expr)
-                            / (Decimal::new(2, 0) * w + Decimal::new(2, 0)));
+                w = step(w).ok_or(EvalError::Overflow)?;
             }
             Ok(w)
         }
         ILog(expr1, expr2) => {
-            let mut n = eval(*expr1)?;
-            let b = eval(*expr2)?;
+            let mut n = eval_with_strategy(expr1, placeholder, context, strategy)?;
+            let b = eval_with_strategy(expr2, placeholder, context, strategy)?;
+            let b = match b.checked_log10() {
+                Some(log) if !log.is_zero() => log,
+                _ => return Err(EvalError::Domain("ilog".to_string())),
+            };
             let mut x = Decimal::ZERO;
             while n > Decimal::new(1, 0) {
                 x += Decimal::new(1, 0);
-                n = (n.log10() / b.log10()).floor();
+                let next = (n.log10() / b).floor();
+                if next >= n {
+                    return Err(EvalError::Domain("ilog".to_string()));
+                }
+                n = next;
             }
             Ok(x)
         }
-        Sqrt(sub_expr) => match eval(*sub_expr)?.sqrt() {
-            Some(result) => Ok(result),
-            None => Err("Unable to compute the square root of negative number".into()),
-        },
-        Root(n_th_expr, x_expr) => Ok(eval(*x_expr)?.powd(Decimal::new(1, 0) / eval(*n_th_expr)?)),
+        Sqrt(sub_expr) => {
+            match eval_with_strategy(sub_expr, placeholder, context, strategy)?.sqrt() {
+                Some(result) => Ok(result),
+                None => Err(EvalError::Domain("sqrt".to_string())),
+            }
+        }
+        Root(n_th_expr, x_expr) => {
+            let x_expr = eval_with_strategy(x_expr, placeholder, context, strategy)?;
+            let n_th_expr = eval_with_strategy(n_th_expr, placeholder, context, strategy)?;
+            if n_th_expr.is_zero() {
+                return Err(EvalError::Domain("root".to_string()));
+            }
+            x_expr
+                .checked_powd(Decimal::new(1, 0) / n_th_expr)
+                .ok_or(EvalError::Overflow)
+        }
         Min(args) => {
             if args.len() > 1 {
                 let mut result = Decimal::MAX;
-                for arg in <Vec<Node> as Clone>::clone(&args).into_iter() {
-                    result = eval(arg).unwrap().min(result);
+                for arg in args.iter() {
+                    result = eval_with_strategy(arg, placeholder, context, strategy)?.min(result);
                 }
                 Ok(result)
             } else {
                 match args.first() {
-                    Some(arg) => Ok(eval((*arg).clone())?),
+                    Some(arg) => Ok(eval_with_strategy(arg, placeholder, context, strategy)?),
                     None => Ok(Decimal::ZERO),
                 }
             }
@@ -160,33 +563,38 @@ pub fn eval(expr: Node) -> Result<Decimal, Box<dyn error::Error>> {
         Max(args) => {
             if args.len() > 1 {
                 let mut result = Decimal::MIN;
-                for arg in <Vec<Node> as Clone>::clone(&args).into_iter() {
-                    result = eval(arg).unwrap().max(result);
+                for arg in args.iter() {
+                    result = eval_with_strategy(arg, placeholder, context, strategy)?.max(result);
                 }
                 Ok(result)
             } else {
                 match args.first() {
-                    Some(arg) => Ok(eval((*arg).clone())?),
+                    Some(arg) => Ok(eval_with_strategy(arg, placeholder, context, strategy)?),
                     None => Ok(Decimal::ZERO),
                 }
             }
         }
         Avg(args) => {
             let mut result = Decimal::ZERO;
-            for arg in <Vec<Node> as Clone>::clone(&args).into_iter() {
-                result += eval(arg).unwrap();
+            for arg in args.iter() {
+                result = result
+                    .checked_add(eval_with_strategy(arg, placeholder, context, strategy)?)
+                    .ok_or(EvalError::Overflow)?;
             }
             Ok(result / Decimal::new(args.len() as i64, 0))
         }
         Med(args) => {
             let mut results = vec![];
-            for arg in <Vec<Node> as Clone>::clone(&args).into_iter() {
-                results.push(eval(arg).unwrap());
+            for arg in args.iter() {
+                results.push(eval_with_strategy(arg, placeholder, context, strategy)?);
             }
             results.sort_by(|a, b| a.partial_cmp(b).unwrap());
             let len = results.len();
             if len % 2 == 0 {
-                Ok((results[len >> 1] + results[(len >> 1) - 1]) / Decimal::new(2, 0))
+                results[len >> 1]
+                    .checked_add(results[(len >> 1) - 1])
+                    .map(|sum| sum / Decimal::new(2, 0))
+                    .ok_or(EvalError::Overflow)
             } else {
                 Ok(results[len >> 1])
             }
@@ -196,40 +604,216 @@ pub fn eval(expr: Node) -> Result<Decimal, Box<dyn error::Error>> {
 
 #[cfg(test)]
 mod tests {
-    use crate::eval_decimal::{ast::eval, parser::Parser};
+    use std::collections::HashMap;
+
+    use crate::eval_decimal::{
+        ast::{eval, eval_with_strategy},
+        parser::Parser,
+    };
+    use crate::utils::EvalError;
+
     use rust_decimal::Decimal;
 
     #[test]
     fn test_expr1() {
-        let ast = Parser::new("1+2-3", None).unwrap().parse().unwrap();
-        let value = eval(ast).unwrap();
+        let ast = Parser::new("1+2-3").unwrap().parse().unwrap();
+        let value = eval(&ast, Decimal::ZERO, &HashMap::new()).unwrap();
         assert_eq!(value, Decimal::ZERO);
     }
     #[test]
     fn test_expr2() {
-        let ast = Parser::new("3+2-1*5/4", None).unwrap().parse().unwrap();
-        let value = eval(ast).unwrap();
+        let ast = Parser::new("3+2-1*5/4").unwrap().parse().unwrap();
+        let value = eval(&ast, Decimal::ZERO, &HashMap::new()).unwrap();
         assert_eq!(value, Decimal::new(375, 2));
     }
     #[test]
     fn test_expr3() {
-        let ast = Parser::new("2*4%3/2", None).unwrap().parse().unwrap();
-        let value = eval(ast).unwrap();
+        let ast = Parser::new("2*4%3/2").unwrap().parse().unwrap();
+        let value = eval(&ast, Decimal::ZERO, &HashMap::new()).unwrap();
         assert_eq!(value, Decimal::new(1, 0));
     }
     #[test]
     fn test_expr4() {
-        let ast = Parser::new("med(5,2,8,9,7)", None)
-            .unwrap()
-            .parse()
-            .unwrap();
-        let value = eval(ast).unwrap();
+        let ast = Parser::new("med(5,2,8,9,7)").unwrap().parse().unwrap();
+        let value = eval(&ast, Decimal::ZERO, &HashMap::new()).unwrap();
         assert_eq!(value, Decimal::new(7, 0));
     }
     #[test]
     fn test_expr5() {
-        let ast = Parser::new("med(5,2,8,9)", None).unwrap().parse().unwrap();
-        let value = eval(ast).unwrap();
+        let ast = Parser::new("med(5,2,8,9)").unwrap().parse().unwrap();
+        let value = eval(&ast, Decimal::ZERO, &HashMap::new()).unwrap();
         assert_eq!(value, Decimal::new(65, 1));
     }
+    #[test]
+    fn test_eval_errors() {
+        let ast = Parser::new("1/0").unwrap().parse().unwrap();
+        assert_eq!(
+            eval(&ast, Decimal::ZERO, &HashMap::new()),
+            Err(EvalError::DivisionByZero)
+        );
+        let ast = Parser::new("79228162514264337593543950335+1")
+            .unwrap()
+            .parse()
+            .unwrap();
+        assert_eq!(
+            eval(&ast, Decimal::ZERO, &HashMap::new()),
+            Err(EvalError::Overflow)
+        );
+        let ast = Parser::new("lambert_w(79228162514264337593543950335)")
+            .unwrap()
+            .parse()
+            .unwrap();
+        assert_eq!(
+            eval(&ast, Decimal::ZERO, &HashMap::new()),
+            Err(EvalError::Overflow)
+        );
+        let ast = Parser::new("ln(0)").unwrap().parse().unwrap();
+        assert_eq!(
+            eval(&ast, Decimal::ZERO, &HashMap::new()),
+            Err(EvalError::Domain("ln".to_string()))
+        );
+        let ast = Parser::new("sqrt(-1)").unwrap().parse().unwrap();
+        assert_eq!(
+            eval(&ast, Decimal::ZERO, &HashMap::new()),
+            Err(EvalError::Domain("sqrt".to_string()))
+        );
+        let ast = Parser::new("(-2)!").unwrap().parse().unwrap();
+        assert_eq!(
+            eval(&ast, Decimal::ZERO, &HashMap::new()),
+            Err(EvalError::NegativeFactorial)
+        );
+    }
+    #[test]
+    fn test_gamma() {
+        let ast = Parser::new("0.5!").unwrap().parse().unwrap();
+        let result = eval(&ast, Decimal::ZERO, &HashMap::new()).unwrap();
+        assert!((result - Decimal::new(886226925452758, 15)).abs() < Decimal::new(1, 9));
+        let ast = Parser::new("(-0.5)!").unwrap().parse().unwrap();
+        let result = eval(&ast, Decimal::ZERO, &HashMap::new()).unwrap();
+        assert!((result - Decimal::new(1772453850905516, 15)).abs() < Decimal::new(1, 9));
+    }
+    #[test]
+    fn test_trigonometry() {
+        let close = |formula: &str, expected: Decimal| {
+            let ast = Parser::new(formula).unwrap().parse().unwrap();
+            let result = eval(&ast, Decimal::ZERO, &HashMap::new()).unwrap();
+            assert!(
+                (result - expected).abs() < Decimal::new(1, 20),
+                "{} = {}",
+                formula,
+                result
+            );
+        };
+        close("sin(30°)", Decimal::new(5, 1));
+        close("cos(pi/3)", Decimal::new(5, 1));
+        close("tan(45°)", Decimal::ONE);
+        close("asin(0.5)rad", Decimal::new(30, 0));
+        close("acos(-1)", Decimal::PI);
+        close("4atan(1)", Decimal::PI);
+        close("atan(-1e27)", -Decimal::HALF_PI);
+        close("atan2(1,-1)rad", Decimal::new(135, 0));
+        close("atan2(-1,0)", -Decimal::HALF_PI);
+        close("atan2(-0.5,-2)+pi-atan(0.25)", Decimal::ZERO);
+        close("cosh(1)^2-sinh(1)^2", Decimal::ONE);
+        close("tanh(0.5)-sinh(0.5)/cosh(0.5)", Decimal::ZERO);
+        close("tanh(-50)", Decimal::NEGATIVE_ONE);
+        close("arsinh(sinh(-2.5))", Decimal::new(-25, 1));
+        close("arcosh(cosh(3))", Decimal::new(3, 0));
+        close("artanh(tanh(0.25))", Decimal::new(25, 2));
+        close("arsinh(1e20)-ln(2e20)", Decimal::ZERO);
+    }
+    #[test]
+    fn test_trigonometry_errors() {
+        for (formula, function) in [
+            ("asin(1.5)", "asin"),
+            ("acos(-2)", "acos"),
+            ("arcosh(0.5)", "arcosh"),
+            ("artanh(1)", "artanh"),
+        ] {
+            let ast = Parser::new(formula).unwrap().parse().unwrap();
+            assert_eq!(
+                eval(&ast, Decimal::ZERO, &HashMap::new()),
+                Err(EvalError::Domain(function.to_string()))
+            );
+        }
+        let ast = Parser::new("sinh(100)").unwrap().parse().unwrap();
+        assert_eq!(
+            eval(&ast, Decimal::ZERO, &HashMap::new()),
+            Err(EvalError::Overflow)
+        );
+    }
+    #[test]
+    fn test_round_digits() {
+        use rust_decimal::RoundingStrategy::*;
+        for (formula, strategy, expected) in [
+            ("round(2.345,2)", MidpointNearestEven, Decimal::new(234, 2)),
+            ("round(2.345,2)", MidpointAwayFromZero, Decimal::new(235, 2)),
+            (
+                "round(-2.345,2)",
+                MidpointAwayFromZero,
+                Decimal::new(-235, 2),
+            ),
+            ("round(2.349,2)", ToZero, Decimal::new(234, 2)),
+            ("round(2.341,2)", AwayFromZero, Decimal::new(235, 2)),
+            ("round(2.5)", MidpointNearestEven, Decimal::new(2, 0)),
+            ("round(2.5)", MidpointAwayFromZero, Decimal::new(3, 0)),
+            (
+                "round(1/3,30)",
+                MidpointNearestEven,
+                Decimal::ONE / Decimal::new(3, 0),
+            ),
+        ] {
+            let ast = Parser::new(formula).unwrap().parse().unwrap();
+            assert_eq!(
+                eval_with_strategy(&ast, Decimal::ZERO, &HashMap::new(), strategy),
+                Ok(expected),
+                "{} with {:?}",
+                formula,
+                strategy
+            );
+        }
+        for formula in ["round(1,-1)", "round(1,0.5)"] {
+            let ast = Parser::new(formula).unwrap().parse().unwrap();
+            assert_eq!(
+                eval(&ast, Decimal::ZERO, &HashMap::new()),
+                Err(EvalError::Domain("round".to_string()))
+            );
+        }
+    }
+    #[test]
+    fn test_logic() {
+        for (formula, expected) in [
+            ("2<3", 1),
+            ("3<=2", 0),
+            ("2==2", 1),
+            ("2!=2", 0),
+            ("3>2", 1),
+            ("2>=3", 0),
+            ("1&&0", 0),
+            ("0||2", 1),
+            ("!0", 1),
+            ("!5", 0),
+            ("1<2?10:20", 10),
+            ("if(0,10,20)", 20),
+            ("0?1:0?2:3", 3),
+            ("0&&x", 0),
+            ("1||x", 1),
+            ("1?2:x", 2),
+            ("if(0,x,3)", 3),
+            ("0.1+0.2==0.3", 1),
+        ] {
+            let ast = Parser::new(formula).unwrap().parse().unwrap();
+            assert_eq!(
+                eval(&ast, Decimal::ZERO, &HashMap::new()),
+                Ok(Decimal::new(expected, 0)),
+                "{}",
+                formula
+            );
+        }
+        let ast = Parser::new("1?x:2").unwrap().parse().unwrap();
+        assert_eq!(
+            eval(&ast, Decimal::ZERO, &HashMap::new()),
+            Err(EvalError::UndefinedVariable("x".to_string()))
+        );
+    }
 }
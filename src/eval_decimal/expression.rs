@@ -0,0 +1,183 @@
+use super::ast::{eval, eval_with_strategy, Node};
+use super::parser::Parser;
+use super::rounding::Rounding;
+use super::simplify::simplify;
+use super::tokenizer::RESERVED_WORDS;
+use crate::utils::{latex, mathml, Context, EvalError, Formula, Functions, ParseError};
+use rust_decimal::Decimal;
+use std::{collections::HashMap, fmt};
+
+/// A formula parsed once into a reusable AST and evaluated into Decimal as many times as needed.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExpressionDecimal {
+    ast: Node,
+}
+
+impl ExpressionDecimal {
+    /// Parse a formula without evaluating it.
+    pub fn new(expr: String) -> Result<Self, ParseError> {
+        let mut math_parser = Parser::new(&expr)?;
+        let ast = math_parser.parse()?;
+        Ok(ExpressionDecimal { ast })
+    }
+    /// Parse a formula that can call the given user-defined functions.
+    pub fn with_functions(
+        expr: String,
+        functions: &Functions<Decimal>,
+    ) -> Result<Self, ParseError> {
+        let mut math_parser = Parser::with_functions(&expr, functions)?;
+        let ast = math_parser.parse()?;
+        Ok(ExpressionDecimal { ast })
+    }
+    /// Evaluate the parsed formula, `@` being replaced by the placeholder.
+    pub fn eval(&self, placeholder: Decimal) -> Result<Decimal, EvalError> {
+        self.eval_with_context(placeholder, &HashMap::<String, Decimal>::new())
+    }
+    /// Evaluate the parsed formula, resolving its named variables with the context.
+    pub fn eval_with_context(
+        &self,
+        placeholder: Decimal,
+        context: &dyn Context<Decimal>,
+    ) -> Result<Decimal, EvalError> {
+        eval(&self.ast, placeholder, context)
+    }
+    /// Evaluate the parsed formula, `round` and the scale of the result following the rounding.
+    pub fn eval_with_rounding(
+        &self,
+        placeholder: Decimal,
+        context: &dyn Context<Decimal>,
+        rounding: Rounding,
+    ) -> Result<Decimal, EvalError> {
+        let value = eval_with_strategy(&self.ast, placeholder, context, rounding.strategy)?;
+        Ok(rounding.apply(value))
+    }
+    /// Simplify the parsed formula, folding its constant parts and combining its like terms, so
+    /// that it evaluates faster.
+    pub fn simplify(&self) -> Self {
+        ExpressionDecimal {
+            ast: simplify(&self.ast),
+        }
+    }
+    /// Write the parsed formula as LaTeX math, without the surrounding `$`.
+    pub fn to_latex(&self) -> String {
+        latex(&self.ast)
+    }
+    /// Write the parsed formula as a MathML `math` element.
+    pub fn to_mathml(&self) -> String {
+        mathml(&self.ast)
+    }
+}
+
+/// Writes the parsed formula back with as few parentheses as possible, as text that parses into
+/// the same formula.
+impl fmt::Display for ExpressionDecimal {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.ast)
+    }
+}
+
+impl Formula for ExpressionDecimal {
+    type Value = Decimal;
+
+    fn parse(expr: String, functions: Option<&Functions<Decimal>>) -> Result<Self, ParseError> {
+        match functions {
+            Some(functions) => ExpressionDecimal::with_functions(expr, functions),
+            None => ExpressionDecimal::new(expr),
+        }
+    }
+    fn evaluate(
+        &self,
+        placeholder: Decimal,
+        context: &dyn Context<Decimal>,
+    ) -> Result<Decimal, EvalError> {
+        self.eval_with_context(placeholder, context)
+    }
+    fn is_reserved(name: &str) -> bool {
+        RESERVED_WORDS.contains(&name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::Arity;
+    use crate::utils::ParseErrorKind;
+    use rust_decimal::RoundingStrategy;
+
+    #[test]
+    fn test_reuse() {
+        let expr = ExpressionDecimal::new("@ * 1.2".to_string()).unwrap();
+        assert_eq!(expr.eval(Decimal::new(10, 0)).unwrap(), Decimal::new(12, 0));
+        assert_eq!(expr.eval(Decimal::new(5, 1)).unwrap(), Decimal::new(6, 1));
+    }
+    #[test]
+    fn test_invalid_expression() {
+        assert!(ExpressionDecimal::new("(2".to_string()).is_err());
+    }
+    #[test]
+    fn test_error_span() {
+        let error = ExpressionDecimal::new("⌊2.5 + 1".to_string()).unwrap_err();
+        assert_eq!(error.kind(), ParseErrorKind::UnbalancedBracket);
+        assert_eq!(error.span(), 10..10);
+        assert_eq!(error.expected(), ["⌋"]);
+        let error = ExpressionDecimal::new("1 + min()".to_string()).unwrap_err();
+        assert_eq!(error.kind(), ParseErrorKind::WrongArity);
+        assert_eq!(error.span(), 4..7);
+        let error = ExpressionDecimal::new("1.5e2.5".to_string()).unwrap_err();
+        assert_eq!(error.kind(), ParseErrorKind::InvalidNumber);
+        assert_eq!(error.span(), 0..7);
+    }
+    #[test]
+    fn test_context() {
+        let expr = ExpressionDecimal::new("price * (1 - discount)".to_string()).unwrap();
+        let mut context = HashMap::new();
+        context.insert("price".to_string(), Decimal::new(1999, 2));
+        context.insert("discount".to_string(), Decimal::new(1, 1));
+        assert_eq!(
+            expr.eval_with_context(Decimal::ZERO, &context).unwrap(),
+            Decimal::new(17991, 3)
+        );
+    }
+    #[test]
+    fn test_unbound_variable() {
+        let expr = ExpressionDecimal::new("price".to_string()).unwrap();
+        assert!(expr.eval(Decimal::ZERO).is_err());
+    }
+    #[test]
+    fn test_custom_functions() {
+        let mut functions = Functions::new();
+        functions.register("vat", Arity::Exactly(1), |args| {
+            Ok(args[0] * Decimal::new(21, 2))
+        });
+        let expr = ExpressionDecimal::with_functions("@ + vat(@)".to_string(), &functions).unwrap();
+        assert_eq!(
+            expr.eval(Decimal::new(100, 0)).unwrap(),
+            Decimal::new(121, 0)
+        );
+    }
+    #[test]
+    fn test_rounding() {
+        let expr = ExpressionDecimal::new("round(@ * 1.0825, 2) + @ / 3".to_string()).unwrap();
+        let context = HashMap::<String, Decimal>::new();
+        let rounding = Rounding::with_scale(RoundingStrategy::MidpointAwayFromZero, 2);
+        assert_eq!(
+            expr.eval_with_rounding(Decimal::new(10, 0), &context, rounding)
+                .unwrap()
+                .to_string(),
+            "14.16"
+        );
+        let expr = ExpressionDecimal::new("@".to_string()).unwrap();
+        assert_eq!(
+            expr.eval_with_rounding(Decimal::new(15, 1), &context, rounding)
+                .unwrap()
+                .to_string(),
+            "1.50"
+        );
+        assert_eq!(
+            expr.eval_with_rounding(Decimal::new(15, 1), &context, Rounding::default())
+                .unwrap()
+                .to_string(),
+            "1.5"
+        );
+    }
+}
@@ -0,0 +1,105 @@
+use super::ast::Node;
+use crate::utils::{write_infix, Notation, Operator, Term, Unary};
+use std::fmt;
+
+impl Notation for Node {
+    fn term(&self) -> Term<'_, Self> {
+        use self::Node::*;
+        match self {
+            Number(value) => Term::number(value.to_string()),
+            Variable(name) => Term::Variable(name),
+            Ans => Term::Ans,
+            Add(left, right) => Term::Binary(Operator::Add, left, right),
+            Subtract(left, right) => Term::Binary(Operator::Subtract, left, right),
+            Multiply(left, right) => Term::Binary(Operator::Multiply, left, right),
+            Divide(left, right) => Term::Binary(Operator::Divide, left, right),
+            Modulo(left, right) => Term::Binary(Operator::Modulo, left, right),
+            Negative(expr) => Term::Unary(Unary::Negative, expr),
+            Abs(expr) => Term::Function("abs", vec![expr]),
+            Sign(expr) => Term::Function("sign", vec![expr]),
+            Sin(expr) => Term::Function("sin", vec![expr]),
+            Cos(expr) => Term::Function("cos", vec![expr]),
+            Tan(expr) => Term::Function("tan", vec![expr]),
+            Sinh(expr) => Term::Function("sinh", vec![expr]),
+            Cosh(expr) => Term::Function("cosh", vec![expr]),
+            Tanh(expr) => Term::Function("tanh", vec![expr]),
+            Arsinh(expr) => Term::Function("arsinh", vec![expr]),
+            Arcosh(expr) => Term::Function("arcosh", vec![expr]),
+            Artanh(expr) => Term::Function("artanh", vec![expr]),
+            Asin(expr) => Term::Function("asin", vec![expr]),
+            Acos(expr) => Term::Function("acos", vec![expr]),
+            Atan(expr) => Term::Function("atan", vec![expr]),
+            Atan2(left, right) => Term::Function("atan2", vec![left, right]),
+            Min(args) => Term::Function("min", args.iter().collect()),
+            Max(args) => Term::Function("max", args.iter().collect()),
+            Avg(args) => Term::Function("avg", args.iter().collect()),
+            Med(args) => Term::Function("med", args.iter().collect()),
+            Floor(expr) => Term::Function("floor", vec![expr]),
+            Ceil(expr) => Term::Function("ceil", vec![expr]),
+            Round(expr) => Term::Function("round", vec![expr]),
+            RoundTo(left, right) => Term::Function("round", vec![left, right]),
+            Truncate(expr) => Term::Function("trunc", vec![expr]),
+            Sqrt(expr) => Term::Function("sqrt", vec![expr]),
+            Root(left, right) => Term::Function("root", vec![left, right]),
+            ILog(left, right) => Term::Function("ilog", vec![left, right]),
+            LambertW(expr) => Term::Function("lambert_w", vec![expr]),
+            Ln(expr) => Term::Function("ln", vec![expr]),
+            Lb(expr) => Term::Function("lb", vec![expr]),
+            Exp(expr) => Term::Function("exp", vec![expr]),
+            Exp2(expr) => Term::Function("exp2", vec![expr]),
+            Pow(left, right) => Term::Binary(Operator::Pow, left, right),
+            Log(left, right) => Term::Function("log", vec![left, right]),
+            Equal(left, right) => Term::Binary(Operator::Equal, left, right),
+            NotEqual(left, right) => Term::Binary(Operator::NotEqual, left, right),
+            Less(left, right) => Term::Binary(Operator::Less, left, right),
+            LessEqual(left, right) => Term::Binary(Operator::LessEqual, left, right),
+            Greater(left, right) => Term::Binary(Operator::Greater, left, right),
+            GreaterEqual(left, right) => Term::Binary(Operator::GreaterEqual, left, right),
+            LogicalAnd(left, right) => Term::Binary(Operator::LogicalAnd, left, right),
+            LogicalOr(left, right) => Term::Binary(Operator::LogicalOr, left, right),
+            LogicalNot(expr) => Term::Unary(Unary::LogicalNot, expr),
+            Factorial(expr) => Term::Factorial(expr),
+            Conditional(condition, then, otherwise) => {
+                Term::Conditional(condition, then, otherwise)
+            }
+            CustomFunction(function, args) => {
+                Term::Function(function.name(), args.iter().collect())
+            }
+        }
+    }
+}
+
+impl fmt::Display for Node {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write_infix(self, f)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::eval_decimal::parser::Parser;
+
+    #[test]
+    fn test_round_trip() {
+        let formulas = [
+            "1.50*-x^2+0.001-(3-4)",
+            "round(x,2)+round(x)+trunc(x)+floor(x)+ceil(x)+abs(x)+sign(x)",
+            "sin(x)+cos(x)+tan(x)+sinh(x)+cosh(x)+tanh(x)+asin(x)+acos(x)+atan(x)+atan2(x,1)",
+            "arsinh(x)+arcosh(x)+artanh(x)+lambert_w(x)+ln(x)+lb(x)+exp(x)+exp2(x)+sqrt(x)",
+            "root(3,x)+ilog(x,2)+log(x,2)+min(x,1)+max(x,1)+avg(x,1)+med(x,1)+x%2+4!",
+            "x>=1&&x!=2||!(x<=0)?1:2",
+        ];
+        for formula in formulas {
+            let ast = Parser::new(formula).unwrap().parse().unwrap();
+            let text = ast.to_string();
+            assert_eq!(
+                Parser::new(&text).unwrap().parse().unwrap(),
+                ast,
+                "{}",
+                text
+            );
+        }
+        let ast = Parser::new("1.50e2 * x").unwrap().parse().unwrap();
+        assert_eq!(ast.to_string(), "150 * x");
+    }
+}
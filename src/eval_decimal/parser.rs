@@ -1,61 +1,96 @@
-use std::sync::Arc;
+use std::{ops::Range, sync::Arc};
 
 use rust_decimal::Decimal;
 
 use super::ast::Node;
 use super::token::{NativeFunction, Token};
 use super::tokenizer::Tokenizer;
-use crate::utils::{OperatorCategory, ParseError};
+use crate::utils::{
+    too_deep, Arity, Functions, OperatorCategory, ParseError, ParseErrorKind, MAX_DEPTH,
+};
 
 pub struct Parser<'a> {
     tokenizer: Tokenizer<'a>,
     current_token: Token,
+    current_span: Range<usize>,
     previous_token: Option<Token>,
-    placeholder: Decimal,
+    depth: usize,
 }
 impl<'a> Parser<'a> {
-    pub fn new(expr: &'a str, placeholder: Option<Decimal>) -> Result<Self, ParseError> {
-        let mut lexer = Tokenizer::new(expr);
+    pub fn new(expr: &'a str) -> Result<Self, ParseError> {
+        Parser::from_tokenizer(Tokenizer::new(expr))
+    }
+    pub fn with_functions(
+        expr: &'a str,
+        functions: &'a Functions<Decimal>,
+    ) -> Result<Self, ParseError> {
+        Parser::from_tokenizer(Tokenizer::with_functions(expr, functions))
+    }
+    fn from_tokenizer(mut lexer: Tokenizer<'a>) -> Result<Self, ParseError> {
+        lexer.skip_whitespace();
+        let start = lexer.position();
         let cur_token = match lexer.next() {
             Some(token) => token,
-            None => return Err(ParseError::InvalidOperator("Invalid character".into())),
+            None => return Err(lexer.error(start)),
         };
         Ok(Parser {
+            current_span: start..lexer.position(),
             tokenizer: lexer,
             current_token: cur_token,
             previous_token: None,
-            placeholder: placeholder.unwrap_or_default(),
+            depth: 0,
         })
     }
     pub fn parse(&mut self) -> Result<Node, ParseError> {
-        let ast = self.generate_ast(OperatorCategory::DefaultZero);
-        match ast {
-            Ok(ast) => Ok(ast),
-            Err(e) => Err(e),
+        let ast = self.generate_ast(OperatorCategory::DefaultZero)?;
+        if self.current_token == Token::Eof {
+            Ok(ast)
+        } else if self.current_token.is_closing_bracket() {
+            Err(ParseError::new(
+                ParseErrorKind::UnbalancedBracket,
+                self.current_span.clone(),
+                format!("Unmatched {}", self.current_token),
+            ))
+        } else {
+            Err(self.unexpected_token(&["an operator", "end of input"]))
         }
     }
     fn get_next_token(&mut self) -> Result<(), ParseError> {
+        self.tokenizer.skip_whitespace();
+        let start = self.tokenizer.position();
         let next_token = match self.tokenizer.next() {
             Some(token) => token,
-            None => return Err(ParseError::InvalidOperator("Invalid character".into())),
+            None => return Err(self.tokenizer.error(start)),
         };
         self.previous_token = Some(self.current_token.clone());
         self.current_token = next_token;
+        self.current_span = start..self.tokenizer.position();
         Ok(())
     }
     fn generate_ast(&mut self, oper_prec: OperatorCategory) -> Result<Node, ParseError> {
+        let depth = self.depth;
+        self.nest()?;
         let mut left_expr = self.parse_number()?;
-
         while oper_prec < self.current_token.get_oper_prec() {
             if self.current_token == Token::Eof {
                 break;
             }
-            let right_expr = self.convert_token_to_node(left_expr.clone())?;
-            left_expr = right_expr;
+            self.nest()?;
+            left_expr = self.convert_token_to_node(left_expr)?;
         }
+        self.depth = depth;
         Ok(left_expr)
     }
-    fn function_static_arguments(&mut self, n: i32) -> Result<Vec<Node>, ParseError> {
+    fn nest(&mut self) -> Result<(), ParseError> {
+        self.depth += 1;
+        if self.depth > MAX_DEPTH {
+            return Err(too_deep(self.current_span.clone()));
+        }
+        Ok(())
+    }
+    fn function_static_arguments(&mut self, n: usize) -> Result<Vec<Node>, ParseError> {
+        let function = self.current_token.clone();
+        let function_span = self.current_span.clone();
         self.get_next_token()?;
         self.check_paren(Token::LeftParen)?;
         let mut args = Vec::new();
@@ -63,9 +98,15 @@ impl<'a> Parser<'a> {
             let arg_expr = self.generate_ast(OperatorCategory::DefaultZero)?;
             args.push(arg_expr);
             if i < n - 1 {
+                if self.current_token == Token::RightParen {
+                    return Err(wrong_arity(&function, function_span, n));
+                }
                 self.check_paren(Token::Comma)?;
             }
         }
+        if self.current_token == Token::Comma {
+            return Err(wrong_arity(&function, function_span, n));
+        }
         self.check_paren(Token::RightParen)?;
         Ok(args)
     }
@@ -98,12 +139,7 @@ impl<'a> Parser<'a> {
                 self.get_next_token()?;
                 break;
             } else {
-                return Err(ParseError::InvalidOperator(format!(
-                    "Expected either {:?} or {:?}, got {:?}",
-                    Token::Comma,
-                    end_token,
-                    self.current_token
-                )));
+                return Err(self.missing_token(&[Token::Comma, end_token]));
             }
         }
         Ok(args)
@@ -113,9 +149,36 @@ impl<'a> Parser<'a> {
         match token {
             Token::Ans => {
                 self.get_next_token()?;
-                Ok(Node::Number(self.placeholder))
+                Ok(Node::Ans)
+            }
+            Token::Identifier(name) => {
+                self.get_next_token()?;
+                self.implicit_multiply(Node::Variable(name))
+            }
+            Token::CustomFunction(function) => {
+                let function_span = self.current_span.clone();
+                let args = match function.arity() {
+                    Arity::Exactly(n) => self.function_static_arguments(n)?,
+                    Arity::AtLeast(n) => {
+                        let args = self.function_arguments()?;
+                        if args.len() < n {
+                            return Err(ParseError::new(
+                                ParseErrorKind::WrongArity,
+                                function_span,
+                                format!(
+                                    "The {} function takes at least {} arguments",
+                                    function.name(),
+                                    n
+                                ),
+                            ));
+                        }
+                        args
+                    }
+                };
+                self.implicit_multiply(Node::CustomFunction(function, Arc::new(args)))
             }
             Token::ExplicitFunction(current_function) => {
+                let function_span = self.current_span.clone();
                 let current_function = match current_function {
                     NativeFunction::Abs => {
                         Node::Abs(Box::new(self.function_static_arguments(1)?[0].clone()))
@@ -130,7 +193,9 @@ impl<'a> Parser<'a> {
                     NativeFunction::Min => {
                         let args = self.function_arguments()?;
                         if args.is_empty() {
-                            return Err(ParseError::UnableToParse(
+                            return Err(ParseError::new(
+                                ParseErrorKind::WrongArity,
+                                function_span,
                                 "There's no arguments in the min function".to_string(),
                             ));
                         }
@@ -139,7 +204,9 @@ impl<'a> Parser<'a> {
                     NativeFunction::Max => {
                         let args = self.function_arguments()?;
                         if args.is_empty() {
-                            return Err(ParseError::UnableToParse(
+                            return Err(ParseError::new(
+                                ParseErrorKind::WrongArity,
+                                function_span,
                                 "There's no arguments in the max function".to_string(),
                             ));
                         }
@@ -156,7 +223,9 @@ impl<'a> Parser<'a> {
                     NativeFunction::Med => {
                         let args = self.function_arguments()?;
                         if args.is_empty() {
-                            return Err(ParseError::UnableToParse(
+                            return Err(ParseError::new(
+                                ParseErrorKind::WrongArity,
+                                function_span,
                                 "Cannot compute the median of no arguments".to_string(),
                             ));
                         } else {
@@ -170,11 +239,58 @@ impl<'a> Parser<'a> {
                         Node::Ceil(Box::new(self.function_static_arguments(1)?[0].clone()))
                     }
                     NativeFunction::Round => {
-                        Node::Round(Box::new(self.function_static_arguments(1)?[0].clone()))
+                        let mut args = self.function_arguments()?;
+                        match args.len() {
+                            1 => Node::Round(Box::new(args.remove(0))),
+                            2 => Node::RoundTo(Box::new(args.remove(0)), Box::new(args.remove(0))),
+                            _ => {
+                                return Err(ParseError::new(
+                                    ParseErrorKind::WrongArity,
+                                    function_span,
+                                    "The round function takes 1 or 2 arguments".to_string(),
+                                ))
+                            }
+                        }
                     }
                     NativeFunction::Truncate => {
                         Node::Truncate(Box::new(self.function_static_arguments(1)?[0].clone()))
                     }
+                    NativeFunction::Sin => {
+                        Node::Sin(Box::new(self.function_static_arguments(1)?[0].clone()))
+                    }
+                    NativeFunction::Cos => {
+                        Node::Cos(Box::new(self.function_static_arguments(1)?[0].clone()))
+                    }
+                    NativeFunction::Tan => {
+                        Node::Tan(Box::new(self.function_static_arguments(1)?[0].clone()))
+                    }
+                    NativeFunction::Sinh => {
+                        Node::Sinh(Box::new(self.function_static_arguments(1)?[0].clone()))
+                    }
+                    NativeFunction::Cosh => {
+                        Node::Cosh(Box::new(self.function_static_arguments(1)?[0].clone()))
+                    }
+                    NativeFunction::Tanh => {
+                        Node::Tanh(Box::new(self.function_static_arguments(1)?[0].clone()))
+                    }
+                    NativeFunction::Asin => {
+                        Node::Asin(Box::new(self.function_static_arguments(1)?[0].clone()))
+                    }
+                    NativeFunction::Acos => {
+                        Node::Acos(Box::new(self.function_static_arguments(1)?[0].clone()))
+                    }
+                    NativeFunction::Atan => {
+                        Node::Atan(Box::new(self.function_static_arguments(1)?[0].clone()))
+                    }
+                    NativeFunction::Arsinh => {
+                        Node::Arsinh(Box::new(self.function_static_arguments(1)?[0].clone()))
+                    }
+                    NativeFunction::Arcosh => {
+                        Node::Arcosh(Box::new(self.function_static_arguments(1)?[0].clone()))
+                    }
+                    NativeFunction::Artanh => {
+                        Node::Artanh(Box::new(self.function_static_arguments(1)?[0].clone()))
+                    }
                     NativeFunction::Sqrt => {
                         Node::Sqrt(Box::new(self.function_static_arguments(1)?[0].clone()))
                     }
@@ -193,6 +309,10 @@ impl<'a> Parser<'a> {
                     NativeFunction::LambertW => {
                         Node::LambertW(Box::new(self.function_static_arguments(1)?[0].clone()))
                     }
+                    NativeFunction::Atan2 => {
+                        let args = self.function_static_arguments(2)?;
+                        Node::Atan2(Box::new(args[0].clone()), Box::new(args[1].clone()))
+                    }
                     NativeFunction::Pow => {
                         let args = self.function_static_arguments(2)?;
                         Node::Pow(Box::new(args[0].clone()), Box::new(args[1].clone()))
@@ -209,6 +329,14 @@ impl<'a> Parser<'a> {
                         let args = self.function_static_arguments(2)?;
                         Node::ILog(Box::new(args[0].clone()), Box::new(args[1].clone()))
                     }
+                    NativeFunction::If => {
+                        let args = self.function_static_arguments(3)?;
+                        Node::Conditional(
+                            Box::new(args[0].clone()),
+                            Box::new(args[1].clone()),
+                            Box::new(args[2].clone()),
+                        )
+                    }
                 };
                 self.implicit_multiply(current_function)
             }
@@ -222,17 +350,29 @@ impl<'a> Parser<'a> {
                 let expr = self.generate_ast(OperatorCategory::Negative)?;
                 Ok(expr)
             }
+            Token::ExclamationMark => {
+                self.get_next_token()?;
+                let expr = self.generate_ast(OperatorCategory::Negative)?;
+                Ok(Node::LogicalNot(Box::new(expr)))
+            }
             Token::Num(i) => {
+                let end = self.current_span.end;
                 self.get_next_token()?;
+                if let Token::Num(_) = self.current_token {
+                    if self.current_span.start > end {
+                        // Numbers separated by whitespace are not multiplied.
+                        return Err(self.unexpected_token(&["an operator"]));
+                    }
+                }
                 self.implicit_multiply(Node::Number(i))
             }
             Token::Pi => {
                 self.get_next_token()?;
-                Ok(Node::Number(Decimal::PI))
+                self.implicit_multiply(Node::Number(Decimal::PI))
             }
             Token::E => {
                 self.get_next_token()?;
-                Ok(Node::Number(Decimal::E))
+                self.implicit_multiply(Node::Number(Decimal::E))
             }
             Token::LeftParen => self.get_enclosed_elements_with_impl_mult(
                 OperatorCategory::DefaultZero,
@@ -249,9 +389,7 @@ impl<'a> Parser<'a> {
                 Token::RightCeiling,
                 |expr| Node::Ceil(Box::new(expr)),
             ),
-            _ => Err(ParseError::UnableToParse(
-                "Unknown parsing token for parsing number".to_string(),
-            )),
+            _ => Err(self.unexpected_token(&["a number", "a variable", "a function", "("])),
         }
     }
     fn implicit_multiply(&mut self, node: Node) -> Result<Node, ParseError> {
@@ -259,7 +397,11 @@ impl<'a> Parser<'a> {
             || (self.current_token == Token::LeftCeiling)
             || (self.current_token == Token::LeftFloor)
             || matches!(self.current_token, Token::ExplicitFunction(_))
+            || matches!(self.current_token, Token::CustomFunction(_))
             || matches!(self.current_token, Token::Num(_))
+            || matches!(self.current_token, Token::Identifier(_))
+            || self.current_token == Token::Pi
+            || self.current_token == Token::E
         {
             let right = self.generate_ast(OperatorCategory::Multiplicative)?;
             return Ok(Node::Multiply(Box::new(node), Box::new(right)));
@@ -277,15 +419,32 @@ impl<'a> Parser<'a> {
         self.check_paren(end_token)?;
         self.implicit_multiply(get_node(expr))
     }
+    fn unexpected_token<S: ToString>(&self, expected: &[S]) -> ParseError {
+        ParseError::new(
+            ParseErrorKind::UnexpectedToken,
+            self.current_span.clone(),
+            format!("Unexpected {}", self.current_token),
+        )
+        .with_expected(expected)
+    }
+    fn missing_token(&self, expected: &[Token]) -> ParseError {
+        let unclosed = self.current_token == Token::Eof || self.current_token.is_closing_bracket();
+        if !(unclosed && expected.iter().any(Token::is_closing_bracket)) {
+            return self.unexpected_token(expected);
+        }
+        ParseError::new(
+            ParseErrorKind::UnbalancedBracket,
+            self.current_span.clone(),
+            format!("Unexpected {}", self.current_token),
+        )
+        .with_expected(expected)
+    }
     fn check_paren(&mut self, expected: Token) -> Result<(), ParseError> {
         if expected == self.current_token {
             self.get_next_token()?;
             Ok(())
         } else {
-            Err(ParseError::InvalidOperator(format!(
-                "Expected {:?}, got {:?}",
-                expected, self.current_token
-            )))
+            Err(self.missing_token(&[expected]))
         }
     }
     fn convert_token_to_node(&mut self, left_expr: Node) -> Result<Node, ParseError> {
@@ -312,13 +471,28 @@ impl<'a> Parser<'a> {
             }
             Token::Caret => {
                 self.get_next_token()?;
-                let right_expr = self.generate_ast(OperatorCategory::Power)?;
+                // Right-associative: the exponent is itself a power, optionally negated.
+                let right_expr = self.generate_ast(OperatorCategory::Negative)?;
                 Ok(Node::Pow(Box::new(left_expr), Box::new(right_expr)))
             }
             Token::ExclamationMark => {
                 self.get_next_token()?;
                 self.implicit_multiply(Node::Factorial(Box::new(left_expr)))
             }
+            Token::DegToRad => {
+                self.get_next_token()?;
+                Ok(Node::Multiply(
+                    Box::new(left_expr),
+                    Box::new(Node::Number(Decimal::PI / Decimal::new(180, 0))),
+                ))
+            }
+            Token::RadToDeg => {
+                self.get_next_token()?;
+                Ok(Node::Multiply(
+                    Box::new(left_expr),
+                    Box::new(Node::Number(Decimal::new(180, 0) / Decimal::PI)),
+                ))
+            }
             Token::Superscript(script) => {
                 self.get_next_token()?;
                 Ok(Node::Pow(
@@ -331,14 +505,74 @@ impl<'a> Parser<'a> {
                 let right_expr = self.generate_ast(OperatorCategory::Multiplicative)?;
                 Ok(Node::Modulo(Box::new(left_expr), Box::new(right_expr)))
             }
-            _ => Err(ParseError::InvalidOperator(format!(
-                "Please enter a valid operator {:?}",
-                self.current_token
-            ))),
+            Token::Equal => {
+                self.get_next_token()?;
+                let right_expr = self.generate_ast(OperatorCategory::Comparison)?;
+                Ok(Node::Equal(Box::new(left_expr), Box::new(right_expr)))
+            }
+            Token::NotEqual => {
+                self.get_next_token()?;
+                let right_expr = self.generate_ast(OperatorCategory::Comparison)?;
+                Ok(Node::NotEqual(Box::new(left_expr), Box::new(right_expr)))
+            }
+            Token::Less => {
+                self.get_next_token()?;
+                let right_expr = self.generate_ast(OperatorCategory::Comparison)?;
+                Ok(Node::Less(Box::new(left_expr), Box::new(right_expr)))
+            }
+            Token::LessEqual => {
+                self.get_next_token()?;
+                let right_expr = self.generate_ast(OperatorCategory::Comparison)?;
+                Ok(Node::LessEqual(Box::new(left_expr), Box::new(right_expr)))
+            }
+            Token::Greater => {
+                self.get_next_token()?;
+                let right_expr = self.generate_ast(OperatorCategory::Comparison)?;
+                Ok(Node::Greater(Box::new(left_expr), Box::new(right_expr)))
+            }
+            Token::GreaterEqual => {
+                self.get_next_token()?;
+                let right_expr = self.generate_ast(OperatorCategory::Comparison)?;
+                Ok(Node::GreaterEqual(
+                    Box::new(left_expr),
+                    Box::new(right_expr),
+                ))
+            }
+            Token::DoubleAmpersand => {
+                self.get_next_token()?;
+                let right_expr = self.generate_ast(OperatorCategory::LogicalAnd)?;
+                Ok(Node::LogicalAnd(Box::new(left_expr), Box::new(right_expr)))
+            }
+            Token::DoubleBar => {
+                self.get_next_token()?;
+                let right_expr = self.generate_ast(OperatorCategory::LogicalOr)?;
+                Ok(Node::LogicalOr(Box::new(left_expr), Box::new(right_expr)))
+            }
+            Token::QuestionMark => {
+                self.get_next_token()?;
+                let then_expr = self.generate_ast(OperatorCategory::DefaultZero)?;
+                self.check_paren(Token::Colon)?;
+                // Right-associative: the else branch can itself be a conditional.
+                let else_expr = self.generate_ast(OperatorCategory::DefaultZero)?;
+                Ok(Node::Conditional(
+                    Box::new(left_expr),
+                    Box::new(then_expr),
+                    Box::new(else_expr),
+                ))
+            }
+            _ => Err(self.unexpected_token(&["an operator"])),
         }
     }
 }
 
+fn wrong_arity(function: &Token, function_span: Range<usize>, n: usize) -> ParseError {
+    ParseError::new(
+        ParseErrorKind::WrongArity,
+        function_span,
+        format!("The {} function takes {} arguments", function, n),
+    )
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -346,67 +580,97 @@ mod tests {
 
     #[test]
     fn test_pi() {
-        let mut parser = Parser::new("pi", None).unwrap();
+        let mut parser = Parser::new("pi").unwrap();
         let expected = Number(Decimal::PI);
         assert_eq!(parser.parse().unwrap(), expected);
     }
     #[test]
     fn test_e() {
-        let mut parser = Parser::new("e", None).unwrap();
+        let mut parser = Parser::new("e").unwrap();
         let expected = Number(Decimal::E);
         assert_eq!(parser.parse().unwrap(), expected);
     }
     #[test]
+    fn test_deg_to_rad() {
+        let mut parser = Parser::new("1°").unwrap();
+        let expected = Multiply(
+            Box::new(Number(Decimal::new(1, 0))),
+            Box::new(Number(Decimal::PI / Decimal::new(180, 0))),
+        );
+        assert_eq!(parser.parse().unwrap(), expected);
+    }
+    #[test]
+    fn test_rad_to_deg() {
+        let mut parser = Parser::new("1rad").unwrap();
+        let expected = Multiply(
+            Box::new(Number(Decimal::new(1, 0))),
+            Box::new(Number(Decimal::new(180, 0) / Decimal::PI)),
+        );
+        assert_eq!(parser.parse().unwrap(), expected);
+    }
+    #[test]
     fn test_floor() {
-        let mut parser = Parser::new("⌊5.25⌋", None).unwrap();
+        let mut parser = Parser::new("⌊5.25⌋").unwrap();
         let expected = Floor(Box::new(Number(Decimal::new(525, 2))));
         assert_eq!(parser.parse().unwrap(), expected);
     }
     #[test]
     fn test_ceil() {
-        let mut parser = Parser::new("⌈5.25⌉", None).unwrap();
+        let mut parser = Parser::new("⌈5.25⌉").unwrap();
         let expected = Ceil(Box::new(Number(Decimal::new(525, 2))));
         assert_eq!(parser.parse().unwrap(), expected);
     }
     #[test]
     fn test_floor_function() {
-        let mut parser = Parser::new("floor(5.25)", None).unwrap();
+        let mut parser = Parser::new("floor(5.25)").unwrap();
         let expected = Floor(Box::new(Number(Decimal::new(525, 2))));
         assert_eq!(parser.parse().unwrap(), expected);
     }
     #[test]
     fn test_ceil_function() {
-        let mut parser = Parser::new("ceil(5.25)", None).unwrap();
+        let mut parser = Parser::new("ceil(5.25)").unwrap();
         let expected = Ceil(Box::new(Number(Decimal::new(525, 2))));
         assert_eq!(parser.parse().unwrap(), expected);
     }
     #[test]
     fn test_round_function() {
-        let mut parser = Parser::new("round(5.25)", None).unwrap();
+        let mut parser = Parser::new("round(5.25)").unwrap();
         let expected = Round(Box::new(Number(Decimal::new(525, 2))));
         assert_eq!(parser.parse().unwrap(), expected);
     }
     #[test]
+    fn test_round_digits() {
+        let mut parser = Parser::new("round(5.25,1)").unwrap();
+        let expected = RoundTo(
+            Box::new(Number(Decimal::new(525, 2))),
+            Box::new(Number(Decimal::new(1, 0))),
+        );
+        assert_eq!(parser.parse().unwrap(), expected);
+        let error = Parser::new("round(5.25,1,2)").unwrap().parse().unwrap_err();
+        assert_eq!(error.kind(), ParseErrorKind::WrongArity);
+        assert_eq!(error.span(), 0..5);
+    }
+    #[test]
     fn test_truncate_function() {
-        let mut parser = Parser::new("truncate(5.25)", None).unwrap();
+        let mut parser = Parser::new("truncate(5.25)").unwrap();
         let expected = Truncate(Box::new(Number(Decimal::new(525, 2))));
         assert_eq!(parser.parse().unwrap(), expected);
     }
     #[test]
     fn test_negative() {
-        let mut parser = Parser::new("-1", None).unwrap();
+        let mut parser = Parser::new("-1").unwrap();
         let expected = Negative(Box::new(Number(Decimal::new(1, 0))));
         assert_eq!(parser.parse().unwrap(), expected);
     }
     #[test]
     fn test_exclamation_mark() {
-        let mut parser = Parser::new("1!", None).unwrap();
+        let mut parser = Parser::new("1!").unwrap();
         let expected = Factorial(Box::new(Number(Decimal::new(1, 0))));
         assert_eq!(parser.parse().unwrap(), expected);
     }
     #[test]
     fn test_add() {
-        let mut parser = Parser::new("1+2", None).unwrap();
+        let mut parser = Parser::new("1+2").unwrap();
         let expected = Add(
             Box::new(Number(Decimal::new(1, 0))),
             Box::new(Number(Decimal::new(2, 0))),
@@ -415,7 +679,7 @@ mod tests {
     }
     #[test]
     fn test_subtract() {
-        let mut parser = Parser::new("1-2", None).unwrap();
+        let mut parser = Parser::new("1-2").unwrap();
         let expected = Subtract(
             Box::new(Number(Decimal::new(1, 0))),
             Box::new(Number(Decimal::new(2, 0))),
@@ -424,7 +688,7 @@ mod tests {
     }
     #[test]
     fn test_multiply() {
-        let mut parser = Parser::new("1*2", None).unwrap();
+        let mut parser = Parser::new("1*2").unwrap();
         let expected = Multiply(
             Box::new(Number(Decimal::new(1, 0))),
             Box::new(Number(Decimal::new(2, 0))),
@@ -433,7 +697,7 @@ mod tests {
     }
     #[test]
     fn test_divide() {
-        let mut parser = Parser::new("1/2", None).unwrap();
+        let mut parser = Parser::new("1/2").unwrap();
         let expected = Divide(
             Box::new(Number(Decimal::new(1, 0))),
             Box::new(Number(Decimal::new(2, 0))),
@@ -442,7 +706,7 @@ mod tests {
     }
     #[test]
     fn test_caret() {
-        let mut parser = Parser::new("1^2", None).unwrap();
+        let mut parser = Parser::new("1^2").unwrap();
         let expected = Pow(
             Box::new(Number(Decimal::new(1, 0))),
             Box::new(Number(Decimal::new(2, 0))),
@@ -450,8 +714,45 @@ mod tests {
         assert_eq!(parser.parse().unwrap(), expected);
     }
     #[test]
+    fn test_caret_right_associative() {
+        let mut parser = Parser::new("2^3^2").unwrap();
+        let expected = Pow(
+            Box::new(Number(Decimal::new(2, 0))),
+            Box::new(Pow(
+                Box::new(Number(Decimal::new(3, 0))),
+                Box::new(Number(Decimal::new(2, 0))),
+            )),
+        );
+        assert_eq!(parser.parse().unwrap(), expected);
+        let mut parser = Parser::new("2^3²").unwrap();
+        assert_eq!(parser.parse().unwrap(), expected);
+    }
+    #[test]
+    fn test_negative_power() {
+        let mut parser = Parser::new("-2^2").unwrap();
+        let expected = Negative(Box::new(Pow(
+            Box::new(Number(Decimal::new(2, 0))),
+            Box::new(Number(Decimal::new(2, 0))),
+        )));
+        assert_eq!(parser.parse().unwrap(), expected);
+        let mut parser = Parser::new("-2²").unwrap();
+        assert_eq!(parser.parse().unwrap(), expected);
+        let mut parser = Parser::new("2^-2").unwrap();
+        let expected = Pow(
+            Box::new(Number(Decimal::new(2, 0))),
+            Box::new(Negative(Box::new(Number(Decimal::new(2, 0))))),
+        );
+        assert_eq!(parser.parse().unwrap(), expected);
+        let mut parser = Parser::new("-2*3").unwrap();
+        let expected = Multiply(
+            Box::new(Negative(Box::new(Number(Decimal::new(2, 0))))),
+            Box::new(Number(Decimal::new(3, 0))),
+        );
+        assert_eq!(parser.parse().unwrap(), expected);
+    }
+    #[test]
     fn test_modulo() {
-        let mut parser = Parser::new("1%2", None).unwrap();
+        let mut parser = Parser::new("1%2").unwrap();
         let expected = Modulo(
             Box::new(Number(Decimal::new(1, 0))),
             Box::new(Number(Decimal::new(2, 0))),
@@ -460,7 +761,7 @@ mod tests {
     }
     #[test]
     fn test_modulo_function() {
-        let mut parser = Parser::new("mod(3,2)", None).unwrap();
+        let mut parser = Parser::new("mod(3,2)").unwrap();
         let expected = Modulo(
             Box::new(Number(Decimal::new(3, 0))),
             Box::new(Number(Decimal::new(2, 0))),
@@ -469,25 +770,106 @@ mod tests {
     }
     #[test]
     fn test_abs_function() {
-        let mut parser = Parser::new("abs(5)", None).unwrap();
+        let mut parser = Parser::new("abs(5)").unwrap();
         let expected = Abs(Box::new(Number(Decimal::new(5, 0))));
         assert_eq!(parser.parse().unwrap(), expected);
     }
     #[test]
     fn test_sign_function() {
-        let mut parser = Parser::new("sign(5)", None).unwrap();
+        let mut parser = Parser::new("sign(5)").unwrap();
         let expected = Sign(Box::new(Number(Decimal::new(5, 0))));
         assert_eq!(parser.parse().unwrap(), expected);
     }
     #[test]
+    fn test_sin_function() {
+        let mut parser = Parser::new("sin(5.25)").unwrap();
+        let expected = Sin(Box::new(Number(Decimal::new(525, 2))));
+        assert_eq!(parser.parse().unwrap(), expected);
+    }
+    #[test]
+    fn test_cos_function() {
+        let mut parser = Parser::new("cos(5.25)").unwrap();
+        let expected = Cos(Box::new(Number(Decimal::new(525, 2))));
+        assert_eq!(parser.parse().unwrap(), expected);
+    }
+    #[test]
+    fn test_tan_function() {
+        let mut parser = Parser::new("tan(5.25)").unwrap();
+        let expected = Tan(Box::new(Number(Decimal::new(525, 2))));
+        assert_eq!(parser.parse().unwrap(), expected);
+    }
+    #[test]
+    fn test_sinh_function() {
+        let mut parser = Parser::new("sinh(5.25)").unwrap();
+        let expected = Sinh(Box::new(Number(Decimal::new(525, 2))));
+        assert_eq!(parser.parse().unwrap(), expected);
+    }
+    #[test]
+    fn test_cosh_function() {
+        let mut parser = Parser::new("cosh(5.25)").unwrap();
+        let expected = Cosh(Box::new(Number(Decimal::new(525, 2))));
+        assert_eq!(parser.parse().unwrap(), expected);
+    }
+    #[test]
+    fn test_tanh_function() {
+        let mut parser = Parser::new("tanh(5.25)").unwrap();
+        let expected = Tanh(Box::new(Number(Decimal::new(525, 2))));
+        assert_eq!(parser.parse().unwrap(), expected);
+    }
+    #[test]
+    fn test_asin_function() {
+        let mut parser = Parser::new("asin(5.25)").unwrap();
+        let expected = Asin(Box::new(Number(Decimal::new(525, 2))));
+        assert_eq!(parser.parse().unwrap(), expected);
+    }
+    #[test]
+    fn test_acos_function() {
+        let mut parser = Parser::new("acos(5.25)").unwrap();
+        let expected = Acos(Box::new(Number(Decimal::new(525, 2))));
+        assert_eq!(parser.parse().unwrap(), expected);
+    }
+    #[test]
+    fn test_atan_function() {
+        let mut parser = Parser::new("atan(5.25)").unwrap();
+        let expected = Atan(Box::new(Number(Decimal::new(525, 2))));
+        assert_eq!(parser.parse().unwrap(), expected);
+    }
+    #[test]
+    fn test_atan2_function() {
+        let mut parser = Parser::new("atan2(5.25,7.8)").unwrap();
+        let expected = Atan2(
+            Box::new(Number(Decimal::new(525, 2))),
+            Box::new(Number(Decimal::new(78, 1))),
+        );
+        assert_eq!(parser.parse().unwrap(), expected);
+    }
+    #[test]
+    fn test_arsinh_function() {
+        let mut parser = Parser::new("arsinh(5.25)").unwrap();
+        let expected = Arsinh(Box::new(Number(Decimal::new(525, 2))));
+        assert_eq!(parser.parse().unwrap(), expected);
+    }
+    #[test]
+    fn test_arcosh_function() {
+        let mut parser = Parser::new("arcosh(5.25)").unwrap();
+        let expected = Arcosh(Box::new(Number(Decimal::new(525, 2))));
+        assert_eq!(parser.parse().unwrap(), expected);
+    }
+    #[test]
+    fn test_artanh_function() {
+        let mut parser = Parser::new("artanh(5.25)").unwrap();
+        let expected = Artanh(Box::new(Number(Decimal::new(525, 2))));
+        assert_eq!(parser.parse().unwrap(), expected);
+    }
+    #[test]
     fn test_min_function() {
-        let mut parser = Parser::new("min(3)", None).unwrap();
+        let mut parser = Parser::new("min(3)").unwrap();
         let expected = Min(Arc::new(vec![Number(Decimal::new(3, 0))]));
         assert_eq!(parser.parse().unwrap(), expected);
     }
     #[test]
     fn test_min_function2() {
-        let mut parser = Parser::new("min(2,3,5)", None).unwrap();
+        let mut parser = Parser::new("min(2,3,5)").unwrap();
         let expected = Min(Arc::new(vec![
             Number(Decimal::new(2, 0)),
             Number(Decimal::new(3, 0)),
@@ -497,13 +879,13 @@ mod tests {
     }
     #[test]
     fn test_max_function() {
-        let mut parser = Parser::new("max(3)", None).unwrap();
+        let mut parser = Parser::new("max(3)").unwrap();
         let expected = Max(Arc::new(vec![Number(Decimal::new(3, 0))]));
         assert_eq!(parser.parse().unwrap(), expected);
     }
     #[test]
     fn test_max_function2() {
-        let mut parser = Parser::new("max(2,3,5)", None).unwrap();
+        let mut parser = Parser::new("max(2,3,5)").unwrap();
         let expected = Max(Arc::new(vec![
             Number(Decimal::new(2, 0)),
             Number(Decimal::new(3, 0)),
@@ -513,13 +895,13 @@ mod tests {
     }
     #[test]
     fn test_avg_function() {
-        let mut parser = Parser::new("avg(3)", None).unwrap();
+        let mut parser = Parser::new("avg(3)").unwrap();
         let expected = Avg(Arc::new(vec![Number(Decimal::new(3, 0))]));
         assert_eq!(parser.parse().unwrap(), expected);
     }
     #[test]
     fn test_avg_function2() {
-        let mut parser = Parser::new("avg(2,3,5)", None).unwrap();
+        let mut parser = Parser::new("avg(2,3,5)").unwrap();
         let expected = Avg(Arc::new(vec![
             Number(Decimal::new(2, 0)),
             Number(Decimal::new(3, 0)),
@@ -529,7 +911,7 @@ mod tests {
     }
     #[test]
     fn test_implicit_mul_prts() {
-        let mut parser = Parser::new("(2)(3)", None).unwrap();
+        let mut parser = Parser::new("(2)(3)").unwrap();
         let expected = Multiply(
             Box::new(Number(Decimal::new(2, 0))),
             Box::new(Number(Decimal::new(3, 0))),
@@ -538,7 +920,7 @@ mod tests {
     }
     #[test]
     fn test_implicit_mul_prts_floor() {
-        let mut parser = Parser::new("(2)⌊3⌋", None).unwrap();
+        let mut parser = Parser::new("(2)⌊3⌋").unwrap();
         let expected = Multiply(
             Box::new(Number(Decimal::new(2, 0))),
             Box::new(Floor(Box::new(Number(Decimal::new(3, 0))))),
@@ -547,7 +929,7 @@ mod tests {
     }
     #[test]
     fn test_implicit_mul_floor_prts() {
-        let mut parser = Parser::new("⌊2⌋(3)", None).unwrap();
+        let mut parser = Parser::new("⌊2⌋(3)").unwrap();
         let expected = Multiply(
             Box::new(Floor(Box::new(Number(Decimal::new(2, 0))))),
             Box::new(Number(Decimal::new(3, 0))),
@@ -556,7 +938,7 @@ mod tests {
     }
     #[test]
     fn test_implicit_mul_prts_ceil() {
-        let mut parser = Parser::new("(2)⌈3⌉", None).unwrap();
+        let mut parser = Parser::new("(2)⌈3⌉").unwrap();
         let expected = Multiply(
             Box::new(Number(Decimal::new(2, 0))),
             Box::new(Ceil(Box::new(Number(Decimal::new(3, 0))))),
@@ -565,7 +947,7 @@ mod tests {
     }
     #[test]
     fn test_implicit_mul_ceil_prts() {
-        let mut parser = Parser::new("⌈2⌉(3)", None).unwrap();
+        let mut parser = Parser::new("⌈2⌉(3)").unwrap();
         let expected = Multiply(
             Box::new(Ceil(Box::new(Number(Decimal::new(2, 0))))),
             Box::new(Number(Decimal::new(3, 0))),
@@ -574,7 +956,7 @@ mod tests {
     }
     #[test]
     fn test_implicit_mul_floors() {
-        let mut parser = Parser::new("⌊2⌋⌊3⌋", None).unwrap();
+        let mut parser = Parser::new("⌊2⌋⌊3⌋").unwrap();
         let expected = Multiply(
             Box::new(Floor(Box::new(Number(Decimal::new(2, 0))))),
             Box::new(Floor(Box::new(Number(Decimal::new(3, 0))))),
@@ -583,7 +965,7 @@ mod tests {
     }
     #[test]
     fn test_implicit_mul_ceils() {
-        let mut parser = Parser::new("⌈2⌉⌈3⌉", None).unwrap();
+        let mut parser = Parser::new("⌈2⌉⌈3⌉").unwrap();
         let expected = Multiply(
             Box::new(Ceil(Box::new(Number(Decimal::new(2, 0))))),
             Box::new(Ceil(Box::new(Number(Decimal::new(3, 0))))),
@@ -592,7 +974,7 @@ mod tests {
     }
     #[test]
     fn test_implicit_mul_floor_ceil() {
-        let mut parser = Parser::new("⌊2⌋⌈3⌉", None).unwrap();
+        let mut parser = Parser::new("⌊2⌋⌈3⌉").unwrap();
         let expected = Multiply(
             Box::new(Floor(Box::new(Number(Decimal::new(2, 0))))),
             Box::new(Ceil(Box::new(Number(Decimal::new(3, 0))))),
@@ -601,7 +983,7 @@ mod tests {
     }
     #[test]
     fn test_implicit_mul_ceil_floor() {
-        let mut parser = Parser::new("⌈2⌉⌊3⌋", None).unwrap();
+        let mut parser = Parser::new("⌈2⌉⌊3⌋").unwrap();
         let expected = Multiply(
             Box::new(Ceil(Box::new(Number(Decimal::new(2, 0))))),
             Box::new(Floor(Box::new(Number(Decimal::new(3, 0))))),
@@ -610,13 +992,13 @@ mod tests {
     }
     #[test]
     fn test_med_function() {
-        let mut parser = Parser::new("med(3)", None).unwrap();
+        let mut parser = Parser::new("med(3)").unwrap();
         let expected = Med(Arc::new(vec![Number(Decimal::new(3, 0))]));
         assert_eq!(parser.parse().unwrap(), expected);
     }
     #[test]
     fn test_med_function2() {
-        let mut parser = Parser::new("med(2,3,5)", None).unwrap();
+        let mut parser = Parser::new("med(2,3,5)").unwrap();
         let expected = Med(Arc::new(vec![
             Number(Decimal::new(2, 0)),
             Number(Decimal::new(3, 0)),
@@ -626,7 +1008,7 @@ mod tests {
     }
     #[test]
     fn test_pow_function() {
-        let mut parser = Parser::new("pow(3,2)", None).unwrap();
+        let mut parser = Parser::new("pow(3,2)").unwrap();
         let expected = Pow(
             Box::new(Number(Decimal::new(3, 0))),
             Box::new(Number(Decimal::new(2, 0))),
@@ -635,7 +1017,7 @@ mod tests {
     }
     #[test]
     fn test_log_function() {
-        let mut parser = Parser::new("log(3,2)", None).unwrap();
+        let mut parser = Parser::new("log(3,2)").unwrap();
         let expected = Log(
             Box::new(Number(Decimal::new(3, 0))),
             Box::new(Number(Decimal::new(2, 0))),
@@ -644,31 +1026,31 @@ mod tests {
     }
     #[test]
     fn test_sqrt_function() {
-        let mut parser = Parser::new("sqrt(5.25)", None).unwrap();
+        let mut parser = Parser::new("sqrt(5.25)").unwrap();
         let expected = Sqrt(Box::new(Number(Decimal::new(525, 2))));
         assert_eq!(parser.parse().unwrap(), expected);
     }
     #[test]
     fn test_exp_function() {
-        let mut parser = Parser::new("exp(5.25)", None).unwrap();
+        let mut parser = Parser::new("exp(5.25)").unwrap();
         let expected = Exp(Box::new(Number(Decimal::new(525, 2))));
         assert_eq!(parser.parse().unwrap(), expected);
     }
     #[test]
     fn test_exp2_function() {
-        let mut parser = Parser::new("exp2(5.25)", None).unwrap();
+        let mut parser = Parser::new("exp2(5.25)").unwrap();
         let expected = Exp2(Box::new(Number(Decimal::new(525, 2))));
         assert_eq!(parser.parse().unwrap(), expected);
     }
     #[test]
     fn test_ln_function() {
-        let mut parser = Parser::new("ln(5.25)", None).unwrap();
+        let mut parser = Parser::new("ln(5.25)").unwrap();
         let expected = Ln(Box::new(Number(Decimal::new(525, 2))));
         assert_eq!(parser.parse().unwrap(), expected);
     }
     #[test]
     fn test_ilog_function() {
-        let mut parser = Parser::new("ilog(.14159,e)", None).unwrap();
+        let mut parser = Parser::new("ilog(.14159,e)").unwrap();
         let expected = ILog(
             Box::new(Number(Decimal::new(14159, 5))),
             Box::new(Number(Decimal::E)),
@@ -677,8 +1059,67 @@ mod tests {
     }
     #[test]
     fn test_lambert_w_function() {
-        let mut parser = Parser::new("w(5.25)", None).unwrap();
+        let mut parser = Parser::new("w(5.25)").unwrap();
         let expected = LambertW(Box::new(Number(Decimal::new(525, 2))));
         assert_eq!(parser.parse().unwrap(), expected);
     }
+    #[test]
+    fn test_variable() {
+        let mut parser = Parser::new("x").unwrap();
+        let expected = Variable("x".to_string());
+        assert_eq!(parser.parse().unwrap(), expected);
+    }
+    #[test]
+    fn test_implicit_mul_variable() {
+        let mut parser = Parser::new("2x").unwrap();
+        let expected = Multiply(
+            Box::new(Number(Decimal::new(2, 0))),
+            Box::new(Variable("x".to_string())),
+        );
+        assert_eq!(parser.parse().unwrap(), expected);
+    }
+    #[test]
+    fn test_conditional() {
+        let mut parser = Parser::new("1+1<3&&!0?4:5").unwrap();
+        let condition = LogicalAnd(
+            Box::new(Less(
+                Box::new(Add(
+                    Box::new(Number(Decimal::new(1, 0))),
+                    Box::new(Number(Decimal::new(1, 0))),
+                )),
+                Box::new(Number(Decimal::new(3, 0))),
+            )),
+            Box::new(LogicalNot(Box::new(Number(Decimal::new(0, 0))))),
+        );
+        let expected = Conditional(
+            Box::new(condition),
+            Box::new(Number(Decimal::new(4, 0))),
+            Box::new(Number(Decimal::new(5, 0))),
+        );
+        assert_eq!(parser.parse().unwrap(), expected);
+        let mut parser = Parser::new("1?2:3?4:5").unwrap();
+        let expected = Conditional(
+            Box::new(Number(Decimal::new(1, 0))),
+            Box::new(Number(Decimal::new(2, 0))),
+            Box::new(Conditional(
+                Box::new(Number(Decimal::new(3, 0))),
+                Box::new(Number(Decimal::new(4, 0))),
+                Box::new(Number(Decimal::new(5, 0))),
+            )),
+        );
+        assert_eq!(parser.parse().unwrap(), expected);
+        let mut parser = Parser::new("if(0||1,2,3)").unwrap();
+        let expected = Conditional(
+            Box::new(LogicalOr(
+                Box::new(Number(Decimal::new(0, 0))),
+                Box::new(Number(Decimal::new(1, 0))),
+            )),
+            Box::new(Number(Decimal::new(2, 0))),
+            Box::new(Number(Decimal::new(3, 0))),
+        );
+        assert_eq!(parser.parse().unwrap(), expected);
+        let error = Parser::new("1?2").unwrap().parse().unwrap_err();
+        assert_eq!(error.kind(), ParseErrorKind::UnexpectedToken);
+        assert_eq!(error.expected(), [":"]);
+    }
 }
@@ -0,0 +1,39 @@
+use rust_decimal::{Decimal, RoundingStrategy};
+
+/// How an `ExpressionDecimal` rounds the `round` function and its result.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Rounding {
+    /// The strategy used by `round` and to fix the scale of the result.
+    pub strategy: RoundingStrategy,
+    /// The number of decimal places of the result, which keeps the scale it was computed with when `None`.
+    pub scale: Option<u32>,
+}
+
+impl Rounding {
+    /// Round the result to the given number of decimal places, padding it with zeros if needed.
+    pub fn with_scale(strategy: RoundingStrategy, scale: u32) -> Self {
+        Rounding {
+            strategy,
+            scale: Some(scale),
+        }
+    }
+    pub(crate) fn apply(&self, value: Decimal) -> Decimal {
+        match self.scale {
+            Some(scale) => {
+                let mut rounded = value.round_dp_with_strategy(scale, self.strategy);
+                rounded.rescale(scale);
+                rounded
+            }
+            None => value,
+        }
+    }
+}
+
+impl Default for Rounding {
+    fn default() -> Self {
+        Rounding {
+            strategy: RoundingStrategy::MidpointNearestEven,
+            scale: None,
+        }
+    }
+}
@@ -1,8 +1,22 @@
-use crate::utils::OperatorCategory;
+use crate::utils::{Function, OperatorCategory};
 use rust_decimal::Decimal;
+use std::fmt;
 
 #[derive(Debug, PartialEq, Clone)]
 pub enum NativeFunction {
+    Sin,
+    Cos,
+    Tan,
+    Sinh,
+    Cosh,
+    Tanh,
+    Asin,
+    Acos,
+    Atan,
+    Atan2,
+    Arcosh,
+    Arsinh,
+    Artanh,
     Abs,
     Truncate,
     Floor,
@@ -24,6 +38,7 @@ pub enum NativeFunction {
     Max,
     Avg,
     Med,
+    If,
 }
 
 #[derive(Debug, PartialEq, Clone)]
@@ -37,6 +52,16 @@ pub enum Token {
     Multiply,
     Divide,
     Modulo,
+    Equal,
+    NotEqual,
+    Less,
+    LessEqual,
+    Greater,
+    GreaterEqual,
+    DoubleAmpersand,
+    DoubleBar,
+    QuestionMark,
+    Colon,
     LeftParen,
     RightParen,
     Comma,
@@ -44,9 +69,13 @@ pub enum Token {
     RightFloor,
     LeftCeiling,
     RightCeiling,
+    DegToRad,
+    RadToDeg,
     ExplicitFunction(NativeFunction),
+    CustomFunction(Function<Decimal>),
     Superscript(Decimal),
     Num(Decimal),
+    Identifier(String),
     Ans,
     Eof,
 }
@@ -55,11 +84,68 @@ impl Token {
     pub fn get_oper_prec(&self) -> OperatorCategory {
         use self::Token::*;
         match *self {
+            QuestionMark => OperatorCategory::Conditional,
+            DoubleBar => OperatorCategory::LogicalOr,
+            DoubleAmpersand => OperatorCategory::LogicalAnd,
+            Equal | NotEqual | Less | LessEqual | Greater | GreaterEqual => {
+                OperatorCategory::Comparison
+            }
             Add | Subtract => OperatorCategory::Additive,
-            Multiply | Divide | Modulo => OperatorCategory::Multiplicative,
+            Multiply | Divide | Modulo | DegToRad | RadToDeg => OperatorCategory::Multiplicative,
             Caret | Superscript(_) => OperatorCategory::Power,
-            ExclamationMark | ExplicitFunction(_) => OperatorCategory::Functional,
+            ExclamationMark | ExplicitFunction(_) | CustomFunction(_) => {
+                OperatorCategory::Functional
+            }
             _ => OperatorCategory::DefaultZero,
         }
     }
+    pub fn is_closing_bracket(&self) -> bool {
+        use self::Token::*;
+        matches!(self, RightParen | RightFloor | RightCeiling)
+    }
+}
+
+impl fmt::Display for Token {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        use self::Token::*;
+        match self {
+            E => write!(f, "e"),
+            Pi => write!(f, "π"),
+            ExclamationMark => write!(f, "!"),
+            Caret => write!(f, "^"),
+            Add => write!(f, "+"),
+            Subtract => write!(f, "-"),
+            Multiply => write!(f, "*"),
+            Divide => write!(f, "/"),
+            Modulo => write!(f, "%"),
+            Equal => write!(f, "=="),
+            NotEqual => write!(f, "!="),
+            Less => write!(f, "<"),
+            LessEqual => write!(f, "<="),
+            Greater => write!(f, ">"),
+            GreaterEqual => write!(f, ">="),
+            DoubleAmpersand => write!(f, "&&"),
+            DoubleBar => write!(f, "||"),
+            QuestionMark => write!(f, "?"),
+            Colon => write!(f, ":"),
+            LeftParen => write!(f, "("),
+            RightParen => write!(f, ")"),
+            Comma => write!(f, ","),
+            LeftFloor => write!(f, "⌊"),
+            RightFloor => write!(f, "⌋"),
+            LeftCeiling => write!(f, "⌈"),
+            RightCeiling => write!(f, "⌉"),
+            DegToRad => write!(f, "°"),
+            RadToDeg => write!(f, "rad"),
+            ExplicitFunction(function) => {
+                write!(f, "{}", format!("{:?}", function).to_lowercase())
+            }
+            CustomFunction(function) => write!(f, "{}", function.name()),
+            Superscript(value) => write!(f, "^{}", value),
+            Num(value) => write!(f, "{}", value),
+            Identifier(name) => write!(f, "{}", name),
+            Ans => write!(f, "@"),
+            Eof => write!(f, "end of input"),
+        }
+    }
 }
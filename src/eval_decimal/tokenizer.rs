@@ -1,27 +1,46 @@
-use crate::utils::deserialize_superscript_number;
+use crate::utils::{
+    deserialize_number, deserialize_superscript_number, peek_identifier, unrecognized_token,
+    Cursor, Functions, ParseError,
+};
 
 use super::token::{NativeFunction, Token};
 use core::str::FromStr;
 use rust_decimal::Decimal;
-use std::iter::Peekable;
-use std::str::Chars;
+
+/// The names read as constants or units rather than variables.
+pub const RESERVED_WORDS: [&str; 3] = ["e", "pi", "rad"];
 
 pub struct Tokenizer<'a> {
-    expr: Peekable<Chars<'a>>,
+    expr: Cursor<'a>,
+    functions: Option<&'a Functions<Decimal>>,
 }
 
 impl<'a> Tokenizer<'a> {
     pub fn new(new_expr: &'a str) -> Self {
         Tokenizer {
-            expr: new_expr.chars().peekable(),
+            expr: Cursor::new(new_expr),
+            functions: None,
         }
     }
-}
-
-impl<'a> Iterator for Tokenizer<'a> {
-    type Item = Token;
-
-    fn next(&mut self) -> Option<Token> {
+    pub fn with_functions(new_expr: &'a str, functions: &'a Functions<Decimal>) -> Self {
+        Tokenizer {
+            expr: Cursor::new(new_expr),
+            functions: Some(functions),
+        }
+    }
+    /// The byte offset of the next token.
+    pub fn position(&self) -> usize {
+        self.expr.position()
+    }
+    /// Consume the whitespaces before the next token, which only separate tokens.
+    pub fn skip_whitespace(&mut self) {
+        self.expr.skip_whitespace()
+    }
+    /// The error explaining why no token could be read at the given byte offset.
+    pub fn error(&self, start: usize) -> ParseError {
+        unrecognized_token(self.expr.source(), start)
+    }
+    fn native_token(&mut self) -> Option<Token> {
         let current_char = self.expr.next();
 
         match current_char {
@@ -33,7 +52,56 @@ impl<'a> Iterator for Tokenizer<'a> {
             Some('^') => Some(Token::Caret),
             Some('(') => Some(Token::LeftParen),
             Some(')') => Some(Token::RightParen),
-            Some('!') => Some(Token::ExclamationMark),
+            Some('!') => {
+                if let Some('=') = self.expr.peek() {
+                    self.expr.next();
+                    Some(Token::NotEqual)
+                } else {
+                    Some(Token::ExclamationMark)
+                }
+            }
+            Some('=') => {
+                if let Some('=') = self.expr.peek() {
+                    self.expr.next();
+                    Some(Token::Equal)
+                } else {
+                    None
+                }
+            }
+            Some('<') => {
+                if let Some('=') = self.expr.peek() {
+                    self.expr.next();
+                    Some(Token::LessEqual)
+                } else {
+                    Some(Token::Less)
+                }
+            }
+            Some('>') => {
+                if let Some('=') = self.expr.peek() {
+                    self.expr.next();
+                    Some(Token::GreaterEqual)
+                } else {
+                    Some(Token::Greater)
+                }
+            }
+            Some('&') => {
+                if let Some('&') = self.expr.peek() {
+                    self.expr.next();
+                    Some(Token::DoubleAmpersand)
+                } else {
+                    None
+                }
+            }
+            Some('|') => {
+                if let Some('|') = self.expr.peek() {
+                    self.expr.next();
+                    Some(Token::DoubleBar)
+                } else {
+                    None
+                }
+            }
+            Some('?') => Some(Token::QuestionMark),
+            Some(':') => Some(Token::Colon),
             Some(',') => Some(Token::Comma),
             Some('%') => Some(Token::Modulo),
             Some('π') => Some(Token::Pi),
@@ -41,19 +109,12 @@ impl<'a> Iterator for Tokenizer<'a> {
             Some('⌋') => Some(Token::RightFloor),
             Some('⌈') => Some(Token::LeftCeiling),
             Some('⌉') => Some(Token::RightCeiling),
+            Some('°') => Some(Token::DegToRad),
             Some('.') => {
                 let next_char = self.expr.peek()?;
                 if next_char.is_ascii_digit() {
-                    let mut number = "0".to_string();
-                    number.push(current_char?);
-                    while let Some(next_char) = self.expr.peek() {
-                        if next_char.is_ascii_digit() {
-                            number.push(self.expr.next()?);
-                        } else {
-                            break;
-                        }
-                    }
-                    Some(Token::Num(Decimal::from_str(&number).unwrap()))
+                    let (number, exponent) = deserialize_number('.', &mut self.expr)?;
+                    Some(Token::Num(parse_decimal(&number, &exponent)?))
                 } else {
                     None
                 }
@@ -63,99 +124,148 @@ impl<'a> Iterator for Tokenizer<'a> {
                     &current_char?,
                     &mut self.expr,
                 ))
-                .unwrap(),
+                .ok()?,
             )),
             Some('¹') => Some(Token::Superscript(
                 Decimal::from_str(&deserialize_superscript_number(
                     &current_char?,
                     &mut self.expr,
                 ))
-                .unwrap(),
+                .ok()?,
             )),
             Some('²') => Some(Token::Superscript(
                 Decimal::from_str(&deserialize_superscript_number(
                     &current_char?,
                     &mut self.expr,
                 ))
-                .unwrap(),
+                .ok()?,
             )),
             Some('³') => Some(Token::Superscript(
                 Decimal::from_str(&deserialize_superscript_number(
                     &current_char?,
                     &mut self.expr,
                 ))
-                .unwrap(),
+                .ok()?,
             )),
             Some('⁴') => Some(Token::Superscript(
                 Decimal::from_str(&deserialize_superscript_number(
                     &current_char?,
                     &mut self.expr,
                 ))
-                .unwrap(),
+                .ok()?,
             )),
             Some('⁵') => Some(Token::Superscript(
                 Decimal::from_str(&deserialize_superscript_number(
                     &current_char?,
                     &mut self.expr,
                 ))
-                .unwrap(),
+                .ok()?,
             )),
             Some('⁶') => Some(Token::Superscript(
                 Decimal::from_str(&deserialize_superscript_number(
                     &current_char?,
                     &mut self.expr,
                 ))
-                .unwrap(),
+                .ok()?,
             )),
             Some('⁷') => Some(Token::Superscript(
                 Decimal::from_str(&deserialize_superscript_number(
                     &current_char?,
                     &mut self.expr,
                 ))
-                .unwrap(),
+                .ok()?,
             )),
             Some('⁸') => Some(Token::Superscript(
                 Decimal::from_str(&deserialize_superscript_number(
                     &current_char?,
                     &mut self.expr,
                 ))
-                .unwrap(),
+                .ok()?,
             )),
             Some('⁹') => Some(Token::Superscript(
                 Decimal::from_str(&deserialize_superscript_number(
                     &current_char?,
                     &mut self.expr,
                 ))
-                .unwrap(),
+                .ok()?,
             )),
             Some('0'..='9') => {
-                let mut number = current_char?.to_string();
-                while let Some(next_char) = self.expr.peek() {
-                    if next_char.is_ascii_digit() || next_char == &'.' {
-                        number.push(self.expr.next()?);
-                    } else {
-                        break;
-                    }
-                }
-                Some(Token::Num(Decimal::from_str(&number).unwrap()))
+                let (number, exponent) = deserialize_number(current_char?, &mut self.expr)?;
+                Some(Token::Num(parse_decimal(&number, &exponent)?))
             }
-            Some('a') => match self.expr.clone().take(3).collect::<String>().as_str() {
-                "bs(" => {
-                    self.expr.by_ref().take(2).for_each(drop);
-                    Some(Token::ExplicitFunction(NativeFunction::Abs))
+            Some('a') => match self.expr.clone().take(6).collect::<String>().as_str() {
+                "rsinh(" => {
+                    self.expr.by_ref().take(5).for_each(drop);
+                    Some(Token::ExplicitFunction(NativeFunction::Arsinh))
                 }
-                "vg(" => {
-                    self.expr.by_ref().take(2).for_each(drop);
-                    Some(Token::ExplicitFunction(NativeFunction::Avg))
+                "rcosh(" => {
+                    self.expr.by_ref().take(5).for_each(drop);
+                    Some(Token::ExplicitFunction(NativeFunction::Arcosh))
                 }
-                _ => None,
+                "rtanh(" => {
+                    self.expr.by_ref().take(5).for_each(drop);
+                    Some(Token::ExplicitFunction(NativeFunction::Artanh))
+                }
+                _ => match self.expr.clone().take(5).collect::<String>().as_str() {
+                    "tan2(" => {
+                        self.expr.by_ref().take(4).for_each(drop);
+                        Some(Token::ExplicitFunction(NativeFunction::Atan2))
+                    }
+                    "sinh(" => {
+                        self.expr.by_ref().take(4).for_each(drop);
+                        Some(Token::ExplicitFunction(NativeFunction::Arsinh))
+                    }
+                    "cosh(" => {
+                        self.expr.by_ref().take(4).for_each(drop);
+                        Some(Token::ExplicitFunction(NativeFunction::Arcosh))
+                    }
+                    "tanh(" => {
+                        self.expr.by_ref().take(4).for_each(drop);
+                        Some(Token::ExplicitFunction(NativeFunction::Artanh))
+                    }
+                    _ => match self.expr.clone().take(4).collect::<String>().as_str() {
+                        "sin(" => {
+                            self.expr.by_ref().take(3).for_each(drop);
+                            Some(Token::ExplicitFunction(NativeFunction::Asin))
+                        }
+                        "cos(" => {
+                            self.expr.by_ref().take(3).for_each(drop);
+                            Some(Token::ExplicitFunction(NativeFunction::Acos))
+                        }
+                        "tan(" => {
+                            self.expr.by_ref().take(3).for_each(drop);
+                            Some(Token::ExplicitFunction(NativeFunction::Atan))
+                        }
+                        _ => match self.expr.clone().take(3).collect::<String>().as_str() {
+                            "bs(" => {
+                                self.expr.by_ref().take(2).for_each(drop);
+                                Some(Token::ExplicitFunction(NativeFunction::Abs))
+                            }
+                            "vg(" => {
+                                self.expr.by_ref().take(2).for_each(drop);
+                                Some(Token::ExplicitFunction(NativeFunction::Avg))
+                            }
+                            _ => None,
+                        },
+                    },
+                },
             },
             Some('c') => match self.expr.clone().take(4).collect::<String>().as_str() {
                 "eil(" => {
                     self.expr.by_ref().take(3).for_each(drop);
                     Some(Token::ExplicitFunction(NativeFunction::Ceil))
                 }
-                _ => None,
+                "osh(" => {
+                    self.expr.by_ref().take(3).for_each(drop);
+                    Some(Token::ExplicitFunction(NativeFunction::Cosh))
+                }
+                _ => match self.expr.clone().take(3).collect::<String>().as_str() {
+                    "os(" => {
+                        self.expr.by_ref().take(2).for_each(drop);
+                        Some(Token::ExplicitFunction(NativeFunction::Cos))
+                    }
+                    _ => None,
+                },
             },
             Some('e') => {
                 if self.expr.clone().take(4).collect::<String>() == "xp2(" {
@@ -180,6 +290,9 @@ impl<'a> Iterator for Tokenizer<'a> {
                 if self.expr.clone().take(4).collect::<String>() == "log(" {
                     self.expr.by_ref().take(3).for_each(drop);
                     Some(Token::ExplicitFunction(NativeFunction::ILog))
+                } else if self.expr.clone().take(2).collect::<String>() == "f(" {
+                    self.expr.by_ref().take(1).for_each(drop);
+                    Some(Token::ExplicitFunction(NativeFunction::If))
                 } else {
                     None
                 }
@@ -248,6 +361,9 @@ impl<'a> Iterator for Tokenizer<'a> {
                 } else if self.expr.clone().take(4).collect::<String>() == "oot(" {
                     self.expr.by_ref().take(3).for_each(drop);
                     Some(Token::ExplicitFunction(NativeFunction::Root))
+                } else if self.expr.clone().take(2).collect::<String>() == "ad" {
+                    self.expr.by_ref().take(2).for_each(drop);
+                    Some(Token::RadToDeg)
                 } else {
                     None
                 }
@@ -258,15 +374,23 @@ impl<'a> Iterator for Tokenizer<'a> {
                     Some(Token::ExplicitFunction(NativeFunction::Sign))
                 }
                 _ => match self.expr.clone().take(4).collect::<String>().as_str() {
-                    "ign(" => {
+                    "inh(" => {
                         self.expr.by_ref().take(3).for_each(drop);
-                        Some(Token::ExplicitFunction(NativeFunction::Sign))
+                        Some(Token::ExplicitFunction(NativeFunction::Sinh))
                     }
                     "qrt(" => {
                         self.expr.by_ref().take(3).for_each(drop);
                         Some(Token::ExplicitFunction(NativeFunction::Sqrt))
                     }
+                    "ign(" => {
+                        self.expr.by_ref().take(3).for_each(drop);
+                        Some(Token::ExplicitFunction(NativeFunction::Sign))
+                    }
                     _ => match self.expr.clone().take(3).collect::<String>().as_str() {
+                        "in(" => {
+                            self.expr.by_ref().take(2).for_each(drop);
+                            Some(Token::ExplicitFunction(NativeFunction::Sin))
+                        }
                         "gn(" => {
                             self.expr.by_ref().take(2).for_each(drop);
                             Some(Token::ExplicitFunction(NativeFunction::Sign))
@@ -282,6 +406,12 @@ impl<'a> Iterator for Tokenizer<'a> {
                 } else if self.expr.clone().take(5).collect::<String>() == "runc(" {
                     self.expr.by_ref().take(4).for_each(drop);
                     Some(Token::ExplicitFunction(NativeFunction::Truncate))
+                } else if self.expr.clone().take(4).collect::<String>() == "anh(" {
+                    self.expr.by_ref().take(3).for_each(drop);
+                    Some(Token::ExplicitFunction(NativeFunction::Tanh))
+                } else if self.expr.clone().take(3).collect::<String>() == "an(" {
+                    self.expr.by_ref().take(2).for_each(drop);
+                    Some(Token::ExplicitFunction(NativeFunction::Tan))
                 } else {
                     None
                 }
@@ -299,6 +429,53 @@ impl<'a> Iterator for Tokenizer<'a> {
     }
 }
 
+/// Parse a number literal, written in scientific notation when it has an exponent.
+fn parse_decimal(number: &str, exponent: &str) -> Option<Decimal> {
+    if exponent.is_empty() {
+        Decimal::from_str(number).ok()
+    } else {
+        Decimal::from_scientific(&format!("{}{}", number, exponent)).ok()
+    }
+}
+
+impl<'a> Iterator for Tokenizer<'a> {
+    type Item = Token;
+
+    fn next(&mut self) -> Option<Token> {
+        self.expr.skip_whitespace();
+        let (identifier, next_char) = peek_identifier(&self.expr);
+        if !identifier.is_empty() {
+            if next_char == Some('(') {
+                if let Some(function) = self
+                    .functions
+                    .and_then(|functions| functions.get(&identifier))
+                {
+                    let function = function.clone();
+                    self.expr.by_ref().take(identifier.len()).for_each(drop);
+                    return Some(Token::CustomFunction(function));
+                }
+            } else if !RESERVED_WORDS.contains(&identifier.as_str()) {
+                self.expr.by_ref().take(identifier.len()).for_each(drop);
+                return Some(Token::Identifier(identifier));
+            }
+        }
+        if next_char == Some('(') && !identifier.is_empty() {
+            // The native functions are matched along with their `(`, which whitespace may set
+            // apart from the name.
+            let call = format!("{}(", identifier);
+            let mut native = Tokenizer::new(&call);
+            let token = native.native_token()?;
+            if native.position() != identifier.len() {
+                // The word before the arguments is not a native function either.
+                return None;
+            }
+            self.expr.by_ref().take(identifier.len()).for_each(drop);
+            return Some(token);
+        }
+        self.native_token()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -317,6 +494,25 @@ mod tests {
         )
     }
     #[test]
+    fn test_scientific_number() {
+        let mut tokenizer = Tokenizer::new("1.5e-3");
+        assert_eq!(tokenizer.next().unwrap(), Token::Num(Decimal::new(15, 4)));
+        let mut tokenizer = Tokenizer::new("6.022E23");
+        assert_eq!(
+            tokenizer.next().unwrap(),
+            Token::Num(Decimal::new(6022, 3) * Decimal::from(10u128.pow(23)))
+        );
+        let mut tokenizer = Tokenizer::new(".5e+2");
+        assert_eq!(tokenizer.next().unwrap(), Token::Num(Decimal::new(50, 0)));
+    }
+    #[test]
+    fn test_e_const_after_number() {
+        let mut tokenizer = Tokenizer::new("2e-x");
+        assert_eq!(tokenizer.next().unwrap(), Token::Num(Decimal::new(2, 0)));
+        assert_eq!(tokenizer.next().unwrap(), Token::E);
+        assert_eq!(tokenizer.next().unwrap(), Token::Subtract);
+    }
+    #[test]
     fn test_e_const() {
         let mut tokenizer = Tokenizer::new("e");
         assert_eq!(tokenizer.next().unwrap(), Token::E)
@@ -372,11 +568,149 @@ mod tests {
         assert_eq!(tokenizer.next().unwrap(), Token::ExclamationMark)
     }
     #[test]
+    fn test_sin_function() {
+        let mut tokenizer = Tokenizer::new("sin(3.14159)");
+        assert_eq!(
+            tokenizer.next().unwrap(),
+            Token::ExplicitFunction(NativeFunction::Sin)
+        )
+    }
+    #[test]
+    fn test_cos_function() {
+        let mut tokenizer = Tokenizer::new("cos(3.14159)");
+        assert_eq!(
+            tokenizer.next().unwrap(),
+            Token::ExplicitFunction(NativeFunction::Cos)
+        )
+    }
+    #[test]
+    fn test_tan_function() {
+        let mut tokenizer = Tokenizer::new("tan(.14159)");
+        assert_eq!(
+            tokenizer.next().unwrap(),
+            Token::ExplicitFunction(NativeFunction::Tan)
+        )
+    }
+    #[test]
+    fn test_sinh_function() {
+        let mut tokenizer = Tokenizer::new("sinh(3.14159)");
+        assert_eq!(
+            tokenizer.next().unwrap(),
+            Token::ExplicitFunction(NativeFunction::Sinh)
+        )
+    }
+    #[test]
+    fn test_cosh_function() {
+        let mut tokenizer = Tokenizer::new("cosh(3.14159)");
+        assert_eq!(
+            tokenizer.next().unwrap(),
+            Token::ExplicitFunction(NativeFunction::Cosh)
+        )
+    }
+    #[test]
+    fn test_tanh_function() {
+        let mut tokenizer = Tokenizer::new("tanh(.14159)");
+        assert_eq!(
+            tokenizer.next().unwrap(),
+            Token::ExplicitFunction(NativeFunction::Tanh)
+        )
+    }
+    #[test]
+    fn test_asin_function() {
+        let mut tokenizer = Tokenizer::new("asin(3.14159)");
+        assert_eq!(
+            tokenizer.next().unwrap(),
+            Token::ExplicitFunction(NativeFunction::Asin)
+        )
+    }
+    #[test]
+    fn test_acos_function() {
+        let mut tokenizer = Tokenizer::new("acos(3.14159)");
+        assert_eq!(
+            tokenizer.next().unwrap(),
+            Token::ExplicitFunction(NativeFunction::Acos)
+        )
+    }
+    #[test]
+    fn test_atan_function() {
+        let mut tokenizer = Tokenizer::new("atan(.14159)");
+        assert_eq!(
+            tokenizer.next().unwrap(),
+            Token::ExplicitFunction(NativeFunction::Atan)
+        )
+    }
+    #[test]
+    fn test_atan2_function() {
+        let mut tokenizer = Tokenizer::new("atan2(.14159, 2.1415)");
+        assert_eq!(
+            tokenizer.next().unwrap(),
+            Token::ExplicitFunction(NativeFunction::Atan2)
+        )
+    }
+    #[test]
+    fn test_asinh_function() {
+        let mut tokenizer = Tokenizer::new("asinh(3.14159)");
+        assert_eq!(
+            tokenizer.next().unwrap(),
+            Token::ExplicitFunction(NativeFunction::Arsinh)
+        )
+    }
+    #[test]
+    fn test_acosh_function() {
+        let mut tokenizer = Tokenizer::new("acosh(3.14159)");
+        assert_eq!(
+            tokenizer.next().unwrap(),
+            Token::ExplicitFunction(NativeFunction::Arcosh)
+        )
+    }
+    #[test]
+    fn test_atanh_function() {
+        let mut tokenizer = Tokenizer::new("atanh(.14159)");
+        assert_eq!(
+            tokenizer.next().unwrap(),
+            Token::ExplicitFunction(NativeFunction::Artanh)
+        )
+    }
+    #[test]
+    fn test_arsinh_function() {
+        let mut tokenizer = Tokenizer::new("arsinh(3.14159)");
+        assert_eq!(
+            tokenizer.next().unwrap(),
+            Token::ExplicitFunction(NativeFunction::Arsinh)
+        )
+    }
+    #[test]
+    fn test_arcosh_function() {
+        let mut tokenizer = Tokenizer::new("arcosh(3.14159)");
+        assert_eq!(
+            tokenizer.next().unwrap(),
+            Token::ExplicitFunction(NativeFunction::Arcosh)
+        )
+    }
+    #[test]
+    fn test_artanh_function() {
+        let mut tokenizer = Tokenizer::new("artanh(.14159)");
+        assert_eq!(
+            tokenizer.next().unwrap(),
+            Token::ExplicitFunction(NativeFunction::Artanh)
+        )
+    }
+    #[test]
     fn test_comma_operator() {
         let mut tokenizer = Tokenizer::new(",");
         assert_eq!(tokenizer.next().unwrap(), Token::Comma)
     }
     #[test]
+    fn test_deg_to_rad_operator() {
+        let mut tokenizer = Tokenizer::new("°");
+        assert_eq!(tokenizer.next().unwrap(), Token::DegToRad)
+    }
+    #[test]
+    fn test_rad_to_deg_operator() {
+        let mut tokenizer = Tokenizer::new("rad");
+        assert_eq!(tokenizer.next().unwrap(), Token::RadToDeg)
+    }
+    #[test]
     fn test_add_operator() {
         let mut tokenizer = Tokenizer::new("+");
         assert_eq!(tokenizer.next().unwrap(), Token::Add)
@@ -614,4 +948,45 @@ mod tests {
             Token::ExplicitFunction(NativeFunction::LambertW)
         )
     }
+    #[test]
+    fn test_identifier() {
+        let mut tokenizer = Tokenizer::new("price_2*x");
+        assert_eq!(
+            tokenizer.next().unwrap(),
+            Token::Identifier("price_2".to_string())
+        );
+        assert_eq!(tokenizer.next().unwrap(), Token::Multiply);
+        assert_eq!(
+            tokenizer.next().unwrap(),
+            Token::Identifier("x".to_string())
+        );
+    }
+    #[test]
+    fn test_identifier_starting_with_reserved_word() {
+        let mut tokenizer = Tokenizer::new("exact");
+        assert_eq!(
+            tokenizer.next().unwrap(),
+            Token::Identifier("exact".to_string())
+        )
+    }
+    #[test]
+    fn test_logical_operators() {
+        let mut tokenizer = Tokenizer::new("==!=<=<>=>&&||!?:if(");
+        for token in [
+            Token::Equal,
+            Token::NotEqual,
+            Token::LessEqual,
+            Token::Less,
+            Token::GreaterEqual,
+            Token::Greater,
+            Token::DoubleAmpersand,
+            Token::DoubleBar,
+            Token::ExclamationMark,
+            Token::QuestionMark,
+            Token::Colon,
+            Token::ExplicitFunction(NativeFunction::If),
+        ] {
+            assert_eq!(tokenizer.next().unwrap(), token);
+        }
+    }
 }
@@ -1,4 +1,6 @@
-use std::{error, sync::Arc};
+use std::sync::Arc;
+
+use crate::utils::{Context, EvalError, Function};
 
 #[derive(Debug, Clone, PartialEq)]
 pub enum Node {
@@ -43,6 +45,19 @@ pub enum Node {
     Avg(Arc<Vec<Node>>),
     Med(Arc<Vec<Node>>),
     Number(f64),
+    CustomFunction(Function<f64>, Arc<Vec<Node>>),
+    Equal(Box<Node>, Box<Node>),
+    NotEqual(Box<Node>, Box<Node>),
+    Less(Box<Node>, Box<Node>),
+    LessEqual(Box<Node>, Box<Node>),
+    Greater(Box<Node>, Box<Node>),
+    GreaterEqual(Box<Node>, Box<Node>),
+    LogicalAnd(Box<Node>, Box<Node>),
+    LogicalOr(Box<Node>, Box<Node>),
+    LogicalNot(Box<Node>),
+    Conditional(Box<Node>, Box<Node>, Box<Node>),
+    Variable(String),
+    Ans,
 }
 
 fn gamma(a: f64) -> f64 {
@@ -78,20 +93,135 @@ fn gamma(a: f64) -> f64 {
     }
 }
 
-pub fn eval(expr: Node) -> Result<f64, Box<dyn error::Error>> {
+fn real(function: &str, result: f64) -> Result<f64, EvalError> {
+    if result.is_nan() {
+        Err(EvalError::Domain(function.to_string()))
+    } else {
+        Ok(result)
+    }
+}
+
+/// The principal branch of the Lambert W function, computed with Halley's method.
+fn lambert_w(x: f64) -> Result<f64, EvalError> {
+    if x.is_nan() || x < -(-1.0f64).exp() {
+        return Err(EvalError::Domain("lambertw".to_string()));
+    }
+    if x.is_infinite() {
+        return Err(EvalError::Overflow);
+    }
+    // Start from the asymptote ln(x) - ln(ln(x)) for the large arguments, so that the method
+    // converges in a few steps.
+    let mut w = if x > std::f64::consts::E {
+        let ln = x.ln();
+        ln - ln.ln()
+    } else {
+        0.0
+    };
+    for _ in 0..32 {
+        // The step divided through by exp(w), which keeps it finite for the large arguments.
+        let residual = w - x * (-w).exp();
+        let next = w - residual / (w + 1.0 - (w + 2.0) * residual / (2.0 * w + 2.0));
+        if !next.is_finite() || next == w {
+            break;
+        }
+        w = next;
+    }
+    Ok(w)
+}
+
+/// 1 when the condition holds and 0 otherwise.
+fn truth(condition: bool) -> f64 {
+    if condition {
+        1.0
+    } else {
+        0.0
+    }
+}
+
+pub fn eval(expr: &Node, placeholder: f64, context: &dyn Context<f64>) -> Result<f64, EvalError> {
     use self::Node::*;
     match expr {
-        Number(i) => Ok(i),
-        Add(expr1, expr2) => Ok(eval(*expr1)? + eval(*expr2)?),
-        Subtract(expr1, expr2) => Ok(eval(*expr1)? - eval(*expr2)?),
-        Multiply(expr1, expr2) => Ok(eval(*expr1)? * eval(*expr2)?),
-        Divide(expr1, expr2) => Ok(eval(*expr1)? / eval(*expr2)?),
-        Modulo(expr1, expr2) => Ok(eval(*expr1)? % eval(*expr2)?),
-        Negative(expr1) => Ok(-(eval(*expr1)?)),
-        Pow(expr1, expr2) => Ok(eval(*expr1)?.powf(eval(*expr2)?)),
-        Root(n_th_expr, x_expr) => Ok(eval(*x_expr)?.powf(1.0 / eval(*n_th_expr)?)),
+        Number(i) => Ok(*i),
+        Ans => Ok(placeholder),
+        CustomFunction(function, args) => {
+            let mut values = vec![];
+            for arg in args.iter() {
+                values.push(eval(arg, placeholder, context)?);
+            }
+            function.call(&values)
+        }
+        Variable(name) => match context.get(name) {
+            Some(value) => Ok(value),
+            None => Err(EvalError::UndefinedVariable(name.clone())),
+        },
+        Add(expr1, expr2) => {
+            Ok(eval(expr1, placeholder, context)? + eval(expr2, placeholder, context)?)
+        }
+        Subtract(expr1, expr2) => {
+            Ok(eval(expr1, placeholder, context)? - eval(expr2, placeholder, context)?)
+        }
+        Multiply(expr1, expr2) => {
+            Ok(eval(expr1, placeholder, context)? * eval(expr2, placeholder, context)?)
+        }
+        Divide(expr1, expr2) => {
+            let dividend = eval(expr1, placeholder, context)?;
+            let divisor = eval(expr2, placeholder, context)?;
+            if divisor == 0.0 {
+                return Err(EvalError::DivisionByZero);
+            }
+            Ok(dividend / divisor)
+        }
+        Modulo(expr1, expr2) => {
+            let dividend = eval(expr1, placeholder, context)?;
+            let divisor = eval(expr2, placeholder, context)?;
+            if divisor == 0.0 {
+                return Err(EvalError::DivisionByZero);
+            }
+            Ok(dividend % divisor)
+        }
+        Equal(expr1, expr2) => Ok(truth(
+            eval(expr1, placeholder, context)? == eval(expr2, placeholder, context)?,
+        )),
+        NotEqual(expr1, expr2) => Ok(truth(
+            eval(expr1, placeholder, context)? != eval(expr2, placeholder, context)?,
+        )),
+        Less(expr1, expr2) => Ok(truth(
+            eval(expr1, placeholder, context)? < eval(expr2, placeholder, context)?,
+        )),
+        LessEqual(expr1, expr2) => Ok(truth(
+            eval(expr1, placeholder, context)? <= eval(expr2, placeholder, context)?,
+        )),
+        Greater(expr1, expr2) => Ok(truth(
+            eval(expr1, placeholder, context)? > eval(expr2, placeholder, context)?,
+        )),
+        GreaterEqual(expr1, expr2) => Ok(truth(
+            eval(expr1, placeholder, context)? >= eval(expr2, placeholder, context)?,
+        )),
+        LogicalAnd(expr1, expr2) => Ok(truth(
+            eval(expr1, placeholder, context)? != 0.0 && eval(expr2, placeholder, context)? != 0.0,
+        )),
+        LogicalOr(expr1, expr2) => Ok(truth(
+            eval(expr1, placeholder, context)? != 0.0 || eval(expr2, placeholder, context)? != 0.0,
+        )),
+        LogicalNot(expr1) => Ok(truth(eval(expr1, placeholder, context)? == 0.0)),
+        Conditional(condition, expr1, expr2) => {
+            if eval(condition, placeholder, context)? != 0.0 {
+                eval(expr1, placeholder, context)
+            } else {
+                eval(expr2, placeholder, context)
+            }
+        }
+        Negative(expr1) => Ok(-(eval(expr1, placeholder, context)?)),
+        Pow(expr1, expr2) => real(
+            "pow",
+            eval(expr1, placeholder, context)?.powf(eval(expr2, placeholder, context)?),
+        ),
+        Root(n_th_expr, x_expr) => real(
+            "root",
+            eval(x_expr, placeholder, context)?.powf(1.0 / eval(n_th_expr, placeholder, context)?),
+        ),
         Factorial(sub_expr) => {
-            let sub_result = eval(*sub_expr)?;
+            let sub_result = eval(sub_expr, placeholder, context)?;
             if sub_result >= 0.0 {
                 if (sub_result % 1.0) > 0.0 {
                     Ok(gamma(sub_result + 1.0))
@@ -99,74 +229,70 @@ pub fn eval(expr: Node) -> Result<f64, Box<dyn error::Error>> {
                     let mut factorial_result = 1.0;
                     for i in 2..=(sub_result as usize) {
                         factorial_result *= i as f64;
+                        if factorial_result.is_infinite() {
+                            break;
+                        }
                     }
                     Ok(factorial_result)
                 }
             } else if (sub_result % 1.0) == 0.0 {
-                Ok(f64::NAN)
+                Err(EvalError::NegativeFactorial)
             } else {
                 Ok(gamma(sub_result + 1.0))
             }
         }
-        LambertW(expr) => {
-            let sub_expr = eval(*expr)?;
-            let min_one: f64 = -1.0;
-            if sub_expr < -min_one.exp() {
-                return Err("The Lambert W function is not defined for {}.".into());
-            }
-            let iterations = (4).max((sub_expr.log10() / 3.0).ceil() as i32);
-            let mut w: f64 = 0.0;
-            for _ in 0..iterations {
-                let exp_w = w.exp();
-                w -= (w * exp_w - sub_expr)
-                    / (exp_w * (w + 1.0) - (w + 2.0) * (w * exp_w - sub_expr) / (2.0 * w + 2.0));
-            }
-            Ok(w)
-        }
+        LambertW(expr) => lambert_w(eval(expr, placeholder, context)?),
         ILog(expr1, expr2) => {
-            let mut n = eval(*expr1)?;
-            let b = eval(*expr2)?;
+            let mut n = eval(expr1, placeholder, context)?;
+            let b = eval(expr2, placeholder, context)?;
             let mut x: f64 = 0.0;
             while n > 1.0 {
                 x += 1.0;
-                n = (n.log10() / b.log10()).floor();
+                let next = (n.log10() / b.log10()).floor();
+                if next.is_nan() || next >= n {
+                    return Err(EvalError::Domain("ilog".to_string()));
+                }
+                n = next;
             }
             Ok(x)
         }
-        Abs(sub_expr) => Ok(eval(*sub_expr)?.abs()),
-        Floor(sub_expr) => Ok(eval(*sub_expr)?.floor()),
-        Ceil(sub_expr) => Ok(eval(*sub_expr)?.ceil()),
-        Round(sub_expr) => Ok(eval(*sub_expr)?.round()),
-        Sin(sub_expr) => Ok(eval(*sub_expr)?.sin()),
-        Cos(sub_expr) => Ok(eval(*sub_expr)?.cos()),
-        Tan(sub_expr) => Ok(eval(*sub_expr)?.tan()),
-        Sinh(sub_expr) => Ok(eval(*sub_expr)?.sinh()),
-        Cosh(sub_expr) => Ok(eval(*sub_expr)?.cosh()),
-        Tanh(sub_expr) => Ok(eval(*sub_expr)?.tanh()),
-        Asin(sub_expr) => Ok(eval(*sub_expr)?.asin()),
-        Acos(sub_expr) => Ok(eval(*sub_expr)?.acos()),
-        Atan(sub_expr) => Ok(eval(*sub_expr)?.atan()),
-        Arsinh(sub_expr) => Ok(eval(*sub_expr)?.asinh()),
-        Arcosh(sub_expr) => Ok(eval(*sub_expr)?.acosh()),
-        Artanh(sub_expr) => Ok(eval(*sub_expr)?.atanh()),
-        Sqrt(sub_expr) => Ok(eval(*sub_expr)?.sqrt()),
-        Ln(sub_expr) => Ok(eval(*sub_expr)?.ln()),
-        Lb(sub_expr) => Ok(eval(*sub_expr)?.log(2.0)),
-        Truncate(sub_expr) => Ok(eval(*sub_expr)?.trunc()),
-        Sign(sub_expr) => Ok(eval(*sub_expr)?.signum()),
-        Exp(sub_expr) => Ok(eval(*sub_expr)?.exp()),
-        Exp2(sub_expr) => Ok(eval(*sub_expr)?.exp2()),
-        Log(expr1, expr2) => Ok(eval(*expr1)?.log(eval(*expr2)?)),
+        Abs(sub_expr) => Ok(eval(sub_expr, placeholder, context)?.abs()),
+        Floor(sub_expr) => Ok(eval(sub_expr, placeholder, context)?.floor()),
+        Ceil(sub_expr) => Ok(eval(sub_expr, placeholder, context)?.ceil()),
+        Round(sub_expr) => Ok(eval(sub_expr, placeholder, context)?.round()),
+        Sin(sub_expr) => Ok(eval(sub_expr, placeholder, context)?.sin()),
+        Cos(sub_expr) => Ok(eval(sub_expr, placeholder, context)?.cos()),
+        Tan(sub_expr) => Ok(eval(sub_expr, placeholder, context)?.tan()),
+        Sinh(sub_expr) => Ok(eval(sub_expr, placeholder, context)?.sinh()),
+        Cosh(sub_expr) => Ok(eval(sub_expr, placeholder, context)?.cosh()),
+        Tanh(sub_expr) => Ok(eval(sub_expr, placeholder, context)?.tanh()),
+        Asin(sub_expr) => real("asin", eval(sub_expr, placeholder, context)?.asin()),
+        Acos(sub_expr) => real("acos", eval(sub_expr, placeholder, context)?.acos()),
+        Atan(sub_expr) => Ok(eval(sub_expr, placeholder, context)?.atan()),
+        Arsinh(sub_expr) => Ok(eval(sub_expr, placeholder, context)?.asinh()),
+        Arcosh(sub_expr) => real("arcosh", eval(sub_expr, placeholder, context)?.acosh()),
+        Artanh(sub_expr) => real("artanh", eval(sub_expr, placeholder, context)?.atanh()),
+        Sqrt(sub_expr) => real("sqrt", eval(sub_expr, placeholder, context)?.sqrt()),
+        Ln(sub_expr) => real("ln", eval(sub_expr, placeholder, context)?.ln()),
+        Lb(sub_expr) => real("lb", eval(sub_expr, placeholder, context)?.log(2.0)),
+        Truncate(sub_expr) => Ok(eval(sub_expr, placeholder, context)?.trunc()),
+        Sign(sub_expr) => Ok(eval(sub_expr, placeholder, context)?.signum()),
+        Exp(sub_expr) => Ok(eval(sub_expr, placeholder, context)?.exp()),
+        Exp2(sub_expr) => Ok(eval(sub_expr, placeholder, context)?.exp2()),
+        Log(expr1, expr2) => real(
+            "log",
+            eval(expr1, placeholder, context)?.log(eval(expr2, placeholder, context)?),
+        ),
         Min(args) => {
             if args.len() > 1 {
                 let mut result = f64::INFINITY;
-                for arg in <Vec<Node> as Clone>::clone(&args).into_iter() {
-                    result = eval(arg).unwrap().min(result);
+                for arg in args.iter() {
+                    result = eval(arg, placeholder, context)?.min(result);
                 }
                 Ok(result)
             } else {
                 match args.first() {
-                    Some(arg) => Ok(eval((*arg).clone())?),
+                    Some(arg) => Ok(eval(arg, placeholder, context)?),
                     None => Ok(0.0),
                 }
             }
@@ -174,29 +300,33 @@ pub fn eval(expr: Node) -> Result<f64, Box<dyn error::Error>> {
         Max(args) => {
             if args.len() > 1 {
                 let mut result = f64::NEG_INFINITY;
-                for arg in <Vec<Node> as Clone>::clone(&args).into_iter() {
-                    result = eval(arg).unwrap().max(result);
+                for arg in args.iter() {
+                    result = eval(arg, placeholder, context)?.max(result);
                 }
                 Ok(result)
             } else {
                 match args.first() {
-                    Some(arg) => Ok(eval((*arg).clone())?),
+                    Some(arg) => Ok(eval(arg, placeholder, context)?),
                     None => Ok(0.0),
                 }
             }
         }
         Avg(args) => {
             let mut result = 0.0;
-            for arg in <Vec<Node> as Clone>::clone(&args).into_iter() {
-                result += eval(arg).unwrap();
+            for arg in args.iter() {
+                result += eval(arg, placeholder, context)?;
             }
             let len = args.len() as f64;
             Ok(result / len)
         }
         Med(args) => {
             let mut results = vec![];
-            for arg in <Vec<Node> as Clone>::clone(&args).into_iter() {
-                results.push(eval(arg).unwrap());
+            for arg in args.iter() {
+                let value = eval(arg, placeholder, context)?;
+                if value.is_nan() {
+                    return Err(EvalError::Domain("med".to_string()));
+                }
+                results.push(value);
             }
             results.sort_by(|a, b| a.partial_cmp(b).unwrap());
             let len = results.len();
@@ -206,67 +336,156 @@ pub fn eval(expr: Node) -> Result<f64, Box<dyn error::Error>> {
                 Ok(results[len >> 1])
             }
         }
-        Atan2(expr1, expr2) => Ok(eval(*expr1)?.atan2(eval(*expr2)?)),
+        Atan2(expr1, expr2) => {
+            Ok(eval(expr1, placeholder, context)?.atan2(eval(expr2, placeholder, context)?))
+        }
     }
 }
 
 //Unit tests
 #[cfg(test)]
 mod tests {
+    use std::collections::HashMap;
+
     use crate::eval_f64::{ast::eval, parser::Parser};
+    use crate::utils::EvalError;
 
     #[test]
     fn test_expr1() {
-        let ast = Parser::new("1+2-3", None).unwrap().parse().unwrap();
-        let value = eval(ast).unwrap();
+        let ast = Parser::new("1+2-3").unwrap().parse().unwrap();
+        let value = eval(&ast, 0.0, &HashMap::new()).unwrap();
         assert_eq!(value, 0.0);
     }
     #[test]
     fn test_expr2() {
-        let ast = Parser::new("3+2-1*5/4", None).unwrap().parse().unwrap();
-        let value = eval(ast).unwrap();
+        let ast = Parser::new("3+2-1*5/4").unwrap().parse().unwrap();
+        let value = eval(&ast, 0.0, &HashMap::new()).unwrap();
         assert_eq!(value, 3.75);
     }
     #[test]
     fn test_expr3() {
-        let ast = Parser::new("5+(2*7-3!)*3", None).unwrap().parse().unwrap();
-        let value = eval(ast).unwrap();
+        let ast = Parser::new("5+(2*7-3!)*3").unwrap().parse().unwrap();
+        let value = eval(&ast, 0.0, &HashMap::new()).unwrap();
         assert_eq!(value, 29.0);
     }
     #[test]
     fn test_expr4() {
-        let ast = Parser::new("2*4%3/2", None).unwrap().parse().unwrap();
-        let value = eval(ast).unwrap();
+        let ast = Parser::new("2*4%3/2").unwrap().parse().unwrap();
+        let value = eval(&ast, 0.0, &HashMap::new()).unwrap();
         assert_eq!(value, 1.0);
     }
     #[test]
     fn test_expr5() {
-        let ast = Parser::new("3*2^3*3", None).unwrap().parse().unwrap();
-        let value = eval(ast).unwrap();
+        let ast = Parser::new("3*2^3*3").unwrap().parse().unwrap();
+        let value = eval(&ast, 0.0, &HashMap::new()).unwrap();
         assert_eq!(value, 72.0);
     }
     #[test]
     fn test_expr6() {
-        let ast = Parser::new("2+3*atan2(3,7)", None)
-            .unwrap()
-            .parse()
-            .unwrap();
-        let value = eval(ast).unwrap();
-        assert_eq!(value, 2.0 + 3.0 * (3.0 as f64).atan2(7.0));
+        let ast = Parser::new("2+3*atan2(3,7)").unwrap().parse().unwrap();
+        let value = eval(&ast, 0.0, &HashMap::new()).unwrap();
+        assert_eq!(value, 2.0 + 3.0 * 3.0_f64.atan2(7.0));
     }
     #[test]
     fn test_expr7() {
-        let ast = Parser::new("med(5,2,8,9,7)", None)
-            .unwrap()
-            .parse()
-            .unwrap();
-        let value = eval(ast).unwrap();
+        let ast = Parser::new("med(5,2,8,9,7)").unwrap().parse().unwrap();
+        let value = eval(&ast, 0.0, &HashMap::new()).unwrap();
         assert_eq!(value, 7.0);
     }
     #[test]
     fn test_expr8() {
-        let ast = Parser::new("med(5,2,8,9)", None).unwrap().parse().unwrap();
-        let value = eval(ast).unwrap();
+        let ast = Parser::new("med(5,2,8,9)").unwrap().parse().unwrap();
+        let value = eval(&ast, 0.0, &HashMap::new()).unwrap();
         assert_eq!(value, 6.5);
     }
+    #[test]
+    fn test_lambert_w() {
+        let lambert_w = |formula: &str| {
+            let ast = Parser::new(formula).unwrap().parse().unwrap();
+            eval(&ast, 0.0, &HashMap::new())
+        };
+        for x in [-0.3, 0.0, 1.0, 10.0, 1e20, 1e308] {
+            let w = lambert_w(&format!("lambert_w({})", x)).unwrap();
+            assert!((w * w.exp() - x).abs() <= 1e-12 * x.abs().max(1.0), "{}", x);
+        }
+        assert_eq!(lambert_w("lambert_w(1e400)"), Err(EvalError::Overflow));
+        assert_eq!(
+            lambert_w("lambert_w(1e400-1e400)"),
+            Err(EvalError::Domain("lambertw".to_string()))
+        );
+    }
+    #[test]
+    fn test_eval_errors() {
+        let ast = Parser::new("1/0").unwrap().parse().unwrap();
+        assert_eq!(
+            eval(&ast, 0.0, &HashMap::new()),
+            Err(EvalError::DivisionByZero)
+        );
+        let ast = Parser::new("sqrt(-1)").unwrap().parse().unwrap();
+        assert_eq!(
+            eval(&ast, 0.0, &HashMap::new()),
+            Err(EvalError::Domain("sqrt".to_string()))
+        );
+        let ast = Parser::new("asin(2)").unwrap().parse().unwrap();
+        assert_eq!(
+            eval(&ast, 0.0, &HashMap::new()),
+            Err(EvalError::Domain("asin".to_string()))
+        );
+        let ast = Parser::new("(-2)!").unwrap().parse().unwrap();
+        assert_eq!(
+            eval(&ast, 0.0, &HashMap::new()),
+            Err(EvalError::NegativeFactorial)
+        );
+        let ast = Parser::new("ilog(100,1)").unwrap().parse().unwrap();
+        assert_eq!(
+            eval(&ast, 0.0, &HashMap::new()),
+            Err(EvalError::Domain("ilog".to_string()))
+        );
+        let ast = Parser::new("med(1e400-1e400,1)").unwrap().parse().unwrap();
+        assert_eq!(
+            eval(&ast, 0.0, &HashMap::new()),
+            Err(EvalError::Domain("med".to_string()))
+        );
+        let ast = Parser::new("min(3,1/0)").unwrap().parse().unwrap();
+        assert_eq!(
+            eval(&ast, 0.0, &HashMap::new()),
+            Err(EvalError::DivisionByZero)
+        );
+    }
+    #[test]
+    fn test_logic() {
+        for (formula, expected) in [
+            ("2<3", 1),
+            ("3<=2", 0),
+            ("2==2", 1),
+            ("2!=2", 0),
+            ("3>2", 1),
+            ("2>=3", 0),
+            ("1&&0", 0),
+            ("0||2", 1),
+            ("!0", 1),
+            ("!5", 0),
+            ("1<2?10:20", 10),
+            ("if(0,10,20)", 20),
+            ("0?1:0?2:3", 3),
+            ("0&&x", 0),
+            ("1||x", 1),
+            ("1?2:x", 2),
+            ("if(0,x,3)", 3),
+            ("-1<-0.5", 1),
+        ] {
+            let ast = Parser::new(formula).unwrap().parse().unwrap();
+            assert_eq!(
+                eval(&ast, 0.0, &HashMap::new()),
+                Ok(expected as f64),
+                "{}",
+                formula
+            );
+        }
+        let ast = Parser::new("1?x:2").unwrap().parse().unwrap();
+        assert_eq!(
+            eval(&ast, 0.0, &HashMap::new()),
+            Err(EvalError::UndefinedVariable("x".to_string()))
+        );
+    }
 }
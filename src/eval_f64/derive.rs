@@ -0,0 +1,406 @@
+use super::ast::Node;
+use crate::utils::EvalError;
+use std::sync::Arc;
+
+fn is_number(expr: &Node, value: f64) -> bool {
+    matches!(expr, Node::Number(number) if *number == value)
+}
+
+fn add(expr1: Node, expr2: Node) -> Node {
+    if is_number(&expr1, 0.0) {
+        expr2
+    } else if is_number(&expr2, 0.0) {
+        expr1
+    } else {
+        Node::Add(Box::new(expr1), Box::new(expr2))
+    }
+}
+
+fn subtract(expr1: Node, expr2: Node) -> Node {
+    if is_number(&expr2, 0.0) {
+        expr1
+    } else if is_number(&expr1, 0.0) {
+        negative(expr2)
+    } else {
+        Node::Subtract(Box::new(expr1), Box::new(expr2))
+    }
+}
+
+fn multiply(expr1: Node, expr2: Node) -> Node {
+    if is_number(&expr1, 0.0) || is_number(&expr2, 0.0) {
+        Node::Number(0.0)
+    } else if is_number(&expr1, 1.0) {
+        expr2
+    } else if is_number(&expr2, 1.0) {
+        expr1
+    } else {
+        Node::Multiply(Box::new(expr1), Box::new(expr2))
+    }
+}
+
+fn divide(expr1: Node, expr2: Node) -> Node {
+    if is_number(&expr1, 0.0) || is_number(&expr2, 1.0) {
+        expr1
+    } else {
+        Node::Divide(Box::new(expr1), Box::new(expr2))
+    }
+}
+
+fn negative(expr: Node) -> Node {
+    match expr {
+        Node::Number(number) => Node::Number(-number),
+        Node::Negative(expr) => *expr,
+        expr => Node::Negative(Box::new(expr)),
+    }
+}
+
+fn square(expr: &Node) -> Node {
+    Node::Pow(Box::new(expr.clone()), Box::new(Node::Number(2.0)))
+}
+
+/// Whether the value of the formula changes with the variable.
+fn depends(expr: &Node, variable: &str) -> bool {
+    use self::Node::*;
+    match expr {
+        Number(_) | Ans => false,
+        Variable(name) => name == variable,
+        Min(args) | Max(args) | Avg(args) | Med(args) | CustomFunction(_, args) => {
+            args.iter().any(|arg| depends(arg, variable))
+        }
+        Add(expr1, expr2)
+        | Subtract(expr1, expr2)
+        | Multiply(expr1, expr2)
+        | Divide(expr1, expr2)
+        | Modulo(expr1, expr2)
+        | Atan2(expr1, expr2)
+        | Root(expr1, expr2)
+        | Pow(expr1, expr2)
+        | Log(expr1, expr2)
+        | ILog(expr1, expr2)
+        | Equal(expr1, expr2)
+        | NotEqual(expr1, expr2)
+        | Less(expr1, expr2)
+        | LessEqual(expr1, expr2)
+        | Greater(expr1, expr2)
+        | GreaterEqual(expr1, expr2)
+        | LogicalAnd(expr1, expr2)
+        | LogicalOr(expr1, expr2) => depends(expr1, variable) || depends(expr2, variable),
+        Conditional(condition, expr1, expr2) => {
+            depends(condition, variable) || depends(expr1, variable) || depends(expr2, variable)
+        }
+        Negative(sub_expr) | Factorial(sub_expr) | Abs(sub_expr) | Floor(sub_expr)
+        | Ceil(sub_expr) | Round(sub_expr) | Sin(sub_expr) | Cos(sub_expr) | Tan(sub_expr)
+        | Sinh(sub_expr) | Cosh(sub_expr) | Tanh(sub_expr) | Arsinh(sub_expr)
+        | Arcosh(sub_expr) | Artanh(sub_expr) | Asin(sub_expr) | Acos(sub_expr)
+        | Atan(sub_expr) | Sqrt(sub_expr) | Ln(sub_expr) | Lb(sub_expr) | Exp(sub_expr)
+        | Exp2(sub_expr) | Truncate(sub_expr) | Sign(sub_expr) | LambertW(sub_expr)
+        | LogicalNot(sub_expr) => depends(sub_expr, variable),
+    }
+}
+
+/// The derivative of the first argument equal to the value, for the functions returning one of
+/// their arguments.
+fn select(args: &[Node], value: &Node, variable: &str) -> Result<Node, EvalError> {
+    let mut derivative: Option<Node> = None;
+    for arg in args.iter().rev() {
+        let arg_derivative = derive(arg, variable)?;
+        derivative = Some(match derivative {
+            Some(other) if other != arg_derivative => Node::Conditional(
+                Box::new(Node::Equal(Box::new(arg.clone()), Box::new(value.clone()))),
+                Box::new(arg_derivative),
+                Box::new(other),
+            ),
+            _ => arg_derivative,
+        });
+    }
+    Ok(derivative.unwrap_or(Node::Number(0.0)))
+}
+
+/// Differentiate a formula with respect to a variable, the placeholder and the other variables
+/// being constants. Rounding functions, comparisons and logical operators, which are piecewise
+/// constant, have a null derivative.
+pub fn derive(expr: &Node, variable: &str) -> Result<Node, EvalError> {
+    use self::Node::*;
+    let derivative = match expr {
+        Variable(name) if name == variable => Number(1.0),
+        Number(_) | Ans | Variable(_) => Number(0.0),
+        Floor(_) | Ceil(_) | Round(_) | Truncate(_) | Sign(_) | ILog(_, _) => Number(0.0),
+        Equal(_, _)
+        | NotEqual(_, _)
+        | Less(_, _)
+        | LessEqual(_, _)
+        | Greater(_, _)
+        | GreaterEqual(_, _)
+        | LogicalAnd(_, _)
+        | LogicalOr(_, _)
+        | LogicalNot(_) => Number(0.0),
+        Factorial(_) | CustomFunction(_, _) if !depends(expr, variable) => Number(0.0),
+        Factorial(_) => return Err(EvalError::NotDifferentiable("factorial".to_string())),
+        CustomFunction(function, _) => {
+            return Err(EvalError::NotDifferentiable(function.name().to_string()))
+        }
+        Add(expr1, expr2) => add(derive(expr1, variable)?, derive(expr2, variable)?),
+        Subtract(expr1, expr2) => subtract(derive(expr1, variable)?, derive(expr2, variable)?),
+        Negative(sub_expr) => negative(derive(sub_expr, variable)?),
+        Multiply(expr1, expr2) => add(
+            multiply(derive(expr1, variable)?, *expr2.clone()),
+            multiply(*expr1.clone(), derive(expr2, variable)?),
+        ),
+        Divide(expr1, expr2) => divide(
+            subtract(
+                multiply(derive(expr1, variable)?, *expr2.clone()),
+                multiply(*expr1.clone(), derive(expr2, variable)?),
+            ),
+            square(expr2),
+        ),
+        // The remainder is truncated: a % b = a - b * trunc(a / b).
+        Modulo(expr1, expr2) => subtract(
+            derive(expr1, variable)?,
+            multiply(
+                derive(expr2, variable)?,
+                Truncate(Box::new(Divide(expr1.clone(), expr2.clone()))),
+            ),
+        ),
+        Pow(base, exponent) if !depends(exponent, variable) => {
+            let exponent_minus_one = match **exponent {
+                Number(number) => Number(number - 1.0),
+                _ => Subtract(exponent.clone(), Box::new(Number(1.0))),
+            };
+            multiply(
+                multiply(
+                    *exponent.clone(),
+                    Pow(base.clone(), Box::new(exponent_minus_one)),
+                ),
+                derive(base, variable)?,
+            )
+        }
+        Pow(base, exponent) => multiply(
+            expr.clone(),
+            add(
+                multiply(derive(exponent, variable)?, Ln(base.clone())),
+                divide(
+                    multiply(*exponent.clone(), derive(base, variable)?),
+                    *base.clone(),
+                ),
+            ),
+        ),
+        Root(n_th_expr, x_expr) => derive(
+            &Pow(
+                x_expr.clone(),
+                Box::new(Divide(Box::new(Number(1.0)), n_th_expr.clone())),
+            ),
+            variable,
+        )?,
+        Log(expr1, expr2) => derive(
+            &Divide(Box::new(Ln(expr1.clone())), Box::new(Ln(expr2.clone()))),
+            variable,
+        )?,
+        Atan2(expr1, expr2) => divide(
+            subtract(
+                multiply(*expr2.clone(), derive(expr1, variable)?),
+                multiply(*expr1.clone(), derive(expr2, variable)?),
+            ),
+            Add(Box::new(square(expr2)), Box::new(square(expr1))),
+        ),
+        Abs(sub_expr) => multiply(Sign(sub_expr.clone()), derive(sub_expr, variable)?),
+        Sin(sub_expr) => multiply(Cos(sub_expr.clone()), derive(sub_expr, variable)?),
+        Cos(sub_expr) => negative(multiply(Sin(sub_expr.clone()), derive(sub_expr, variable)?)),
+        Tan(sub_expr) => divide(derive(sub_expr, variable)?, square(&Cos(sub_expr.clone()))),
+        Sinh(sub_expr) => multiply(Cosh(sub_expr.clone()), derive(sub_expr, variable)?),
+        Cosh(sub_expr) => multiply(Sinh(sub_expr.clone()), derive(sub_expr, variable)?),
+        Tanh(sub_expr) => divide(derive(sub_expr, variable)?, square(&Cosh(sub_expr.clone()))),
+        Arsinh(sub_expr) => divide(
+            derive(sub_expr, variable)?,
+            Sqrt(Box::new(Add(
+                Box::new(square(sub_expr)),
+                Box::new(Number(1.0)),
+            ))),
+        ),
+        Arcosh(sub_expr) => divide(
+            derive(sub_expr, variable)?,
+            Sqrt(Box::new(Subtract(
+                Box::new(square(sub_expr)),
+                Box::new(Number(1.0)),
+            ))),
+        ),
+        Artanh(sub_expr) => divide(
+            derive(sub_expr, variable)?,
+            Subtract(Box::new(Number(1.0)), Box::new(square(sub_expr))),
+        ),
+        Asin(sub_expr) => divide(
+            derive(sub_expr, variable)?,
+            Sqrt(Box::new(Subtract(
+                Box::new(Number(1.0)),
+                Box::new(square(sub_expr)),
+            ))),
+        ),
+        Acos(sub_expr) => negative(divide(
+            derive(sub_expr, variable)?,
+            Sqrt(Box::new(Subtract(
+                Box::new(Number(1.0)),
+                Box::new(square(sub_expr)),
+            ))),
+        )),
+        Atan(sub_expr) => divide(
+            derive(sub_expr, variable)?,
+            Add(Box::new(Number(1.0)), Box::new(square(sub_expr))),
+        ),
+        Sqrt(sub_expr) => divide(
+            derive(sub_expr, variable)?,
+            Multiply(Box::new(Number(2.0)), Box::new(expr.clone())),
+        ),
+        Ln(sub_expr) => divide(derive(sub_expr, variable)?, *sub_expr.clone()),
+        Lb(sub_expr) => divide(
+            derive(sub_expr, variable)?,
+            Multiply(sub_expr.clone(), Box::new(Ln(Box::new(Number(2.0))))),
+        ),
+        Exp(sub_expr) => multiply(expr.clone(), derive(sub_expr, variable)?),
+        Exp2(sub_expr) => multiply(
+            multiply(expr.clone(), Ln(Box::new(Number(2.0)))),
+            derive(sub_expr, variable)?,
+        ),
+        // W'(x) = 1 / (e^W(x) (1 + W(x))), which unlike W(x) / (x (1 + W(x))) is defined at 0.
+        LambertW(sub_expr) => divide(
+            derive(sub_expr, variable)?,
+            Multiply(
+                Box::new(Exp(Box::new(expr.clone()))),
+                Box::new(Add(Box::new(Number(1.0)), Box::new(expr.clone()))),
+            ),
+        ),
+        Conditional(condition, expr1, expr2) => {
+            let derivative1 = derive(expr1, variable)?;
+            let derivative2 = derive(expr2, variable)?;
+            if derivative1 == derivative2 {
+                derivative1
+            } else {
+                Conditional(
+                    condition.clone(),
+                    Box::new(derivative1),
+                    Box::new(derivative2),
+                )
+            }
+        }
+        Min(args) | Max(args) => select(args, expr, variable)?,
+        Avg(args) => {
+            let mut sum = Number(0.0);
+            for arg in args.iter() {
+                sum = add(sum, derive(arg, variable)?);
+            }
+            divide(sum, Number(args.len() as f64))
+        }
+        Med(args) if args.len() % 2 == 1 => select(args, expr, variable)?,
+        Med(args) => {
+            // The median is the mean of the largest argument below it and the smallest one above.
+            let lower = args.iter().map(|arg| {
+                Conditional(
+                    Box::new(LessEqual(Box::new(arg.clone()), Box::new(expr.clone()))),
+                    Box::new(arg.clone()),
+                    Box::new(Min(args.clone())),
+                )
+            });
+            let upper = args.iter().map(|arg| {
+                Conditional(
+                    Box::new(GreaterEqual(Box::new(arg.clone()), Box::new(expr.clone()))),
+                    Box::new(arg.clone()),
+                    Box::new(Max(args.clone())),
+                )
+            });
+            divide(
+                add(
+                    select(args, &Max(Arc::new(lower.collect())), variable)?,
+                    select(args, &Min(Arc::new(upper.collect())), variable)?,
+                ),
+                Number(2.0),
+            )
+        }
+    };
+    Ok(derivative)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::eval_f64::{ast::eval, parser::Parser};
+    use crate::utils::{Arity, Functions};
+    use std::collections::HashMap;
+
+    fn derivative(formula: &str) -> Node {
+        let ast = Parser::new(formula).unwrap().parse().unwrap();
+        derive(&ast, "x").unwrap()
+    }
+
+    #[test]
+    fn test_derive_polynomial() {
+        use self::Node::*;
+        let expected = Multiply(
+            Box::new(Number(3.0)),
+            Box::new(Pow(
+                Box::new(Variable("x".to_string())),
+                Box::new(Number(2.0)),
+            )),
+        );
+        assert_eq!(derivative("x^3"), expected);
+        assert_eq!(derivative("2x+y"), Number(2.0));
+        assert_eq!(derivative("y*@+floor(x)"), Number(0.0));
+    }
+    #[test]
+    fn test_derive_functions() {
+        let formulas = [
+            "x*sin(x)/(1+x²)",
+            "cos(2x)-tan(x)",
+            "sinh(x)+cosh(x)*tanh(x)",
+            "arsinh(x)+arcosh(x+1)+artanh(x/3)",
+            "asin(x/3)+acos(x/4)+atan(x)",
+            "sqrt(x)+ln(x)+lb(x)+log(x,3)+log(5,x)",
+            "exp(x²)+exp2(x)",
+            "x^x+2^x+x^2.5",
+            "root(3,x)+root(x,8)",
+            "atan2(x,2)+atan2(1,x)",
+            "lambert_w(x)+lambert_w(x²)",
+            "abs(x-2)+7%x+x%0.5",
+            "x<1?x²:-x",
+            "min(x,1,x²)+max(x,1,x²)",
+            "avg(x,1,x²)+med(x,1,x²)+med(x,5,x²,0)",
+        ];
+        for formula in formulas {
+            let ast = Parser::new(formula).unwrap().parse().unwrap();
+            let derived = derivative(formula);
+            let at = |ast: &Node, x: f64| {
+                let mut context = HashMap::new();
+                context.insert("x".to_string(), x);
+                eval(ast, 0.0, &context).unwrap()
+            };
+            for x in [0.8, 1.3, 2.1] {
+                let expected = (at(&ast, x + 1e-6) - at(&ast, x - 1e-6)) / 2e-6;
+                let value = at(&derived, x);
+                assert!(
+                    (value - expected).abs() < 1e-4,
+                    "The derivative of {} at {} is {} instead of {}",
+                    formula,
+                    x,
+                    value,
+                    expected
+                );
+            }
+        }
+    }
+    #[test]
+    fn test_not_differentiable() {
+        let ast = Parser::new("x!").unwrap().parse().unwrap();
+        assert_eq!(
+            derive(&ast, "x"),
+            Err(EvalError::NotDifferentiable("factorial".to_string()))
+        );
+        assert_eq!(derive(&ast, "y"), Ok(Node::Number(0.0)));
+        let mut functions = Functions::<f64>::new();
+        functions.register("f", Arity::Exactly(1), |args| Ok(args[0]));
+        let ast = Parser::with_functions("f(x)", &functions)
+            .unwrap()
+            .parse()
+            .unwrap();
+        assert_eq!(
+            derive(&ast, "x"),
+            Err(EvalError::NotDifferentiable("f".to_string()))
+        );
+    }
+}
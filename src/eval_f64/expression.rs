@@ -0,0 +1,220 @@
+use super::ast::{eval, Node};
+use super::derive::derive;
+use super::parser::Parser;
+use super::simplify::simplify;
+use super::tokenizer::RESERVED_WORDS;
+use crate::utils::{latex, mathml, Context, EvalError, Formula, Functions, ParseError};
+use std::{collections::HashMap, fmt};
+
+/// A formula parsed once into a reusable AST and evaluated into f64 as many times as needed.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExpressionF64 {
+    ast: Node,
+}
+
+impl ExpressionF64 {
+    /// Parse a formula without evaluating it.
+    pub fn new(expr: String) -> Result<Self, ParseError> {
+        let mut math_parser = Parser::new(&expr)?;
+        let ast = math_parser.parse()?;
+        Ok(ExpressionF64 { ast })
+    }
+    /// Parse a formula that can call the given user-defined functions.
+    pub fn with_functions(expr: String, functions: &Functions<f64>) -> Result<Self, ParseError> {
+        let mut math_parser = Parser::with_functions(&expr, functions)?;
+        let ast = math_parser.parse()?;
+        Ok(ExpressionF64 { ast })
+    }
+    /// Evaluate the parsed formula, `@` being replaced by the placeholder.
+    pub fn eval(&self, placeholder: f64) -> Result<f64, EvalError> {
+        self.eval_with_context(placeholder, &HashMap::<String, f64>::new())
+    }
+    /// Evaluate the parsed formula, resolving its named variables with the context.
+    pub fn eval_with_context(
+        &self,
+        placeholder: f64,
+        context: &dyn Context<f64>,
+    ) -> Result<f64, EvalError> {
+        eval(&self.ast, placeholder, context)
+    }
+    /// Differentiate the formula with respect to the named variable, `@` and the other variables
+    /// being constants.
+    pub fn derive(&self, variable: &str) -> Result<Self, EvalError> {
+        Ok(ExpressionF64 {
+            ast: derive(&self.ast, variable)?,
+        })
+    }
+    /// Simplify the parsed formula, folding its constant parts and combining its like terms, so
+    /// that it evaluates faster.
+    pub fn simplify(&self) -> Self {
+        ExpressionF64 {
+            ast: simplify(&self.ast),
+        }
+    }
+    /// Write the parsed formula as LaTeX math, without the surrounding `$`.
+    pub fn to_latex(&self) -> String {
+        latex(&self.ast)
+    }
+    /// Write the parsed formula as a MathML `math` element.
+    pub fn to_mathml(&self) -> String {
+        mathml(&self.ast)
+    }
+}
+
+/// Writes the parsed formula back with as few parentheses as possible, as text that parses into
+/// the same formula.
+impl fmt::Display for ExpressionF64 {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.ast)
+    }
+}
+
+impl Formula for ExpressionF64 {
+    type Value = f64;
+
+    fn parse(expr: String, functions: Option<&Functions<f64>>) -> Result<Self, ParseError> {
+        match functions {
+            Some(functions) => ExpressionF64::with_functions(expr, functions),
+            None => ExpressionF64::new(expr),
+        }
+    }
+    fn evaluate(&self, placeholder: f64, context: &dyn Context<f64>) -> Result<f64, EvalError> {
+        self.eval_with_context(placeholder, context)
+    }
+    fn is_reserved(name: &str) -> bool {
+        RESERVED_WORDS.contains(&name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::Arity;
+    use crate::utils::ParseErrorKind;
+
+    #[test]
+    fn test_reuse() {
+        let expr = ExpressionF64::new("2*@+1".to_string()).unwrap();
+        assert_eq!(expr.eval(3.0).unwrap(), 7.0);
+        assert_eq!(expr.eval(0.5).unwrap(), 2.0);
+        assert_eq!(expr.eval(-1.0).unwrap(), -1.0);
+    }
+    #[test]
+    fn test_placeholder_in_function() {
+        let expr = ExpressionF64::new("max(@, 2) + sqrt(@²)".to_string()).unwrap();
+        assert_eq!(expr.eval(3.0).unwrap(), 6.0);
+        assert_eq!(expr.eval(1.0).unwrap(), 3.0);
+    }
+    #[test]
+    fn test_invalid_expression() {
+        assert!(ExpressionF64::new("2+".to_string()).is_err());
+    }
+    #[test]
+    fn test_error_span() {
+        let error = ExpressionF64::new("1 + (2 * 3".to_string()).unwrap_err();
+        assert_eq!(error.kind(), ParseErrorKind::UnbalancedBracket);
+        assert_eq!(error.span(), 10..10);
+        assert_eq!(error.expected(), [")"]);
+        let error = ExpressionF64::new("2 + foo(3)".to_string()).unwrap_err();
+        assert_eq!(error.kind(), ParseErrorKind::UnknownFunction);
+        assert_eq!(error.span(), 4..7);
+        let error = ExpressionF64::new(" atan2(1)".to_string()).unwrap_err();
+        assert_eq!(error.kind(), ParseErrorKind::WrongArity);
+        assert_eq!(error.span(), 1..6);
+        let error = ExpressionF64::new("max(1, 2".to_string()).unwrap_err();
+        assert_eq!(error.kind(), ParseErrorKind::UnbalancedBracket);
+        assert_eq!(error.expected(), [",", ")"]);
+        let error = ExpressionF64::new("2 * π )".to_string()).unwrap_err();
+        assert_eq!(error.kind(), ParseErrorKind::UnbalancedBracket);
+        assert_eq!(error.span(), 7..8);
+        let error = ExpressionF64::new("2 ×  3".to_string()).unwrap_err();
+        assert_eq!(error.kind(), ParseErrorKind::InvalidCharacter);
+        assert_eq!(error.span(), 2..4);
+        let error = ExpressionF64::new("2 * * 3".to_string()).unwrap_err();
+        assert_eq!(error.kind(), ParseErrorKind::UnexpectedToken);
+        assert_eq!(error.span(), 4..5);
+        let error = ExpressionF64::new("2 + .".to_string()).unwrap_err();
+        assert_eq!(error.kind(), ParseErrorKind::InvalidNumber);
+        assert_eq!(error.span(), 4..5);
+        let error = ExpressionF64::new("2 + 1e3.5".to_string()).unwrap_err();
+        assert_eq!(error.kind(), ParseErrorKind::InvalidNumber);
+        assert_eq!(error.span(), 4..9);
+    }
+    #[test]
+    fn test_context() {
+        let expr = ExpressionF64::new("price * (1 - discount) + shipping".to_string()).unwrap();
+        let mut context = HashMap::new();
+        context.insert("price".to_string(), 100.0);
+        context.insert("discount".to_string(), 0.25);
+        context.insert("shipping".to_string(), 5.0);
+        assert_eq!(expr.eval_with_context(0.0, &context).unwrap(), 80.0);
+        context.insert("discount".to_string(), 0.5);
+        assert_eq!(expr.eval_with_context(0.0, &context).unwrap(), 55.0);
+    }
+    #[test]
+    fn test_unbound_variable() {
+        let expr = ExpressionF64::new("2x".to_string()).unwrap();
+        assert_eq!(
+            expr.eval(0.0),
+            Err(EvalError::UndefinedVariable("x".to_string()))
+        );
+    }
+    #[test]
+    fn test_custom_functions() {
+        let mut functions = Functions::<f64>::new();
+        functions
+            .register("clamp", Arity::Exactly(3), |args| {
+                Ok(args[0].max(args[1]).min(args[2]))
+            })
+            .register("tax", Arity::Exactly(2), |args| {
+                Ok(args[0] * if args[1] == 1.0 { 0.2 } else { 0.1 })
+            })
+            .register("sum", Arity::AtLeast(1), |args| Ok(args.iter().sum()));
+        let expr = ExpressionF64::with_functions(
+            "clamp(@, 0, 10) + tax(100, 1) + 2sum(1, 2, 3)".to_string(),
+            &functions,
+        )
+        .unwrap();
+        assert_eq!(expr.eval(15.0).unwrap(), 42.0);
+        assert_eq!(expr.eval(-5.0).unwrap(), 32.0);
+    }
+    #[test]
+    fn test_custom_function_shadowing_constant() {
+        let mut functions = Functions::<f64>::new();
+        functions.register("expo", Arity::Exactly(1), |args| Ok(args[0] * 10.0));
+        let expr = ExpressionF64::with_functions("expo(2)".to_string(), &functions).unwrap();
+        assert_eq!(expr.eval(0.0).unwrap(), 20.0);
+    }
+    #[test]
+    fn test_custom_function_arity() {
+        let mut functions = Functions::<f64>::new();
+        functions
+            .register("clamp", Arity::Exactly(3), |args| {
+                Ok(args[0].max(args[1]).min(args[2]))
+            })
+            .register("sum", Arity::AtLeast(2), |args| Ok(args.iter().sum()));
+        assert!(ExpressionF64::with_functions("clamp(1, 2)".to_string(), &functions).is_err());
+        assert!(
+            ExpressionF64::with_functions("clamp(1, 2, 3, 4)".to_string(), &functions).is_err()
+        );
+        assert!(ExpressionF64::with_functions("sum(1)".to_string(), &functions).is_err());
+    }
+    #[test]
+    fn test_unknown_function() {
+        assert!(ExpressionF64::new("clamp(1, 2, 3)".to_string()).is_err());
+    }
+    #[test]
+    fn test_derive() {
+        let expr = ExpressionF64::new("x^2 + 3x + y".to_string()).unwrap();
+        let derivative = expr.derive("x").unwrap();
+        let mut context = HashMap::new();
+        context.insert("x".to_string(), 2.0);
+        context.insert("y".to_string(), 5.0);
+        assert_eq!(derivative.eval_with_context(0.0, &context), Ok(7.0));
+        let expr = ExpressionF64::new("x!".to_string()).unwrap();
+        assert_eq!(
+            expr.derive("x"),
+            Err(EvalError::NotDifferentiable("factorial".to_string()))
+        );
+    }
+}
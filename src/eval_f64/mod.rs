@@ -1,16 +1,16 @@
 mod ast;
+mod derive;
+mod expression;
+mod notation;
 mod parser;
+mod simplify;
 mod token;
 mod tokenizer;
 
-use crate::utils::ParseError;
-use ast::eval;
-use parser::Parser;
+use crate::utils::Error;
+pub use expression::ExpressionF64;
 
 /// Evaluate a formula inside a string and compute it into f64.
-pub fn eval_f64(expr: String, placeholder: f64) -> Result<f64, ParseError> {
-    let expr = expr.split_whitespace().collect::<String>();
-    let mut math_parser = Parser::new(&expr, Some(placeholder))?;
-    let ast = math_parser.parse()?;
-    Ok(eval(ast)?)
+pub fn eval_f64(expr: String, placeholder: f64) -> Result<f64, Error> {
+    Ok(ExpressionF64::new(expr)?.eval(placeholder)?)
 }
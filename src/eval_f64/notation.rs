@@ -0,0 +1,192 @@
+use super::ast::Node;
+use crate::utils::{real_text, write_infix, Notation, Operator, Term, Unary};
+use std::fmt;
+
+impl Notation for Node {
+    fn term(&self) -> Term<'_, Self> {
+        use self::Node::*;
+        match self {
+            Number(value) => Term::number(real_text(*value)),
+            Variable(name) => Term::Variable(name),
+            Ans => Term::Ans,
+            Add(left, right) => Term::Binary(Operator::Add, left, right),
+            Subtract(left, right) => Term::Binary(Operator::Subtract, left, right),
+            Multiply(left, right) => Term::Binary(Operator::Multiply, left, right),
+            Divide(left, right) => Term::Binary(Operator::Divide, left, right),
+            Modulo(left, right) => Term::Binary(Operator::Modulo, left, right),
+            Pow(left, right) => Term::Binary(Operator::Pow, left, right),
+            Equal(left, right) => Term::Binary(Operator::Equal, left, right),
+            NotEqual(left, right) => Term::Binary(Operator::NotEqual, left, right),
+            Less(left, right) => Term::Binary(Operator::Less, left, right),
+            LessEqual(left, right) => Term::Binary(Operator::LessEqual, left, right),
+            Greater(left, right) => Term::Binary(Operator::Greater, left, right),
+            GreaterEqual(left, right) => Term::Binary(Operator::GreaterEqual, left, right),
+            LogicalAnd(left, right) => Term::Binary(Operator::LogicalAnd, left, right),
+            LogicalOr(left, right) => Term::Binary(Operator::LogicalOr, left, right),
+            Negative(expr) => Term::Unary(Unary::Negative, expr),
+            LogicalNot(expr) => Term::Unary(Unary::LogicalNot, expr),
+            Factorial(expr) => Term::Factorial(expr),
+            Conditional(condition, then, otherwise) => {
+                Term::Conditional(condition, then, otherwise)
+            }
+            Atan2(left, right) => Term::Function("atan2", vec![left, right]),
+            Root(left, right) => Term::Function("root", vec![left, right]),
+            Log(left, right) => Term::Function("log", vec![left, right]),
+            ILog(left, right) => Term::Function("ilog", vec![left, right]),
+            Abs(expr) => Term::Function("abs", vec![expr]),
+            Floor(expr) => Term::Function("floor", vec![expr]),
+            Ceil(expr) => Term::Function("ceil", vec![expr]),
+            Round(expr) => Term::Function("round", vec![expr]),
+            Sin(expr) => Term::Function("sin", vec![expr]),
+            Cos(expr) => Term::Function("cos", vec![expr]),
+            Tan(expr) => Term::Function("tan", vec![expr]),
+            Sinh(expr) => Term::Function("sinh", vec![expr]),
+            Cosh(expr) => Term::Function("cosh", vec![expr]),
+            Tanh(expr) => Term::Function("tanh", vec![expr]),
+            Arsinh(expr) => Term::Function("arsinh", vec![expr]),
+            Arcosh(expr) => Term::Function("arcosh", vec![expr]),
+            Artanh(expr) => Term::Function("artanh", vec![expr]),
+            Asin(expr) => Term::Function("asin", vec![expr]),
+            Acos(expr) => Term::Function("acos", vec![expr]),
+            Atan(expr) => Term::Function("atan", vec![expr]),
+            Sqrt(expr) => Term::Function("sqrt", vec![expr]),
+            Ln(expr) => Term::Function("ln", vec![expr]),
+            Lb(expr) => Term::Function("lb", vec![expr]),
+            Exp(expr) => Term::Function("exp", vec![expr]),
+            Exp2(expr) => Term::Function("exp2", vec![expr]),
+            Truncate(expr) => Term::Function("trunc", vec![expr]),
+            Sign(expr) => Term::Function("sign", vec![expr]),
+            LambertW(expr) => Term::Function("lambert_w", vec![expr]),
+            Min(args) => Term::Function("min", args.iter().collect()),
+            Max(args) => Term::Function("max", args.iter().collect()),
+            Avg(args) => Term::Function("avg", args.iter().collect()),
+            Med(args) => Term::Function("med", args.iter().collect()),
+            CustomFunction(function, args) => {
+                Term::Function(function.name(), args.iter().collect())
+            }
+        }
+    }
+}
+
+impl fmt::Display for Node {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write_infix(self, f)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::eval_f64::ast::Node;
+    use crate::eval_f64::eval_f64;
+    use crate::eval_f64::parser::Parser;
+    use crate::utils::{latex, mathml};
+
+    fn infix(formula: &str) -> String {
+        Parser::new(formula).unwrap().parse().unwrap().to_string()
+    }
+
+    #[test]
+    fn test_infix() {
+        assert_eq!(infix("((1+2))*3"), "(1 + 2) * 3");
+        assert_eq!(infix("1-(2-3)-4"), "1 - (2 - 3) - 4");
+        assert_eq!(infix("2^3^4"), "2^3^4");
+        assert_eq!(infix("(2^3)^4"), "(2^3)^4");
+        assert_eq!(infix("(-2)^2 + -2^2"), "(-2)^2 + -2^2");
+        assert_eq!(infix("2x³"), "2 * x^3");
+        assert_eq!(infix("a*2x"), "a * (2 * x)");
+        assert_eq!(infix("⌊x/2⌋!"), "floor(x / 2)!");
+        assert_eq!(infix("x<1||!(x>2)&&@"), "x < 1 || !(x > 2) && @");
+        assert_eq!(
+            infix("(x?1:2)+(y?3:4?5:6)"),
+            "(x ? 1 : 2) + (y ? 3 : 4 ? 5 : 6)"
+        );
+        assert_eq!(infix("max(1e400, 1)"), "max(1e999, 1)");
+        let nan = Node::Pow(
+            Box::new(Node::Number(f64::NAN)),
+            Box::new(Node::Number(f64::NEG_INFINITY)),
+        );
+        assert_eq!(nan.to_string(), "(0 * 1e999)^-1e999");
+        assert!(eval_f64("0 * 1e999".into(), 0.0).unwrap().is_nan());
+    }
+    #[test]
+    fn test_round_trip() {
+        let formulas = [
+            "-(x+1)*-y/(z%2)",
+            "--x-(-x)",
+            "!!x!!",
+            "(x+1)!^2",
+            "2^-x^y",
+            "-x^2!",
+            "a*(b*c)/(d/e)",
+            "1<2==(3<4)",
+            "x&&(y||z)",
+            "(x?y:z)?1:2",
+            "sin(x)cos(x)+tan(x)+sinh(x)+cosh(x)+tanh(x)+asin(x)+acos(x)+atan(x)",
+            "arsinh(x)+arcosh(x)+artanh(x)+ln(x)+lb(x)+exp(x)+exp2(x)+sqrt(x)+trunc(x)",
+            "abs(x)+⌈x⌉+round(x)+sign(x)+lambert_w(x)+atan2(x,y)+root(3,x)+log(x,2)",
+            "ilog(x,2)+min(x,1)+max(x,2,3)+avg(x)+med(1,2)+mod(x,2)+pow(x,2)+if(x,1,2)",
+            "90°+x rad+2π e",
+        ];
+        for formula in formulas {
+            let ast = Parser::new(formula).unwrap().parse().unwrap();
+            let text = ast.to_string();
+            assert_eq!(
+                Parser::new(&text).unwrap().parse().unwrap(),
+                ast,
+                "{}",
+                text
+            );
+        }
+    }
+    #[test]
+    fn test_latex() {
+        let latex = |formula| latex(&Parser::new(formula).unwrap().parse().unwrap());
+        assert_eq!(latex("(1+x)/2*y"), "\\frac{1 + x}{2} \\cdot y");
+        assert_eq!(latex("(x/2)^(y+1)"), "\\left(\\frac{x}{2}\\right)^{y + 1}");
+        assert_eq!(latex("-(a-b)!"), "-\\left(a - b\\right)!");
+        assert_eq!(
+            latex("sqrt(x)+root(3,x)+⌊x⌋+abs(x)"),
+            "\\sqrt{x} + \\sqrt[3]{x} + \\left\\lfloor x\\right\\rfloor + \\left|x\\right|"
+        );
+        assert_eq!(
+            latex("sin(x)+log(x,2)+lambert_w(speed)"),
+            "\\sin\\left(x\\right) + \\log_{2}\\left(x\\right) \
+             + \\operatorname{lambert\\_w}\\left(\\mathrm{speed}\\right)"
+        );
+        assert_eq!(
+            latex("x<=0?0:1"),
+            "\\begin{cases} 0 & \\text{if } x \\leq 0 \\\\ 1 & \\text{otherwise} \\end{cases}"
+        );
+        assert_eq!(latex("x-1e400"), "x - \\infty");
+        assert_eq!(
+            crate::utils::latex(&Node::Number(f64::NAN)),
+            "\\mathrm{NaN}"
+        );
+    }
+    #[test]
+    fn test_mathml() {
+        let mathml = |formula| mathml(&Parser::new(formula).unwrap().parse().unwrap());
+        assert_eq!(
+            mathml("(x+1)/2"),
+            "<math xmlns=\"http://www.w3.org/1998/Math/MathML\">\
+             <mfrac><mrow><mi>x</mi><mo>+</mo><mn>1</mn></mrow><mn>2</mn></mfrac></math>"
+        );
+        assert_eq!(
+            mathml("-(a-b)^2"),
+            "<math xmlns=\"http://www.w3.org/1998/Math/MathML\"><mrow><mo>&#x2212;</mo>\
+             <msup><mrow><mo>(</mo><mrow><mi>a</mi><mo>&#x2212;</mo><mi>b</mi></mrow>\
+             <mo>)</mo></mrow><mn>2</mn></msup></mrow></math>"
+        );
+        assert_eq!(
+            mathml("root(3,x)<sin(x)"),
+            "<math xmlns=\"http://www.w3.org/1998/Math/MathML\"><mrow><mroot><mi>x</mi>\
+             <mn>3</mn></mroot><mo>&lt;</mo><mrow><mi>sin</mi><mo>&#x2061;</mo><mrow><mo>(</mo>\
+             <mi>x</mi><mo>)</mo></mrow></mrow></mrow></math>"
+        );
+        assert_eq!(
+            mathml("-1e400"),
+            "<math xmlns=\"http://www.w3.org/1998/Math/MathML\"><mrow><mo>&#x2212;</mo>\
+             <mi>&#x221E;</mi></mrow></math>"
+        );
+    }
+}
@@ -1,4 +1,5 @@
-use crate::utils::OperatorCategory;
+use crate::utils::{Function, OperatorCategory};
+use std::fmt;
 
 #[derive(Debug, PartialEq, Clone)]
 pub enum NativeFunction {
@@ -36,6 +37,7 @@ pub enum NativeFunction {
     Avg,
     Med,
     Mod,
+    If,
 }
 
 #[derive(Debug, PartialEq, Clone)]
@@ -47,6 +49,16 @@ pub enum Token {
     Caret,
     ExclamationMark,
     Modulo,
+    Equal,
+    NotEqual,
+    Less,
+    LessEqual,
+    Greater,
+    GreaterEqual,
+    DoubleAmpersand,
+    DoubleBar,
+    QuestionMark,
+    Colon,
     LeftParen,
     RightParen,
     LeftFloor,
@@ -59,8 +71,10 @@ pub enum Token {
     DegToRad,
     RadToDeg,
     ExplicitFunction(NativeFunction),
+    CustomFunction(Function<f64>),
     Superscript(f64),
     Num(f64),
+    Identifier(String),
     Ans,
     Eof,
 }
@@ -69,11 +83,68 @@ impl Token {
     pub fn get_oper_prec(&self) -> OperatorCategory {
         use self::Token::*;
         match *self {
+            QuestionMark => OperatorCategory::Conditional,
+            DoubleBar => OperatorCategory::LogicalOr,
+            DoubleAmpersand => OperatorCategory::LogicalAnd,
+            Equal | NotEqual | Less | LessEqual | Greater | GreaterEqual => {
+                OperatorCategory::Comparison
+            }
             Add | Subtract => OperatorCategory::Additive,
             Multiply | Divide | Modulo | DegToRad | RadToDeg => OperatorCategory::Multiplicative,
             Caret | Superscript(_) => OperatorCategory::Power,
-            ExclamationMark | ExplicitFunction(_) => OperatorCategory::Functional,
+            ExclamationMark | ExplicitFunction(_) | CustomFunction(_) => {
+                OperatorCategory::Functional
+            }
             _ => OperatorCategory::DefaultZero,
         }
     }
+    pub fn is_closing_bracket(&self) -> bool {
+        use self::Token::*;
+        matches!(self, RightParen | RightFloor | RightCeiling)
+    }
+}
+
+impl fmt::Display for Token {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        use self::Token::*;
+        match self {
+            Add => write!(f, "+"),
+            Subtract => write!(f, "-"),
+            Multiply => write!(f, "*"),
+            Divide => write!(f, "/"),
+            Caret => write!(f, "^"),
+            ExclamationMark => write!(f, "!"),
+            Modulo => write!(f, "%"),
+            Equal => write!(f, "=="),
+            NotEqual => write!(f, "!="),
+            Less => write!(f, "<"),
+            LessEqual => write!(f, "<="),
+            Greater => write!(f, ">"),
+            GreaterEqual => write!(f, ">="),
+            DoubleAmpersand => write!(f, "&&"),
+            DoubleBar => write!(f, "||"),
+            QuestionMark => write!(f, "?"),
+            Colon => write!(f, ":"),
+            LeftParen => write!(f, "("),
+            RightParen => write!(f, ")"),
+            LeftFloor => write!(f, "⌊"),
+            RightFloor => write!(f, "⌋"),
+            LeftCeiling => write!(f, "⌈"),
+            RightCeiling => write!(f, "⌉"),
+            E => write!(f, "e"),
+            Pi => write!(f, "π"),
+            Comma => write!(f, ","),
+            DegToRad => write!(f, "°"),
+            RadToDeg => write!(f, "rad"),
+            ExplicitFunction(function) => {
+                write!(f, "{}", format!("{:?}", function).to_lowercase())
+            }
+            CustomFunction(function) => write!(f, "{}", function.name()),
+            Superscript(value) => write!(f, "^{}", value),
+            Num(value) => write!(f, "{}", value),
+            Identifier(name) => write!(f, "{}", name),
+            Ans => write!(f, "@"),
+            Eof => write!(f, "end of input"),
+        }
+    }
 }
@@ -1,24 +1,43 @@
 use super::token::{NativeFunction, Token};
-use crate::utils::deserialize_superscript_number;
-use std::iter::Peekable;
-use std::str::Chars;
+use crate::utils::{
+    deserialize_number, deserialize_superscript_number, peek_identifier, unrecognized_token,
+    Cursor, Functions, ParseError,
+};
+
+/// The names read as constants or units rather than variables.
+pub const RESERVED_WORDS: [&str; 3] = ["e", "pi", "rad"];
 
 pub struct Tokenizer<'a> {
-    expr: Peekable<Chars<'a>>,
+    expr: Cursor<'a>,
+    functions: Option<&'a Functions<f64>>,
 }
 
 impl<'a> Tokenizer<'a> {
     pub fn new(new_expr: &'a str) -> Self {
         Tokenizer {
-            expr: new_expr.chars().peekable(),
+            expr: Cursor::new(new_expr),
+            functions: None,
         }
     }
-}
-
-impl<'a> Iterator for Tokenizer<'a> {
-    type Item = Token;
-
-    fn next(&mut self) -> Option<Token> {
+    pub fn with_functions(new_expr: &'a str, functions: &'a Functions<f64>) -> Self {
+        Tokenizer {
+            expr: Cursor::new(new_expr),
+            functions: Some(functions),
+        }
+    }
+    /// The byte offset of the next token.
+    pub fn position(&self) -> usize {
+        self.expr.position()
+    }
+    /// Consume the whitespaces before the next token, which only separate tokens.
+    pub fn skip_whitespace(&mut self) {
+        self.expr.skip_whitespace()
+    }
+    /// The error explaining why no token could be read at the given byte offset.
+    pub fn error(&self, start: usize) -> ParseError {
+        unrecognized_token(self.expr.source(), start)
+    }
+    fn native_token(&mut self) -> Option<Token> {
         let current_char = self.expr.next();
 
         match current_char {
@@ -30,7 +49,56 @@ impl<'a> Iterator for Tokenizer<'a> {
             Some('^') => Some(Token::Caret),
             Some('(') => Some(Token::LeftParen),
             Some(')') => Some(Token::RightParen),
-            Some('!') => Some(Token::ExclamationMark),
+            Some('!') => {
+                if let Some('=') = self.expr.peek() {
+                    self.expr.next();
+                    Some(Token::NotEqual)
+                } else {
+                    Some(Token::ExclamationMark)
+                }
+            }
+            Some('=') => {
+                if let Some('=') = self.expr.peek() {
+                    self.expr.next();
+                    Some(Token::Equal)
+                } else {
+                    None
+                }
+            }
+            Some('<') => {
+                if let Some('=') = self.expr.peek() {
+                    self.expr.next();
+                    Some(Token::LessEqual)
+                } else {
+                    Some(Token::Less)
+                }
+            }
+            Some('>') => {
+                if let Some('=') = self.expr.peek() {
+                    self.expr.next();
+                    Some(Token::GreaterEqual)
+                } else {
+                    Some(Token::Greater)
+                }
+            }
+            Some('&') => {
+                if let Some('&') = self.expr.peek() {
+                    self.expr.next();
+                    Some(Token::DoubleAmpersand)
+                } else {
+                    None
+                }
+            }
+            Some('|') => {
+                if let Some('|') = self.expr.peek() {
+                    self.expr.next();
+                    Some(Token::DoubleBar)
+                } else {
+                    None
+                }
+            }
+            Some('?') => Some(Token::QuestionMark),
+            Some(':') => Some(Token::Colon),
             Some(',') => Some(Token::Comma),
             Some('%') => Some(Token::Modulo),
             Some('π') => Some(Token::Pi),
@@ -42,16 +110,9 @@ impl<'a> Iterator for Tokenizer<'a> {
             Some('.') => {
                 let next_char = self.expr.peek()?;
                 if next_char.is_ascii_digit() {
-                    let mut number = "0".to_string();
-                    number.push(current_char?);
-                    while let Some(next_char) = self.expr.peek() {
-                        if next_char.is_ascii_digit() {
-                            number.push(self.expr.next()?);
-                        } else {
-                            break;
-                        }
-                    }
-                    Some(Token::Num(number.parse::<f64>().unwrap()))
+                    let (mut number, exponent) = deserialize_number('.', &mut self.expr)?;
+                    number.push_str(&exponent);
+                    Some(Token::Num(number.parse::<f64>().ok()?))
                 } else {
                     None
                 }
@@ -59,63 +120,57 @@ impl<'a> Iterator for Tokenizer<'a> {
             Some('⁰') => Some(Token::Superscript(
                 deserialize_superscript_number(&current_char?, &mut self.expr)
                     .parse::<f64>()
-                    .unwrap(),
+                    .ok()?,
             )),
             Some('¹') => Some(Token::Superscript(
                 deserialize_superscript_number(&current_char?, &mut self.expr)
                     .parse::<f64>()
-                    .unwrap(),
+                    .ok()?,
             )),
             Some('²') => Some(Token::Superscript(
                 deserialize_superscript_number(&current_char?, &mut self.expr)
                     .parse::<f64>()
-                    .unwrap(),
+                    .ok()?,
             )),
             Some('³') => Some(Token::Superscript(
                 deserialize_superscript_number(&current_char?, &mut self.expr)
                     .parse::<f64>()
-                    .unwrap(),
+                    .ok()?,
             )),
             Some('⁴') => Some(Token::Superscript(
                 deserialize_superscript_number(&current_char?, &mut self.expr)
                     .parse::<f64>()
-                    .unwrap(),
+                    .ok()?,
             )),
             Some('⁵') => Some(Token::Superscript(
                 deserialize_superscript_number(&current_char?, &mut self.expr)
                     .parse::<f64>()
-                    .unwrap(),
+                    .ok()?,
             )),
             Some('⁶') => Some(Token::Superscript(
                 deserialize_superscript_number(&current_char?, &mut self.expr)
                     .parse::<f64>()
-                    .unwrap(),
+                    .ok()?,
             )),
             Some('⁷') => Some(Token::Superscript(
                 deserialize_superscript_number(&current_char?, &mut self.expr)
                     .parse::<f64>()
-                    .unwrap(),
+                    .ok()?,
             )),
             Some('⁸') => Some(Token::Superscript(
                 deserialize_superscript_number(&current_char?, &mut self.expr)
                     .parse::<f64>()
-                    .unwrap(),
+                    .ok()?,
             )),
             Some('⁹') => Some(Token::Superscript(
                 deserialize_superscript_number(&current_char?, &mut self.expr)
                     .parse::<f64>()
-                    .unwrap(),
+                    .ok()?,
             )),
             Some('0'..='9') => {
-                let mut number = current_char?.to_string();
-                while let Some(next_char) = self.expr.peek() {
-                    if next_char.is_ascii_digit() || next_char == &'.' {
-                        number.push(self.expr.next()?);
-                    } else {
-                        break;
-                    }
-                }
-                Some(Token::Num(number.parse::<f64>().unwrap()))
+                let (mut number, exponent) = deserialize_number(current_char?, &mut self.expr)?;
+                number.push_str(&exponent);
+                Some(Token::Num(number.parse::<f64>().ok()?))
             }
             Some('a') => match self.expr.clone().take(6).collect::<String>().as_str() {
                 "rsinh(" => {
@@ -214,6 +269,9 @@ impl<'a> Iterator for Tokenizer<'a> {
                 if self.expr.clone().take(4).collect::<String>() == "log(" {
                     self.expr.by_ref().take(3).for_each(drop);
                     Some(Token::ExplicitFunction(NativeFunction::ILog))
+                } else if self.expr.clone().take(2).collect::<String>() == "f(" {
+                    self.expr.by_ref().take(1).for_each(drop);
+                    Some(Token::ExplicitFunction(NativeFunction::If))
                 } else {
                     None
                 }
@@ -350,9 +408,48 @@ impl<'a> Iterator for Tokenizer<'a> {
     }
 }
 
+impl<'a> Iterator for Tokenizer<'a> {
+    type Item = Token;
+
+    fn next(&mut self) -> Option<Token> {
+        self.expr.skip_whitespace();
+        let (identifier, next_char) = peek_identifier(&self.expr);
+        if !identifier.is_empty() {
+            if next_char == Some('(') {
+                if let Some(function) = self
+                    .functions
+                    .and_then(|functions| functions.get(&identifier))
+                {
+                    let function = function.clone();
+                    self.expr.by_ref().take(identifier.len()).for_each(drop);
+                    return Some(Token::CustomFunction(function));
+                }
+            } else if !RESERVED_WORDS.contains(&identifier.as_str()) {
+                self.expr.by_ref().take(identifier.len()).for_each(drop);
+                return Some(Token::Identifier(identifier));
+            }
+        }
+        if next_char == Some('(') && !identifier.is_empty() {
+            // The native functions are matched along with their `(`, which whitespace may set
+            // apart from the name.
+            let call = format!("{}(", identifier);
+            let mut native = Tokenizer::new(&call);
+            let token = native.native_token()?;
+            if native.position() != identifier.len() {
+                // The word before the arguments is not a native function either.
+                return None;
+            }
+            self.expr.by_ref().take(identifier.len()).for_each(drop);
+            return Some(token);
+        }
+        self.native_token()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::utils::Arity;
 
     #[test]
     fn test_integer_number() {
@@ -375,6 +472,22 @@ mod tests {
         assert_eq!(tokenizer.next().unwrap(), Token::Num(0.5))
     }
     #[test]
+    fn test_scientific_number() {
+        let mut tokenizer = Tokenizer::new("1.5e-3");
+        assert_eq!(tokenizer.next().unwrap(), Token::Num(0.0015));
+        let mut tokenizer = Tokenizer::new("6.022E23");
+        assert_eq!(tokenizer.next().unwrap(), Token::Num(6.022e23));
+        let mut tokenizer = Tokenizer::new(".5e+2");
+        assert_eq!(tokenizer.next().unwrap(), Token::Num(50.0));
+    }
+    #[test]
+    fn test_e_const_after_number() {
+        let mut tokenizer = Tokenizer::new("2e-x");
+        assert_eq!(tokenizer.next().unwrap(), Token::Num(2.0));
+        assert_eq!(tokenizer.next().unwrap(), Token::E);
+        assert_eq!(tokenizer.next().unwrap(), Token::Subtract);
+    }
+    #[test]
     fn test_e_const() {
         let mut tokenizer = Tokenizer::new("e");
         assert_eq!(tokenizer.next().unwrap(), Token::E)
@@ -810,4 +923,93 @@ mod tests {
             Token::ExplicitFunction(NativeFunction::Med)
         )
     }
+    #[test]
+    fn test_identifier() {
+        let mut tokenizer = Tokenizer::new("price_2*x");
+        assert_eq!(
+            tokenizer.next().unwrap(),
+            Token::Identifier("price_2".to_string())
+        );
+        assert_eq!(tokenizer.next().unwrap(), Token::Multiply);
+        assert_eq!(
+            tokenizer.next().unwrap(),
+            Token::Identifier("x".to_string())
+        );
+    }
+    #[test]
+    fn test_identifier_starting_with_reserved_word() {
+        let mut tokenizer = Tokenizer::new("radius");
+        assert_eq!(
+            tokenizer.next().unwrap(),
+            Token::Identifier("radius".to_string())
+        )
+    }
+    #[test]
+    fn test_custom_function() {
+        let mut functions = Functions::<f64>::new();
+        functions.register(
+            "sinc",
+            Arity::Exactly(1),
+            |args| Ok(args[0].sin() / args[0]),
+        );
+        let mut tokenizer = Tokenizer::with_functions("sinc(x) sinc (x)", &functions);
+        for _ in 0..2 {
+            assert_eq!(
+                tokenizer.next().unwrap(),
+                Token::CustomFunction(functions.get("sinc").unwrap().clone())
+            );
+            assert_eq!(tokenizer.next().unwrap(), Token::LeftParen);
+            assert_eq!(
+                tokenizer.next().unwrap(),
+                Token::Identifier("x".to_string())
+            );
+            assert_eq!(tokenizer.next().unwrap(), Token::RightParen);
+        }
+    }
+    #[test]
+    fn test_logical_operators() {
+        let mut tokenizer = Tokenizer::new("==!=<=<>=>&&||!?:if(");
+        for token in [
+            Token::Equal,
+            Token::NotEqual,
+            Token::LessEqual,
+            Token::Less,
+            Token::GreaterEqual,
+            Token::Greater,
+            Token::DoubleAmpersand,
+            Token::DoubleBar,
+            Token::ExclamationMark,
+            Token::QuestionMark,
+            Token::Colon,
+            Token::ExplicitFunction(NativeFunction::If),
+        ] {
+            assert_eq!(tokenizer.next().unwrap(), token);
+        }
+    }
+    #[test]
+    fn test_whitespace() {
+        let mut tokenizer = Tokenizer::new(" 2 3\t+\nx y ");
+        for token in [
+            Token::Num(2.0),
+            Token::Num(3.0),
+            Token::Add,
+            Token::Identifier("x".to_string()),
+            Token::Identifier("y".to_string()),
+            Token::Eof,
+        ] {
+            assert_eq!(tokenizer.next().unwrap(), token);
+        }
+        let mut tokenizer = Tokenizer::new("s in(x)");
+        assert_eq!(
+            tokenizer.next().unwrap(),
+            Token::Identifier("s".to_string())
+        );
+        assert_eq!(tokenizer.next(), None);
+        let mut tokenizer = Tokenizer::new("sqrt (4)");
+        assert_eq!(
+            tokenizer.next().unwrap(),
+            Token::ExplicitFunction(NativeFunction::Sqrt)
+        );
+        assert_eq!(tokenizer.next().unwrap(), Token::LeftParen);
+    }
 }
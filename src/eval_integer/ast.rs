@@ -0,0 +1,1071 @@
+use std::sync::Arc;
+
+use super::Integer;
+use crate::utils::{Context, EvalError, Function, OverflowMode};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Node<T> {
+    And(Box<Node<T>>, Box<Node<T>>),
+    Or(Box<Node<T>>, Box<Node<T>>),
+    Xor(Box<Node<T>>, Box<Node<T>>),
+    Not(Box<Node<T>>),
+    LeftShift(Box<Node<T>>, Box<Node<T>>),
+    RightShift(Box<Node<T>>, Box<Node<T>>),
+    UnsignedRightShift(Box<Node<T>>, Box<Node<T>>),
+    Rotl(Box<Node<T>>, Box<Node<T>>),
+    Rotr(Box<Node<T>>, Box<Node<T>>),
+    Popcnt(Box<Node<T>>),
+    Clz(Box<Node<T>>),
+    Ctz(Box<Node<T>>),
+    Add(Box<Node<T>>, Box<Node<T>>),
+    Subtract(Box<Node<T>>, Box<Node<T>>),
+    Multiply(Box<Node<T>>, Box<Node<T>>),
+    Divide(Box<Node<T>>, Box<Node<T>>),
+    Modulo(Box<Node<T>>, Box<Node<T>>),
+    Pow(Box<Node<T>>, Box<Node<T>>),
+    Root(Box<Node<T>>, Box<Node<T>>),
+    Log(Box<Node<T>>, Box<Node<T>>),
+    Negative(Box<Node<T>>),
+    Factorial(Box<Node<T>>),
+    Abs(Box<Node<T>>),
+    Floor(Box<Node<T>>),
+    Ceil(Box<Node<T>>),
+    Round(Box<Node<T>>),
+    Truncate(Box<Node<T>>),
+    Sqrt(Box<Node<T>>),
+    Ln(Box<Node<T>>),
+    Lb(Box<Node<T>>),
+    Exp(Box<Node<T>>),
+    Exp2(Box<Node<T>>),
+    Sign(Box<Node<T>>),
+    Min(Arc<Vec<Node<T>>>),
+    Max(Arc<Vec<Node<T>>>),
+    Avg(Arc<Vec<Node<T>>>),
+    Med(Arc<Vec<Node<T>>>),
+    Gcd(Arc<Vec<Node<T>>>),
+    Lcm(Arc<Vec<Node<T>>>),
+    Number(T),
+    CustomFunction(Function<T>, Arc<Vec<Node<T>>>),
+    Equal(Box<Node<T>>, Box<Node<T>>),
+    NotEqual(Box<Node<T>>, Box<Node<T>>),
+    Less(Box<Node<T>>, Box<Node<T>>),
+    LessEqual(Box<Node<T>>, Box<Node<T>>),
+    Greater(Box<Node<T>>, Box<Node<T>>),
+    GreaterEqual(Box<Node<T>>, Box<Node<T>>),
+    LogicalAnd(Box<Node<T>>, Box<Node<T>>),
+    LogicalOr(Box<Node<T>>, Box<Node<T>>),
+    LogicalNot(Box<Node<T>>),
+    Conditional(Box<Node<T>>, Box<Node<T>>, Box<Node<T>>),
+    Variable(String),
+    Ans,
+}
+
+fn gcd<T: Integer>(expr1: T, expr2: T, mode: OverflowMode) -> Result<T, EvalError> {
+    let mut a = expr1;
+    let mut b = expr2;
+    while b != T::ZERO {
+        let remainder = a.wrapping_rem(b);
+        a = b;
+        b = remainder;
+    }
+    abs(a, mode)
+}
+fn lcm<T: Integer>(expr1: T, expr2: T, mode: OverflowMode) -> Result<T, EvalError> {
+    if expr1 == T::ZERO || expr2 == T::ZERO {
+        return Ok(T::ZERO);
+    }
+    let product = multiply(expr1 / gcd(expr1, expr2, mode)?, expr2, mode)?;
+    abs(product, mode)
+}
+/// Pick the result of an operation matching the overflow mode, `None` meaning the checked operation overflowed.
+fn resolve<T>(
+    mode: OverflowMode,
+    checked: Option<T>,
+    wrapping: T,
+    saturating: T,
+) -> Result<T, EvalError> {
+    match mode {
+        OverflowMode::Checked => checked.ok_or(EvalError::Overflow),
+        OverflowMode::Wrapping => Ok(wrapping),
+        OverflowMode::Saturating => Ok(saturating),
+    }
+}
+fn add<T: Integer>(a: T, b: T, mode: OverflowMode) -> Result<T, EvalError> {
+    resolve(
+        mode,
+        a.checked_add(b),
+        a.wrapping_add(b),
+        a.saturating_add(b),
+    )
+}
+fn multiply<T: Integer>(a: T, b: T, mode: OverflowMode) -> Result<T, EvalError> {
+    resolve(
+        mode,
+        a.checked_mul(b),
+        a.wrapping_mul(b),
+        a.saturating_mul(b),
+    )
+}
+/// The mean of the values rounded toward zero, which always fits in the type. Each value is split
+/// into its quotient and remainder by the count, and the remainders are carried into the quotient
+/// as they add up, so that no partial sum leaves the type.
+fn average<T: Integer>(values: &[T]) -> Result<T, EvalError> {
+    let count = T::from_usize(values.len()).ok_or(EvalError::Overflow)?;
+    let mut quotient = T::ZERO;
+    let mut remainder = T::ZERO;
+    for &value in values {
+        quotient = quotient.wrapping_add(value / count);
+        let rest = value.wrapping_rem(count);
+        if rest > T::ZERO && remainder >= count.wrapping_sub(rest) {
+            quotient = quotient.wrapping_add(T::ONE);
+            remainder = remainder.wrapping_sub(count.wrapping_sub(rest));
+        } else if rest < T::ZERO && remainder <= T::ZERO.wrapping_sub(count.wrapping_add(rest)) {
+            quotient = quotient.wrapping_sub(T::ONE);
+            remainder = remainder.wrapping_add(count.wrapping_add(rest));
+        } else {
+            remainder = remainder.wrapping_add(rest);
+        }
+    }
+    // The sum is `quotient * count + remainder`, the remainder being smaller than the count.
+    Ok(if quotient > T::ZERO && remainder < T::ZERO {
+        quotient.wrapping_sub(T::ONE)
+    } else if quotient < T::ZERO && remainder > T::ZERO {
+        quotient.wrapping_add(T::ONE)
+    } else {
+        quotient
+    })
+}
+fn divide<T: Integer>(dividend: T, divisor: T, mode: OverflowMode) -> Result<T, EvalError> {
+    if divisor == T::ZERO {
+        return Err(EvalError::DivisionByZero);
+    }
+    resolve(
+        mode,
+        dividend.checked_div(divisor),
+        dividend.wrapping_div(divisor),
+        dividend.saturating_div(divisor),
+    )
+}
+/// Move a quotient truncated toward zero down (floor), up (ceil) or to the nearest integer, halves
+/// away from zero (round), depending on the remainder of the division.
+fn round_quotient<T: Integer>(
+    rounding: &Node<T>,
+    quotient: T,
+    remainder: T,
+    divisor: T,
+    mode: OverflowMode,
+) -> Result<T, EvalError> {
+    if remainder == T::ZERO {
+        return Ok(quotient);
+    }
+    // The exact quotient is negative when the remainder and the divisor have opposite signs.
+    let negative = (remainder < T::ZERO) != (divisor < T::ZERO);
+    let away_from_zero = match rounding {
+        Node::Floor(_) => negative,
+        Node::Ceil(_) => !negative,
+        Node::Round(_) => {
+            let remainder = remainder.wrapping_abs();
+            // |divisor| - |remainder|, without computing |divisor| which may overflow.
+            let rest = if divisor < T::ZERO {
+                T::ZERO.wrapping_sub(divisor.wrapping_add(remainder))
+            } else {
+                divisor.wrapping_sub(remainder)
+            };
+            remainder >= rest
+        }
+        _ => false,
+    };
+    match (away_from_zero, negative) {
+        (false, _) => Ok(quotient),
+        (true, false) => add(quotient, T::ONE, mode),
+        (true, true) => resolve(
+            mode,
+            quotient.checked_sub(T::ONE),
+            quotient.wrapping_sub(T::ONE),
+            quotient.saturating_sub(T::ONE),
+        ),
+    }
+}
+fn abs<T: Integer>(a: T, mode: OverflowMode) -> Result<T, EvalError> {
+    resolve(mode, a.checked_abs(), a.wrapping_abs(), a.saturating_abs())
+}
+/// Raise to a power by squaring, so that even the largest exponents take a bounded time.
+fn pow<T: Integer>(base: T, exponent: T, mode: OverflowMode) -> Result<T, EvalError> {
+    if exponent < T::ZERO {
+        return Err(EvalError::Domain("pow".to_string()));
+    }
+    let mut base = base;
+    let mut exponent = exponent;
+    let mut result = T::ONE;
+    while exponent > T::ONE {
+        if exponent & T::ONE == T::ONE {
+            result = multiply(result, base, mode)?;
+        }
+        exponent = exponent >> 1;
+        base = multiply(base, base, mode)?;
+    }
+    if exponent == T::ONE {
+        result = multiply(result, base, mode)?;
+    }
+    Ok(result)
+}
+/// The shift amount, which must not be negative in any mode, and must be less than the width of
+/// the type unless it wraps, a saturated shift moving every bit out.
+fn shift_amount<T: Integer>(shift: T, mode: OverflowMode) -> Result<u32, EvalError> {
+    let bits = T::from_u32(T::BITS);
+    match mode {
+        _ if shift < T::ZERO => Err(EvalError::InvalidShift(shift.to_string())),
+        _ if shift < bits => Ok(shift.to_u32()),
+        OverflowMode::Wrapping => Ok(rotation_amount(shift)),
+        OverflowMode::Saturating => Ok(T::BITS),
+        OverflowMode::Checked => Err(EvalError::InvalidShift(shift.to_string())),
+    }
+}
+/// Shift to the left, overflowing when a bit that differs from the resulting sign is shifted out.
+fn left_shift<T: Integer>(value: T, shift: u32, mode: OverflowMode) -> Result<T, EvalError> {
+    // Shifting back gives the value again unless bits were lost, the sign bit included.
+    let (shifted, exact) = match value.checked_shl(shift) {
+        Some(shifted) => (shifted, shifted.checked_shr(shift) == Some(value)),
+        None => (T::ZERO, value == T::ZERO),
+    };
+    let saturated = if value < T::ZERO { T::MIN } else { T::MAX };
+    resolve(
+        mode,
+        Some(shifted).filter(|_| exact),
+        shifted,
+        if exact { shifted } else { saturated },
+    )
+}
+/// The rotation amount modulo the width of the type.
+fn rotation_amount<T: Integer>(shift: T) -> u32 {
+    (shift & T::from_u32(T::BITS - 1)).to_u32()
+}
+/// The integer square root, the real one being only an estimate for the widest types.
+fn sqrt<T: Integer>(value: T) -> T {
+    let mut root = T::from_f64(value.to_f64().sqrt());
+    let exceeds = |root: T| root.checked_mul(root).map_or(true, |square| square > value);
+    while exceeds(root) {
+        root = root.wrapping_sub(T::ONE);
+    }
+    while !exceeds(root.wrapping_add(T::ONE)) {
+        root = root.wrapping_add(T::ONE);
+    }
+    root
+}
+fn real_to_integer<T: Integer>(
+    function: &str,
+    result: f64,
+    mode: OverflowMode,
+) -> Result<T, EvalError> {
+    let result = result.trunc();
+    if result.is_nan() {
+        Err(EvalError::Domain(function.to_string()))
+    } else if (result >= T::MAX.to_f64() + 1.0 || result < T::MIN.to_f64())
+        && mode == OverflowMode::Checked
+    {
+        Err(EvalError::Overflow)
+    } else {
+        // Out of range reals are clamped, as there is nothing to wrap around.
+        Ok(T::from_f64(result))
+    }
+}
+
+/// 1 when the condition holds and 0 otherwise.
+fn truth<T: Integer>(condition: bool) -> T {
+    if condition {
+        T::ONE
+    } else {
+        T::ZERO
+    }
+}
+
+pub fn eval<T: Integer>(
+    expr: &Node<T>,
+    placeholder: T,
+    context: &dyn Context<T>,
+) -> Result<T, EvalError> {
+    eval_with_mode(expr, placeholder, context, OverflowMode::Checked)
+}
+
+pub fn eval_with_mode<T: Integer>(
+    expr: &Node<T>,
+    placeholder: T,
+    context: &dyn Context<T>,
+    mode: OverflowMode,
+) -> Result<T, EvalError> {
+    use self::Node::*;
+    match expr {
+        Number(i) => Ok(*i),
+        Ans => Ok(placeholder),
+        CustomFunction(function, args) => {
+            let mut values = vec![];
+            for arg in args.iter() {
+                values.push(eval_with_mode(arg, placeholder, context, mode)?);
+            }
+            function.call(&values)
+        }
+        Variable(name) => match context.get(name) {
+            Some(value) => Ok(value),
+            None => Err(EvalError::UndefinedVariable(name.clone())),
+        },
+        And(expr1, expr2) => Ok(eval_with_mode(expr1, placeholder, context, mode)?
+            & eval_with_mode(expr2, placeholder, context, mode)?),
+        Or(expr1, expr2) => Ok(eval_with_mode(expr1, placeholder, context, mode)?
+            | eval_with_mode(expr2, placeholder, context, mode)?),
+        Xor(expr1, expr2) => Ok(eval_with_mode(expr1, placeholder, context, mode)?
+            ^ eval_with_mode(expr2, placeholder, context, mode)?),
+        Not(expr1) => Ok(!eval_with_mode(expr1, placeholder, context, mode)?),
+        LeftShift(expr1, expr2) => {
+            let value = eval_with_mode(expr1, placeholder, context, mode)?;
+            let shift = shift_amount(eval_with_mode(expr2, placeholder, context, mode)?, mode)?;
+            left_shift(value, shift, mode)
+        }
+        RightShift(expr1, expr2) => {
+            let value = eval_with_mode(expr1, placeholder, context, mode)?;
+            let shift = shift_amount(eval_with_mode(expr2, placeholder, context, mode)?, mode)?;
+            let sign_fill = if value < T::ZERO { !T::ZERO } else { T::ZERO };
+            Ok(value.checked_shr(shift).unwrap_or(sign_fill))
+        }
+        UnsignedRightShift(expr1, expr2) => {
+            let value = eval_with_mode(expr1, placeholder, context, mode)?;
+            let shift = shift_amount(eval_with_mode(expr2, placeholder, context, mode)?, mode)?;
+            Ok(value.checked_logical_shr(shift).unwrap_or(T::ZERO))
+        }
+        Rotl(expr1, expr2) => {
+            let value = eval_with_mode(expr1, placeholder, context, mode)?;
+            Ok(value.rotate_left(rotation_amount(eval_with_mode(
+                expr2,
+                placeholder,
+                context,
+                mode,
+            )?)))
+        }
+        Rotr(expr1, expr2) => {
+            let value = eval_with_mode(expr1, placeholder, context, mode)?;
+            Ok(value.rotate_right(rotation_amount(eval_with_mode(
+                expr2,
+                placeholder,
+                context,
+                mode,
+            )?)))
+        }
+        Popcnt(expr1) => Ok(T::from_u32(
+            eval_with_mode(expr1, placeholder, context, mode)?.count_ones(),
+        )),
+        Clz(expr1) => Ok(T::from_u32(
+            eval_with_mode(expr1, placeholder, context, mode)?.leading_zeros(),
+        )),
+        Ctz(expr1) => Ok(T::from_u32(
+            eval_with_mode(expr1, placeholder, context, mode)?.trailing_zeros(),
+        )),
+        Add(expr1, expr2) => add(
+            eval_with_mode(expr1, placeholder, context, mode)?,
+            eval_with_mode(expr2, placeholder, context, mode)?,
+            mode,
+        ),
+        Subtract(expr1, expr2) => {
+            let a = eval_with_mode(expr1, placeholder, context, mode)?;
+            let b = eval_with_mode(expr2, placeholder, context, mode)?;
+            resolve(
+                mode,
+                a.checked_sub(b),
+                a.wrapping_sub(b),
+                a.saturating_sub(b),
+            )
+        }
+        Multiply(expr1, expr2) => multiply(
+            eval_with_mode(expr1, placeholder, context, mode)?,
+            eval_with_mode(expr2, placeholder, context, mode)?,
+            mode,
+        ),
+        Divide(expr1, expr2) => divide(
+            eval_with_mode(expr1, placeholder, context, mode)?,
+            eval_with_mode(expr2, placeholder, context, mode)?,
+            mode,
+        ),
+        Modulo(expr1, expr2) => {
+            let dividend = eval_with_mode(expr1, placeholder, context, mode)?;
+            let divisor = eval_with_mode(expr2, placeholder, context, mode)?;
+            if divisor == T::ZERO {
+                return Err(EvalError::DivisionByZero);
+            }
+            Ok(dividend.wrapping_rem(divisor))
+        }
+        Equal(expr1, expr2) => Ok(truth(
+            eval_with_mode(expr1, placeholder, context, mode)?
+                == eval_with_mode(expr2, placeholder, context, mode)?,
+        )),
+        NotEqual(expr1, expr2) => Ok(truth(
+            eval_with_mode(expr1, placeholder, context, mode)?
+                != eval_with_mode(expr2, placeholder, context, mode)?,
+        )),
+        Less(expr1, expr2) => Ok(truth(
+            eval_with_mode(expr1, placeholder, context, mode)?
+                < eval_with_mode(expr2, placeholder, context, mode)?,
+        )),
+        LessEqual(expr1, expr2) => Ok(truth(
+            eval_with_mode(expr1, placeholder, context, mode)?
+                <= eval_with_mode(expr2, placeholder, context, mode)?,
+        )),
+        Greater(expr1, expr2) => Ok(truth(
+            eval_with_mode(expr1, placeholder, context, mode)?
+                > eval_with_mode(expr2, placeholder, context, mode)?,
+        )),
+        GreaterEqual(expr1, expr2) => Ok(truth(
+            eval_with_mode(expr1, placeholder, context, mode)?
+                >= eval_with_mode(expr2, placeholder, context, mode)?,
+        )),
+        LogicalAnd(expr1, expr2) => Ok(truth(
+            eval_with_mode(expr1, placeholder, context, mode)? != T::ZERO
+                && eval_with_mode(expr2, placeholder, context, mode)? != T::ZERO,
+        )),
+        LogicalOr(expr1, expr2) => Ok(truth(
+            eval_with_mode(expr1, placeholder, context, mode)? != T::ZERO
+                || eval_with_mode(expr2, placeholder, context, mode)? != T::ZERO,
+        )),
+        LogicalNot(expr1) => Ok(truth(
+            eval_with_mode(expr1, placeholder, context, mode)? == T::ZERO,
+        )),
+        Conditional(condition, expr1, expr2) => {
+            if eval_with_mode(condition, placeholder, context, mode)? != T::ZERO {
+                eval_with_mode(expr1, placeholder, context, mode)
+            } else {
+                eval_with_mode(expr2, placeholder, context, mode)
+            }
+        }
+        Negative(expr1) => {
+            let value = eval_with_mode(expr1, placeholder, context, mode)?;
+            resolve(
+                mode,
+                value.checked_neg(),
+                value.wrapping_neg(),
+                value.saturating_neg(),
+            )
+        }
+        Pow(expr1, expr2) => pow(
+            eval_with_mode(expr1, placeholder, context, mode)?,
+            eval_with_mode(expr2, placeholder, context, mode)?,
+            mode,
+        ),
+        Factorial(sub_expr) => {
+            let sub_result = eval_with_mode(sub_expr, placeholder, context, mode)?;
+            if sub_result >= T::ZERO {
+                let mut factorial_result = T::ONE;
+                // Counting up to the operand rather than past it, which could overflow the counter.
+                let mut i = T::ONE;
+                while i < sub_result {
+                    i = i.wrapping_add(T::ONE);
+                    factorial_result = multiply(factorial_result, i, mode)?;
+                    // A wrapped factorial ends up a multiple of 2^BITS and a saturated one stays at the bound.
+                    if factorial_result == T::ZERO || factorial_result == T::MAX {
+                        break;
+                    }
+                }
+                Ok(factorial_result)
+            } else {
+                Err(EvalError::NegativeFactorial)
+            }
+        }
+        Abs(sub_expr) => abs(eval_with_mode(sub_expr, placeholder, context, mode)?, mode),
+        Floor(sub_expr) | Ceil(sub_expr) | Round(sub_expr) | Truncate(sub_expr) => {
+            match sub_expr.as_ref() {
+                // A quotient is rounded from its exact value rather than from the truncated one.
+                Divide(expr1, expr2) => {
+                    let dividend = eval_with_mode(expr1, placeholder, context, mode)?;
+                    let divisor = eval_with_mode(expr2, placeholder, context, mode)?;
+                    let quotient = divide(dividend, divisor, mode)?;
+                    round_quotient(
+                        expr,
+                        quotient,
+                        dividend.wrapping_rem(divisor),
+                        divisor,
+                        mode,
+                    )
+                }
+                // Any other integer is its own floor, ceiling and rounding.
+                _ => eval_with_mode(sub_expr, placeholder, context, mode),
+            }
+        }
+        Sqrt(sub_expr) => {
+            let before_sqr = eval_with_mode(sub_expr, placeholder, context, mode)?;
+            if before_sqr < T::ZERO {
+                return Err(EvalError::Domain("sqrt".to_string()));
+            }
+            Ok(sqrt(before_sqr))
+        }
+        Root(n_th_expr, x_expr) => {
+            let n_th_expr = eval_with_mode(n_th_expr, placeholder, context, mode)?.to_f64();
+            let x_expr = eval_with_mode(x_expr, placeholder, context, mode)?.to_f64();
+            real_to_integer("root", x_expr.powf(1.0 / n_th_expr), mode)
+        }
+        Ln(sub_expr) => {
+            let before_sqr = eval_with_mode(sub_expr, placeholder, context, mode)?;
+            if before_sqr <= T::ZERO {
+                return Err(EvalError::Domain("ln".to_string()));
+            }
+            Ok(T::from_f64(before_sqr.to_f64().ln()))
+        }
+        Lb(sub_expr) => {
+            let before_sqr = eval_with_mode(sub_expr, placeholder, context, mode)?;
+            if before_sqr <= T::ZERO {
+                return Err(EvalError::Domain("lb".to_string()));
+            }
+            Ok(T::from_u32(T::BITS - 1 - before_sqr.leading_zeros()))
+        }
+        Sign(sub_expr) => Ok(eval_with_mode(sub_expr, placeholder, context, mode)?.signum()),
+        Exp(sub_expr) => real_to_integer(
+            "exp",
+            eval_with_mode(sub_expr, placeholder, context, mode)?
+                .to_f64()
+                .exp(),
+            mode,
+        ),
+        Exp2(sub_expr) => {
+            let result = eval_with_mode(sub_expr, placeholder, context, mode)?;
+            if result < T::ZERO {
+                Ok(T::ZERO)
+            } else {
+                pow(T::from_u32(2), result, mode)
+            }
+        }
+        Log(expr1, expr2) => {
+            let eval_1 = eval_with_mode(expr1, placeholder, context, mode)?;
+            let eval_2 = eval_with_mode(expr2, placeholder, context, mode)?;
+            if eval_1 <= T::ZERO || eval_2 <= T::ZERO || eval_2 == T::ONE {
+                return Err(EvalError::Domain("log".to_string()));
+            }
+            Ok(T::from_f64(eval_1.to_f64().log(eval_2.to_f64())))
+        }
+        Gcd(args) => {
+            // Ok(gcd(eval_with_mode(expr1, placeholder, context, mode)?, eval_with_mode(expr2, placeholder, context, mode)?))
+            if args.len() > 1 {
+                let mut result: Option<T> = None;
+                for arg in args.iter() {
+                    let right_art = eval_with_mode(arg, placeholder, context, mode)?;
+                    result = match result {
+                        Some(left_arg) => Some(gcd(left_arg, right_art, mode)?),
+                        None => Some(right_art),
+                    };
+                }
+                Ok(result.unwrap())
+            } else {
+                match args.first() {
+                    Some(arg) => Ok(eval_with_mode(arg, placeholder, context, mode)?),
+                    None => Ok(T::ZERO),
+                }
+            }
+        }
+        Lcm(args) => {
+            if args.len() > 1 {
+                let mut result: Option<T> = None;
+                for arg in args.iter() {
+                    let right_art = eval_with_mode(arg, placeholder, context, mode)?;
+                    result = match result {
+                        Some(left_arg) => Some(lcm(left_arg, right_art, mode)?),
+                        None => Some(right_art),
+                    };
+                }
+                Ok(result.unwrap())
+            } else {
+                match args.first() {
+                    Some(arg) => Ok(eval_with_mode(arg, placeholder, context, mode)?),
+                    None => Ok(T::ZERO),
+                }
+            }
+        }
+        Min(args) => {
+            let mut result: Option<T> = None;
+            for arg in args.iter() {
+                let value = eval_with_mode(arg, placeholder, context, mode)?;
+                result = Some(result.map_or(value, |result| result.min(value)));
+            }
+            Ok(result.unwrap_or(T::ZERO))
+        }
+        Max(args) => {
+            let mut result: Option<T> = None;
+            for arg in args.iter() {
+                let value = eval_with_mode(arg, placeholder, context, mode)?;
+                result = Some(result.map_or(value, |result| result.max(value)));
+            }
+            Ok(result.unwrap_or(T::ZERO))
+        }
+        Avg(args) => {
+            let mut results = vec![];
+            for arg in args.iter() {
+                results.push(eval_with_mode(arg, placeholder, context, mode)?);
+            }
+            average(&results)
+        }
+        Med(args) => {
+            let mut results = vec![];
+            for arg in args.iter() {
+                results.push(eval_with_mode(arg, placeholder, context, mode)?);
+            }
+            results.sort();
+            let len = results.len();
+            if len % 2 == 0 {
+                average(&results[(len >> 1) - 1..=len >> 1])
+            } else {
+                Ok(results[len >> 1])
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use crate::eval_integer::ast::{eval, eval_with_mode};
+    use crate::eval_integer::Integer;
+    use crate::utils::{EvalError, OverflowMode};
+
+    type Parser<'a> = crate::eval_integer::parser::Parser<'a, i64>;
+
+    fn eval_as<T: Integer>(
+        formula: &str,
+        placeholder: T,
+        mode: OverflowMode,
+    ) -> Result<T, EvalError> {
+        let ast = crate::eval_integer::parser::Parser::<T>::new(formula)
+            .unwrap()
+            .parse()
+            .unwrap();
+        eval_with_mode(&ast, placeholder, &HashMap::new(), mode)
+    }
+
+    #[test]
+    fn test_expr1() {
+        let ast = Parser::new("1+2-3").unwrap().parse().unwrap();
+        let value = eval(&ast, 0, &HashMap::new()).unwrap();
+        assert_eq!(value, 0);
+    }
+    #[test]
+    fn test_expr2() {
+        let ast = Parser::new("3+2-1*5/4").unwrap().parse().unwrap();
+        let value = eval(&ast, 0, &HashMap::new()).unwrap();
+        assert_eq!(value, 4);
+    }
+    #[test]
+    fn test_expr3() {
+        let ast = Parser::new("5+(2*7-3!)*3").unwrap().parse().unwrap();
+        let value = eval(&ast, 0, &HashMap::new()).unwrap();
+        assert_eq!(value, 29);
+    }
+    #[test]
+    fn test_expr4() {
+        let ast = Parser::new("2*4%3/2").unwrap().parse().unwrap();
+        let value = eval(&ast, 0, &HashMap::new()).unwrap();
+        assert_eq!(value, 1);
+    }
+    #[test]
+    fn test_expr5() {
+        let ast = Parser::new("3*2^3*3").unwrap().parse().unwrap();
+        let value = eval(&ast, 0, &HashMap::new()).unwrap();
+        assert_eq!(value, 72);
+    }
+    #[test]
+    fn test_expr6() {
+        let ast = Parser::new("1<<2").unwrap().parse().unwrap();
+        let value = eval(&ast, 0, &HashMap::new()).unwrap();
+        assert_eq!(value, 4);
+    }
+    #[test]
+    fn test_expr7() {
+        let ast = Parser::new("root(2,35)").unwrap().parse().unwrap();
+        let value = eval(&ast, 0, &HashMap::new()).unwrap();
+        assert_eq!(value, 5);
+    }
+    #[test]
+    fn test_expr8() {
+        let ast = Parser::new("1|2|4").unwrap().parse().unwrap();
+        let value = eval(&ast, 0, &HashMap::new()).unwrap();
+        assert_eq!(value, 7);
+    }
+    #[test]
+    fn test_expr9() {
+        let ast = Parser::new("1&3").unwrap().parse().unwrap();
+        let value = eval(&ast, 0, &HashMap::new()).unwrap();
+        assert_eq!(value, 1);
+    }
+    #[test]
+    fn test_expr10() {
+        let ast = Parser::new("1+1&3|1").unwrap().parse().unwrap();
+        let value = eval(&ast, 0, &HashMap::new()).unwrap();
+        assert_eq!(value, 3);
+    }
+    #[test]
+    fn test_expr11() {
+        let ast = Parser::new("med(5,2,8,9,7)").unwrap().parse().unwrap();
+        let value = eval(&ast, 0, &HashMap::new()).unwrap();
+        assert_eq!(value, 7);
+    }
+    #[test]
+    fn test_expr12() {
+        let ast = Parser::new("med(5,2,8,9)").unwrap().parse().unwrap();
+        let value = eval(&ast, 0, &HashMap::new()).unwrap();
+        assert_eq!(value, 6);
+    }
+    #[test]
+    fn test_mean_bounds() {
+        use OverflowMode::*;
+        for mode in [Checked, Wrapping, Saturating] {
+            assert_eq!(eval_as::<u8>("med(200,200)", 0, mode), Ok(200));
+            assert_eq!(eval_as::<u8>("avg(255,255,254)", 0, mode), Ok(254));
+            assert_eq!(eval_as::<i8>("avg(@,@,-127)", -128, mode), Ok(-127));
+            assert_eq!(eval_as::<i8>("avg(127,@,127)", -128, mode), Ok(42));
+            assert_eq!(eval_as::<i8>("avg(-1,-2,0,1)", 0, mode), Ok(0));
+            assert_eq!(eval_as::<i64>("med(@,@)", i64::MAX, mode), Ok(i64::MAX));
+            assert_eq!(
+                eval_as::<i64>("med(@,@,1,2)", i64::MIN, mode),
+                Ok((i64::MIN + 1) / 2)
+            );
+            assert_eq!(eval_as::<i64>("avg(@,@)", i64::MIN, mode), Ok(i64::MIN));
+            assert_eq!(eval_as::<i64>("avg(@,-(@+1))", i64::MIN, mode), Ok(0));
+            assert_eq!(
+                eval_as::<u128>("avg(@,@,@)", u128::MAX, mode),
+                Ok(u128::MAX)
+            );
+        }
+    }
+    #[test]
+    fn test_min_literal() {
+        use OverflowMode::*;
+        for mode in [Checked, Wrapping, Saturating] {
+            assert_eq!(eval_as::<i8>("-128", 0, mode), Ok(i8::MIN));
+            assert_eq!(eval_as::<i8>("@--128", -1, mode), Ok(i8::MAX));
+            assert_eq!(
+                eval_as::<i64>("-9223372036854775808", 0, mode),
+                Ok(i64::MIN)
+            );
+        }
+    }
+    #[test]
+    fn test_bitwise() {
+        let ast = Parser::new("0b1100⊕0b1010").unwrap().parse().unwrap();
+        assert_eq!(eval(&ast, 0, &HashMap::new()).unwrap(), 0b0110);
+        let ast = Parser::new("~0xF0&0xFF").unwrap().parse().unwrap();
+        assert_eq!(eval(&ast, 0, &HashMap::new()).unwrap(), 0x0F);
+        let ast = Parser::new("-1>>>60").unwrap().parse().unwrap();
+        assert_eq!(eval(&ast, 0, &HashMap::new()).unwrap(), 0xF);
+        let ast = Parser::new("-16>>2").unwrap().parse().unwrap();
+        assert_eq!(eval(&ast, 0, &HashMap::new()).unwrap(), -4);
+        let ast = Parser::new("popcnt(0b1011)+clz(1)+ctz(8)")
+            .unwrap()
+            .parse()
+            .unwrap();
+        assert_eq!(eval(&ast, 0, &HashMap::new()).unwrap(), 3 + 63 + 3);
+        let ast = Parser::new("rotl(1,65)").unwrap().parse().unwrap();
+        assert_eq!(eval(&ast, 0, &HashMap::new()).unwrap(), 2);
+        let ast = Parser::new("rotr(1,1)").unwrap().parse().unwrap();
+        assert_eq!(eval(&ast, 0, &HashMap::new()).unwrap(), i64::MIN);
+        let ast = Parser::new("rotl(1,-1)").unwrap().parse().unwrap();
+        assert_eq!(eval(&ast, 0, &HashMap::new()).unwrap(), i64::MIN);
+    }
+    #[test]
+    fn test_eval_errors() {
+        let ast = Parser::new("1/0").unwrap().parse().unwrap();
+        assert_eq!(
+            eval(&ast, 0, &HashMap::new()),
+            Err(EvalError::DivisionByZero)
+        );
+        let ast = Parser::new("5%0").unwrap().parse().unwrap();
+        assert_eq!(
+            eval(&ast, 0, &HashMap::new()),
+            Err(EvalError::DivisionByZero)
+        );
+        let ast = Parser::new("9223372036854775807+1")
+            .unwrap()
+            .parse()
+            .unwrap();
+        assert_eq!(eval(&ast, 0, &HashMap::new()), Err(EvalError::Overflow));
+        let ast = Parser::new("3037000500*3037000500")
+            .unwrap()
+            .parse()
+            .unwrap();
+        assert_eq!(eval(&ast, 0, &HashMap::new()), Err(EvalError::Overflow));
+        let ast = Parser::new("2^63").unwrap().parse().unwrap();
+        assert_eq!(eval(&ast, 0, &HashMap::new()), Err(EvalError::Overflow));
+        let ast = Parser::new("2^(-1)").unwrap().parse().unwrap();
+        assert_eq!(
+            eval(&ast, 0, &HashMap::new()),
+            Err(EvalError::Domain("pow".to_string()))
+        );
+        let ast = Parser::new("(-3)!").unwrap().parse().unwrap();
+        assert_eq!(
+            eval(&ast, 0, &HashMap::new()),
+            Err(EvalError::NegativeFactorial)
+        );
+        let ast = Parser::new("21!").unwrap().parse().unwrap();
+        assert_eq!(eval(&ast, 0, &HashMap::new()), Err(EvalError::Overflow));
+        let ast = Parser::new("1<<64").unwrap().parse().unwrap();
+        assert_eq!(
+            eval(&ast, 0, &HashMap::new()),
+            Err(EvalError::InvalidShift("64".to_string()))
+        );
+        let ast = Parser::new("1>>(-1)").unwrap().parse().unwrap();
+        assert_eq!(
+            eval(&ast, 0, &HashMap::new()),
+            Err(EvalError::InvalidShift("-1".to_string()))
+        );
+        let ast = Parser::new("sqrt(-4)").unwrap().parse().unwrap();
+        assert_eq!(
+            eval(&ast, 0, &HashMap::new()),
+            Err(EvalError::Domain("sqrt".to_string()))
+        );
+        let ast = Parser::new("ln(0)").unwrap().parse().unwrap();
+        assert_eq!(
+            eval(&ast, 0, &HashMap::new()),
+            Err(EvalError::Domain("ln".to_string()))
+        );
+    }
+    #[test]
+    fn test_overflow_modes() {
+        use EvalError::{InvalidShift, Overflow};
+        // The expected results in the checked, wrapping and saturating modes.
+        let cases = [
+            (
+                "9223372036854775807+1",
+                0,
+                [Err(Overflow), Ok(i64::MIN), Ok(i64::MAX)],
+            ),
+            ("@-1", i64::MIN, [Err(Overflow), Ok(i64::MAX), Ok(i64::MIN)]),
+            (
+                "@/-1",
+                i64::MIN,
+                [Err(Overflow), Ok(i64::MIN), Ok(i64::MAX)],
+            ),
+            ("-@", i64::MIN, [Err(Overflow), Ok(i64::MIN), Ok(i64::MAX)]),
+            (
+                "abs(@)",
+                i64::MIN,
+                [Err(Overflow), Ok(i64::MIN), Ok(i64::MAX)],
+            ),
+            (
+                "3^100",
+                0,
+                [Err(Overflow), Ok(3i64.wrapping_pow(100)), Ok(i64::MAX)],
+            ),
+            (
+                "(-3)^101",
+                0,
+                [Err(Overflow), Ok((-3i64).wrapping_pow(101)), Ok(i64::MIN)],
+            ),
+            ("2^@", i64::MAX, [Err(Overflow), Ok(0), Ok(i64::MAX)]),
+            ("exp2(64)", 0, [Err(Overflow), Ok(0), Ok(i64::MAX)]),
+            (
+                "25!",
+                0,
+                [Err(Overflow), Ok(7034535277573963776), Ok(i64::MAX)],
+            ),
+            ("@!", i64::MAX, [Err(Overflow), Ok(0), Ok(i64::MAX)]),
+            (
+                "1<<64",
+                0,
+                [Err(InvalidShift("64".to_string())), Ok(1), Ok(i64::MAX)],
+            ),
+            (
+                "0<<64",
+                0,
+                [Err(InvalidShift("64".to_string())), Ok(0), Ok(0)],
+            ),
+            ("3<<62", 0, [Err(Overflow), Ok(-1 << 62), Ok(i64::MAX)]),
+            ("1<<63", 0, [Err(Overflow), Ok(i64::MIN), Ok(i64::MAX)]),
+            ("-1<<63", 0, [Ok(i64::MIN), Ok(i64::MIN), Ok(i64::MIN)]),
+            ("-3<<62", 0, [Err(Overflow), Ok(1 << 62), Ok(i64::MIN)]),
+            (
+                "-8>>70",
+                0,
+                [Err(InvalidShift("70".to_string())), Ok(-1), Ok(-1)],
+            ),
+            (
+                "-1>>>64",
+                0,
+                [Err(InvalidShift("64".to_string())), Ok(-1), Ok(0)],
+            ),
+            (
+                "1<<-1",
+                0,
+                [
+                    Err(InvalidShift("-1".to_string())),
+                    Err(InvalidShift("-1".to_string())),
+                    Err(InvalidShift("-1".to_string())),
+                ],
+            ),
+            ("exp(100)", 0, [Err(Overflow), Ok(i64::MAX), Ok(i64::MAX)]),
+        ];
+        let modes = [
+            OverflowMode::Checked,
+            OverflowMode::Wrapping,
+            OverflowMode::Saturating,
+        ];
+        for (formula, placeholder, expected) in &cases {
+            let ast = Parser::new(formula).unwrap().parse().unwrap();
+            for (mode, expected) in modes.iter().zip(expected) {
+                assert_eq!(
+                    &eval_with_mode(&ast, *placeholder, &HashMap::new(), *mode),
+                    expected,
+                    "{} in the {:?} mode",
+                    formula,
+                    mode
+                );
+            }
+        }
+    }
+    #[test]
+    fn test_large_powers() {
+        let ast = Parser::new("(-1)^9999999999+1^4294967296")
+            .unwrap()
+            .parse()
+            .unwrap();
+        assert_eq!(eval(&ast, 0, &HashMap::new()).unwrap(), 0);
+    }
+    #[test]
+    fn test_gcd_lcm() {
+        let ast = Parser::new("gcd(4,6)").unwrap().parse().unwrap();
+        assert_eq!(eval(&ast, 0, &HashMap::new()).unwrap(), 2);
+        let ast = Parser::new("lcm(4,6)").unwrap().parse().unwrap();
+        assert_eq!(eval(&ast, 0, &HashMap::new()).unwrap(), 12);
+    }
+    #[test]
+    fn test_min_max() {
+        let ast = Parser::new("min(5,2,8)").unwrap().parse().unwrap();
+        assert_eq!(eval(&ast, 0, &HashMap::new()).unwrap(), 2);
+        let ast = Parser::new("max(5,2,8)").unwrap().parse().unwrap();
+        assert_eq!(eval(&ast, 0, &HashMap::new()).unwrap(), 8);
+    }
+    #[test]
+    fn test_rounded_quotients() {
+        for (formula, expected) in [
+            ("⌊7/2⌋", 3),
+            ("⌈7/2⌉", 4),
+            ("⌊-7/2⌋", -4),
+            ("⌈-7/2⌉", -3),
+            ("floor(7/-2)", -4),
+            ("ceil(-7/-2)", 4),
+            ("round(7/2)", 4),
+            ("round(-7/2)", -4),
+            ("round(8/3)", 3),
+            ("round(-4/3)", -1),
+            ("trunc(-7/2)", -3),
+            ("⌊6/3⌋", 2),
+            ("⌈7⌉+⌊-7⌋", 0),
+            ("⌊7/2*2⌋", 6),
+        ] {
+            let ast = Parser::new(formula).unwrap().parse().unwrap();
+            assert_eq!(eval(&ast, 0, &HashMap::new()), Ok(expected), "{}", formula);
+        }
+        let ast = Parser::new("⌊1/0⌋").unwrap().parse().unwrap();
+        assert_eq!(
+            eval(&ast, 0, &HashMap::new()),
+            Err(EvalError::DivisionByZero)
+        );
+        use OverflowMode::*;
+        assert_eq!(eval_as::<i8>("round(-127/(-127-1))", 0, Checked), Ok(1));
+        assert_eq!(eval_as::<i8>("round(64/(-127-1))", 0, Checked), Ok(-1));
+        assert_eq!(eval_as::<u8>("ceil(255/2)", 0, Checked), Ok(128));
+        assert_eq!(eval_as::<u8>("round(255/2)", 0, Checked), Ok(128));
+    }
+    #[test]
+    fn test_u8() {
+        use OverflowMode::*;
+        assert_eq!(
+            eval_as::<u8>("200+100", 0, Checked),
+            Err(EvalError::Overflow)
+        );
+        assert_eq!(eval_as::<u8>("200+100", 0, Wrapping), Ok(44));
+        assert_eq!(eval_as::<u8>("200+100", 0, Saturating), Ok(255));
+        assert_eq!(eval_as::<u8>("1-2", 0, Checked), Err(EvalError::Overflow));
+        assert_eq!(eval_as::<u8>("1-2", 0, Wrapping), Ok(255));
+        assert_eq!(eval_as::<u8>("-@", 5, Saturating), Ok(0));
+        assert_eq!(eval_as::<u8>("~0", 0, Checked), Ok(255));
+        assert_eq!(eval_as::<u8>("0xFF>>7", 0, Checked), Ok(1));
+        assert_eq!(eval_as::<u8>("clz(1)+rotl(1,9)", 0, Checked), Ok(9));
+        assert_eq!(
+            eval_as::<u8>("1<<8", 0, Checked),
+            Err(EvalError::InvalidShift("8".to_string()))
+        );
+        assert_eq!(eval_as::<u8>("5!", 0, Checked), Ok(120));
+        assert_eq!(eval_as::<u8>("6!", 0, Checked), Err(EvalError::Overflow));
+        assert_eq!(eval_as::<u8>("@!", 255, Wrapping), Ok(0));
+        assert_eq!(eval_as::<u8>("sqrt(255)+lb(255)", 0, Checked), Ok(22));
+        assert_eq!(
+            eval_as::<u8>("exp(6)", 0, Checked),
+            Err(EvalError::Overflow)
+        );
+        assert_eq!(eval_as::<u8>("exp(5)", 0, Checked), Ok(148));
+    }
+    #[test]
+    fn test_i8() {
+        use OverflowMode::*;
+        assert_eq!(eval_as::<i8>("0xFF", 0, Checked), Ok(-1));
+        assert_eq!(eval_as::<i8>("-127-1", 0, Checked), Ok(i8::MIN));
+        assert_eq!(eval_as::<i8>("-1>>>4", 0, Checked), Ok(15));
+        assert_eq!(eval_as::<i8>("-1>>9", 0, Saturating), Ok(-1));
+        assert_eq!(eval_as::<i8>("2^7", 0, Checked), Err(EvalError::Overflow));
+        assert_eq!(eval_as::<i8>("2^7", 0, Saturating), Ok(i8::MAX));
+    }
+    #[test]
+    fn test_wide_integers() {
+        use OverflowMode::*;
+        assert_eq!(eval_as::<u32>("0xDEAD_BEEF", 0, Checked), Ok(0xDEAD_BEEF));
+        assert_eq!(eval_as::<u32>("popcnt(0xFFFF_FFFF)", 0, Checked), Ok(32));
+        assert_eq!(eval_as::<i128>("2^100", 0, Checked), Ok(1 << 100));
+        assert_eq!(
+            eval_as::<i128>("25!", 0, Checked),
+            Ok(15511210043330985984000000)
+        );
+        assert_eq!(
+            eval_as::<i128>("@+1", i128::MAX, Checked),
+            Err(EvalError::Overflow)
+        );
+        assert_eq!(
+            eval_as::<u128>("2^128", 0, Checked),
+            Err(EvalError::Overflow)
+        );
+        assert_eq!(eval_as::<u128>("1<<127", 0, Checked), Ok(1 << 127));
+        assert_eq!(
+            eval_as::<u128>("sqrt(@)", u128::MAX, Checked),
+            Ok(u64::MAX as u128)
+        );
+        assert_eq!(
+            eval_as::<u64>("sqrt(@)", u64::MAX, Checked),
+            Ok(u32::MAX as u64)
+        );
+    }
+    #[test]
+    fn test_logic() {
+        for (formula, expected) in [
+            ("2<3", 1),
+            ("3<=2", 0),
+            ("2==2", 1),
+            ("2!=2", 0),
+            ("3>2", 1),
+            ("2>=3", 0),
+            ("1&&0", 0),
+            ("0||2", 1),
+            ("!0", 1),
+            ("!5", 0),
+            ("1<2?10:20", 10),
+            ("if(0,10,20)", 20),
+            ("0?1:0?2:3", 3),
+            ("0&&x", 0),
+            ("1||x", 1),
+            ("1?2:x", 2),
+            ("if(0,x,3)", 3),
+            ("1|2==3", 1),
+            ("1<<2<=4", 1),
+        ] {
+            assert_eq!(
+                eval_as::<i64>(formula, 0, OverflowMode::Checked),
+                Ok(expected),
+                "{}",
+                formula
+            );
+        }
+        assert_eq!(
+            eval_as::<i64>("1?x:2", 0, OverflowMode::Checked),
+            Err(EvalError::UndefinedVariable("x".to_string()))
+        );
+    }
+}
@@ -0,0 +1,207 @@
+use super::ast::{eval, eval_with_mode, Node};
+use super::parser::Parser;
+use super::simplify::simplify;
+use super::tokenizer::RESERVED_WORDS;
+use super::Integer;
+use crate::utils::{
+    latex, mathml, Context, EvalError, Formula, Functions, OverflowMode, ParseError,
+};
+use std::{collections::HashMap, fmt};
+
+/// A formula parsed once into a reusable AST and evaluated into an integer type as many times as needed.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExpressionInteger<T> {
+    ast: Node<T>,
+}
+
+impl<T: Integer> ExpressionInteger<T> {
+    /// Parse a formula without evaluating it.
+    pub fn new(expr: String) -> Result<Self, ParseError> {
+        let mut math_parser = Parser::new(&expr)?;
+        let ast = math_parser.parse()?;
+        Ok(ExpressionInteger { ast })
+    }
+    /// Parse a formula that can call the given user-defined functions.
+    pub fn with_functions(expr: String, functions: &Functions<T>) -> Result<Self, ParseError> {
+        let mut math_parser = Parser::with_functions(&expr, functions)?;
+        let ast = math_parser.parse()?;
+        Ok(ExpressionInteger { ast })
+    }
+    /// Evaluate the parsed formula, `@` being replaced by the placeholder.
+    pub fn eval(&self, placeholder: T) -> Result<T, EvalError> {
+        self.eval_with_context(placeholder, &HashMap::<String, T>::new())
+    }
+    /// Evaluate the parsed formula, resolving its named variables with the context.
+    pub fn eval_with_context(
+        &self,
+        placeholder: T,
+        context: &dyn Context<T>,
+    ) -> Result<T, EvalError> {
+        eval(&self.ast, placeholder, context)
+    }
+    /// Evaluate the parsed formula, results that don't fit in the integer type being handled by the overflow mode.
+    pub fn eval_with_mode(
+        &self,
+        placeholder: T,
+        context: &dyn Context<T>,
+        mode: OverflowMode,
+    ) -> Result<T, EvalError> {
+        eval_with_mode(&self.ast, placeholder, context, mode)
+    }
+    /// Simplify the parsed formula, folding its constant parts and removing the operations
+    /// without effect, so that it evaluates faster.
+    /// Sums and products keep the order of their operands, so that the simplified formula gives
+    /// the same result as the original one in every [`OverflowMode`].
+    pub fn simplify(&self) -> Self {
+        ExpressionInteger {
+            ast: simplify(&self.ast),
+        }
+    }
+    /// Write the parsed formula as LaTeX math, without the surrounding `$`.
+    pub fn to_latex(&self) -> String {
+        latex(&self.ast)
+    }
+    /// Write the parsed formula as a MathML `math` element.
+    pub fn to_mathml(&self) -> String {
+        mathml(&self.ast)
+    }
+}
+
+/// Writes the parsed formula back with as few parentheses as possible, as text that parses into
+/// the same formula.
+impl<T: Integer> fmt::Display for ExpressionInteger<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.ast)
+    }
+}
+
+impl<T: Integer> Formula for ExpressionInteger<T> {
+    type Value = T;
+
+    fn parse(expr: String, functions: Option<&Functions<T>>) -> Result<Self, ParseError> {
+        match functions {
+            Some(functions) => ExpressionInteger::with_functions(expr, functions),
+            None => ExpressionInteger::new(expr),
+        }
+    }
+    fn evaluate(&self, placeholder: T, context: &dyn Context<T>) -> Result<T, EvalError> {
+        self.eval_with_context(placeholder, context)
+    }
+    fn is_reserved(name: &str) -> bool {
+        RESERVED_WORDS.contains(&name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::Arity;
+    use crate::utils::ParseErrorKind;
+
+    type ExpressionI64 = ExpressionInteger<i64>;
+
+    #[test]
+    fn test_reuse() {
+        let expr = ExpressionI64::new("@ << 2 | 1".to_string()).unwrap();
+        assert_eq!(expr.eval(1).unwrap(), 5);
+        assert_eq!(expr.eval(2).unwrap(), 9);
+        assert_eq!(expr.eval(0).unwrap(), 1);
+    }
+    #[test]
+    fn test_radix_literals() {
+        let expr = ExpressionI64::new("0xFF_FF & 0b1010".to_string()).unwrap();
+        assert_eq!(expr.eval(0).unwrap(), 0b1010);
+        let error = ExpressionI64::new("0x1_0000_0000_0000_0000".to_string()).unwrap_err();
+        assert_eq!(error.kind(), ParseErrorKind::InvalidNumber);
+        assert_eq!(error.span(), 0..23);
+        let error = ExpressionI64::new("0b102".to_string()).unwrap_err();
+        assert_eq!(error.kind(), ParseErrorKind::InvalidNumber);
+        assert_eq!(error.span(), 0..5);
+        for formula in ["0x", "1 + 0x_", "0o8", "0xFFg"] {
+            let error = ExpressionI64::new(formula.to_string()).unwrap_err();
+            assert_eq!(error.kind(), ParseErrorKind::InvalidNumber, "{}", formula);
+        }
+    }
+    #[test]
+    fn test_overflow_mode() {
+        let expr = ExpressionI64::new("@ * 2".to_string()).unwrap();
+        let context = HashMap::new();
+        assert_eq!(
+            expr.eval_with_mode(i64::MAX, &context, OverflowMode::Checked),
+            Err(EvalError::Overflow)
+        );
+        assert_eq!(
+            expr.eval_with_mode(i64::MAX, &context, OverflowMode::Wrapping),
+            Ok(-2)
+        );
+        assert_eq!(
+            expr.eval_with_mode(i64::MAX, &context, OverflowMode::Saturating),
+            Ok(i64::MAX)
+        );
+    }
+    #[test]
+    fn test_invalid_expression() {
+        assert!(ExpressionI64::new("2*".to_string()).is_err());
+    }
+    #[test]
+    fn test_error_span() {
+        let error = ExpressionI64::new("1 $ 2".to_string()).unwrap_err();
+        assert_eq!(error.kind(), ParseErrorKind::InvalidCharacter);
+        assert_eq!(error.span(), 2..3);
+        let error = ExpressionI64::new("gcd(4, 6))".to_string()).unwrap_err();
+        assert_eq!(error.kind(), ParseErrorKind::UnbalancedBracket);
+        assert_eq!(error.span(), 9..10);
+    }
+    #[test]
+    fn test_context() {
+        let expr = ExpressionI64::new("flags & mask".to_string()).unwrap();
+        let mut context = HashMap::new();
+        context.insert("flags".to_string(), 0b1110);
+        context.insert("mask".to_string(), 0b0110);
+        assert_eq!(expr.eval_with_context(0, &context).unwrap(), 0b0110);
+    }
+    #[test]
+    fn test_unbound_variable() {
+        let expr = ExpressionI64::new("mask".to_string()).unwrap();
+        assert!(expr.eval(0).is_err());
+    }
+    #[test]
+    fn test_custom_functions() {
+        let mut functions = Functions::new();
+        functions
+            .register("bit", Arity::Exactly(1), |args: &[i64]| {
+                u32::try_from(args[0])
+                    .ok()
+                    .and_then(|shift| 1_i64.checked_shl(shift))
+                    .ok_or_else(|| EvalError::InvalidShift(args[0].to_string()))
+            })
+            .register("all", Arity::AtLeast(1), |args| {
+                Ok(args.iter().fold(0, |acc, arg| acc | arg))
+            });
+        let expr = ExpressionI64::with_functions("all(bit(0), bit(@), 8)".to_string(), &functions)
+            .unwrap();
+        assert_eq!(expr.eval(1).unwrap(), 11);
+        assert_eq!(expr.eval(2).unwrap(), 13);
+        assert_eq!(
+            expr.eval(64),
+            Err(EvalError::InvalidShift("64".to_string()))
+        );
+    }
+    #[test]
+    fn test_integer_types() {
+        let expr = ExpressionInteger::<u16>::new("@ * 2".to_string()).unwrap();
+        assert_eq!(expr.eval(40000), Err(EvalError::Overflow));
+        assert_eq!(
+            expr.eval_with_mode(40000, &HashMap::new(), OverflowMode::Wrapping),
+            Ok(14464)
+        );
+        let error = ExpressionInteger::<u8>::new("1 + 256".to_string()).unwrap_err();
+        assert_eq!(error.kind(), ParseErrorKind::InvalidNumber);
+        assert_eq!(error.span(), 4..7);
+        let error = ExpressionInteger::<u8>::new("x * 1.5".to_string()).unwrap_err();
+        assert_eq!(error.kind(), ParseErrorKind::InvalidNumber);
+        assert_eq!(error.span(), 4..7);
+        let expr = ExpressionInteger::<i128>::new("@ << 100".to_string()).unwrap();
+        assert_eq!(expr.eval(1).unwrap(), 1 << 100);
+    }
+}
@@ -0,0 +1,244 @@
+use std::fmt;
+use std::ops::{BitAnd, BitOr, BitXor, Div, Not, Shr};
+
+/// A primitive integer type that formulas can be evaluated into.
+///
+/// The trait is implemented for every signed and unsigned primitive integer, `i8` to `i128` and
+/// `u8` to `u128`.
+pub trait Integer:
+    Copy
+    + Ord
+    + Send
+    + Sync
+    + 'static
+    + fmt::Debug
+    + fmt::Display
+    + fmt::Binary
+    + fmt::Octal
+    + fmt::LowerHex
+    + BitAnd<Output = Self>
+    + BitOr<Output = Self>
+    + BitXor<Output = Self>
+    + Not<Output = Self>
+    + Div<Output = Self>
+    + Shr<u32, Output = Self>
+{
+    const ZERO: Self;
+    const ONE: Self;
+    const MIN: Self;
+    const MAX: Self;
+    /// The width of the type in bits.
+    const BITS: u32;
+
+    /// Parse digits of the given radix, without sign nor prefix.
+    fn parse_radix(digits: &str, radix: u32) -> Option<Self>;
+    /// Parse digits of the given radix as a bit pattern, so that a signed type reads all ones as -1.
+    fn parse_bits(digits: &str, radix: u32) -> Option<Self>;
+    fn from_u32(value: u32) -> Self;
+    fn from_usize(value: usize) -> Option<Self>;
+    /// Convert a real number, rounding toward zero and clamping to the bounds of the type.
+    fn from_f64(value: f64) -> Self;
+    fn to_f64(self) -> f64;
+    /// The lowest 32 bits of the integer.
+    fn to_u32(self) -> u32;
+
+    fn checked_add(self, rhs: Self) -> Option<Self>;
+    fn wrapping_add(self, rhs: Self) -> Self;
+    fn saturating_add(self, rhs: Self) -> Self;
+    fn checked_sub(self, rhs: Self) -> Option<Self>;
+    fn wrapping_sub(self, rhs: Self) -> Self;
+    fn saturating_sub(self, rhs: Self) -> Self;
+    fn checked_mul(self, rhs: Self) -> Option<Self>;
+    fn wrapping_mul(self, rhs: Self) -> Self;
+    fn saturating_mul(self, rhs: Self) -> Self;
+    fn checked_div(self, rhs: Self) -> Option<Self>;
+    fn wrapping_div(self, rhs: Self) -> Self;
+    fn saturating_div(self, rhs: Self) -> Self;
+    fn wrapping_rem(self, rhs: Self) -> Self;
+    fn checked_neg(self) -> Option<Self>;
+    fn wrapping_neg(self) -> Self;
+    fn saturating_neg(self) -> Self;
+    fn checked_abs(self) -> Option<Self>;
+    fn wrapping_abs(self) -> Self;
+    fn saturating_abs(self) -> Self;
+    fn signum(self) -> Self;
+
+    fn checked_shl(self, shift: u32) -> Option<Self>;
+    fn checked_shr(self, shift: u32) -> Option<Self>;
+    /// Shift right filling with zeros whatever the sign, `None` when the shift is too large.
+    fn checked_logical_shr(self, shift: u32) -> Option<Self>;
+    fn rotate_left(self, shift: u32) -> Self;
+    fn rotate_right(self, shift: u32) -> Self;
+    fn count_ones(self) -> u32;
+    fn leading_zeros(self) -> u32;
+    fn trailing_zeros(self) -> u32;
+}
+
+macro_rules! integer {
+    ($t:ty, $unsigned:ty, { $($sign:tt)* }) => {
+        impl Integer for $t {
+            const ZERO: Self = 0;
+            const ONE: Self = 1;
+            const MIN: Self = <$t>::MIN;
+            const MAX: Self = <$t>::MAX;
+            const BITS: u32 = <$t>::BITS;
+
+            fn parse_radix(digits: &str, radix: u32) -> Option<Self> {
+                <$t>::from_str_radix(digits, radix).ok()
+            }
+            fn parse_bits(digits: &str, radix: u32) -> Option<Self> {
+                <$unsigned>::from_str_radix(digits, radix)
+                    .ok()
+                    .map(|value| value as $t)
+            }
+            fn from_u32(value: u32) -> Self {
+                value as $t
+            }
+            fn from_usize(value: usize) -> Option<Self> {
+                <$t>::try_from(value).ok()
+            }
+            fn from_f64(value: f64) -> Self {
+                value as $t
+            }
+            fn to_f64(self) -> f64 {
+                self as f64
+            }
+            fn to_u32(self) -> u32 {
+                self as u32
+            }
+
+            fn checked_add(self, rhs: Self) -> Option<Self> {
+                <$t>::checked_add(self, rhs)
+            }
+            fn wrapping_add(self, rhs: Self) -> Self {
+                <$t>::wrapping_add(self, rhs)
+            }
+            fn saturating_add(self, rhs: Self) -> Self {
+                <$t>::saturating_add(self, rhs)
+            }
+            fn checked_sub(self, rhs: Self) -> Option<Self> {
+                <$t>::checked_sub(self, rhs)
+            }
+            fn wrapping_sub(self, rhs: Self) -> Self {
+                <$t>::wrapping_sub(self, rhs)
+            }
+            fn saturating_sub(self, rhs: Self) -> Self {
+                <$t>::saturating_sub(self, rhs)
+            }
+            fn checked_mul(self, rhs: Self) -> Option<Self> {
+                <$t>::checked_mul(self, rhs)
+            }
+            fn wrapping_mul(self, rhs: Self) -> Self {
+                <$t>::wrapping_mul(self, rhs)
+            }
+            fn saturating_mul(self, rhs: Self) -> Self {
+                <$t>::saturating_mul(self, rhs)
+            }
+            fn checked_div(self, rhs: Self) -> Option<Self> {
+                <$t>::checked_div(self, rhs)
+            }
+            fn wrapping_div(self, rhs: Self) -> Self {
+                <$t>::wrapping_div(self, rhs)
+            }
+            fn saturating_div(self, rhs: Self) -> Self {
+                <$t>::saturating_div(self, rhs)
+            }
+            fn wrapping_rem(self, rhs: Self) -> Self {
+                <$t>::wrapping_rem(self, rhs)
+            }
+            fn checked_neg(self) -> Option<Self> {
+                <$t>::checked_neg(self)
+            }
+            fn wrapping_neg(self) -> Self {
+                <$t>::wrapping_neg(self)
+            }
+
+            fn checked_shl(self, shift: u32) -> Option<Self> {
+                <$t>::checked_shl(self, shift)
+            }
+            fn checked_shr(self, shift: u32) -> Option<Self> {
+                <$t>::checked_shr(self, shift)
+            }
+            fn checked_logical_shr(self, shift: u32) -> Option<Self> {
+                (self as $unsigned).checked_shr(shift).map(|value| value as $t)
+            }
+            fn rotate_left(self, shift: u32) -> Self {
+                <$t>::rotate_left(self, shift)
+            }
+            fn rotate_right(self, shift: u32) -> Self {
+                <$t>::rotate_right(self, shift)
+            }
+            fn count_ones(self) -> u32 {
+                <$t>::count_ones(self)
+            }
+            fn leading_zeros(self) -> u32 {
+                <$t>::leading_zeros(self)
+            }
+            fn trailing_zeros(self) -> u32 {
+                <$t>::trailing_zeros(self)
+            }
+
+            $($sign)*
+        }
+    };
+}
+
+macro_rules! signed_integer {
+    ($($t:ty, $unsigned:ty;)*) => {$(
+        integer!($t, $unsigned, {
+            fn saturating_neg(self) -> Self {
+                <$t>::saturating_neg(self)
+            }
+            fn checked_abs(self) -> Option<Self> {
+                <$t>::checked_abs(self)
+            }
+            fn wrapping_abs(self) -> Self {
+                <$t>::wrapping_abs(self)
+            }
+            fn saturating_abs(self) -> Self {
+                <$t>::saturating_abs(self)
+            }
+            fn signum(self) -> Self {
+                <$t>::signum(self)
+            }
+        });
+    )*};
+}
+
+macro_rules! unsigned_integer {
+    ($($t:ty;)*) => {$(
+        integer!($t, $t, {
+            fn saturating_neg(self) -> Self {
+                0
+            }
+            fn checked_abs(self) -> Option<Self> {
+                Some(self)
+            }
+            fn wrapping_abs(self) -> Self {
+                self
+            }
+            fn saturating_abs(self) -> Self {
+                self
+            }
+            fn signum(self) -> Self {
+                (self != 0) as $t
+            }
+        });
+    )*};
+}
+
+signed_integer! {
+    i8, u8;
+    i16, u16;
+    i32, u32;
+    i64, u64;
+    i128, u128;
+}
+
+unsigned_integer! {
+    u8;
+    u16;
+    u32;
+    u64;
+    u128;
+}
@@ -0,0 +1,38 @@
+mod ast;
+mod expression;
+mod integer;
+mod notation;
+mod parser;
+mod simplify;
+mod token;
+mod tokenizer;
+
+pub use expression::ExpressionInteger;
+pub use integer::Integer;
+
+/// Generate the `eval_*` function and the `Expression*` alias of an integer type behind its feature.
+macro_rules! integer_backend {
+    ($($feature:literal, $eval:ident, $expression:ident, $t:ty;)*) => {$(
+        #[cfg(feature = $feature)]
+        #[doc = concat!("Evaluate a formula inside a string and compute it into ", stringify!($t), ".")]
+        pub fn $eval(expr: String, placeholder: $t) -> Result<$t, crate::utils::Error> {
+            Ok(ExpressionInteger::new(expr)?.eval(placeholder)?)
+        }
+        #[cfg(feature = $feature)]
+        #[doc = concat!("A formula parsed once and evaluated into ", stringify!($t), ".")]
+        pub type $expression = ExpressionInteger<$t>;
+    )*};
+}
+
+integer_backend! {
+    "eval_i8", eval_i8, ExpressionI8, i8;
+    "eval_i16", eval_i16, ExpressionI16, i16;
+    "eval_i32", eval_i32, ExpressionI32, i32;
+    "eval_i64", eval_i64, ExpressionI64, i64;
+    "eval_i128", eval_i128, ExpressionI128, i128;
+    "eval_u8", eval_u8, ExpressionU8, u8;
+    "eval_u16", eval_u16, ExpressionU16, u16;
+    "eval_u32", eval_u32, ExpressionU32, u32;
+    "eval_u64", eval_u64, ExpressionU64, u64;
+    "eval_u128", eval_u128, ExpressionU128, u128;
+}
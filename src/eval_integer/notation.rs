@@ -0,0 +1,123 @@
+use super::{ast::Node, Integer};
+use crate::utils::{write_infix, Notation, Operator, Term, Unary};
+use std::fmt;
+
+impl<T: Integer> Notation for Node<T> {
+    fn term(&self) -> Term<'_, Self> {
+        use self::Node::*;
+        match self {
+            Number(value) => Term::number(value.to_string()),
+            Variable(name) => Term::Variable(name),
+            Ans => Term::Ans,
+            And(left, right) => Term::Binary(Operator::BitwiseAnd, left, right),
+            Or(left, right) => Term::Binary(Operator::BitwiseOr, left, right),
+            Xor(left, right) => Term::Binary(Operator::BitwiseXor, left, right),
+            Not(expr) => Term::Unary(Unary::Complement, expr),
+            LeftShift(left, right) => Term::Binary(Operator::LeftShift, left, right),
+            RightShift(left, right) => Term::Binary(Operator::RightShift, left, right),
+            UnsignedRightShift(left, right) => {
+                Term::Binary(Operator::UnsignedRightShift, left, right)
+            }
+            Rotl(left, right) => Term::Function("rotl", vec![left, right]),
+            Rotr(left, right) => Term::Function("rotr", vec![left, right]),
+            Popcnt(expr) => Term::Function("popcnt", vec![expr]),
+            Clz(expr) => Term::Function("clz", vec![expr]),
+            Ctz(expr) => Term::Function("ctz", vec![expr]),
+            Add(left, right) => Term::Binary(Operator::Add, left, right),
+            Subtract(left, right) => Term::Binary(Operator::Subtract, left, right),
+            Multiply(left, right) => Term::Binary(Operator::Multiply, left, right),
+            Divide(left, right) => Term::Binary(Operator::Divide, left, right),
+            Modulo(left, right) => Term::Binary(Operator::Modulo, left, right),
+            Pow(left, right) => Term::Binary(Operator::Pow, left, right),
+            Root(left, right) => Term::Function("root", vec![left, right]),
+            Log(left, right) => Term::Function("log", vec![left, right]),
+            Negative(expr) => Term::Unary(Unary::Negative, expr),
+            Abs(expr) => Term::Function("abs", vec![expr]),
+            Floor(expr) => Term::Function("floor", vec![expr]),
+            Ceil(expr) => Term::Function("ceil", vec![expr]),
+            Round(expr) => Term::Function("round", vec![expr]),
+            Truncate(expr) => Term::Function("trunc", vec![expr]),
+            Sqrt(expr) => Term::Function("sqrt", vec![expr]),
+            Ln(expr) => Term::Function("ln", vec![expr]),
+            Lb(expr) => Term::Function("lb", vec![expr]),
+            Exp(expr) => Term::Function("exp", vec![expr]),
+            Exp2(expr) => Term::Function("exp2", vec![expr]),
+            Sign(expr) => Term::Function("sign", vec![expr]),
+            Min(args) => Term::Function("min", args.iter().collect()),
+            Max(args) => Term::Function("max", args.iter().collect()),
+            Avg(args) => Term::Function("avg", args.iter().collect()),
+            Med(args) => Term::Function("med", args.iter().collect()),
+            Gcd(args) => Term::Function("gcd", args.iter().collect()),
+            Lcm(args) => Term::Function("lcm", args.iter().collect()),
+            Equal(left, right) => Term::Binary(Operator::Equal, left, right),
+            NotEqual(left, right) => Term::Binary(Operator::NotEqual, left, right),
+            Less(left, right) => Term::Binary(Operator::Less, left, right),
+            LessEqual(left, right) => Term::Binary(Operator::LessEqual, left, right),
+            Greater(left, right) => Term::Binary(Operator::Greater, left, right),
+            GreaterEqual(left, right) => Term::Binary(Operator::GreaterEqual, left, right),
+            LogicalAnd(left, right) => Term::Binary(Operator::LogicalAnd, left, right),
+            LogicalOr(left, right) => Term::Binary(Operator::LogicalOr, left, right),
+            LogicalNot(expr) => Term::Unary(Unary::LogicalNot, expr),
+            Factorial(expr) => Term::Factorial(expr),
+            Conditional(condition, then, otherwise) => {
+                Term::Conditional(condition, then, otherwise)
+            }
+            CustomFunction(function, args) => {
+                Term::Function(function.name(), args.iter().collect())
+            }
+        }
+    }
+}
+
+impl<T: Integer> fmt::Display for Node<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write_infix(self, f)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::eval_integer::ast::Node;
+    use crate::eval_integer::parser::Parser;
+
+    fn parsed(formula: &str) -> Node<i64> {
+        Parser::new(formula).unwrap().parse().unwrap()
+    }
+
+    #[test]
+    fn test_infix() {
+        assert_eq!(parsed("1|2&3").to_string(), "1 | 2 & 3");
+        assert_eq!(parsed("(1|2)&3").to_string(), "(1 | 2) & 3");
+        assert_eq!(parsed("~x<<2+1").to_string(), "~x << 2 + 1");
+        assert_eq!(parsed("0xFF ⊕ 0b11").to_string(), "255 ⊕ 3");
+        assert_eq!(
+            Node::Negative(Box::new(Node::Number(-3))).to_string(),
+            "-(-3)"
+        );
+    }
+    #[test]
+    fn test_round_trip() {
+        let formulas = [
+            "1|2⊕3&4<<5>>6>>>7+8*9^2",
+            "((((1|2)⊕3)&4)<<5)*(6>>>7)",
+            "~~x&~(y|z)",
+            "-(x%3)!+!x==(y<z)",
+            "rotl(x,3)+rotr(x,1)+popcnt(x)+clz(x)+ctz(x)+gcd(x,4)+lcm(x,6)",
+            "abs(x)+⌊x⌋+⌈x⌉+round(x)+trunc(x)+sqrt(x)+ln(x)+lb(x)+exp(x)+exp2(x)+sign(x)",
+            "root(2,x)+log(x,2)+min(x,1)+max(x,1)+avg(x,1)+med(x,1)+(x?1:2)",
+        ];
+        for formula in formulas {
+            let ast = parsed(formula);
+            assert_eq!(parsed(&ast.to_string()), ast, "{}", ast);
+        }
+    }
+    #[test]
+    fn test_latex() {
+        let latex = |formula| crate::utils::latex(&parsed(formula));
+        assert_eq!(latex("x<<1 & ~y"), "x \\ll 1 \\mathbin{\\&} {\\sim}y");
+        assert_eq!(
+            latex("gcd(x,12)⊕clz(x)"),
+            "\\gcd\\left(x, 12\\right) \\oplus \\operatorname{clz}\\left(x\\right)"
+        );
+    }
+}
@@ -0,0 +1,961 @@
+use std::{ops::Range, sync::Arc};
+
+use super::ast::Node;
+use super::token::{NativeFunction, Token};
+use super::tokenizer::Tokenizer;
+use super::Integer;
+use crate::utils::{
+    too_deep, Arity, Functions, OperatorCategory, ParseError, ParseErrorKind, MAX_DEPTH,
+};
+
+pub struct Parser<'a, T> {
+    tokenizer: Tokenizer<'a, T>,
+    current_token: Token<T>,
+    current_span: Range<usize>,
+    previous_token: Option<Token<T>>,
+    depth: usize,
+}
+impl<'a, T: Integer> Parser<'a, T> {
+    pub fn new(expr: &'a str) -> Result<Self, ParseError> {
+        Parser::from_tokenizer(Tokenizer::new(expr))
+    }
+    pub fn with_functions(expr: &'a str, functions: &'a Functions<T>) -> Result<Self, ParseError> {
+        Parser::from_tokenizer(Tokenizer::with_functions(expr, functions))
+    }
+    fn from_tokenizer(mut lexer: Tokenizer<'a, T>) -> Result<Self, ParseError> {
+        lexer.skip_whitespace();
+        let start = lexer.position();
+        let cur_token = match lexer.next() {
+            Some(token) => token,
+            None => return Err(lexer.error(start)),
+        };
+        Ok(Parser {
+            current_span: start..lexer.position(),
+            tokenizer: lexer,
+            current_token: cur_token,
+            previous_token: None,
+            depth: 0,
+        })
+    }
+    pub fn parse(&mut self) -> Result<Node<T>, ParseError> {
+        let ast = self.generate_ast(OperatorCategory::DefaultZero)?;
+        if self.current_token == Token::Eof {
+            Ok(ast)
+        } else if self.current_token.is_closing_bracket() {
+            Err(ParseError::new(
+                ParseErrorKind::UnbalancedBracket,
+                self.current_span.clone(),
+                format!("Unmatched {}", self.current_token),
+            ))
+        } else {
+            Err(self.unexpected_token(&["an operator", "end of input"]))
+        }
+    }
+    fn get_next_token(&mut self) -> Result<(), ParseError> {
+        self.tokenizer.skip_whitespace();
+        let start = self.tokenizer.position();
+        let next_token = match self.tokenizer.next() {
+            Some(token) => token,
+            None => return Err(self.tokenizer.error(start)),
+        };
+        self.previous_token = Some(self.current_token.clone());
+        self.current_token = next_token;
+        self.current_span = start..self.tokenizer.position();
+        Ok(())
+    }
+    fn generate_ast(&mut self, oper_prec: OperatorCategory) -> Result<Node<T>, ParseError> {
+        let depth = self.depth;
+        self.nest()?;
+        let mut left_expr = self.parse_number()?;
+        while oper_prec < self.current_token.get_oper_prec() {
+            if self.current_token == Token::Eof {
+                break;
+            }
+            self.nest()?;
+            left_expr = self.convert_token_to_node(left_expr)?;
+        }
+        self.depth = depth;
+        Ok(left_expr)
+    }
+    fn nest(&mut self) -> Result<(), ParseError> {
+        self.depth += 1;
+        if self.depth > MAX_DEPTH {
+            return Err(too_deep(self.current_span.clone()));
+        }
+        Ok(())
+    }
+    fn function_static_arguments(&mut self, n: usize) -> Result<Vec<Node<T>>, ParseError> {
+        let function = self.current_token.clone();
+        let function_span = self.current_span.clone();
+        self.get_next_token()?;
+        self.check_paren(Token::LeftParen)?;
+        let mut args = Vec::new();
+        for i in 0..n {
+            let arg_expr = self.generate_ast(OperatorCategory::DefaultZero)?;
+            args.push(arg_expr);
+            if i < n - 1 {
+                if self.current_token == Token::RightParen {
+                    return Err(wrong_arity(&function, function_span, n));
+                }
+                self.check_paren(Token::Comma)?;
+            }
+        }
+        if self.current_token == Token::Comma {
+            return Err(wrong_arity(&function, function_span, n));
+        }
+        self.check_paren(Token::RightParen)?;
+        Ok(args)
+    }
+    fn function_arguments(&mut self) -> Result<Vec<Node<T>>, ParseError> {
+        self.find_item_list(
+            Token::LeftParen,
+            Token::RightParen,
+            OperatorCategory::DefaultZero,
+        )
+    }
+    fn find_item_list(
+        &mut self,
+        start_token: Token<T>,
+        end_token: Token<T>,
+        oper_prec: OperatorCategory,
+    ) -> Result<Vec<Node<T>>, ParseError> {
+        self.get_next_token()?;
+        self.check_paren(start_token)?;
+        let mut args = Vec::new();
+        loop {
+            if args.is_empty() && (end_token == self.current_token) {
+                self.get_next_token()?;
+                break;
+            }
+            let arg_expr = self.generate_ast(oper_prec.clone())?;
+            args.push(arg_expr);
+            if Token::Comma == self.current_token {
+                self.get_next_token()?;
+            } else if end_token == self.current_token {
+                self.get_next_token()?;
+                break;
+            } else {
+                return Err(self.missing_token(&[Token::Comma, end_token]));
+            }
+        }
+        Ok(args)
+    }
+    fn parse_number(&mut self) -> Result<Node<T>, ParseError> {
+        let token = self.current_token.clone();
+        match token {
+            Token::Ans => {
+                self.get_next_token()?;
+                Ok(Node::Ans)
+            }
+            Token::Identifier(name) => {
+                self.get_next_token()?;
+                self.implicit_multiply(Node::Variable(name))
+            }
+            Token::CustomFunction(function) => {
+                let function_span = self.current_span.clone();
+                let args = match function.arity() {
+                    Arity::Exactly(n) => self.function_static_arguments(n)?,
+                    Arity::AtLeast(n) => {
+                        let args = self.function_arguments()?;
+                        if args.len() < n {
+                            return Err(ParseError::new(
+                                ParseErrorKind::WrongArity,
+                                function_span,
+                                format!(
+                                    "The {} function takes at least {} arguments",
+                                    function.name(),
+                                    n
+                                ),
+                            ));
+                        }
+                        args
+                    }
+                };
+                self.implicit_multiply(Node::CustomFunction(function, Arc::new(args)))
+            }
+            Token::ExplicitFunction(current_function) => {
+                let function_span = self.current_span.clone();
+                let current_function = match current_function {
+                    NativeFunction::Abs => {
+                        Node::Abs(Box::new(self.function_static_arguments(1)?[0].clone()))
+                    }
+                    NativeFunction::Floor => {
+                        Node::Floor(Box::new(self.function_static_arguments(1)?[0].clone()))
+                    }
+                    NativeFunction::Ceil => {
+                        Node::Ceil(Box::new(self.function_static_arguments(1)?[0].clone()))
+                    }
+                    NativeFunction::Round => {
+                        Node::Round(Box::new(self.function_static_arguments(1)?[0].clone()))
+                    }
+                    NativeFunction::Truncate => {
+                        Node::Truncate(Box::new(self.function_static_arguments(1)?[0].clone()))
+                    }
+                    NativeFunction::Sqrt => {
+                        Node::Sqrt(Box::new(self.function_static_arguments(1)?[0].clone()))
+                    }
+                    NativeFunction::Root => {
+                        let args = self.function_static_arguments(2)?;
+                        Node::Root(Box::new(args[0].clone()), Box::new(args[1].clone()))
+                    }
+                    NativeFunction::Exp => {
+                        Node::Exp(Box::new(self.function_static_arguments(1)?[0].clone()))
+                    }
+                    NativeFunction::Popcnt => {
+                        Node::Popcnt(Box::new(self.function_static_arguments(1)?[0].clone()))
+                    }
+                    NativeFunction::Clz => {
+                        Node::Clz(Box::new(self.function_static_arguments(1)?[0].clone()))
+                    }
+                    NativeFunction::Ctz => {
+                        Node::Ctz(Box::new(self.function_static_arguments(1)?[0].clone()))
+                    }
+                    NativeFunction::Rotl => {
+                        let args = self.function_static_arguments(2)?;
+                        Node::Rotl(Box::new(args[0].clone()), Box::new(args[1].clone()))
+                    }
+                    NativeFunction::Rotr => {
+                        let args = self.function_static_arguments(2)?;
+                        Node::Rotr(Box::new(args[0].clone()), Box::new(args[1].clone()))
+                    }
+                    NativeFunction::Exp2 => {
+                        Node::Exp2(Box::new(self.function_static_arguments(1)?[0].clone()))
+                    }
+                    NativeFunction::Ln => {
+                        Node::Ln(Box::new(self.function_static_arguments(1)?[0].clone()))
+                    }
+                    NativeFunction::Lb => {
+                        Node::Lb(Box::new(self.function_static_arguments(1)?[0].clone()))
+                    }
+                    NativeFunction::Sign => {
+                        Node::Sign(Box::new(self.function_static_arguments(1)?[0].clone()))
+                    }
+                    NativeFunction::Pow => {
+                        let args = self.function_static_arguments(2)?;
+                        Node::Pow(Box::new(args[0].clone()), Box::new(args[1].clone()))
+                    }
+                    NativeFunction::Log => {
+                        let args = self.function_static_arguments(2)?;
+                        Node::Log(Box::new(args[0].clone()), Box::new(args[1].clone()))
+                    }
+                    NativeFunction::Mod => {
+                        let args = self.function_static_arguments(2)?;
+                        Node::Modulo(Box::new(args[0].clone()), Box::new(args[1].clone()))
+                    }
+                    NativeFunction::Gcd => {
+                        let args = self.function_arguments()?;
+                        if args.is_empty() {
+                            return Err(ParseError::new(
+                                ParseErrorKind::WrongArity,
+                                function_span,
+                                "There's no arguments in the gcd function".to_string(),
+                            ));
+                        }
+                        Node::Gcd(Arc::new(args))
+                    }
+                    NativeFunction::Lcm => {
+                        let args = self.function_arguments()?;
+                        if args.is_empty() {
+                            return Err(ParseError::new(
+                                ParseErrorKind::WrongArity,
+                                function_span,
+                                "There's no arguments in the gcd function".to_string(),
+                            ));
+                        }
+                        Node::Lcm(Arc::new(args))
+                    }
+                    NativeFunction::Min => {
+                        let args = self.function_arguments()?;
+                        if args.is_empty() {
+                            return Err(ParseError::new(
+                                ParseErrorKind::WrongArity,
+                                function_span,
+                                "There's no arguments in the min function".to_string(),
+                            ));
+                        }
+                        Node::Min(Arc::new(args))
+                    }
+                    NativeFunction::Max => {
+                        let args = self.function_arguments()?;
+                        if args.is_empty() {
+                            return Err(ParseError::new(
+                                ParseErrorKind::WrongArity,
+                                function_span,
+                                "There's no arguments in the max function".to_string(),
+                            ));
+                        }
+                        Node::Max(Arc::new(args))
+                    }
+                    NativeFunction::Avg => {
+                        let args = self.function_arguments()?;
+                        if args.is_empty() {
+                            Node::Number(T::ZERO)
+                        } else {
+                            Node::Avg(Arc::new(args))
+                        }
+                    }
+                    NativeFunction::Med => {
+                        let args = self.function_arguments()?;
+                        if args.is_empty() {
+                            return Err(ParseError::new(
+                                ParseErrorKind::WrongArity,
+                                function_span,
+                                "Cannot compute the median of no arguments".to_string(),
+                            ));
+                        } else {
+                            Node::Med(Arc::new(args))
+                        }
+                    }
+                    NativeFunction::If => {
+                        let args = self.function_static_arguments(3)?;
+                        Node::Conditional(
+                            Box::new(args[0].clone()),
+                            Box::new(args[1].clone()),
+                            Box::new(args[2].clone()),
+                        )
+                    }
+                };
+                self.implicit_multiply(current_function)
+            }
+            Token::Subtract => {
+                self.get_next_token()?;
+                if let Token::MinMagnitude(min) = self.current_token {
+                    // The negation is folded into the literal, unless a power or a factorial
+                    // applies to the magnitude first.
+                    let start = self.current_span.start;
+                    self.current_token = Token::Num(min);
+                    let expr = self.parse_number()?;
+                    if self.current_token.get_oper_prec() > OperatorCategory::Negative {
+                        return Err(self.tokenizer.error(start));
+                    }
+                    return Ok(expr);
+                }
+                let expr = self.generate_ast(OperatorCategory::Negative)?;
+                Ok(Node::Negative(Box::new(expr)))
+            }
+            Token::Add => {
+                self.get_next_token()?;
+                let expr = self.generate_ast(OperatorCategory::Negative)?;
+                Ok(expr)
+            }
+            Token::ExclamationMark => {
+                self.get_next_token()?;
+                let expr = self.generate_ast(OperatorCategory::Negative)?;
+                Ok(Node::LogicalNot(Box::new(expr)))
+            }
+            Token::Tilde => {
+                self.get_next_token()?;
+                let expr = self.generate_ast(OperatorCategory::Negative)?;
+                Ok(Node::Not(Box::new(expr)))
+            }
+            Token::Num(i) => {
+                let end = self.current_span.end;
+                self.get_next_token()?;
+                if let Token::Num(_) = self.current_token {
+                    if self.current_span.start > end {
+                        // Numbers separated by whitespace are not multiplied.
+                        return Err(self.unexpected_token(&["an operator"]));
+                    }
+                }
+                self.implicit_multiply(Node::Number(i))
+            }
+            Token::MinMagnitude(_) => Err(self.tokenizer.error(self.current_span.start)),
+            Token::LeftParen => self.get_enclosed_elements_with_impl_mult(
+                OperatorCategory::DefaultZero,
+                Token::RightParen,
+                |expr| expr,
+            ),
+            Token::LeftFloor => self.get_enclosed_elements_with_impl_mult(
+                OperatorCategory::DefaultZero,
+                Token::RightFloor,
+                |expr| Node::Floor(Box::new(expr)),
+            ),
+            Token::LeftCeiling => self.get_enclosed_elements_with_impl_mult(
+                OperatorCategory::DefaultZero,
+                Token::RightCeiling,
+                |expr| Node::Ceil(Box::new(expr)),
+            ),
+            _ => Err(self.unexpected_token(&["a number", "a variable", "a function", "("])),
+        }
+    }
+    fn implicit_multiply(&mut self, node: Node<T>) -> Result<Node<T>, ParseError> {
+        if (self.current_token == Token::LeftParen)
+            || (self.current_token == Token::LeftCeiling)
+            || (self.current_token == Token::LeftFloor)
+            || matches!(self.current_token, Token::ExplicitFunction(_))
+            || matches!(self.current_token, Token::CustomFunction(_))
+            || matches!(self.current_token, Token::Num(_) | Token::MinMagnitude(_))
+            || matches!(self.current_token, Token::Identifier(_))
+        {
+            let right = self.generate_ast(OperatorCategory::Multiplicative)?;
+            return Ok(Node::Multiply(Box::new(node), Box::new(right)));
+        }
+        Ok(node)
+    }
+    fn get_enclosed_elements_with_impl_mult(
+        &mut self,
+        oper_prec: OperatorCategory,
+        end_token: Token<T>,
+        get_node: fn(Node<T>) -> Node<T>,
+    ) -> Result<Node<T>, ParseError> {
+        self.get_next_token()?;
+        let expr = self.generate_ast(oper_prec)?;
+        self.check_paren(end_token)?;
+        self.implicit_multiply(get_node(expr))
+    }
+    fn unexpected_token<S: ToString>(&self, expected: &[S]) -> ParseError {
+        ParseError::new(
+            ParseErrorKind::UnexpectedToken,
+            self.current_span.clone(),
+            format!("Unexpected {}", self.current_token),
+        )
+        .with_expected(expected)
+    }
+    fn missing_token(&self, expected: &[Token<T>]) -> ParseError {
+        let unclosed = self.current_token == Token::Eof || self.current_token.is_closing_bracket();
+        if !(unclosed && expected.iter().any(Token::is_closing_bracket)) {
+            return self.unexpected_token(expected);
+        }
+        ParseError::new(
+            ParseErrorKind::UnbalancedBracket,
+            self.current_span.clone(),
+            format!("Unexpected {}", self.current_token),
+        )
+        .with_expected(expected)
+    }
+    fn check_paren(&mut self, expected: Token<T>) -> Result<(), ParseError> {
+        if expected == self.current_token {
+            self.get_next_token()?;
+            Ok(())
+        } else {
+            Err(self.missing_token(&[expected]))
+        }
+    }
+    fn convert_token_to_node(&mut self, left_expr: Node<T>) -> Result<Node<T>, ParseError> {
+        match self.current_token {
+            Token::Ampersand => {
+                self.get_next_token()?;
+                let right_expr = self.generate_ast(OperatorCategory::BitwiseAnd)?;
+                Ok(Node::And(Box::new(left_expr), Box::new(right_expr)))
+            }
+            Token::Bar => {
+                self.get_next_token()?;
+                let right_expr = self.generate_ast(OperatorCategory::BitwiseOr)?;
+                Ok(Node::Or(Box::new(left_expr), Box::new(right_expr)))
+            }
+            Token::CirclePlus => {
+                self.get_next_token()?;
+                let right_expr = self.generate_ast(OperatorCategory::BitwiseXor)?;
+                Ok(Node::Xor(Box::new(left_expr), Box::new(right_expr)))
+            }
+            Token::LeftShift => {
+                self.get_next_token()?;
+                let right_expr = self.generate_ast(OperatorCategory::Shift)?;
+                Ok(Node::LeftShift(Box::new(left_expr), Box::new(right_expr)))
+            }
+            Token::RightShift => {
+                self.get_next_token()?;
+                let right_expr = self.generate_ast(OperatorCategory::Shift)?;
+                Ok(Node::RightShift(Box::new(left_expr), Box::new(right_expr)))
+            }
+            Token::UnsignedRightShift => {
+                self.get_next_token()?;
+                let right_expr = self.generate_ast(OperatorCategory::Shift)?;
+                Ok(Node::UnsignedRightShift(
+                    Box::new(left_expr),
+                    Box::new(right_expr),
+                ))
+            }
+            Token::Add => {
+                self.get_next_token()?;
+                let right_expr = self.generate_ast(OperatorCategory::Additive)?;
+                Ok(Node::Add(Box::new(left_expr), Box::new(right_expr)))
+            }
+            Token::Subtract => {
+                self.get_next_token()?;
+                let right_expr = self.generate_ast(OperatorCategory::Additive)?;
+                Ok(Node::Subtract(Box::new(left_expr), Box::new(right_expr)))
+            }
+            Token::Multiply => {
+                self.get_next_token()?;
+                let right_expr = self.generate_ast(OperatorCategory::Multiplicative)?;
+                Ok(Node::Multiply(Box::new(left_expr), Box::new(right_expr)))
+            }
+            Token::Divide => {
+                self.get_next_token()?;
+                let right_expr = self.generate_ast(OperatorCategory::Multiplicative)?;
+                Ok(Node::Divide(Box::new(left_expr), Box::new(right_expr)))
+            }
+            Token::Caret => {
+                self.get_next_token()?;
+                // Right-associative: the exponent is itself a power, optionally negated.
+                let right_expr = self.generate_ast(OperatorCategory::Negative)?;
+                Ok(Node::Pow(Box::new(left_expr), Box::new(right_expr)))
+            }
+            Token::ExclamationMark => {
+                self.get_next_token()?;
+                self.implicit_multiply(Node::Factorial(Box::new(left_expr)))
+            }
+            Token::Superscript(script) => {
+                self.get_next_token()?;
+                Ok(Node::Pow(
+                    Box::new(left_expr),
+                    Box::new(Node::Number(script)),
+                ))
+            }
+            Token::Modulo => {
+                self.get_next_token()?;
+                let right_expr = self.generate_ast(OperatorCategory::Multiplicative)?;
+                Ok(Node::Modulo(Box::new(left_expr), Box::new(right_expr)))
+            }
+            Token::Equal => {
+                self.get_next_token()?;
+                let right_expr = self.generate_ast(OperatorCategory::Comparison)?;
+                Ok(Node::Equal(Box::new(left_expr), Box::new(right_expr)))
+            }
+            Token::NotEqual => {
+                self.get_next_token()?;
+                let right_expr = self.generate_ast(OperatorCategory::Comparison)?;
+                Ok(Node::NotEqual(Box::new(left_expr), Box::new(right_expr)))
+            }
+            Token::Less => {
+                self.get_next_token()?;
+                let right_expr = self.generate_ast(OperatorCategory::Comparison)?;
+                Ok(Node::Less(Box::new(left_expr), Box::new(right_expr)))
+            }
+            Token::LessEqual => {
+                self.get_next_token()?;
+                let right_expr = self.generate_ast(OperatorCategory::Comparison)?;
+                Ok(Node::LessEqual(Box::new(left_expr), Box::new(right_expr)))
+            }
+            Token::Greater => {
+                self.get_next_token()?;
+                let right_expr = self.generate_ast(OperatorCategory::Comparison)?;
+                Ok(Node::Greater(Box::new(left_expr), Box::new(right_expr)))
+            }
+            Token::GreaterEqual => {
+                self.get_next_token()?;
+                let right_expr = self.generate_ast(OperatorCategory::Comparison)?;
+                Ok(Node::GreaterEqual(
+                    Box::new(left_expr),
+                    Box::new(right_expr),
+                ))
+            }
+            Token::DoubleAmpersand => {
+                self.get_next_token()?;
+                let right_expr = self.generate_ast(OperatorCategory::LogicalAnd)?;
+                Ok(Node::LogicalAnd(Box::new(left_expr), Box::new(right_expr)))
+            }
+            Token::DoubleBar => {
+                self.get_next_token()?;
+                let right_expr = self.generate_ast(OperatorCategory::LogicalOr)?;
+                Ok(Node::LogicalOr(Box::new(left_expr), Box::new(right_expr)))
+            }
+            Token::QuestionMark => {
+                self.get_next_token()?;
+                let then_expr = self.generate_ast(OperatorCategory::DefaultZero)?;
+                self.check_paren(Token::Colon)?;
+                // Right-associative: the else branch can itself be a conditional.
+                let else_expr = self.generate_ast(OperatorCategory::DefaultZero)?;
+                Ok(Node::Conditional(
+                    Box::new(left_expr),
+                    Box::new(then_expr),
+                    Box::new(else_expr),
+                ))
+            }
+            _ => Err(self.unexpected_token(&["an operator"])),
+        }
+    }
+}
+
+fn wrong_arity<T: Integer>(
+    function: &Token<T>,
+    function_span: Range<usize>,
+    n: usize,
+) -> ParseError {
+    ParseError::new(
+        ParseErrorKind::WrongArity,
+        function_span,
+        format!("The {} function takes {} arguments", function, n),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::eval_integer::ast::Node::*;
+
+    type Parser<'a> = super::Parser<'a, i64>;
+    type Node = super::Node<i64>;
+
+    #[test]
+    fn test_and() {
+        let mut parser = Parser::new("1&2").unwrap();
+        let expected = Node::And(Box::new(Number(1)), Box::new(Number(2)));
+        assert_eq!(parser.parse().unwrap(), expected);
+    }
+    #[test]
+    fn test_or() {
+        let mut parser = Parser::new("1|2").unwrap();
+        let expected = Node::Or(Box::new(Number(1)), Box::new(Number(2)));
+        assert_eq!(parser.parse().unwrap(), expected);
+    }
+    #[test]
+    fn test_left_shift() {
+        let mut parser = Parser::new("1<<2").unwrap();
+        let expected = Node::LeftShift(Box::new(Number(1)), Box::new(Number(2)));
+        assert_eq!(parser.parse().unwrap(), expected);
+    }
+    #[test]
+    fn test_right_shift() {
+        let mut parser = Parser::new("1>>2").unwrap();
+        let expected = Node::RightShift(Box::new(Number(1)), Box::new(Number(2)));
+        assert_eq!(parser.parse().unwrap(), expected);
+    }
+    #[test]
+    fn test_unsigned_right_shift() {
+        let mut parser = Parser::new("1>>>2").unwrap();
+        let expected = Node::UnsignedRightShift(Box::new(Number(1)), Box::new(Number(2)));
+        assert_eq!(parser.parse().unwrap(), expected);
+    }
+    #[test]
+    fn test_xor() {
+        let mut parser = Parser::new("1|2⊕3&4").unwrap();
+        let expected = Node::Or(
+            Box::new(Number(1)),
+            Box::new(Node::Xor(
+                Box::new(Number(2)),
+                Box::new(Node::And(Box::new(Number(3)), Box::new(Number(4)))),
+            )),
+        );
+        assert_eq!(parser.parse().unwrap(), expected);
+    }
+    #[test]
+    fn test_not() {
+        let mut parser = Parser::new("~1&2").unwrap();
+        let expected = Node::And(
+            Box::new(Node::Not(Box::new(Number(1)))),
+            Box::new(Number(2)),
+        );
+        assert_eq!(parser.parse().unwrap(), expected);
+    }
+    #[test]
+    fn test_rotl_function() {
+        let mut parser = Parser::new("rotl(1,2)").unwrap();
+        let expected = Node::Rotl(Box::new(Number(1)), Box::new(Number(2)));
+        assert_eq!(parser.parse().unwrap(), expected);
+    }
+    #[test]
+    fn test_negative() {
+        let mut parser = Parser::new("-1").unwrap();
+        let expected = Negative(Box::new(Number(1)));
+        assert_eq!(parser.parse().unwrap(), expected);
+    }
+    #[test]
+    fn test_min_literal() {
+        let parse = |formula| super::Parser::<i8>::new(formula)?.parse();
+        assert_eq!(parse("-128").unwrap(), super::Node::Number(i8::MIN));
+        assert_eq!(
+            parse("2*-128x").unwrap(),
+            super::Node::Multiply(
+                Box::new(Number(2)),
+                Box::new(Multiply(
+                    Box::new(Number(i8::MIN)),
+                    Box::new(Variable("x".to_string()))
+                ))
+            )
+        );
+        for (formula, span) in [
+            ("128", 0..3),
+            ("1-128", 2..5),
+            ("-(128)", 2..5),
+            ("-128^2", 1..4),
+        ] {
+            let error = parse(formula).unwrap_err();
+            assert_eq!(error.kind(), ParseErrorKind::InvalidNumber);
+            assert_eq!(error.span(), span);
+        }
+        let mut parser = Parser::new("-9223372036854775808").unwrap();
+        assert_eq!(parser.parse().unwrap(), Number(i64::MIN));
+    }
+    #[test]
+    fn test_add() {
+        let mut parser = Parser::new("1+2").unwrap();
+        let expected = Add(Box::new(Number(1)), Box::new(Number(2)));
+        assert_eq!(parser.parse().unwrap(), expected);
+    }
+    #[test]
+    fn test_subtract() {
+        let mut parser = Parser::new("1-2").unwrap();
+        let expected = Subtract(Box::new(Number(1)), Box::new(Number(2)));
+        assert_eq!(parser.parse().unwrap(), expected);
+    }
+    #[test]
+    fn test_multiply() {
+        let mut parser = Parser::new("1*2").unwrap();
+        let expected = Multiply(Box::new(Number(1)), Box::new(Number(2)));
+        assert_eq!(parser.parse().unwrap(), expected);
+    }
+    #[test]
+    fn test_divide() {
+        let mut parser = Parser::new("1/2").unwrap();
+        let expected = Divide(Box::new(Number(1)), Box::new(Number(2)));
+        assert_eq!(parser.parse().unwrap(), expected);
+    }
+    #[test]
+    fn test_modulo() {
+        let mut parser = Parser::new("1%2").unwrap();
+        let expected = Modulo(Box::new(Number(1)), Box::new(Number(2)));
+        assert_eq!(parser.parse().unwrap(), expected);
+    }
+    #[test]
+    fn test_caret() {
+        let mut parser = Parser::new("1^2").unwrap();
+        let expected = Pow(Box::new(Number(1)), Box::new(Number(2)));
+        assert_eq!(parser.parse().unwrap(), expected);
+    }
+    #[test]
+    fn test_caret_right_associative() {
+        let mut parser = Parser::new("2^3^2").unwrap();
+        let expected = Pow(
+            Box::new(Number(2)),
+            Box::new(Pow(Box::new(Number(3)), Box::new(Number(2)))),
+        );
+        assert_eq!(parser.parse().unwrap(), expected);
+        let mut parser = Parser::new("2^3²").unwrap();
+        assert_eq!(parser.parse().unwrap(), expected);
+    }
+    #[test]
+    fn test_negative_power() {
+        let mut parser = Parser::new("-2^2").unwrap();
+        let expected = Negative(Box::new(Pow(Box::new(Number(2)), Box::new(Number(2)))));
+        assert_eq!(parser.parse().unwrap(), expected);
+        let mut parser = Parser::new("-2²").unwrap();
+        assert_eq!(parser.parse().unwrap(), expected);
+        let mut parser = Parser::new("2^-2").unwrap();
+        let expected = Pow(Box::new(Number(2)), Box::new(Negative(Box::new(Number(2)))));
+        assert_eq!(parser.parse().unwrap(), expected);
+        let mut parser = Parser::new("-2*3").unwrap();
+        let expected = Multiply(Box::new(Negative(Box::new(Number(2)))), Box::new(Number(3)));
+        assert_eq!(parser.parse().unwrap(), expected);
+    }
+    #[test]
+    fn test_exclamation_mark() {
+        let mut parser = Parser::new("1!").unwrap();
+        let expected = Factorial(Box::new(Number(1)));
+        assert_eq!(parser.parse().unwrap(), expected);
+    }
+    #[test]
+    fn test_modulo_function() {
+        let mut parser = Parser::new("mod(3,2)").unwrap();
+        let expected = Modulo(Box::new(Number(3)), Box::new(Number(2)));
+        assert_eq!(parser.parse().unwrap(), expected);
+    }
+    #[test]
+    fn test_pow_function() {
+        let mut parser = Parser::new("pow(3,2)").unwrap();
+        let expected = Pow(Box::new(Number(3)), Box::new(Number(2)));
+        assert_eq!(parser.parse().unwrap(), expected);
+    }
+    #[test]
+    fn test_log_function() {
+        let mut parser = Parser::new("log(3,2)").unwrap();
+        let expected = Log(Box::new(Number(3)), Box::new(Number(2)));
+        assert_eq!(parser.parse().unwrap(), expected);
+    }
+    #[test]
+    fn test_abs_function() {
+        let mut parser = Parser::new("abs(5)").unwrap();
+        let expected = Abs(Box::new(Number(5)));
+        assert_eq!(parser.parse().unwrap(), expected);
+    }
+    #[test]
+    fn test_floor() {
+        let mut parser = Parser::new("⌊7/2⌋").unwrap();
+        let expected = Floor(Box::new(Divide(Box::new(Number(7)), Box::new(Number(2)))));
+        assert_eq!(parser.parse().unwrap(), expected);
+    }
+    #[test]
+    fn test_implicit_mul_ceil() {
+        let mut parser = Parser::new("2⌈5⌉").unwrap();
+        let expected = Multiply(Box::new(Number(2)), Box::new(Ceil(Box::new(Number(5)))));
+        assert_eq!(parser.parse().unwrap(), expected);
+    }
+    #[test]
+    fn test_round_function() {
+        let mut parser = Parser::new("round(5)").unwrap();
+        let expected = Round(Box::new(Number(5)));
+        assert_eq!(parser.parse().unwrap(), expected);
+    }
+    #[test]
+    fn test_sqrt_function() {
+        let mut parser = Parser::new("sqrt(55)").unwrap();
+        let expected = Sqrt(Box::new(Number(55)));
+        assert_eq!(parser.parse().unwrap(), expected);
+    }
+    #[test]
+    fn test_exp_function() {
+        let mut parser = Parser::new("exp(5)").unwrap();
+        let expected = Exp(Box::new(Number(5)));
+        assert_eq!(parser.parse().unwrap(), expected);
+    }
+    #[test]
+    fn test_exp2_function() {
+        let mut parser = Parser::new("exp2(5)").unwrap();
+        let expected = Exp2(Box::new(Number(5)));
+        assert_eq!(parser.parse().unwrap(), expected);
+    }
+    #[test]
+    fn test_ln_function() {
+        let mut parser = Parser::new("ln(5)").unwrap();
+        let expected = Ln(Box::new(Number(5)));
+        assert_eq!(parser.parse().unwrap(), expected);
+    }
+    #[test]
+    fn test_sign_function() {
+        let mut parser = Parser::new("sign(5)").unwrap();
+        let expected = Sign(Box::new(Number(5)));
+        assert_eq!(parser.parse().unwrap(), expected);
+    }
+    #[test]
+    fn test_min_function() {
+        let mut parser = Parser::new("min(3)").unwrap();
+        let expected = Min(Arc::new(vec![Number(3)]));
+        assert_eq!(parser.parse().unwrap(), expected);
+    }
+    #[test]
+    fn test_min_function2() {
+        let mut parser = Parser::new("min(2,3,5)").unwrap();
+        let expected = Min(Arc::new(vec![Number(2), Number(3), Number(5)]));
+        assert_eq!(parser.parse().unwrap(), expected);
+    }
+    #[test]
+    fn test_max_function() {
+        let mut parser = Parser::new("max(3)").unwrap();
+        let expected = Max(Arc::new(vec![Number(3)]));
+        assert_eq!(parser.parse().unwrap(), expected);
+    }
+    #[test]
+    fn test_max_function2() {
+        let mut parser = Parser::new("max(2,3,5)").unwrap();
+        let expected = Max(Arc::new(vec![Number(2), Number(3), Number(5)]));
+        assert_eq!(parser.parse().unwrap(), expected);
+    }
+    #[test]
+    fn test_avg_function() {
+        let mut parser = Parser::new("avg(3)").unwrap();
+        let expected = Avg(Arc::new(vec![Number(3)]));
+        assert_eq!(parser.parse().unwrap(), expected);
+    }
+    #[test]
+    fn test_avg_function2() {
+        let mut parser = Parser::new("avg(2,3,5)").unwrap();
+        let expected = Avg(Arc::new(vec![Number(2), Number(3), Number(5)]));
+        assert_eq!(parser.parse().unwrap(), expected);
+    }
+    #[test]
+    fn test_gcd_function() {
+        let mut parser = Parser::new("gcd(3)").unwrap();
+        let expected = Gcd(Arc::new(vec![Number(3)]));
+        assert_eq!(parser.parse().unwrap(), expected);
+    }
+    #[test]
+    fn test_gcd_function2() {
+        let mut parser = Parser::new("gcd(2,3,5)").unwrap();
+        let expected = Gcd(Arc::new(vec![Number(2), Number(3), Number(5)]));
+        assert_eq!(parser.parse().unwrap(), expected);
+    }
+    #[test]
+    fn test_lcm_function() {
+        let mut parser = Parser::new("lcm(3)").unwrap();
+        let expected = Lcm(Arc::new(vec![Number(3)]));
+        assert_eq!(parser.parse().unwrap(), expected);
+    }
+    #[test]
+    fn test_lcm_function2() {
+        let mut parser = Parser::new("lcm(2,3,5)").unwrap();
+        let expected = Lcm(Arc::new(vec![Number(2), Number(3), Number(5)]));
+        assert_eq!(parser.parse().unwrap(), expected);
+    }
+    #[test]
+    fn test_implicit_mul_prts() {
+        let mut parser = Parser::new("(2)(3)").unwrap();
+        let expected = Multiply(Box::new(Number(2)), Box::new(Number(3)));
+        assert_eq!(parser.parse().unwrap(), expected);
+    }
+    #[test]
+    fn test_med_function() {
+        let mut parser = Parser::new("med(3)").unwrap();
+        let expected = Med(Arc::new(vec![Number(3)]));
+        assert_eq!(parser.parse().unwrap(), expected);
+    }
+    #[test]
+    fn test_med_function2() {
+        let mut parser = Parser::new("med(2,3,5)").unwrap();
+        let expected = Med(Arc::new(vec![Number(2), Number(3), Number(5)]));
+        assert_eq!(parser.parse().unwrap(), expected);
+    }
+    #[test]
+    fn test_variable() {
+        let mut parser = Parser::new("x").unwrap();
+        let expected = Variable("x".to_string());
+        assert_eq!(parser.parse().unwrap(), expected);
+    }
+    #[test]
+    fn test_implicit_mul_variable() {
+        let mut parser = Parser::new("2x").unwrap();
+        let expected = Multiply(Box::new(Number(2)), Box::new(Variable("x".to_string())));
+        assert_eq!(parser.parse().unwrap(), expected);
+    }
+    #[test]
+    fn test_conditional() {
+        let mut parser = Parser::new("1+1<3&&!0?4:5").unwrap();
+        let condition = LogicalAnd(
+            Box::new(Less(
+                Box::new(Add(Box::new(Number(1)), Box::new(Number(1)))),
+                Box::new(Number(3)),
+            )),
+            Box::new(LogicalNot(Box::new(Number(0)))),
+        );
+        let expected = Conditional(
+            Box::new(condition),
+            Box::new(Number(4)),
+            Box::new(Number(5)),
+        );
+        assert_eq!(parser.parse().unwrap(), expected);
+        let mut parser = Parser::new("1?2:3?4:5").unwrap();
+        let expected = Conditional(
+            Box::new(Number(1)),
+            Box::new(Number(2)),
+            Box::new(Conditional(
+                Box::new(Number(3)),
+                Box::new(Number(4)),
+                Box::new(Number(5)),
+            )),
+        );
+        assert_eq!(parser.parse().unwrap(), expected);
+        let mut parser = Parser::new("if(0||1,2,3)").unwrap();
+        let expected = Conditional(
+            Box::new(LogicalOr(Box::new(Number(0)), Box::new(Number(1)))),
+            Box::new(Number(2)),
+            Box::new(Number(3)),
+        );
+        assert_eq!(parser.parse().unwrap(), expected);
+        let error = Parser::new("1?2").unwrap().parse().unwrap_err();
+        assert_eq!(error.kind(), ParseErrorKind::UnexpectedToken);
+        assert_eq!(error.expected(), [":"]);
+    }
+    #[test]
+    fn test_whitespace() {
+        let error = Parser::new("1 0").unwrap().parse().unwrap_err();
+        assert_eq!(error.kind(), ParseErrorKind::UnexpectedToken);
+        assert_eq!(error.span(), 2..3);
+        let error = Parser::new("1 < < 2").unwrap().parse().unwrap_err();
+        assert_eq!(error.kind(), ParseErrorKind::UnexpectedToken);
+        assert_eq!(error.span(), 4..5);
+        assert_eq!(
+            Parser::new("abs (x)").unwrap().parse().unwrap(),
+            Parser::new("abs(x)").unwrap().parse().unwrap()
+        );
+    }
+}
@@ -0,0 +1,185 @@
+use super::ast::{eval, Node};
+use super::Integer;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+fn zero<T: Integer>() -> Node<T> {
+    Node::Number(T::ZERO)
+}
+
+fn one<T: Integer>() -> Node<T> {
+    Node::Number(T::ONE)
+}
+
+fn is_literal<T: Integer>(expr: &Node<T>) -> bool {
+    matches!(expr, Node::Number(_))
+}
+
+/// Evaluate a formula without variables into a literal, or `None` if its evaluation fails, in
+/// which case the error is left to be raised when evaluating the whole formula.
+fn fold<T: Integer>(expr: &Node<T>) -> Option<Node<T>> {
+    eval(expr, T::ZERO, &HashMap::<String, T>::new())
+        .ok()
+        .map(Node::Number)
+}
+
+/// Whether a literal condition holds.
+fn holds<T: Integer>(condition: &Node<T>) -> bool {
+    let test = Node::Conditional(
+        Box::new(condition.clone()),
+        Box::new(one()),
+        Box::new(zero()),
+    );
+    fold(&test) == Some(one())
+}
+
+/// Rebuild a node with each of its operands replaced.
+fn map<T: Integer>(expr: &Node<T>, f: &mut dyn FnMut(&Node<T>) -> Node<T>) -> Node<T> {
+    use self::Node::*;
+    match expr {
+        And(expr1, expr2) => And(Box::new(f(expr1)), Box::new(f(expr2))),
+        Or(expr1, expr2) => Or(Box::new(f(expr1)), Box::new(f(expr2))),
+        Xor(expr1, expr2) => Xor(Box::new(f(expr1)), Box::new(f(expr2))),
+        Not(sub_expr) => Not(Box::new(f(sub_expr))),
+        LeftShift(expr1, expr2) => LeftShift(Box::new(f(expr1)), Box::new(f(expr2))),
+        RightShift(expr1, expr2) => RightShift(Box::new(f(expr1)), Box::new(f(expr2))),
+        UnsignedRightShift(expr1, expr2) => {
+            UnsignedRightShift(Box::new(f(expr1)), Box::new(f(expr2)))
+        }
+        Rotl(expr1, expr2) => Rotl(Box::new(f(expr1)), Box::new(f(expr2))),
+        Rotr(expr1, expr2) => Rotr(Box::new(f(expr1)), Box::new(f(expr2))),
+        Popcnt(sub_expr) => Popcnt(Box::new(f(sub_expr))),
+        Clz(sub_expr) => Clz(Box::new(f(sub_expr))),
+        Ctz(sub_expr) => Ctz(Box::new(f(sub_expr))),
+        Add(expr1, expr2) => Add(Box::new(f(expr1)), Box::new(f(expr2))),
+        Subtract(expr1, expr2) => Subtract(Box::new(f(expr1)), Box::new(f(expr2))),
+        Multiply(expr1, expr2) => Multiply(Box::new(f(expr1)), Box::new(f(expr2))),
+        Divide(expr1, expr2) => Divide(Box::new(f(expr1)), Box::new(f(expr2))),
+        Modulo(expr1, expr2) => Modulo(Box::new(f(expr1)), Box::new(f(expr2))),
+        Pow(expr1, expr2) => Pow(Box::new(f(expr1)), Box::new(f(expr2))),
+        Root(expr1, expr2) => Root(Box::new(f(expr1)), Box::new(f(expr2))),
+        Log(expr1, expr2) => Log(Box::new(f(expr1)), Box::new(f(expr2))),
+        Negative(sub_expr) => Negative(Box::new(f(sub_expr))),
+        Factorial(sub_expr) => Factorial(Box::new(f(sub_expr))),
+        Abs(sub_expr) => Abs(Box::new(f(sub_expr))),
+        Floor(sub_expr) => Floor(Box::new(f(sub_expr))),
+        Ceil(sub_expr) => Ceil(Box::new(f(sub_expr))),
+        Round(sub_expr) => Round(Box::new(f(sub_expr))),
+        Truncate(sub_expr) => Truncate(Box::new(f(sub_expr))),
+        Sqrt(sub_expr) => Sqrt(Box::new(f(sub_expr))),
+        Ln(sub_expr) => Ln(Box::new(f(sub_expr))),
+        Lb(sub_expr) => Lb(Box::new(f(sub_expr))),
+        Exp(sub_expr) => Exp(Box::new(f(sub_expr))),
+        Exp2(sub_expr) => Exp2(Box::new(f(sub_expr))),
+        Sign(sub_expr) => Sign(Box::new(f(sub_expr))),
+        Min(args) => Min(Arc::new(args.iter().map(&mut *f).collect())),
+        Max(args) => Max(Arc::new(args.iter().map(&mut *f).collect())),
+        Avg(args) => Avg(Arc::new(args.iter().map(&mut *f).collect())),
+        Med(args) => Med(Arc::new(args.iter().map(&mut *f).collect())),
+        Gcd(args) => Gcd(Arc::new(args.iter().map(&mut *f).collect())),
+        Lcm(args) => Lcm(Arc::new(args.iter().map(&mut *f).collect())),
+        CustomFunction(function, args) => CustomFunction(
+            function.clone(),
+            Arc::new(args.iter().map(&mut *f).collect()),
+        ),
+        Equal(expr1, expr2) => Equal(Box::new(f(expr1)), Box::new(f(expr2))),
+        NotEqual(expr1, expr2) => NotEqual(Box::new(f(expr1)), Box::new(f(expr2))),
+        Less(expr1, expr2) => Less(Box::new(f(expr1)), Box::new(f(expr2))),
+        LessEqual(expr1, expr2) => LessEqual(Box::new(f(expr1)), Box::new(f(expr2))),
+        Greater(expr1, expr2) => Greater(Box::new(f(expr1)), Box::new(f(expr2))),
+        GreaterEqual(expr1, expr2) => GreaterEqual(Box::new(f(expr1)), Box::new(f(expr2))),
+        LogicalAnd(expr1, expr2) => LogicalAnd(Box::new(f(expr1)), Box::new(f(expr2))),
+        LogicalOr(expr1, expr2) => LogicalOr(Box::new(f(expr1)), Box::new(f(expr2))),
+        LogicalNot(sub_expr) => LogicalNot(Box::new(f(sub_expr))),
+        Conditional(condition, expr1, expr2) => Conditional(
+            Box::new(f(condition)),
+            Box::new(f(expr1)),
+            Box::new(f(expr2)),
+        ),
+        Number(_) | Variable(_) | Ans => expr.clone(),
+    }
+}
+
+/// Simplify a formula: fold its constant parts and remove the operations without effect, such as
+/// `x*1`, `x+0` and `x^1`. The other operations keep their order, as an intermediate result of a
+/// reordered sum or product may overflow where the original formula does not. User-defined
+/// functions are never folded.
+pub fn simplify<T: Integer>(expr: &Node<T>) -> Node<T> {
+    use self::Node::*;
+    let mut literal = true;
+    let expr = map(expr, &mut |operand| {
+        let operand = simplify(operand);
+        literal &= is_literal(&operand);
+        operand
+    });
+    match expr {
+        Number(_) | Variable(_) | Ans | CustomFunction(_, _) => expr,
+        _ if literal => fold(&expr).unwrap_or(expr),
+        Conditional(condition, expr1, expr2) if is_literal(&condition) => {
+            if holds(&condition) {
+                *expr1
+            } else {
+                *expr2
+            }
+        }
+        Add(expr1, expr2) if *expr1 == zero() => *expr2,
+        Add(expr1, expr2) | Subtract(expr1, expr2) if *expr2 == zero() => *expr1,
+        Multiply(expr1, expr2) if *expr1 == one() => *expr2,
+        Multiply(expr1, expr2) | Divide(expr1, expr2) | Pow(expr1, expr2) if *expr2 == one() => {
+            *expr1
+        }
+        expr => expr,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::eval_integer::parser::Parser;
+
+    fn parsed<T: Integer>(formula: &str) -> Node<T> {
+        Parser::new(formula).unwrap().parse().unwrap()
+    }
+
+    #[test]
+    fn test_simplify() {
+        assert_eq!(simplify(&parsed::<i64>("2x+(1-1)+x*3^0")), parsed("2x+x"));
+        assert_eq!(simplify(&parsed::<i64>("x<<(1+1)")), parsed("x<<2"));
+        assert_eq!(
+            simplify(&parsed::<i8>("x*(100+100)")),
+            parsed("x*(100+100)")
+        );
+        assert_eq!(simplify(&parsed::<u8>("0+x-y+0")), parsed("x-y"));
+        assert_eq!(simplify(&parsed::<u8>("--x/1")), parsed("--x"));
+    }
+    #[test]
+    fn test_same_value() {
+        let formulas = ["y-x", "x+y+x", "z*x*y", "x*y*2", "-(-x)"];
+        for formula in formulas {
+            let ast = parsed::<i8>(formula);
+            for (x, y, z) in [
+                (-128, -1, 0),
+                (100, -100, 100),
+                (-1, 0, 127),
+                (127, 1, -128),
+            ] {
+                let context: HashMap<String, i8> = [
+                    ("x".to_string(), x),
+                    ("y".to_string(), y),
+                    ("z".to_string(), z),
+                ]
+                .into_iter()
+                .collect();
+                assert_eq!(
+                    eval(&simplify(&ast), 0, &context),
+                    eval(&ast, 0, &context),
+                    "{} with x={}, y={}, z={}",
+                    formula,
+                    x,
+                    y,
+                    z
+                );
+            }
+        }
+    }
+}
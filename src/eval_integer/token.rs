@@ -0,0 +1,157 @@
+use super::Integer;
+use crate::utils::{Function, OperatorCategory};
+use std::fmt;
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum NativeFunction {
+    Gcd,
+    Lcm,
+    Ln,
+    Lb,
+    Log,
+    Pow,
+    Sqrt,
+    Root,
+    Exp,
+    Exp2,
+    Abs,
+    Floor,
+    Ceil,
+    Round,
+    Truncate,
+    Mod,
+    Sign,
+    Min,
+    Max,
+    Avg,
+    Med,
+    Popcnt,
+    Clz,
+    Ctz,
+    Rotl,
+    Rotr,
+    If,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum Token<T> {
+    Ampersand,
+    Bar,
+    CirclePlus,
+    Tilde,
+    LeftShift,
+    RightShift,
+    UnsignedRightShift,
+    Add,
+    Subtract,
+    Multiply,
+    Divide,
+    Caret,
+    ExclamationMark,
+    Modulo,
+    Equal,
+    NotEqual,
+    Less,
+    LessEqual,
+    Greater,
+    GreaterEqual,
+    DoubleAmpersand,
+    DoubleBar,
+    QuestionMark,
+    Colon,
+    LeftParen,
+    RightParen,
+    LeftFloor,
+    RightFloor,
+    LeftCeiling,
+    RightCeiling,
+    Comma,
+    ExplicitFunction(NativeFunction),
+    CustomFunction(Function<T>),
+    Superscript(T),
+    Num(T),
+    /// A decimal literal one past the highest value, holding the lowest value that it is the
+    /// magnitude of, as `128` in an i8, which is only a number once negated.
+    MinMagnitude(T),
+    Identifier(String),
+    Ans,
+    Eof,
+}
+
+impl<T> Token<T> {
+    pub fn get_oper_prec(&self) -> OperatorCategory {
+        use self::Token::*;
+        match *self {
+            QuestionMark => OperatorCategory::Conditional,
+            DoubleBar => OperatorCategory::LogicalOr,
+            DoubleAmpersand => OperatorCategory::LogicalAnd,
+            Equal | NotEqual | Less | LessEqual | Greater | GreaterEqual => {
+                OperatorCategory::Comparison
+            }
+            Bar => OperatorCategory::BitwiseOr,
+            CirclePlus => OperatorCategory::BitwiseXor,
+            Ampersand => OperatorCategory::BitwiseAnd,
+            LeftShift | RightShift | UnsignedRightShift => OperatorCategory::Shift,
+            Add | Subtract => OperatorCategory::Additive,
+            Multiply | Divide | Modulo => OperatorCategory::Multiplicative,
+            Caret | Superscript(_) => OperatorCategory::Power,
+            ExclamationMark | ExplicitFunction(_) | CustomFunction(_) => {
+                OperatorCategory::Functional
+            }
+            _ => OperatorCategory::DefaultZero,
+        }
+    }
+    pub fn is_closing_bracket(&self) -> bool {
+        use self::Token::*;
+        matches!(self, RightParen | RightFloor | RightCeiling)
+    }
+}
+
+impl<T: Integer> fmt::Display for Token<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        use self::Token::*;
+        match self {
+            Ampersand => write!(f, "&"),
+            Bar => write!(f, "|"),
+            CirclePlus => write!(f, "⊕"),
+            Tilde => write!(f, "~"),
+            LeftShift => write!(f, "<<"),
+            RightShift => write!(f, ">>"),
+            UnsignedRightShift => write!(f, ">>>"),
+            Add => write!(f, "+"),
+            Subtract => write!(f, "-"),
+            Multiply => write!(f, "*"),
+            Divide => write!(f, "/"),
+            Caret => write!(f, "^"),
+            ExclamationMark => write!(f, "!"),
+            Modulo => write!(f, "%"),
+            Equal => write!(f, "=="),
+            NotEqual => write!(f, "!="),
+            Less => write!(f, "<"),
+            LessEqual => write!(f, "<="),
+            Greater => write!(f, ">"),
+            GreaterEqual => write!(f, ">="),
+            DoubleAmpersand => write!(f, "&&"),
+            DoubleBar => write!(f, "||"),
+            QuestionMark => write!(f, "?"),
+            Colon => write!(f, ":"),
+            LeftParen => write!(f, "("),
+            RightParen => write!(f, ")"),
+            LeftFloor => write!(f, "⌊"),
+            RightFloor => write!(f, "⌋"),
+            LeftCeiling => write!(f, "⌈"),
+            RightCeiling => write!(f, "⌉"),
+            Comma => write!(f, ","),
+            ExplicitFunction(function) => {
+                write!(f, "{}", format!("{:?}", function).to_lowercase())
+            }
+            CustomFunction(function) => write!(f, "{}", function.name()),
+            Superscript(value) => write!(f, "^{}", value),
+            Num(value) => write!(f, "{}", value),
+            MinMagnitude(value) => write!(f, "{}", value.to_string().trim_start_matches('-')),
+            Identifier(name) => write!(f, "{}", name),
+            Ans => write!(f, "@"),
+            Eof => write!(f, "end of input"),
+        }
+    }
+}
@@ -0,0 +1,790 @@
+use super::token::{NativeFunction, Token};
+use super::Integer;
+use crate::utils::{
+    deserialize_superscript_number, peek_identifier, unrecognized_token, Cursor, Functions,
+    ParseError,
+};
+
+/// The names read as constants or units rather than variables.
+pub const RESERVED_WORDS: [&str; 0] = [];
+
+pub struct Tokenizer<'a, T> {
+    expr: Cursor<'a>,
+    functions: Option<&'a Functions<T>>,
+}
+
+impl<'a, T: Integer> Tokenizer<'a, T> {
+    pub fn new(new_expr: &'a str) -> Self {
+        Tokenizer {
+            expr: Cursor::new(new_expr),
+            functions: None,
+        }
+    }
+    pub fn with_functions(new_expr: &'a str, functions: &'a Functions<T>) -> Self {
+        Tokenizer {
+            expr: Cursor::new(new_expr),
+            functions: Some(functions),
+        }
+    }
+    /// The byte offset of the next token.
+    pub fn position(&self) -> usize {
+        self.expr.position()
+    }
+    /// Consume the whitespaces before the next token, which only separate tokens.
+    pub fn skip_whitespace(&mut self) {
+        self.expr.skip_whitespace()
+    }
+    /// The error explaining why no token could be read at the given byte offset.
+    pub fn error(&self, start: usize) -> ParseError {
+        unrecognized_token(self.expr.source(), start)
+    }
+    /// The radix of a `0x`, `0o` or `0b` prefix following a `0`.
+    fn radix_prefix(&self) -> Option<u32> {
+        match self.expr.clone().next()? {
+            'x' | 'X' => Some(16),
+            'o' | 'O' => Some(8),
+            'b' | 'B' => Some(2),
+            _ => None,
+        }
+    }
+    /// Read the digits of the given radix, skipping the `_` separators.
+    fn digits(&mut self, radix: u32) -> String {
+        let mut digits = String::new();
+        while let Some(next_char) = self.expr.peek() {
+            if next_char.is_digit(radix) {
+                digits.push(next_char);
+            } else if next_char != '_' {
+                break;
+            }
+            self.expr.next();
+        }
+        digits
+    }
+    fn native_token(&mut self) -> Option<Token<T>> {
+        let current_char = self.expr.next();
+
+        match current_char {
+            Some('@') => Some(Token::Ans),
+            Some('+') => Some(Token::Add),
+            Some('-') => Some(Token::Subtract),
+            Some('*') => Some(Token::Multiply),
+            Some('/') => Some(Token::Divide),
+            Some('^') => Some(Token::Caret),
+            Some('(') => Some(Token::LeftParen),
+            Some(')') => Some(Token::RightParen),
+            Some('!') => {
+                if let Some('=') = self.expr.peek() {
+                    self.expr.next();
+                    Some(Token::NotEqual)
+                } else {
+                    Some(Token::ExclamationMark)
+                }
+            }
+            Some('=') => {
+                if let Some('=') = self.expr.peek() {
+                    self.expr.next();
+                    Some(Token::Equal)
+                } else {
+                    None
+                }
+            }
+            Some('&') => {
+                if let Some('&') = self.expr.peek() {
+                    self.expr.next();
+                    Some(Token::DoubleAmpersand)
+                } else {
+                    Some(Token::Ampersand)
+                }
+            }
+            Some('|') => {
+                if let Some('|') = self.expr.peek() {
+                    self.expr.next();
+                    Some(Token::DoubleBar)
+                } else {
+                    Some(Token::Bar)
+                }
+            }
+            Some('⊕') => Some(Token::CirclePlus),
+            Some('~') => Some(Token::Tilde),
+            Some(',') => Some(Token::Comma),
+            Some('%') => Some(Token::Modulo),
+            Some('?') => Some(Token::QuestionMark),
+            Some(':') => Some(Token::Colon),
+            Some('⌊') => Some(Token::LeftFloor),
+            Some('⌋') => Some(Token::RightFloor),
+            Some('⌈') => Some(Token::LeftCeiling),
+            Some('⌉') => Some(Token::RightCeiling),
+            Some('<') => {
+                if self.expr.clone().take(1).collect::<String>() == "<" {
+                    self.expr.by_ref().take(1).for_each(drop);
+                    Some(Token::LeftShift)
+                } else if self.expr.clone().take(1).collect::<String>() == "=" {
+                    self.expr.by_ref().take(1).for_each(drop);
+                    Some(Token::LessEqual)
+                } else {
+                    Some(Token::Less)
+                }
+            }
+            Some('>') => {
+                if self.expr.clone().take(2).collect::<String>() == ">>" {
+                    self.expr.by_ref().take(2).for_each(drop);
+                    Some(Token::UnsignedRightShift)
+                } else if self.expr.clone().take(1).collect::<String>() == ">" {
+                    self.expr.by_ref().take(1).for_each(drop);
+                    Some(Token::RightShift)
+                } else if self.expr.clone().take(1).collect::<String>() == "=" {
+                    self.expr.by_ref().take(1).for_each(drop);
+                    Some(Token::GreaterEqual)
+                } else {
+                    Some(Token::Greater)
+                }
+            }
+            Some('⁰') => Some(Token::Superscript(T::parse_radix(
+                &deserialize_superscript_number(&current_char?, &mut self.expr),
+                10,
+            )?)),
+            Some('¹') => Some(Token::Superscript(T::parse_radix(
+                &deserialize_superscript_number(&current_char?, &mut self.expr),
+                10,
+            )?)),
+            Some('²') => Some(Token::Superscript(T::parse_radix(
+                &deserialize_superscript_number(&current_char?, &mut self.expr),
+                10,
+            )?)),
+            Some('³') => Some(Token::Superscript(T::parse_radix(
+                &deserialize_superscript_number(&current_char?, &mut self.expr),
+                10,
+            )?)),
+            Some('⁴') => Some(Token::Superscript(T::parse_radix(
+                &deserialize_superscript_number(&current_char?, &mut self.expr),
+                10,
+            )?)),
+            Some('⁵') => Some(Token::Superscript(T::parse_radix(
+                &deserialize_superscript_number(&current_char?, &mut self.expr),
+                10,
+            )?)),
+            Some('⁶') => Some(Token::Superscript(T::parse_radix(
+                &deserialize_superscript_number(&current_char?, &mut self.expr),
+                10,
+            )?)),
+            Some('⁷') => Some(Token::Superscript(T::parse_radix(
+                &deserialize_superscript_number(&current_char?, &mut self.expr),
+                10,
+            )?)),
+            Some('⁸') => Some(Token::Superscript(T::parse_radix(
+                &deserialize_superscript_number(&current_char?, &mut self.expr),
+                10,
+            )?)),
+            Some('⁹') => Some(Token::Superscript(T::parse_radix(
+                &deserialize_superscript_number(&current_char?, &mut self.expr),
+                10,
+            )?)),
+            Some('0') if self.radix_prefix().is_some() => {
+                let radix = self.radix_prefix()?;
+                self.expr.next();
+                let digits = self.digits(radix);
+                // A prefix without digits, or digits running into letters or other digits as in
+                // `0b102`, is an invalid number rather than a product.
+                match self.expr.peek() {
+                    _ if digits.is_empty() => return None,
+                    Some(next_char) if next_char.is_ascii_alphanumeric() => return None,
+                    _ => {}
+                }
+                // Prefixed literals are bit patterns, so `0xFFFF_FFFF_FFFF_FFFF` is -1 in an i64.
+                Some(Token::Num(T::parse_bits(&digits, radix)?))
+            }
+            Some('0'..='9') => {
+                let mut number = current_char?.to_string();
+                number.push_str(&self.digits(10));
+                if let Some('.') = self.expr.peek() {
+                    return None;
+                }
+                match T::parse_radix(&number, 10) {
+                    Some(value) => Some(Token::Num(value)),
+                    // Left to the parser, which accepts it right after a unary minus.
+                    None => Some(Token::MinMagnitude(T::parse_radix(
+                        &format!("-{}", number),
+                        10,
+                    )?)),
+                }
+            }
+            Some('a') => match self.expr.clone().take(3).collect::<String>().as_str() {
+                "bs(" => {
+                    self.expr.by_ref().take(2).for_each(drop);
+                    Some(Token::ExplicitFunction(NativeFunction::Abs))
+                }
+                "vg(" => {
+                    self.expr.by_ref().take(2).for_each(drop);
+                    Some(Token::ExplicitFunction(NativeFunction::Avg))
+                }
+                _ => None,
+            },
+            Some('c') => match self.expr.clone().take(4).collect::<String>().as_str() {
+                "eil(" => {
+                    self.expr.by_ref().take(3).for_each(drop);
+                    Some(Token::ExplicitFunction(NativeFunction::Ceil))
+                }
+                _ => match self.expr.clone().take(3).collect::<String>().as_str() {
+                    "lz(" => {
+                        self.expr.by_ref().take(2).for_each(drop);
+                        Some(Token::ExplicitFunction(NativeFunction::Clz))
+                    }
+                    "tz(" => {
+                        self.expr.by_ref().take(2).for_each(drop);
+                        Some(Token::ExplicitFunction(NativeFunction::Ctz))
+                    }
+                    _ => None,
+                },
+            },
+            Some('e') => {
+                if self.expr.clone().take(4).collect::<String>() == "xp2(" {
+                    self.expr.by_ref().take(3).for_each(drop);
+                    Some(Token::ExplicitFunction(NativeFunction::Exp2))
+                } else if self.expr.clone().take(3).collect::<String>() == "xp(" {
+                    self.expr.by_ref().take(2).for_each(drop);
+                    Some(Token::ExplicitFunction(NativeFunction::Exp))
+                } else {
+                    None
+                }
+            }
+            Some('f') => {
+                if self.expr.clone().take(5).collect::<String>() == "loor(" {
+                    self.expr.by_ref().take(4).for_each(drop);
+                    Some(Token::ExplicitFunction(NativeFunction::Floor))
+                } else {
+                    None
+                }
+            }
+            Some('g') => {
+                if self.expr.clone().take(3).collect::<String>() == "cd(" {
+                    self.expr.by_ref().take(2).for_each(drop);
+                    Some(Token::ExplicitFunction(NativeFunction::Gcd))
+                } else {
+                    None
+                }
+            }
+            Some('i') => {
+                if self.expr.clone().take(2).collect::<String>() == "f(" {
+                    self.expr.by_ref().take(1).for_each(drop);
+                    Some(Token::ExplicitFunction(NativeFunction::If))
+                } else {
+                    None
+                }
+            }
+            Some('l') => match self.expr.clone().take(3).collect::<String>().as_str() {
+                "og(" => {
+                    self.expr.by_ref().take(2).for_each(drop);
+                    Some(Token::ExplicitFunction(NativeFunction::Log))
+                }
+                "cm(" => {
+                    self.expr.by_ref().take(2).for_each(drop);
+                    Some(Token::ExplicitFunction(NativeFunction::Lcm))
+                }
+                _ => match self.expr.clone().take(2).collect::<String>().as_str() {
+                    "n(" => {
+                        self.expr.by_ref().take(1).for_each(drop);
+                        Some(Token::ExplicitFunction(NativeFunction::Ln))
+                    }
+                    "b(" => {
+                        self.expr.by_ref().take(1).for_each(drop);
+                        Some(Token::ExplicitFunction(NativeFunction::Lb))
+                    }
+                    _ => None,
+                },
+            },
+            Some('m') => match self.expr.clone().take(6).collect::<String>().as_str() {
+                "edian(" => {
+                    self.expr.by_ref().take(5).for_each(drop);
+                    Some(Token::ExplicitFunction(NativeFunction::Med))
+                }
+                _ => match self.expr.clone().take(3).collect::<String>().as_str() {
+                    "in(" => {
+                        self.expr.by_ref().take(2).for_each(drop);
+                        Some(Token::ExplicitFunction(NativeFunction::Min))
+                    }
+                    "ax(" => {
+                        self.expr.by_ref().take(2).for_each(drop);
+                        Some(Token::ExplicitFunction(NativeFunction::Max))
+                    }
+                    "od(" => {
+                        self.expr.by_ref().take(2).for_each(drop);
+                        Some(Token::ExplicitFunction(NativeFunction::Mod))
+                    }
+                    "ed(" => {
+                        self.expr.by_ref().take(2).for_each(drop);
+                        Some(Token::ExplicitFunction(NativeFunction::Med))
+                    }
+                    _ => None,
+                },
+            },
+            Some('p') => {
+                if self.expr.clone().take(6).collect::<String>() == "opcnt(" {
+                    self.expr.by_ref().take(5).for_each(drop);
+                    Some(Token::ExplicitFunction(NativeFunction::Popcnt))
+                } else if self.expr.clone().take(3).collect::<String>() == "ow(" {
+                    self.expr.by_ref().take(2).for_each(drop);
+                    Some(Token::ExplicitFunction(NativeFunction::Pow))
+                } else {
+                    None
+                }
+            }
+            Some('r') => match self.expr.clone().take(5).collect::<String>().as_str() {
+                "ound(" => {
+                    self.expr.by_ref().take(4).for_each(drop);
+                    Some(Token::ExplicitFunction(NativeFunction::Round))
+                }
+                _ => match self.expr.clone().take(4).collect::<String>().as_str() {
+                    "oot(" => {
+                        self.expr.by_ref().take(3).for_each(drop);
+                        Some(Token::ExplicitFunction(NativeFunction::Root))
+                    }
+                    "otl(" => {
+                        self.expr.by_ref().take(3).for_each(drop);
+                        Some(Token::ExplicitFunction(NativeFunction::Rotl))
+                    }
+                    "otr(" => {
+                        self.expr.by_ref().take(3).for_each(drop);
+                        Some(Token::ExplicitFunction(NativeFunction::Rotr))
+                    }
+                    _ => None,
+                },
+            },
+            Some('s') => match self.expr.clone().take(6).collect::<String>().as_str() {
+                "ignum(" => {
+                    self.expr.by_ref().take(5).for_each(drop);
+                    Some(Token::ExplicitFunction(NativeFunction::Sign))
+                }
+                _ => match self.expr.clone().take(4).collect::<String>().as_str() {
+                    "qrt(" => {
+                        self.expr.by_ref().take(3).for_each(drop);
+                        Some(Token::ExplicitFunction(NativeFunction::Sqrt))
+                    }
+                    "ign(" => {
+                        self.expr.by_ref().take(3).for_each(drop);
+                        Some(Token::ExplicitFunction(NativeFunction::Sign))
+                    }
+                    _ => match self.expr.clone().take(3).collect::<String>().as_str() {
+                        "gn(" => {
+                            self.expr.by_ref().take(2).for_each(drop);
+                            Some(Token::ExplicitFunction(NativeFunction::Sign))
+                        }
+                        _ => None,
+                    },
+                },
+            },
+            Some('t') => {
+                if self.expr.clone().take(8).collect::<String>() == "runcate(" {
+                    self.expr.by_ref().take(7).for_each(drop);
+                    Some(Token::ExplicitFunction(NativeFunction::Truncate))
+                } else if self.expr.clone().take(5).collect::<String>() == "runc(" {
+                    self.expr.by_ref().take(4).for_each(drop);
+                    Some(Token::ExplicitFunction(NativeFunction::Truncate))
+                } else {
+                    None
+                }
+            }
+            None => Some(Token::Eof),
+            Some(_) => None,
+        }
+    }
+}
+
+impl<'a, T: Integer> Iterator for Tokenizer<'a, T> {
+    type Item = Token<T>;
+
+    fn next(&mut self) -> Option<Token<T>> {
+        self.expr.skip_whitespace();
+        let (identifier, next_char) = peek_identifier(&self.expr);
+        if !identifier.is_empty() {
+            if next_char == Some('(') {
+                if let Some(function) = self
+                    .functions
+                    .and_then(|functions| functions.get(&identifier))
+                {
+                    let function = function.clone();
+                    self.expr.by_ref().take(identifier.len()).for_each(drop);
+                    return Some(Token::CustomFunction(function));
+                }
+            } else if !RESERVED_WORDS.contains(&identifier.as_str()) {
+                self.expr.by_ref().take(identifier.len()).for_each(drop);
+                return Some(Token::Identifier(identifier));
+            }
+        }
+        if next_char == Some('(') && !identifier.is_empty() {
+            // The native functions are matched along with their `(`, which whitespace may set
+            // apart from the name.
+            let call = format!("{}(", identifier);
+            let mut native = Tokenizer::<T>::new(&call);
+            let token = native.native_token()?;
+            if native.position() != identifier.len() {
+                // The word before the arguments is not a native function either.
+                return None;
+            }
+            self.expr.by_ref().take(identifier.len()).for_each(drop);
+            return Some(token);
+        }
+        self.native_token()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    type Tokenizer<'a> = super::Tokenizer<'a, i64>;
+
+    #[test]
+    fn test_integer_number() {
+        let mut tokenizer = Tokenizer::new("34");
+        assert_eq!(tokenizer.next().unwrap(), Token::Num(34))
+    }
+    #[test]
+    fn test_radix_number() {
+        let mut tokenizer = Tokenizer::new("0xFF_FF&0b1010|0o17");
+        assert_eq!(tokenizer.next().unwrap(), Token::Num(0xFFFF));
+        assert_eq!(tokenizer.next().unwrap(), Token::Ampersand);
+        assert_eq!(tokenizer.next().unwrap(), Token::Num(0b1010));
+        assert_eq!(tokenizer.next().unwrap(), Token::Bar);
+        assert_eq!(tokenizer.next().unwrap(), Token::Num(0o17));
+        let mut tokenizer = Tokenizer::new("0xFFFF_FFFF_FFFF_FFFF");
+        assert_eq!(tokenizer.next().unwrap(), Token::Num(-1));
+    }
+    #[test]
+    fn test_digit_separator() {
+        let mut tokenizer = Tokenizer::new("1_000_000");
+        assert_eq!(tokenizer.next().unwrap(), Token::Num(1_000_000))
+    }
+    #[test]
+    fn test_zero_before_identifier() {
+        let mut tokenizer = Tokenizer::new("0y");
+        assert_eq!(tokenizer.next().unwrap(), Token::Num(0));
+        assert_eq!(
+            tokenizer.next().unwrap(),
+            Token::Identifier("y".to_string())
+        );
+        // A radix prefix needs digits.
+        let mut tokenizer = Tokenizer::new("0x");
+        assert_eq!(tokenizer.next(), None);
+    }
+    #[test]
+    fn test_superscript_number() {
+        let mut tokenizer = Tokenizer::new("⁰¹²³⁴⁵⁶⁷⁸⁹");
+        assert_eq!(tokenizer.next().unwrap(), Token::Superscript(123456789))
+    }
+    #[test]
+    fn test_left_parenthesis_operator() {
+        let mut tokenizer = Tokenizer::new("(");
+        assert_eq!(tokenizer.next().unwrap(), Token::LeftParen)
+    }
+    #[test]
+    fn test_right_parenthesis_operator() {
+        let mut tokenizer = Tokenizer::new(")");
+        assert_eq!(tokenizer.next().unwrap(), Token::RightParen)
+    }
+    #[test]
+    fn test_left_shift_operator() {
+        let mut tokenizer = Tokenizer::new("<<");
+        assert_eq!(tokenizer.next().unwrap(), Token::LeftShift)
+    }
+    #[test]
+    fn test_right_shift_operator() {
+        let mut tokenizer = Tokenizer::new(">>");
+        assert_eq!(tokenizer.next().unwrap(), Token::RightShift)
+    }
+    #[test]
+    fn test_unsigned_right_shift_operator() {
+        let mut tokenizer = Tokenizer::new(">>>");
+        assert_eq!(tokenizer.next().unwrap(), Token::UnsignedRightShift)
+    }
+    #[test]
+    fn test_xor_operator() {
+        let mut tokenizer = Tokenizer::new("⊕");
+        assert_eq!(tokenizer.next().unwrap(), Token::CirclePlus)
+    }
+    #[test]
+    fn test_not_operator() {
+        let mut tokenizer = Tokenizer::new("~");
+        assert_eq!(tokenizer.next().unwrap(), Token::Tilde)
+    }
+    #[test]
+    fn test_bit_functions() {
+        let mut tokenizer = Tokenizer::new("popcnt(clz(ctz(rotl(rotr(");
+        assert_eq!(
+            tokenizer.next().unwrap(),
+            Token::ExplicitFunction(NativeFunction::Popcnt)
+        );
+        tokenizer.next();
+        assert_eq!(
+            tokenizer.next().unwrap(),
+            Token::ExplicitFunction(NativeFunction::Clz)
+        );
+        tokenizer.next();
+        assert_eq!(
+            tokenizer.next().unwrap(),
+            Token::ExplicitFunction(NativeFunction::Ctz)
+        );
+        tokenizer.next();
+        assert_eq!(
+            tokenizer.next().unwrap(),
+            Token::ExplicitFunction(NativeFunction::Rotl)
+        );
+        tokenizer.next();
+        assert_eq!(
+            tokenizer.next().unwrap(),
+            Token::ExplicitFunction(NativeFunction::Rotr)
+        );
+    }
+    #[test]
+    fn test_floor_and_ceiling() {
+        let mut tokenizer = Tokenizer::new("⌊⌋⌈⌉floor(ceil(round(trunc(truncate(");
+        assert_eq!(tokenizer.next().unwrap(), Token::LeftFloor);
+        assert_eq!(tokenizer.next().unwrap(), Token::RightFloor);
+        assert_eq!(tokenizer.next().unwrap(), Token::LeftCeiling);
+        assert_eq!(tokenizer.next().unwrap(), Token::RightCeiling);
+        for function in [
+            NativeFunction::Floor,
+            NativeFunction::Ceil,
+            NativeFunction::Round,
+            NativeFunction::Truncate,
+            NativeFunction::Truncate,
+        ] {
+            assert_eq!(tokenizer.next().unwrap(), Token::ExplicitFunction(function));
+            assert_eq!(tokenizer.next().unwrap(), Token::LeftParen);
+        }
+    }
+    #[test]
+    fn test_ans_operator() {
+        let mut tokenizer = Tokenizer::new("@");
+        assert_eq!(tokenizer.next().unwrap(), Token::Ans)
+    }
+    #[test]
+    fn test_comma_operator() {
+        let mut tokenizer = Tokenizer::new(",");
+        assert_eq!(tokenizer.next().unwrap(), Token::Comma)
+    }
+    #[test]
+    fn test_add_operator() {
+        let mut tokenizer = Tokenizer::new("+");
+        assert_eq!(tokenizer.next().unwrap(), Token::Add)
+    }
+    #[test]
+    fn test_subtract_operator() {
+        let mut tokenizer = Tokenizer::new("-");
+        assert_eq!(tokenizer.next().unwrap(), Token::Subtract)
+    }
+    #[test]
+    fn test_multiply_operator() {
+        let mut tokenizer = Tokenizer::new("*");
+        assert_eq!(tokenizer.next().unwrap(), Token::Multiply)
+    }
+    #[test]
+    fn test_divide_operator() {
+        let mut tokenizer = Tokenizer::new("/");
+        assert_eq!(tokenizer.next().unwrap(), Token::Divide)
+    }
+    #[test]
+    fn test_modulo_operator() {
+        let mut tokenizer = Tokenizer::new("%");
+        assert_eq!(tokenizer.next().unwrap(), Token::Modulo)
+    }
+    #[test]
+    fn test_caret_operator() {
+        let mut tokenizer = Tokenizer::new("^");
+        assert_eq!(tokenizer.next().unwrap(), Token::Caret)
+    }
+    #[test]
+    fn test_ampersand_operator() {
+        let mut tokenizer = Tokenizer::new("&");
+        assert_eq!(tokenizer.next().unwrap(), Token::Ampersand)
+    }
+    #[test]
+    fn test_bar_operator() {
+        let mut tokenizer = Tokenizer::new("|");
+        assert_eq!(tokenizer.next().unwrap(), Token::Bar)
+    }
+    #[test]
+    fn test_exclamation_mark_operator() {
+        let mut tokenizer = Tokenizer::new("!");
+        assert_eq!(tokenizer.next().unwrap(), Token::ExclamationMark)
+    }
+    #[test]
+    fn test_ln_function() {
+        let mut tokenizer = Tokenizer::new("ln(10)");
+        assert_eq!(
+            tokenizer.next().unwrap(),
+            Token::ExplicitFunction(NativeFunction::Ln)
+        )
+    }
+    #[test]
+    fn test_log_function() {
+        let mut tokenizer = Tokenizer::new("log(20,2)");
+        assert_eq!(
+            tokenizer.next().unwrap(),
+            Token::ExplicitFunction(NativeFunction::Log)
+        )
+    }
+    #[test]
+    fn test_mod_function() {
+        let mut tokenizer = Tokenizer::new("mod(20,2)");
+        assert_eq!(
+            tokenizer.next().unwrap(),
+            Token::ExplicitFunction(NativeFunction::Mod)
+        )
+    }
+    #[test]
+    fn test_pow_function() {
+        let mut tokenizer = Tokenizer::new("pow(20,2)");
+        assert_eq!(
+            tokenizer.next().unwrap(),
+            Token::ExplicitFunction(NativeFunction::Pow)
+        )
+    }
+    #[test]
+    fn test_sqrt_function() {
+        let mut tokenizer = Tokenizer::new("sqrt(14159)");
+        assert_eq!(
+            tokenizer.next().unwrap(),
+            Token::ExplicitFunction(NativeFunction::Sqrt)
+        )
+    }
+    #[test]
+    fn test_exp_function() {
+        let mut tokenizer = Tokenizer::new("exp(14159)");
+        assert_eq!(
+            tokenizer.next().unwrap(),
+            Token::ExplicitFunction(NativeFunction::Exp)
+        )
+    }
+    #[test]
+    fn test_exp2_function() {
+        let mut tokenizer = Tokenizer::new("exp2(14159)");
+        assert_eq!(
+            tokenizer.next().unwrap(),
+            Token::ExplicitFunction(NativeFunction::Exp2)
+        )
+    }
+    #[test]
+    fn test_abs_function() {
+        let mut tokenizer = Tokenizer::new("abs(14159)");
+        assert_eq!(
+            tokenizer.next().unwrap(),
+            Token::ExplicitFunction(NativeFunction::Abs)
+        )
+    }
+    #[test]
+    fn test_sign_function() {
+        let mut tokenizer = Tokenizer::new("sign(14159)");
+        assert_eq!(
+            tokenizer.next().unwrap(),
+            Token::ExplicitFunction(NativeFunction::Sign)
+        )
+    }
+    #[test]
+    fn test_sign2_function() {
+        let mut tokenizer = Tokenizer::new("sgn(14159)");
+        assert_eq!(
+            tokenizer.next().unwrap(),
+            Token::ExplicitFunction(NativeFunction::Sign)
+        )
+    }
+    #[test]
+    fn test_sign3_function() {
+        let mut tokenizer = Tokenizer::new("signum(14159)");
+        assert_eq!(
+            tokenizer.next().unwrap(),
+            Token::ExplicitFunction(NativeFunction::Sign)
+        )
+    }
+    #[test]
+    fn test_min_function() {
+        let mut tokenizer = Tokenizer::new("min(14159,2)");
+        assert_eq!(
+            tokenizer.next().unwrap(),
+            Token::ExplicitFunction(NativeFunction::Min)
+        )
+    }
+    #[test]
+    fn test_max_function() {
+        let mut tokenizer = Tokenizer::new("max(14159,2)");
+        assert_eq!(
+            tokenizer.next().unwrap(),
+            Token::ExplicitFunction(NativeFunction::Max)
+        )
+    }
+    #[test]
+    fn test_avg_function() {
+        let mut tokenizer = Tokenizer::new("avg(10,20)");
+        assert_eq!(
+            tokenizer.next().unwrap(),
+            Token::ExplicitFunction(NativeFunction::Avg)
+        )
+    }
+    #[test]
+    fn test_median_function() {
+        let mut tokenizer = Tokenizer::new("med(10,20)");
+        assert_eq!(
+            tokenizer.next().unwrap(),
+            Token::ExplicitFunction(NativeFunction::Med)
+        )
+    }
+    #[test]
+    fn test_median_function2() {
+        let mut tokenizer = Tokenizer::new("median(10,20)");
+        assert_eq!(
+            tokenizer.next().unwrap(),
+            Token::ExplicitFunction(NativeFunction::Med)
+        )
+    }
+    #[test]
+    fn test_gcd_function() {
+        let mut tokenizer = Tokenizer::new("gcd(10,20)");
+        assert_eq!(
+            tokenizer.next().unwrap(),
+            Token::ExplicitFunction(NativeFunction::Gcd)
+        )
+    }
+    #[test]
+    fn test_lcm_function() {
+        let mut tokenizer = Tokenizer::new("lcm(10,20)");
+        assert_eq!(
+            tokenizer.next().unwrap(),
+            Token::ExplicitFunction(NativeFunction::Lcm)
+        )
+    }
+    #[test]
+    fn test_identifier() {
+        let mut tokenizer = Tokenizer::new("price_2*x");
+        assert_eq!(
+            tokenizer.next().unwrap(),
+            Token::Identifier("price_2".to_string())
+        );
+        assert_eq!(tokenizer.next().unwrap(), Token::Multiply);
+        assert_eq!(
+            tokenizer.next().unwrap(),
+            Token::Identifier("x".to_string())
+        );
+    }
+    #[test]
+    fn test_logical_operators() {
+        let mut tokenizer = Tokenizer::new("<<<=<>>>>=>>&&&||!=!?:>if(");
+        for token in [
+            Token::LeftShift,
+            Token::LessEqual,
+            Token::Less,
+            Token::UnsignedRightShift,
+            Token::GreaterEqual,
+            Token::RightShift,
+            Token::DoubleAmpersand,
+            Token::Ampersand,
+            Token::DoubleBar,
+            Token::NotEqual,
+            Token::ExclamationMark,
+            Token::QuestionMark,
+            Token::Colon,
+            Token::Greater,
+            Token::ExplicitFunction(NativeFunction::If),
+        ] {
+            assert_eq!(tokenizer.next().unwrap(), token);
+        }
+    }
+}
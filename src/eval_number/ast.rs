@@ -1,5 +1,9 @@
 use super::Number;
-use std::{error, sync::Arc};
+use num_complex::Complex64;
+use std::cmp::Ordering;
+use std::sync::Arc;
+
+use crate::utils::{Context, EvalError, Function};
 
 #[derive(Debug, Clone, PartialEq)]
 pub enum Node {
@@ -44,6 +48,19 @@ pub enum Node {
     Avg(Arc<Vec<Node>>),
     Med(Arc<Vec<Node>>),
     Num(Number),
+    CustomFunction(Function<Number>, Arc<Vec<Node>>),
+    Equal(Box<Node>, Box<Node>),
+    NotEqual(Box<Node>, Box<Node>),
+    Less(Box<Node>, Box<Node>),
+    LessEqual(Box<Node>, Box<Node>),
+    Greater(Box<Node>, Box<Node>),
+    GreaterEqual(Box<Node>, Box<Node>),
+    LogicalAnd(Box<Node>, Box<Node>),
+    LogicalOr(Box<Node>, Box<Node>),
+    LogicalNot(Box<Node>),
+    Conditional(Box<Node>, Box<Node>, Box<Node>),
+    Variable(String),
+    Ans,
 }
 
 fn gamma(a: f64) -> f64 {
@@ -79,156 +96,447 @@ fn gamma(a: f64) -> f64 {
     }
 }
 
-pub fn eval(expr: Node) -> Result<Number, Box<dyn error::Error>> {
-    use self::Node::*;
-    match expr {
-        Num(i) => Ok(i),
-        Add(expr1, expr2) => {
-            let a = eval(*expr1)?;
-            let b = eval(*expr2)?;
-            match a {
-                Number::Integer(value_a) => match b {
-                    Number::Integer(value_b) => match value_a.checked_add(value_b) {
-                        Some(sum) => Ok(Number::Integer(sum)),
-                        None => Ok(Number::Float((value_a as f64) + (value_b as f64))),
-                    },
-                    Number::Float(value_b) => Ok(Number::Float((value_a as f64) + value_b)),
-                },
-                Number::Float(value_a) => match b {
-                    Number::Integer(value_b) => Ok(Number::Float(value_a + (value_b as f64))),
-                    Number::Float(value_b) => Ok(Number::Float(value_a + value_b)),
-                },
+/// The principal branch of the Lambert W function, computed with Halley's method.
+fn lambert_w(x: f64) -> Result<f64, EvalError> {
+    if x.is_nan() || x < -(-1.0f64).exp() {
+        return Err(EvalError::Domain("lambertw".to_string()));
+    }
+    if x.is_infinite() {
+        return Err(EvalError::Overflow);
+    }
+    // Start from the asymptote ln(x) - ln(ln(x)) for the large arguments, so that the method
+    // converges in a few steps.
+    let mut w = if x > std::f64::consts::E {
+        let ln = x.ln();
+        ln - ln.ln()
+    } else {
+        0.0
+    };
+    for _ in 0..32 {
+        // The step divided through by exp(w), which keeps it finite for the large arguments.
+        let residual = w - x * (-w).exp();
+        let next = w - residual / (w + 1.0 - (w + 2.0) * residual / (2.0 * w + 2.0));
+        if !next.is_finite() || next == w {
+            break;
+        }
+        w = next;
+    }
+    Ok(w)
+}
+
+/// Apply a real function, or its complex extension to a complex number or when the real result
+/// is NaN, such as the square root of a negative number.
+fn real_or_complex(
+    x: &Number,
+    real: impl Fn(f64) -> f64,
+    complex: impl Fn(Complex64) -> Complex64,
+) -> Number {
+    if !matches!(x, Number::Complex(_, _)) {
+        let result = real(x.to_f64());
+        if !result.is_nan() || x.to_f64().is_nan() {
+            return Number::from(result);
+        }
+    }
+    Number::from(complex(x.to_complex()))
+}
+
+/// Apply an operation exactly when both numbers are exact and the result fits, and on floats otherwise.
+fn exact_or_float(
+    a: &Number,
+    b: &Number,
+    exact: impl Fn((i128, i128), (i128, i128)) -> Option<(i128, i128)>,
+    float: impl Fn(f64, f64) -> f64,
+) -> Number {
+    if let (Some(a), Some(b)) = (a.to_fraction(), b.to_fraction()) {
+        if let Some(result) =
+            exact(a, b).and_then(|(numer, denom)| Number::from_fraction(numer, denom))
+        {
+            return result;
+        }
+    }
+    Number::Float(float(a.to_f64(), b.to_f64()))
+}
+fn add(a: &Number, b: &Number) -> Number {
+    if let (Number::Complex(_, _), _) | (_, Number::Complex(_, _)) = (a, b) {
+        let ((a_re, a_im), (b_re, b_im)) = (a.parts(), b.parts());
+        return Number::complex(add(&a_re, &b_re), add(&a_im, &b_im));
+    }
+    exact_or_float(
+        a,
+        b,
+        |(a_numer, a_denom), (b_numer, b_denom)| {
+            Some((
+                (a_numer * b_denom).checked_add(b_numer * a_denom)?,
+                a_denom * b_denom,
+            ))
+        },
+        |a, b| a + b,
+    )
+}
+fn subtract(a: &Number, b: &Number) -> Number {
+    if let (Number::Complex(_, _), _) | (_, Number::Complex(_, _)) = (a, b) {
+        let ((a_re, a_im), (b_re, b_im)) = (a.parts(), b.parts());
+        return Number::complex(subtract(&a_re, &b_re), subtract(&a_im, &b_im));
+    }
+    exact_or_float(
+        a,
+        b,
+        |(a_numer, a_denom), (b_numer, b_denom)| {
+            Some((
+                (a_numer * b_denom).checked_sub(b_numer * a_denom)?,
+                a_denom * b_denom,
+            ))
+        },
+        |a, b| a - b,
+    )
+}
+fn multiply(a: &Number, b: &Number) -> Number {
+    if let (Number::Complex(_, _), _) | (_, Number::Complex(_, _)) = (a, b) {
+        let ((a_re, a_im), (b_re, b_im)) = (a.parts(), b.parts());
+        return Number::complex(
+            subtract(&multiply(&a_re, &b_re), &multiply(&a_im, &b_im)),
+            add(&multiply(&a_re, &b_im), &multiply(&a_im, &b_re)),
+        );
+    }
+    exact_or_float(
+        a,
+        b,
+        |(a_numer, a_denom), (b_numer, b_denom)| Some((a_numer * b_numer, a_denom * b_denom)),
+        |a, b| a * b,
+    )
+}
+/// Divide by a number that isn't zero.
+fn divide(a: &Number, b: &Number) -> Number {
+    if let (Number::Complex(_, _), _) | (_, Number::Complex(_, _)) = (a, b) {
+        let ((a_re, a_im), (b_re, b_im)) = (a.parts(), b.parts());
+        let norm = add(&multiply(&b_re, &b_re), &multiply(&b_im, &b_im));
+        return Number::complex(
+            divide(
+                &add(&multiply(&a_re, &b_re), &multiply(&a_im, &b_im)),
+                &norm,
+            ),
+            divide(
+                &subtract(&multiply(&a_im, &b_re), &multiply(&a_re, &b_im)),
+                &norm,
+            ),
+        );
+    }
+    exact_or_float(
+        a,
+        b,
+        |(a_numer, a_denom), (b_numer, b_denom)| Some((a_numer * b_denom, a_denom * b_numer)),
+        |a, b| a / b,
+    )
+}
+/// The remainder of a division by a number that isn't zero, neither of them being complex.
+fn modulo(a: &Number, b: &Number) -> Number {
+    exact_or_float(
+        a,
+        b,
+        |(a_numer, a_denom), (b_numer, b_denom)| {
+            Some(((a_numer * b_denom) % (b_numer * a_denom), a_denom * b_denom))
+        },
+        |a, b| a % b,
+    )
+}
+fn negative(x: &Number) -> Number {
+    if let Number::Complex(re, im) = x {
+        return Number::complex(negative(re), negative(im));
+    }
+    x.to_fraction()
+        .and_then(|(numer, denom)| Number::from_fraction(-numer, denom))
+        .unwrap_or(Number::Float(-x.to_f64()))
+}
+/// The n-th root of an integer, if it is an integer too.
+fn integer_root(value: i128, n: u32) -> Option<i128> {
+    if n == 0 || (value < 0 && n % 2 == 0) {
+        return None;
+    }
+    let estimate = (value.unsigned_abs() as f64).powf(1.0 / n as f64).round() as i128;
+    let root = (estimate.saturating_sub(1).max(0)..=estimate + 1)
+        .find(|root| root.checked_pow(n) == Some(value.abs()))?;
+    Some(if value < 0 { -root } else { root })
+}
+/// The n-th root of an exact number, if it is exact too, the square root of a negative number
+/// being imaginary.
+fn exact_root(value: &Number, n: u32) -> Option<Number> {
+    let (numer, denom) = value.to_fraction()?;
+    if numer < 0 && n == 2 {
+        let root = Number::from_fraction(integer_root(-numer, 2)?, integer_root(denom, 2)?)?;
+        return Some(Number::complex(Number::Integer(0), root));
+    }
+    Number::from_fraction(integer_root(numer, n)?, integer_root(denom, n)?)
+}
+/// Raise to a power, exactly when the base is exact and the exponent is a fraction whose
+/// denominator is the degree of an exact root of the base.
+/// Raise the base to the exponent, a negative power of zero being a division by zero.
+fn checked_pow(base: &Number, exponent: &Number) -> Result<Number, EvalError> {
+    if base.is_zero() && exponent.to_f64() < 0.0 {
+        return Err(EvalError::DivisionByZero);
+    }
+    Ok(pow(base, exponent))
+}
+fn pow(base: &Number, exponent: &Number) -> Number {
+    if let Number::Complex(_, _) = exponent {
+        return Number::from(base.to_complex().powc(exponent.to_complex()));
+    }
+    if let Number::Complex(_, _) = base {
+        // Small integer powers are multiplied out to stay exact.
+        return match exponent {
+            Number::Integer(p) if p.unsigned_abs() <= 64 => {
+                let mut result = Number::Integer(1);
+                for _ in 0..p.unsigned_abs() {
+                    result = multiply(&result, base);
+                }
+                if *p < 0 {
+                    divide(&Number::Integer(1), &result)
+                } else {
+                    result
+                }
             }
+            _ => Number::from(base.to_complex().powc(exponent.to_complex())),
+        };
+    }
+    let exact = || {
+        let (p, q) = exponent.to_fraction()?;
+        let root = exact_root(base, u32::try_from(q).ok()?)?;
+        if let Number::Complex(_, _) = root {
+            return Some(pow(&root, &Number::Integer(p as i64)));
+        }
+        let (mut numer, mut denom) = root.to_fraction()?;
+        if p < 0 {
+            std::mem::swap(&mut numer, &mut denom);
+        }
+        let p = u32::try_from(p.unsigned_abs()).ok()?;
+        Number::from_fraction(numer.checked_pow(p)?, denom.checked_pow(p)?)
+    };
+    if let Some(result) = exact() {
+        return result;
+    }
+    let fraction = exponent.to_fraction();
+    let (base, exponent) = (base.to_f64(), exponent.to_f64());
+    let result = base.powf(exponent);
+    // The result stays a float even when it is whole, such as a power underflowing to zero.
+    if !result.is_nan() || base.is_nan() || exponent.is_nan() {
+        return Number::Float(result);
+    }
+    match fraction {
+        // An odd root of a negative number is real.
+        Some((p, q)) if q % 2 == 1 => {
+            let magnitude = (-base).powf(exponent);
+            Number::Float(if p % 2 == 0 { magnitude } else { -magnitude })
         }
-        Subtract(expr1, expr2) => {
-            let a = eval(*expr1)?;
-            let b = eval(*expr2)?;
-            match a {
-                Number::Integer(value_a) => match b {
-                    Number::Integer(value_b) => match value_a.checked_sub(value_b) {
-                        Some(sub) => Ok(Number::Integer(sub)),
-                        None => Ok(Number::Float((value_a as f64) - (value_b as f64))),
-                    },
-                    Number::Float(value_b) => Ok(Number::Float((value_a as f64) - value_b)),
-                },
-                Number::Float(value_a) => match b {
-                    Number::Integer(value_b) => Ok(Number::Float(value_a - (value_b as f64))),
-                    Number::Float(value_b) => Ok(Number::Float(value_a - value_b)),
-                },
+        _ => Number::from(Complex64::new(base, 0.0).powf(exponent)),
+    }
+}
+fn floor(x: Number) -> Number {
+    match x {
+        Number::Integer(n) => Number::Integer(n),
+        Number::Float(n) => {
+            let f = n.floor();
+            if (f <= (i64::MAX as f64)) && (f >= (i64::MIN as f64)) {
+                Number::Integer(n as i64)
+            } else {
+                Number::Float(f)
             }
         }
-        Multiply(expr1, expr2) => {
-            let a = eval(*expr1)?;
-            let b = eval(*expr2)?;
-            match a {
-                Number::Integer(value_a) => match b {
-                    Number::Integer(value_b) => match value_a.checked_mul(value_b) {
-                        Some(sub) => Ok(Number::Integer(sub)),
-                        None => Ok(Number::Float((value_a as f64) * (value_b as f64))),
-                    },
-                    Number::Float(value_b) => Ok(Number::Float((value_a as f64) * value_b)),
-                },
-                Number::Float(value_a) => match b {
-                    Number::Integer(value_b) => Ok(Number::Float(value_a * (value_b as f64))),
-                    Number::Float(value_b) => Ok(Number::Float(value_a * value_b)),
-                },
+        Number::Rational(n) => Number::Integer(n.numer().div_euclid(*n.denom())),
+        Number::Complex(re, im) => Number::complex(floor(*re), floor(*im)),
+    }
+}
+fn ceil(x: Number) -> Number {
+    match x {
+        Number::Integer(n) => Number::Integer(n),
+        Number::Float(n) => {
+            let f = n.ceil();
+            if (f <= (i64::MAX as f64)) && (f >= (i64::MIN as f64)) {
+                Number::Integer(n as i64)
+            } else {
+                Number::Float(f)
             }
         }
-        Divide(expr1, expr2) => {
-            let a = eval(*expr1)?;
-            let b = eval(*expr2)?;
-            match a {
-                Number::Integer(value_a) => match b {
-                    Number::Integer(value_b) => match value_a.checked_rem_euclid(value_b) {
-                        Some(r) => {
-                            if r == 0 {
-                                Ok(Number::Integer(value_a / value_b))
-                            } else {
-                                Ok(Number::Float((value_a as f64) / (value_b as f64)))
-                            }
-                        }
-                        None => Ok(Number::Float((value_a as f64) / (value_b as f64))),
-                    },
-                    Number::Float(value_b) => Ok(Number::Float((value_a as f64) / value_b)),
-                },
-                Number::Float(value_a) => match b {
-                    Number::Integer(value_b) => Ok(Number::Float(value_a / (value_b as f64))),
-                    Number::Float(value_b) => Ok(Number::Float(value_a / value_b)),
-                },
+        // A rational is never an integer, so its ceiling is right above its floor.
+        Number::Rational(n) => Number::Integer(n.numer().div_euclid(*n.denom()) + 1),
+        Number::Complex(re, im) => Number::complex(ceil(*re), ceil(*im)),
+    }
+}
+fn round(x: Number) -> Number {
+    match x {
+        Number::Integer(n) => Number::Integer(n),
+        Number::Float(n) => {
+            let f = n.round();
+            if (f <= (i64::MAX as f64)) && (f >= (i64::MIN as f64)) {
+                Number::Integer(n as i64)
+            } else {
+                Number::from(f)
             }
         }
-        Modulo(expr1, expr2) => {
-            let a = eval(*expr1)?;
-            let b = eval(*expr2)?;
-            match a {
-                Number::Integer(value_a) => match b {
-                    Number::Integer(value_b) => Ok(Number::Integer(value_a % value_b)),
-                    Number::Float(value_b) => Ok(Number::Float((value_a as f64) % value_b)),
-                },
-                Number::Float(value_a) => match b {
-                    Number::Integer(value_b) => Ok(Number::Float(value_a % (value_b as f64))),
-                    Number::Float(value_b) => Ok(Number::Float(value_a % value_b)),
-                },
+        Number::Rational(n) => {
+            let (numer, denom) = (*n.numer(), *n.denom());
+            let remainder = (numer % denom).abs();
+            if remainder >= denom - remainder {
+                Number::Integer(numer / denom + numer.signum())
+            } else {
+                Number::Integer(numer / denom)
             }
         }
-        Negative(expr1) => {
-            let x = eval(*expr1)?;
-            match x {
-                Number::Integer(v) => match 0_i64.checked_sub(v) {
-                    Some(neg) => Ok(Number::Integer(neg)),
-                    None => Ok(Number::Float(-(v as f64))),
-                },
-                Number::Float(v) => Ok(Number::Float(-v)),
+        Number::Complex(re, im) => Number::complex(round(*re), round(*im)),
+    }
+}
+fn truncate(x: Number) -> Number {
+    match x {
+        Number::Integer(n) => Number::Integer(n),
+        Number::Float(n) => Number::from(n.trunc()),
+        Number::Rational(n) => Number::Integer(n.numer() / n.denom()),
+        Number::Complex(re, im) => Number::complex(truncate(*re), truncate(*im)),
+    }
+}
+/// Fail with a domain error on a complex number, for the functions only defined on real numbers.
+fn real(function: &str, x: Number) -> Result<Number, EvalError> {
+    if let Number::Complex(_, _) = x {
+        Err(EvalError::Domain(function.to_string()))
+    } else {
+        Ok(x)
+    }
+}
+
+/// 1 when the condition holds and 0 otherwise.
+fn truth(condition: bool) -> Number {
+    Number::Integer(condition as i64)
+}
+
+/// Whether two numbers have equal real and imaginary parts.
+fn equal(x: &Number, y: &Number) -> bool {
+    let ((x_re, x_im), (y_re, y_im)) = (x.parts(), y.parts());
+    x_re.compare(&y_re) == Some(Ordering::Equal) && x_im.compare(&y_im) == Some(Ordering::Equal)
+}
+
+/// Compare two real numbers, returning a domain error for a complex one.
+fn compare(operator: &str, x: Number, y: Number) -> Result<Option<Ordering>, EvalError> {
+    Ok(real(operator, x)?.compare(&real(operator, y)?))
+}
+
+pub fn eval(
+    expr: &Node,
+    placeholder: &Number,
+    context: &dyn Context<Number>,
+) -> Result<Number, EvalError> {
+    use self::Node::*;
+    match expr {
+        Num(i) => Ok(i.clone()),
+        Ans => Ok(placeholder.clone()),
+        CustomFunction(function, args) => {
+            let mut values = vec![];
+            for arg in args.iter() {
+                values.push(eval(arg, placeholder, context)?);
+            }
+            function.call(&values)
+        }
+        Variable(name) => match context.get(name) {
+            Some(value) => Ok(value),
+            None => Err(EvalError::UndefinedVariable(name.clone())),
+        },
+        Add(expr1, expr2) => Ok(add(
+            &eval(expr1, placeholder, context)?,
+            &eval(expr2, placeholder, context)?,
+        )),
+        Subtract(expr1, expr2) => Ok(subtract(
+            &eval(expr1, placeholder, context)?,
+            &eval(expr2, placeholder, context)?,
+        )),
+        Multiply(expr1, expr2) => Ok(multiply(
+            &eval(expr1, placeholder, context)?,
+            &eval(expr2, placeholder, context)?,
+        )),
+        Divide(expr1, expr2) => {
+            let a = eval(expr1, placeholder, context)?;
+            let b = eval(expr2, placeholder, context)?;
+            if b.is_zero() {
+                return Err(EvalError::DivisionByZero);
             }
+            Ok(divide(&a, &b))
         }
-        Pow(expr1, expr2) => {
-            let a = eval(*expr1)?;
-            let b = eval(*expr2)?;
-            match a {
-                Number::Integer(value_a) => match b {
-                    Number::Integer(value_b) => {
-                        if value_b >= 0 {
-                            if value_b <= (u32::MAX as i64) {
-                                match value_a.checked_pow(value_b as u32) {
-                                    Some(p) => Ok(Number::Integer(p)),
-                                    None => Ok(Number::from((value_a as f64).powf(value_b as f64))),
-                                }
-                            } else {
-                                Ok(Number::from((value_a as f64).powf(value_b as f64)))
-                            }
-                        } else {
-                            let value_a = value_a as f64;
-                            if value_b < (i32::MIN as i64) {
-                                Ok(Number::from(value_a.powf(value_b as f64)))
-                            } else {
-                                Ok(Number::from(value_a.powi(value_b as i32)))
-                            }
-                        }
-                    }
-                    Number::Float(value_b) => Ok(Number::from((value_a as f64) / value_b)),
-                },
-                Number::Float(value_a) => match b {
-                    Number::Integer(value_b) => Ok(Number::from(value_a / (value_b as f64))),
-                    Number::Float(value_b) => Ok(Number::from(value_a / value_b)),
-                },
+        Modulo(expr1, expr2) => {
+            let a = real("mod", eval(expr1, placeholder, context)?)?;
+            let b = real("mod", eval(expr2, placeholder, context)?)?;
+            if b.is_zero() {
+                return Err(EvalError::DivisionByZero);
+            }
+            Ok(modulo(&a, &b))
+        }
+        Equal(expr1, expr2) => Ok(truth(equal(
+            &eval(expr1, placeholder, context)?,
+            &eval(expr2, placeholder, context)?,
+        ))),
+        NotEqual(expr1, expr2) => Ok(truth(!equal(
+            &eval(expr1, placeholder, context)?,
+            &eval(expr2, placeholder, context)?,
+        ))),
+        Less(expr1, expr2) => Ok(truth(matches!(
+            compare(
+                "<",
+                eval(expr1, placeholder, context)?,
+                eval(expr2, placeholder, context)?,
+            )?,
+            Some(Ordering::Less)
+        ))),
+        LessEqual(expr1, expr2) => Ok(truth(matches!(
+            compare(
+                "<=",
+                eval(expr1, placeholder, context)?,
+                eval(expr2, placeholder, context)?,
+            )?,
+            Some(Ordering::Less | Ordering::Equal)
+        ))),
+        Greater(expr1, expr2) => Ok(truth(matches!(
+            compare(
+                ">",
+                eval(expr1, placeholder, context)?,
+                eval(expr2, placeholder, context)?,
+            )?,
+            Some(Ordering::Greater)
+        ))),
+        GreaterEqual(expr1, expr2) => Ok(truth(matches!(
+            compare(
+                ">=",
+                eval(expr1, placeholder, context)?,
+                eval(expr2, placeholder, context)?,
+            )?,
+            Some(Ordering::Greater | Ordering::Equal)
+        ))),
+        LogicalAnd(expr1, expr2) => Ok(truth(
+            !eval(expr1, placeholder, context)?.is_zero()
+                && !eval(expr2, placeholder, context)?.is_zero(),
+        )),
+        LogicalOr(expr1, expr2) => Ok(truth(
+            !eval(expr1, placeholder, context)?.is_zero()
+                || !eval(expr2, placeholder, context)?.is_zero(),
+        )),
+        LogicalNot(expr1) => Ok(truth(eval(expr1, placeholder, context)?.is_zero())),
+        Conditional(condition, expr1, expr2) => {
+            if !eval(condition, placeholder, context)?.is_zero() {
+                eval(expr1, placeholder, context)
+            } else {
+                eval(expr2, placeholder, context)
             }
         }
+        Negative(expr1) => Ok(negative(&eval(expr1, placeholder, context)?)),
+        Pow(expr1, expr2) => checked_pow(
+            &eval(expr1, placeholder, context)?,
+            &eval(expr2, placeholder, context)?,
+        ),
         Root(n_th_expr, x_expr) => {
-            let x = eval(*x_expr)?;
-            let root = eval(*n_th_expr)?;
-            match root {
-                Number::Integer(n) => match x {
-                    Number::Integer(x) => Ok(Number::from((x as f64).powf(1.0 / (n as f64)))),
-                    Number::Float(x) => Ok(Number::from(x.powf(1.0 / (n as f64)))),
-                },
-                Number::Float(n) => match x {
-                    Number::Integer(x) => Ok(Number::from((x as f64).powf(1.0 / n))),
-                    Number::Float(x) => Ok(Number::from(x.powf(1.0 / n))),
-                },
-            }
+            let x = eval(x_expr, placeholder, context)?;
+            let n_th = eval(n_th_expr, placeholder, context)?;
+            let exponent = if n_th.is_zero() {
+                Number::Float(f64::INFINITY)
+            } else {
+                divide(&Number::Integer(1), &n_th)
+            };
+            checked_pow(&x, &exponent)
         }
         Factorial(sub_expr) => {
-            let sub_result = eval(*sub_expr)?;
+            let sub_result = real("factorial", eval(sub_expr, placeholder, context)?)?;
             match sub_result {
                 Number::Integer(n) => {
                     if (0..=20).contains(&n) {
@@ -237,217 +545,129 @@ pub fn eval(expr: Node) -> Result<Number, Box<dyn error::Error>> {
                             factorial_result *= i as i64;
                         }
                         Ok(Number::Integer(factorial_result))
+                    } else if n < 0 {
+                        Err(EvalError::NegativeFactorial)
                     } else {
                         Ok(Number::Float(gamma((n as f64) + 1.0)))
                     }
                 }
-                Number::Float(n) => Ok(Number::Float(gamma(n + 1.0))),
+                Number::Float(n) if n < 0.0 && n.fract() == 0.0 => {
+                    Err(EvalError::NegativeFactorial)
+                }
+                sub_result => Ok(Number::Float(gamma(sub_result.to_f64() + 1.0))),
             }
         }
         LambertW(expr) => {
-            let sub_expr = eval(*expr)?;
-            let sub_expr = match sub_expr {
-                Number::Integer(x) => x as f64,
-                Number::Float(x) => x,
-            };
-            let min_one: f64 = -1.0;
-            if sub_expr < -min_one.exp() {
-                return Err("The Lambert W function is not defined for {}.".into());
-            }
-            let iterations = (4).max((sub_expr.log10() / 3.0).ceil() as i32);
-            let mut w: f64 = 0.0;
-            for _ in 0..iterations {
-                let exp_w = w.exp();
-                w -= (w * exp_w - sub_expr)
-                    / (exp_w * (w + 1.0) - (w + 2.0) * (w * exp_w - sub_expr) / (2.0 * w + 2.0));
-            }
-            Ok(Number::Float(w))
+            let sub_expr = real("lambertw", eval(expr, placeholder, context)?)?.to_f64();
+            Ok(Number::Float(lambert_w(sub_expr)?))
         }
         ILog(expr1, expr2) => {
-            let n = eval(*expr1)?;
-            let mut n = match n {
-                Number::Integer(x) => x as f64,
-                Number::Float(x) => x,
-            };
-            let b = eval(*expr2)?;
-            let b = match b {
-                Number::Integer(x) => x as f64,
-                Number::Float(x) => x,
-            };
+            let mut n = real("ilog", eval(expr1, placeholder, context)?)?.to_f64();
+            let b = real("ilog", eval(expr2, placeholder, context)?)?.to_f64();
             let mut x: i64 = 0;
             while n > 1.0 {
                 x += 1;
-                n = (n.log10() / b.log10()).floor();
+                let next = (n.log10() / b.log10()).floor();
+                if next.is_nan() || next >= n {
+                    return Err(EvalError::Domain("ilog".to_string()));
+                }
+                n = next;
             }
             Ok(Number::Integer(x))
         }
         Abs(sub_expr) => {
-            let x = eval(*sub_expr)?;
-            match x {
-                Number::Integer(x) => match x.checked_abs() {
-                    Some(x) => Ok(Number::Integer(x)),
-                    None => Ok(Number::Float((x as f64).abs())),
-                },
-                Number::Float(x) => Ok(Number::Float(x.abs())),
-            }
-        }
-        Floor(sub_expr) => {
-            let x = eval(*sub_expr)?;
-            match x {
-                Number::Integer(n) => Ok(Number::Integer(n)),
-                Number::Float(n) => {
-                    let f = n.floor();
-                    if (f <= (i64::MAX as f64)) && (f >= (i64::MIN as f64)) {
-                        Ok(Number::Integer(n as i64))
-                    } else {
-                        Ok(Number::Float(f))
-                    }
-                }
-            }
-        }
-        Ceil(sub_expr) => {
-            let x = eval(*sub_expr)?;
-            match x {
-                Number::Integer(n) => Ok(Number::Integer(n)),
-                Number::Float(n) => {
-                    let f = n.ceil();
-                    if (f <= (i64::MAX as f64)) && (f >= (i64::MIN as f64)) {
-                        Ok(Number::Integer(n as i64))
-                    } else {
-                        Ok(Number::Float(f))
-                    }
-                }
-            }
-        }
-        Round(sub_expr) => {
-            let x = eval(*sub_expr)?;
-            match x {
-                Number::Integer(n) => Ok(Number::Integer(n)),
-                Number::Float(n) => {
-                    let f = n.round();
-                    if (f <= (i64::MAX as f64)) && (f >= (i64::MIN as f64)) {
-                        Ok(Number::Integer(n as i64))
-                    } else {
-                        Ok(Number::from(f))
-                    }
-                }
-            }
-        }
-        Sin(sub_expr) => {
-            let sub_expr = eval(*sub_expr)?;
-            match sub_expr {
-                Number::Float(f) => Ok(Number::from(f.sin())),
-                Number::Integer(i) => Ok(Number::from((i as f64).sin())),
-            }
-        }
-        Cos(sub_expr) => {
-            let sub_expr = eval(*sub_expr)?;
-            match sub_expr {
-                Number::Float(f) => Ok(Number::from(f.cos())),
-                Number::Integer(i) => Ok(Number::from((i as f64).cos())),
-            }
-        }
-        Tan(sub_expr) => {
-            let sub_expr = eval(*sub_expr)?;
-            match sub_expr {
-                Number::Float(f) => Ok(Number::from(f.tan())),
-                Number::Integer(i) => Ok(Number::from((i as f64).tan())),
-            }
-        }
-        Sinh(sub_expr) => {
-            let sub_expr = eval(*sub_expr)?;
-            match sub_expr {
-                Number::Float(f) => Ok(Number::from(f.sinh())),
-                Number::Integer(i) => Ok(Number::from((i as f64).sinh())),
-            }
-        }
-        Cosh(sub_expr) => {
-            let sub_expr = eval(*sub_expr)?;
-            match sub_expr {
-                Number::Float(f) => Ok(Number::from(f.cosh())),
-                Number::Integer(i) => Ok(Number::from((i as f64).cosh())),
-            }
-        }
-        Tanh(sub_expr) => {
-            let sub_expr = eval(*sub_expr)?;
-            match sub_expr {
-                Number::Float(f) => Ok(Number::from(f.tanh())),
-                Number::Integer(i) => Ok(Number::from((i as f64).tanh())),
-            }
-        }
-        Asin(sub_expr) => {
-            let sub_expr = eval(*sub_expr)?;
-            match sub_expr {
-                Number::Float(f) => Ok(Number::from(f.asin())),
-                Number::Integer(i) => Ok(Number::from((i as f64).asin())),
-            }
-        }
-        Acos(sub_expr) => {
-            let sub_expr = eval(*sub_expr)?;
-            match sub_expr {
-                Number::Float(f) => Ok(Number::from(f.acos())),
-                Number::Integer(i) => Ok(Number::from((i as f64).acos())),
-            }
-        }
-        Atan(sub_expr) => {
-            let sub_expr = eval(*sub_expr)?;
-            match sub_expr {
-                Number::Float(f) => Ok(Number::from(f.atan())),
-                Number::Integer(i) => Ok(Number::from((i as f64).atan())),
-            }
-        }
-        Arsinh(sub_expr) => {
-            let sub_expr = eval(*sub_expr)?;
-            match sub_expr {
-                Number::Float(f) => Ok(Number::from(f.asinh())),
-                Number::Integer(i) => Ok(Number::from((i as f64).asinh())),
-            }
-        }
-        Arcosh(sub_expr) => {
-            let sub_expr = eval(*sub_expr)?;
-            match sub_expr {
-                Number::Float(f) => Ok(Number::from(f.acosh())),
-                Number::Integer(i) => Ok(Number::from((i as f64).acosh())),
-            }
-        }
-        Artanh(sub_expr) => {
-            let sub_expr = eval(*sub_expr)?;
-            match sub_expr {
-                Number::Float(f) => Ok(Number::from(f.atanh())),
-                Number::Integer(i) => Ok(Number::from((i as f64).atanh())),
-            }
-        }
+            let x = eval(sub_expr, placeholder, context)?;
+            if let Number::Complex(re, im) = &x {
+                let square = add(&multiply(re, re), &multiply(im, im));
+                return Ok(exact_root(&square, 2).unwrap_or(Number::from(x.to_complex().norm())));
+            }
+            Ok(x.to_fraction()
+                .and_then(|(numer, denom)| Number::from_fraction(numer.abs(), denom))
+                .unwrap_or(Number::Float(x.to_f64().abs())))
+        }
+        Floor(sub_expr) => Ok(floor(eval(sub_expr, placeholder, context)?)),
+        Ceil(sub_expr) => Ok(ceil(eval(sub_expr, placeholder, context)?)),
+        Round(sub_expr) => Ok(round(eval(sub_expr, placeholder, context)?)),
+        Sin(sub_expr) => Ok(real_or_complex(
+            &eval(sub_expr, placeholder, context)?,
+            f64::sin,
+            Complex64::sin,
+        )),
+        Cos(sub_expr) => Ok(real_or_complex(
+            &eval(sub_expr, placeholder, context)?,
+            f64::cos,
+            Complex64::cos,
+        )),
+        Tan(sub_expr) => Ok(real_or_complex(
+            &eval(sub_expr, placeholder, context)?,
+            f64::tan,
+            Complex64::tan,
+        )),
+        Sinh(sub_expr) => Ok(real_or_complex(
+            &eval(sub_expr, placeholder, context)?,
+            f64::sinh,
+            Complex64::sinh,
+        )),
+        Cosh(sub_expr) => Ok(real_or_complex(
+            &eval(sub_expr, placeholder, context)?,
+            f64::cosh,
+            Complex64::cosh,
+        )),
+        Tanh(sub_expr) => Ok(real_or_complex(
+            &eval(sub_expr, placeholder, context)?,
+            f64::tanh,
+            Complex64::tanh,
+        )),
+        Asin(sub_expr) => Ok(real_or_complex(
+            &eval(sub_expr, placeholder, context)?,
+            f64::asin,
+            Complex64::asin,
+        )),
+        Acos(sub_expr) => Ok(real_or_complex(
+            &eval(sub_expr, placeholder, context)?,
+            f64::acos,
+            Complex64::acos,
+        )),
+        Atan(sub_expr) => Ok(real_or_complex(
+            &eval(sub_expr, placeholder, context)?,
+            f64::atan,
+            Complex64::atan,
+        )),
+        Arsinh(sub_expr) => Ok(real_or_complex(
+            &eval(sub_expr, placeholder, context)?,
+            f64::asinh,
+            Complex64::asinh,
+        )),
+        Arcosh(sub_expr) => Ok(real_or_complex(
+            &eval(sub_expr, placeholder, context)?,
+            f64::acosh,
+            Complex64::acosh,
+        )),
+        Artanh(sub_expr) => Ok(real_or_complex(
+            &eval(sub_expr, placeholder, context)?,
+            f64::atanh,
+            Complex64::atanh,
+        )),
         Sqrt(sub_expr) => {
-            // @todo: check if it's a perfect square, if so and a i64, then cast to integer
-            let sqr = eval(*sub_expr)?;
-            match sqr {
-                Number::Integer(i) => Ok(Number::from((i as f64).sqrt())),
-                Number::Float(f) => Ok(Number::from(f.sqrt())),
-            }
-        }
-        Ln(sub_expr) => {
-            let sqr = eval(*sub_expr)?;
-            match sqr {
-                Number::Integer(i) => Ok(Number::from((i as f64).ln())),
-                Number::Float(f) => Ok(Number::from(f.ln())),
-            }
-        }
-        Lb(sub_expr) => {
-            let sqr = eval(*sub_expr)?;
-            match sqr {
-                Number::Integer(i) => Ok(Number::from((i as f64).log(2.0))),
-                Number::Float(f) => Ok(Number::from(f.log(2.0))),
-            }
-        }
-        Truncate(sub_expr) => {
-            let x = eval(*sub_expr)?;
-            match x {
-                Number::Integer(n) => Ok(Number::Integer(n)),
-                Number::Float(n) => Ok(Number::from(n.trunc())),
-            }
-        }
+            let x = eval(sub_expr, placeholder, context)?;
+            match exact_root(&x, 2) {
+                Some(root) => Ok(root),
+                None => Ok(real_or_complex(&x, f64::sqrt, Complex64::sqrt)),
+            }
+        }
+        Ln(sub_expr) => Ok(real_or_complex(
+            &eval(sub_expr, placeholder, context)?,
+            f64::ln,
+            Complex64::ln,
+        )),
+        Lb(sub_expr) => Ok(real_or_complex(
+            &eval(sub_expr, placeholder, context)?,
+            |x| x.log(2.0),
+            |x| x.log(2.0),
+        )),
+        Truncate(sub_expr) => Ok(truncate(eval(sub_expr, placeholder, context)?)),
         Sign(sub_expr) => {
-            let x = eval(*sub_expr)?;
+            let x = eval(sub_expr, placeholder, context)?;
             match x {
                 Number::Integer(n) => Ok(Number::Integer(n.signum())),
                 Number::Float(n) => {
@@ -459,162 +679,93 @@ pub fn eval(expr: Node) -> Result<Number, Box<dyn error::Error>> {
                         Ok(Number::Integer(-1))
                     }
                 }
+                Number::Rational(n) => Ok(Number::Integer(n.numer().signum())),
+                Number::Complex(_, _) => {
+                    let z = x.to_complex();
+                    Ok(Number::from(z / z.norm()))
+                }
             }
         }
-        Exp(sub_expr) => {
-            let sub_expr = eval(*sub_expr)?;
-            let sub_expr = match sub_expr {
-                Number::Integer(x) => x as f64,
-                Number::Float(x) => x,
-            };
-            Ok(Number::from(sub_expr.exp()))
-        }
-        Exp2(sub_expr) => {
-            let sub_expr = eval(*sub_expr)?;
-            let sub_expr = match sub_expr {
-                Number::Integer(x) => x as f64,
-                Number::Float(x) => x,
-            };
-            Ok(Number::from(sub_expr.exp2()))
-        }
+        Exp(sub_expr) => Ok(real_or_complex(
+            &eval(sub_expr, placeholder, context)?,
+            f64::exp,
+            Complex64::exp,
+        )),
+        Exp2(sub_expr) => Ok(real_or_complex(
+            &eval(sub_expr, placeholder, context)?,
+            f64::exp2,
+            Complex64::exp2,
+        )),
         Log(expr1, expr2) => {
-            let expr1 = eval(*expr1)?;
-            let expr1 = match expr1 {
-                Number::Integer(x) => x as f64,
-                Number::Float(x) => x,
-            };
-            let expr2 = eval(*expr2)?;
-            let expr2 = match expr2 {
-                Number::Integer(x) => x as f64,
-                Number::Float(x) => x,
-            };
-            Ok(Number::from(expr1.log(expr2)))
+            let expr1 = eval(expr1, placeholder, context)?;
+            let expr2 = eval(expr2, placeholder, context)?;
+            if let (Number::Complex(_, _), _) | (_, Number::Complex(_, _)) = (&expr1, &expr2) {
+                return Ok(Number::from(
+                    expr1.to_complex().ln() / expr2.to_complex().ln(),
+                ));
+            }
+            let (x, b) = (expr1.to_f64(), expr2.to_f64());
+            let result = x.log(b);
+            if !result.is_nan() || x.is_nan() || b.is_nan() {
+                Ok(Number::from(result))
+            } else {
+                Ok(Number::from(
+                    expr1.to_complex().ln() / expr2.to_complex().ln(),
+                ))
+            }
         }
         Min(args) => {
-            if args.len() > 1 {
-                let mut result: Option<Number> = None;
-                for arg in <Vec<Node> as Clone>::clone(&args).into_iter() {
-                    let r = eval(arg)?;
-                    match result {
-                        Some(l) => {
-                            let lf64 = match l.clone() {
-                                Number::Float(f) => f,
-                                Number::Integer(i) => i as f64,
-                            };
-                            let rf64 = match r.clone() {
-                                Number::Float(f) => f,
-                                Number::Integer(i) => i as f64,
-                            };
-                            if lf64 < rf64 {
-                                result = Some(l);
-                            } else {
-                                result = Some(r);
-                            }
-                        }
-                        None => {
-                            result = Some(r);
-                        }
-                    }
-                }
-                Ok(result.unwrap())
-            } else {
-                match args.first() {
-                    Some(arg) => Ok(eval((*arg).clone())?),
-                    None => Ok(Number::Integer(0)),
-                }
+            let mut result: Option<Number> = None;
+            for arg in args.iter() {
+                let r = real("min", eval(arg, placeholder, context)?)?;
+                result = match result {
+                    Some(l) if l.compare(&r) == Some(Ordering::Less) => Some(l),
+                    _ => Some(r),
+                };
             }
+            Ok(result.unwrap_or(Number::Integer(0)))
         }
         Max(args) => {
-            if args.len() > 1 {
-                let mut result: Option<Number> = None;
-                for arg in <Vec<Node> as Clone>::clone(&args).into_iter() {
-                    let r = eval(arg)?;
-                    match result {
-                        Some(l) => {
-                            let lf64 = match l.clone() {
-                                Number::Float(f) => f,
-                                Number::Integer(i) => i as f64,
-                            };
-                            let rf64 = match r.clone() {
-                                Number::Float(f) => f,
-                                Number::Integer(i) => i as f64,
-                            };
-                            if lf64 > rf64 {
-                                result = Some(l);
-                            } else {
-                                result = Some(r);
-                            }
-                        }
-                        None => {
-                            result = Some(r);
-                        }
-                    }
-                }
-                Ok(result.unwrap())
-            } else {
-                match args.first() {
-                    Some(arg) => Ok(eval((*arg).clone())?),
-                    None => Ok(Number::Integer(0)),
-                }
+            let mut result: Option<Number> = None;
+            for arg in args.iter() {
+                let r = real("max", eval(arg, placeholder, context)?)?;
+                result = match result {
+                    Some(l) if l.compare(&r) == Some(Ordering::Greater) => Some(l),
+                    _ => Some(r),
+                };
             }
+            Ok(result.unwrap_or(Number::Integer(0)))
         }
         Avg(args) => {
-            let mut result = 0.0;
-            for arg in <Vec<Node> as Clone>::clone(&args).into_iter() {
-                let sub_expr = eval(arg)?;
-                let sub_expr = match sub_expr {
-                    Number::Integer(x) => x as f64,
-                    Number::Float(x) => x,
-                };
-                result += sub_expr;
+            let mut result = Number::Integer(0);
+            for arg in args.iter() {
+                result = add(&result, &eval(arg, placeholder, context)?);
+            }
+            match divide(&result, &Number::Integer(args.len() as i64)) {
+                Number::Float(average) => Ok(Number::from(average)),
+                average => Ok(average),
             }
-            let len = args.len() as f64;
-            Ok(Number::from(result / len))
         }
         Med(args) => {
             let mut results = vec![];
-            for arg in <Vec<Node> as Clone>::clone(&args).into_iter() {
-                results.push(eval(arg).unwrap());
+            for arg in args.iter() {
+                results.push(real("median", eval(arg, placeholder, context)?)?);
             }
-            results.sort_by(|a, b| {
-                let a = match a {
-                    Number::Integer(x) => (*x) as f64,
-                    Number::Float(x) => *x,
-                };
-                let b = match b {
-                    Number::Integer(x) => (*x) as f64,
-                    Number::Float(x) => *x,
-                };
-                a.partial_cmp(&b).unwrap()
-            });
+            results.sort_by(|a, b| a.compare(b).unwrap_or(Ordering::Equal));
             let len = results.len();
             if len % 2 == 0 {
-                let a = results[len >> 1].clone();
-                let a = match a {
-                    Number::Integer(x) => x as f64,
-                    Number::Float(x) => x,
-                };
-                let b = results[(len >> 1) - 1].clone();
-                let b = match b {
-                    Number::Integer(x) => x as f64,
-                    Number::Float(x) => x,
-                };
-                Ok(Number::from((a + b) / 2.0))
+                let sum = add(&results[len >> 1], &results[(len >> 1) - 1]);
+                match divide(&sum, &Number::Integer(2)) {
+                    Number::Float(median) => Ok(Number::from(median)),
+                    median => Ok(median),
+                }
             } else {
                 Ok(results[len >> 1].clone())
             }
         }
         Atan2(expr1, expr2) => {
-            let expr1 = eval(*expr1)?;
-            let expr1 = match expr1 {
-                Number::Integer(x) => x as f64,
-                Number::Float(x) => x,
-            };
-            let expr2 = eval(*expr2)?;
-            let expr2 = match expr2 {
-                Number::Integer(x) => x as f64,
-                Number::Float(x) => x,
-            };
+            let expr1 = real("atan2", eval(expr1, placeholder, context)?)?.to_f64();
+            let expr2 = real("atan2", eval(expr2, placeholder, context)?)?.to_f64();
             Ok(Number::from(expr1.atan2(expr2)))
         }
     }
@@ -623,84 +774,242 @@ pub fn eval(expr: Node) -> Result<Number, Box<dyn error::Error>> {
 //Unit tests
 #[cfg(test)]
 mod tests {
+    use std::collections::HashMap;
+
     use crate::eval_number::{ast::eval, parser::Parser, Number};
+    use crate::utils::EvalError;
+    use num_rational::Rational64;
 
     #[test]
     fn test_div() {
-        let ast = Parser::new("1/2", None).unwrap().parse().unwrap();
-        let value = eval(ast).unwrap();
-        assert_eq!(value, Number::Float(0.5));
+        let ast = Parser::new("1/2").unwrap().parse().unwrap();
+        let value = eval(&ast, &Number::Integer(0), &HashMap::new()).unwrap();
+        assert_eq!(value, Number::Rational(Rational64::new(1, 2)));
+    }
+    #[test]
+    fn test_rational() {
+        let cases = [
+            ("1/3*3", Number::Integer(1)),
+            ("1/3+1/6", Number::Rational(Rational64::new(1, 2))),
+            ("(2/3)^2", Number::Rational(Rational64::new(4, 9))),
+            ("2^-2", Number::Rational(Rational64::new(1, 4))),
+            ("(1/4)^(-1/2)", Number::Integer(2)),
+            ("8^(2/3)", Number::Integer(4)),
+            ("sqrt(9/4)", Number::Rational(Rational64::new(3, 2))),
+            ("root(3,-8/27)", Number::Rational(Rational64::new(-2, 3))),
+            ("7/2%1", Number::Rational(Rational64::new(1, 2))),
+            ("-7/2", Number::Rational(Rational64::new(-7, 2))),
+            ("abs(-7/2)", Number::Rational(Rational64::new(7, 2))),
+            ("floor(-7/2)", Number::Integer(-4)),
+            ("ceil(-7/2)", Number::Integer(-3)),
+            ("round(-7/2)", Number::Integer(-4)),
+            ("trunc(-7/2)", Number::Integer(-3)),
+            ("sign(-7/2)", Number::Integer(-1)),
+            ("min(1/2,1/3)", Number::Rational(Rational64::new(1, 3))),
+            ("avg(1,2)", Number::Rational(Rational64::new(3, 2))),
+            ("1/3+0.5", Number::Float(1.0 / 3.0 + 0.5)),
+            ("sqrt(2)", Number::Float(2.0_f64.sqrt())),
+            ("sin(1/2)", Number::Float(0.5_f64.sin())),
+            ("10^-400", Number::Float(0.0)),
+            ("0.5^2000", Number::Float(0.0)),
+            (
+                "1/9223372036854775807+1/9223372036854775806",
+                Number::Float(2.0 / 9.223372036854776e18),
+            ),
+        ];
+        for (formula, expected) in cases {
+            let ast = Parser::new(formula).unwrap().parse().unwrap();
+            let value = eval(&ast, &Number::Integer(0), &HashMap::new()).unwrap();
+            assert_eq!(value, expected, "{}", formula);
+        }
     }
     #[test]
     fn test_modulo() {
-        let ast = Parser::new("1%2", None).unwrap().parse().unwrap();
-        let value = eval(ast).unwrap();
+        let ast = Parser::new("1%2").unwrap().parse().unwrap();
+        let value = eval(&ast, &Number::Integer(0), &HashMap::new()).unwrap();
         assert_eq!(value, Number::Integer(1));
     }
     #[test]
     fn test_modulo2() {
-        let ast = Parser::new("2%2", None).unwrap().parse().unwrap();
-        let value = eval(ast).unwrap();
+        let ast = Parser::new("2%2").unwrap().parse().unwrap();
+        let value = eval(&ast, &Number::Integer(0), &HashMap::new()).unwrap();
         assert_eq!(value, Number::Integer(0));
     }
     #[test]
     fn test_modulo3() {
-        let ast = Parser::new("3%2", None).unwrap().parse().unwrap();
-        let value = eval(ast).unwrap();
+        let ast = Parser::new("3%2").unwrap().parse().unwrap();
+        let value = eval(&ast, &Number::Integer(0), &HashMap::new()).unwrap();
         assert_eq!(value, Number::Integer(1));
     }
     #[test]
+    fn test_complex() {
+        let cases = [
+            ("sqrt(-4)", Number::from((0, 2))),
+            (
+                "sqrt(-1/4)",
+                Number::complex(Number::Integer(0), Number::Rational(Rational64::new(1, 2))),
+            ),
+            ("root(2,-9)", Number::from((0, 3))),
+            ("(-4)^(1/2)", Number::from((0, 2))),
+            ("i*i", Number::Integer(-1)),
+            ("(1+i)^2", Number::from((0, 2))),
+            (
+                "(1+i)^-2",
+                Number::complex(Number::Integer(0), Number::Rational(Rational64::new(-1, 2))),
+            ),
+            ("(3+4i)/(1-2i)", Number::from((-1, 2))),
+            ("abs(3+4i)", Number::Integer(5)),
+            ("abs(1+i)", Number::Float(2.0_f64.sqrt())),
+            ("floor(5/2+7/2*i)", Number::from((2, 3))),
+            ("ln(-1)", Number::from((0.0, std::f64::consts::PI))),
+            (
+                "exp(i*pi)",
+                Number::from((-1.0, std::f64::consts::PI.sin())),
+            ),
+            ("(-8)^(1/3)", Number::Integer(-2)),
+            ("(-2)^(1/3)", Number::Float(-(2.0_f64.cbrt()))),
+            (
+                "avg(i,1)",
+                Number::complex(
+                    Number::Rational(Rational64::new(1, 2)),
+                    Number::Rational(Rational64::new(1, 2)),
+                ),
+            ),
+        ];
+        for (formula, expected) in cases {
+            let ast = Parser::new(formula).unwrap().parse().unwrap();
+            let value = eval(&ast, &Number::Integer(0), &HashMap::new()).unwrap();
+            assert_eq!(value, expected, "{}", formula);
+        }
+    }
+    #[test]
     fn test_expr1() {
-        let ast = Parser::new("1+2-3", None).unwrap().parse().unwrap();
-        let value = eval(ast).unwrap();
+        let ast = Parser::new("1+2-3").unwrap().parse().unwrap();
+        let value = eval(&ast, &Number::Integer(0), &HashMap::new()).unwrap();
         assert_eq!(value, Number::Integer(0));
     }
     #[test]
     fn test_expr2() {
-        let ast = Parser::new("3+2-1*5/4", None).unwrap().parse().unwrap();
-        let value = eval(ast).unwrap();
-        assert_eq!(value, Number::Float(3.75));
+        let ast = Parser::new("3+2-1*5/4").unwrap().parse().unwrap();
+        let value = eval(&ast, &Number::Integer(0), &HashMap::new()).unwrap();
+        assert_eq!(value, Number::Rational(Rational64::new(15, 4)));
     }
     #[test]
     fn test_expr3() {
-        let ast = Parser::new("5+(2*7-3!)*3", None).unwrap().parse().unwrap();
-        let value = eval(ast).unwrap();
+        let ast = Parser::new("5+(2*7-3!)*3").unwrap().parse().unwrap();
+        let value = eval(&ast, &Number::Integer(0), &HashMap::new()).unwrap();
         assert_eq!(value, Number::Integer(29));
     }
     #[test]
     fn test_expr4() {
-        let ast = Parser::new("2*4%3/2", None).unwrap().parse().unwrap();
-        let value = eval(ast).unwrap();
+        let ast = Parser::new("2*4%3/2").unwrap().parse().unwrap();
+        let value = eval(&ast, &Number::Integer(0), &HashMap::new()).unwrap();
         assert_eq!(value, Number::Integer(1));
     }
     #[test]
     fn test_expr5() {
-        let ast = Parser::new("3*2^3*3", None).unwrap().parse().unwrap();
-        let value = eval(ast).unwrap();
+        let ast = Parser::new("3*2^3*3").unwrap().parse().unwrap();
+        let value = eval(&ast, &Number::Integer(0), &HashMap::new()).unwrap();
         assert_eq!(value, Number::Integer(72));
     }
     #[test]
     fn test_expr6() {
-        let ast = Parser::new("2+3*atan2(3,7)", None)
-            .unwrap()
-            .parse()
-            .unwrap();
-        let value = eval(ast).unwrap();
-        assert_eq!(value, Number::Float(2.0 + 3.0 * (3.0 as f64).atan2(7.0)));
+        let ast = Parser::new("2+3*atan2(3,7)").unwrap().parse().unwrap();
+        let value = eval(&ast, &Number::Integer(0), &HashMap::new()).unwrap();
+        assert_eq!(value, Number::Float(2.0 + 3.0 * 3.0_f64.atan2(7.0)));
     }
     #[test]
     fn test_expr7() {
-        let ast = Parser::new("med(5,2,8,9,7)", None)
-            .unwrap()
-            .parse()
-            .unwrap();
-        let value = eval(ast).unwrap();
+        let ast = Parser::new("med(5,2,8,9,7)").unwrap().parse().unwrap();
+        let value = eval(&ast, &Number::Integer(0), &HashMap::new()).unwrap();
         assert_eq!(value, Number::Integer(7));
     }
     #[test]
     fn test_expr8() {
-        let ast = Parser::new("med(5,2,8,9)", None).unwrap().parse().unwrap();
-        let value = eval(ast).unwrap();
-        assert_eq!(value, Number::Float(6.5));
+        let ast = Parser::new("med(5,2,8,9)").unwrap().parse().unwrap();
+        let value = eval(&ast, &Number::Integer(0), &HashMap::new()).unwrap();
+        assert_eq!(value, Number::Rational(Rational64::new(13, 2)));
+    }
+    #[test]
+    fn test_eval_errors() {
+        let ast = Parser::new("1/0").unwrap().parse().unwrap();
+        assert_eq!(
+            eval(&ast, &Number::Integer(0), &HashMap::new()),
+            Err(EvalError::DivisionByZero)
+        );
+        for formula in ["0^(-1)", "0.0^(-1/2)", "root(-2, 0)"] {
+            let ast = Parser::new(formula).unwrap().parse().unwrap();
+            assert_eq!(
+                eval(&ast, &Number::Integer(0), &HashMap::new()),
+                Err(EvalError::DivisionByZero),
+                "{}",
+                formula
+            );
+        }
+        let ast = Parser::new("5%0").unwrap().parse().unwrap();
+        assert_eq!(
+            eval(&ast, &Number::Integer(0), &HashMap::new()),
+            Err(EvalError::DivisionByZero)
+        );
+        let ast = Parser::new("(1+i)%2").unwrap().parse().unwrap();
+        assert_eq!(
+            eval(&ast, &Number::Integer(0), &HashMap::new()),
+            Err(EvalError::Domain("mod".to_string()))
+        );
+        let ast = Parser::new("max(1,i)").unwrap().parse().unwrap();
+        assert_eq!(
+            eval(&ast, &Number::Integer(0), &HashMap::new()),
+            Err(EvalError::Domain("max".to_string()))
+        );
+        let ast = Parser::new("(-2)!").unwrap().parse().unwrap();
+        assert_eq!(
+            eval(&ast, &Number::Integer(0), &HashMap::new()),
+            Err(EvalError::NegativeFactorial)
+        );
+        let ast = Parser::new("lambert_w(1e400)").unwrap().parse().unwrap();
+        assert_eq!(
+            eval(&ast, &Number::Integer(0), &HashMap::new()),
+            Err(EvalError::Overflow)
+        );
+    }
+    #[test]
+    fn test_logic() {
+        for (formula, expected) in [
+            ("2<3", 1),
+            ("3<=2", 0),
+            ("2==2", 1),
+            ("2!=2", 0),
+            ("3>2", 1),
+            ("2>=3", 0),
+            ("1&&0", 0),
+            ("0||2", 1),
+            ("!0", 1),
+            ("!5", 0),
+            ("1<2?10:20", 10),
+            ("if(0,10,20)", 20),
+            ("0?1:0?2:3", 3),
+            ("0&&x", 0),
+            ("1||x", 1),
+            ("1?2:x", 2),
+            ("if(0,x,3)", 3),
+            ("1/2==0.5", 1),
+            ("1/3<0.34", 1),
+            ("(1+i)^2==2i", 1),
+        ] {
+            let ast = Parser::new(formula).unwrap().parse().unwrap();
+            assert_eq!(
+                eval(&ast, &Number::Integer(0), &HashMap::new()),
+                Ok(Number::Integer(expected)),
+                "{}",
+                formula
+            );
+        }
+        for (formula, error) in [
+            ("1?x:2", EvalError::UndefinedVariable("x".to_string())),
+            ("i>=1", EvalError::Domain(">=".to_string())),
+        ] {
+            let ast = Parser::new(formula).unwrap().parse().unwrap();
+            assert_eq!(eval(&ast, &Number::Integer(0), &HashMap::new()), Err(error));
+        }
     }
 }
@@ -0,0 +1,174 @@
+use super::ast::{eval, Node};
+use super::derive::derive;
+use super::parser::Parser;
+use super::simplify::simplify;
+use super::tokenizer::RESERVED_WORDS;
+use super::Number;
+use crate::utils::{latex, mathml, Context, EvalError, Formula, Functions, ParseError};
+use std::{collections::HashMap, fmt};
+
+/// A formula parsed once into a reusable AST and evaluated into Number as many times as needed.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExpressionNumber {
+    ast: Node,
+}
+
+impl ExpressionNumber {
+    /// Parse a formula without evaluating it.
+    pub fn new(expr: String) -> Result<Self, ParseError> {
+        let mut math_parser = Parser::new(&expr)?;
+        let ast = math_parser.parse()?;
+        Ok(ExpressionNumber { ast })
+    }
+    /// Parse a formula that can call the given user-defined functions.
+    pub fn with_functions(expr: String, functions: &Functions<Number>) -> Result<Self, ParseError> {
+        let mut math_parser = Parser::with_functions(&expr, functions)?;
+        let ast = math_parser.parse()?;
+        Ok(ExpressionNumber { ast })
+    }
+    /// Evaluate the parsed formula, `@` being replaced by the placeholder.
+    pub fn eval(&self, placeholder: Number) -> Result<Number, EvalError> {
+        self.eval_with_context(placeholder, &HashMap::<String, Number>::new())
+    }
+    /// Evaluate the parsed formula, resolving its named variables with the context.
+    pub fn eval_with_context(
+        &self,
+        placeholder: Number,
+        context: &dyn Context<Number>,
+    ) -> Result<Number, EvalError> {
+        eval(&self.ast, &placeholder, context)
+    }
+    /// Differentiate the formula with respect to the named variable, `@` and the other variables
+    /// being constants.
+    pub fn derive(&self, variable: &str) -> Result<Self, EvalError> {
+        Ok(ExpressionNumber {
+            ast: derive(&self.ast, variable)?,
+        })
+    }
+    /// Simplify the parsed formula, folding its constant parts and combining its like terms, so
+    /// that it evaluates faster.
+    pub fn simplify(&self) -> Self {
+        ExpressionNumber {
+            ast: simplify(&self.ast),
+        }
+    }
+    /// Write the parsed formula as LaTeX math, without the surrounding `$`.
+    pub fn to_latex(&self) -> String {
+        latex(&self.ast)
+    }
+    /// Write the parsed formula as a MathML `math` element.
+    pub fn to_mathml(&self) -> String {
+        mathml(&self.ast)
+    }
+}
+
+/// Writes the parsed formula back with as few parentheses as possible, as text that parses into
+/// the same formula.
+impl fmt::Display for ExpressionNumber {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.ast)
+    }
+}
+
+impl Formula for ExpressionNumber {
+    type Value = Number;
+
+    fn parse(expr: String, functions: Option<&Functions<Number>>) -> Result<Self, ParseError> {
+        match functions {
+            Some(functions) => ExpressionNumber::with_functions(expr, functions),
+            None => ExpressionNumber::new(expr),
+        }
+    }
+    fn evaluate(
+        &self,
+        placeholder: Number,
+        context: &dyn Context<Number>,
+    ) -> Result<Number, EvalError> {
+        self.eval_with_context(placeholder, context)
+    }
+    fn is_reserved(name: &str) -> bool {
+        RESERVED_WORDS.contains(&name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::Arity;
+    use crate::utils::ParseErrorKind;
+
+    #[test]
+    fn test_reuse() {
+        let expr = ExpressionNumber::new("@ / 2".to_string()).unwrap();
+        assert_eq!(expr.eval(Number::Integer(4)).unwrap(), Number::Integer(2));
+        assert_eq!(
+            expr.eval(Number::Integer(3)).unwrap(),
+            Number::Rational(num_rational::Rational64::new(3, 2))
+        );
+    }
+    #[test]
+    fn test_display() {
+        let display = |formula: &str| {
+            ExpressionNumber::new(formula.to_string())
+                .unwrap()
+                .eval(Number::Integer(0))
+                .unwrap()
+                .to_string()
+        };
+        assert_eq!(display("sqrt(-4)"), "2i");
+        assert_eq!(display("1-i"), "1-i");
+        assert_eq!(display("1/(1+i)"), "1/2-(1/2)i");
+        assert_eq!(display("0.5+2.5i"), "0.5+2.5i");
+    }
+    #[test]
+    fn test_invalid_expression() {
+        assert!(ExpressionNumber::new("2/".to_string()).is_err());
+    }
+    #[test]
+    fn test_error_span() {
+        let error = ExpressionNumber::new("3 + ) ".to_string()).unwrap_err();
+        assert_eq!(error.kind(), ParseErrorKind::UnexpectedToken);
+        assert_eq!(error.span(), 4..5);
+        assert_eq!(
+            error.to_string(),
+            "Unexpected ) at 4..5, expected a number, a variable, a function or ("
+        );
+        let error = ExpressionNumber::new("1.5e2.5".to_string()).unwrap_err();
+        assert_eq!(error.kind(), ParseErrorKind::InvalidNumber);
+        assert_eq!(error.span(), 0..7);
+        let error = ExpressionNumber::new("1.2.3".to_string()).unwrap_err();
+        assert_eq!(error.kind(), ParseErrorKind::InvalidNumber);
+        assert_eq!(error.span(), 0..5);
+        let error = ExpressionNumber::new("x + 2..5".to_string()).unwrap_err();
+        assert_eq!(error.kind(), ParseErrorKind::InvalidNumber);
+        assert_eq!(error.span(), 4..8);
+    }
+    #[test]
+    fn test_context() {
+        let expr = ExpressionNumber::new("a / b".to_string()).unwrap();
+        let mut context = HashMap::new();
+        context.insert("a".to_string(), Number::Integer(6));
+        context.insert("b".to_string(), Number::Integer(3));
+        assert_eq!(
+            expr.eval_with_context(Number::Integer(0), &context)
+                .unwrap(),
+            Number::Integer(2)
+        );
+    }
+    #[test]
+    fn test_unbound_variable() {
+        let expr = ExpressionNumber::new("a".to_string()).unwrap();
+        assert!(expr.eval(Number::Integer(0)).is_err());
+    }
+    #[test]
+    fn test_custom_functions() {
+        let mut functions = Functions::new();
+        functions.register("count", Arity::AtLeast(0), |args| {
+            Ok(Number::Integer(args.len() as i64))
+        });
+        let expr =
+            ExpressionNumber::with_functions("count() + count(1, 2.5, @)".to_string(), &functions)
+                .unwrap();
+        assert_eq!(expr.eval(Number::Integer(0)).unwrap(), Number::Integer(3));
+    }
+}
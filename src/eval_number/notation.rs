@@ -0,0 +1,181 @@
+use super::ast::Node;
+use super::Number;
+use crate::utils::{real_text, write_infix, Notation, Operator, OperatorCategory, Term, Unary};
+use std::fmt;
+
+/// Write a complex number with an infinite or NaN part, which its `Display` doesn't write as
+/// literals.
+fn complex_text(re: f64, im: f64) -> String {
+    let imaginary = format!("{}i", real_text(im.abs()));
+    if re == 0.0 {
+        format!("{}{}", if im < 0.0 { "-" } else { "" }, imaginary)
+    } else {
+        let sign = if im < 0.0 { '-' } else { '+' };
+        format!("{} {} {}", real_text(re), sign, imaginary)
+    }
+}
+
+/// Write a number as the literals the tokenizer reads, a float keeping its decimal point.
+fn literal<'a>(value: &Number) -> Term<'a, Node> {
+    match value {
+        Number::Float(value) if !value.is_finite() => Term::number(real_text(*value)),
+        Number::Float(value) if value.fract() == 0.0 => Term::number(format!("{}.0", value)),
+        Number::Integer(_) | Number::Float(_) => Term::number(value.to_string()),
+        Number::Rational(_) => Term::Literal(value.to_string(), OperatorCategory::Multiplicative),
+        Number::Complex(re, im) => {
+            let text = if re.to_f64().is_finite() && im.to_f64().is_finite() {
+                value.to_string()
+            } else {
+                complex_text(re.to_f64(), im.to_f64())
+            };
+            let category = if !re.is_zero() {
+                OperatorCategory::Additive
+            } else if text.starts_with('-') {
+                OperatorCategory::Negative
+            } else if text == "i" {
+                OperatorCategory::Functional
+            } else {
+                OperatorCategory::Multiplicative
+            };
+            Term::Literal(text, category)
+        }
+    }
+}
+
+impl Notation for Node {
+    fn term(&self) -> Term<'_, Self> {
+        use self::Node::*;
+        match self {
+            Num(value) => literal(value),
+            Variable(name) => Term::Variable(name),
+            Ans => Term::Ans,
+            Add(left, right) => Term::Binary(Operator::Add, left, right),
+            Subtract(left, right) => Term::Binary(Operator::Subtract, left, right),
+            Multiply(left, right) => Term::Binary(Operator::Multiply, left, right),
+            Divide(left, right) => Term::Binary(Operator::Divide, left, right),
+            Modulo(left, right) => Term::Binary(Operator::Modulo, left, right),
+            Atan2(left, right) => Term::Function("atan2", vec![left, right]),
+            Root(left, right) => Term::Function("root", vec![left, right]),
+            Pow(left, right) => Term::Binary(Operator::Pow, left, right),
+            Log(left, right) => Term::Function("log", vec![left, right]),
+            ILog(left, right) => Term::Function("ilog", vec![left, right]),
+            Negative(expr) => Term::Unary(Unary::Negative, expr),
+            Abs(expr) => Term::Function("abs", vec![expr]),
+            Floor(expr) => Term::Function("floor", vec![expr]),
+            Ceil(expr) => Term::Function("ceil", vec![expr]),
+            Round(expr) => Term::Function("round", vec![expr]),
+            Sin(expr) => Term::Function("sin", vec![expr]),
+            Cos(expr) => Term::Function("cos", vec![expr]),
+            Tan(expr) => Term::Function("tan", vec![expr]),
+            Sinh(expr) => Term::Function("sinh", vec![expr]),
+            Cosh(expr) => Term::Function("cosh", vec![expr]),
+            Tanh(expr) => Term::Function("tanh", vec![expr]),
+            Arsinh(expr) => Term::Function("arsinh", vec![expr]),
+            Arcosh(expr) => Term::Function("arcosh", vec![expr]),
+            Artanh(expr) => Term::Function("artanh", vec![expr]),
+            Asin(expr) => Term::Function("asin", vec![expr]),
+            Acos(expr) => Term::Function("acos", vec![expr]),
+            Atan(expr) => Term::Function("atan", vec![expr]),
+            Sqrt(expr) => Term::Function("sqrt", vec![expr]),
+            Ln(expr) => Term::Function("ln", vec![expr]),
+            Lb(expr) => Term::Function("lb", vec![expr]),
+            Exp(expr) => Term::Function("exp", vec![expr]),
+            Exp2(expr) => Term::Function("exp2", vec![expr]),
+            Truncate(expr) => Term::Function("trunc", vec![expr]),
+            Sign(expr) => Term::Function("sign", vec![expr]),
+            LambertW(expr) => Term::Function("lambert_w", vec![expr]),
+            Min(args) => Term::Function("min", args.iter().collect()),
+            Max(args) => Term::Function("max", args.iter().collect()),
+            Avg(args) => Term::Function("avg", args.iter().collect()),
+            Med(args) => Term::Function("med", args.iter().collect()),
+            Equal(left, right) => Term::Binary(Operator::Equal, left, right),
+            NotEqual(left, right) => Term::Binary(Operator::NotEqual, left, right),
+            Less(left, right) => Term::Binary(Operator::Less, left, right),
+            LessEqual(left, right) => Term::Binary(Operator::LessEqual, left, right),
+            Greater(left, right) => Term::Binary(Operator::Greater, left, right),
+            GreaterEqual(left, right) => Term::Binary(Operator::GreaterEqual, left, right),
+            LogicalAnd(left, right) => Term::Binary(Operator::LogicalAnd, left, right),
+            LogicalOr(left, right) => Term::Binary(Operator::LogicalOr, left, right),
+            LogicalNot(expr) => Term::Unary(Unary::LogicalNot, expr),
+            Factorial(expr) => Term::Factorial(expr),
+            Conditional(condition, then, otherwise) => {
+                Term::Conditional(condition, then, otherwise)
+            }
+            CustomFunction(function, args) => {
+                Term::Function(function.name(), args.iter().collect())
+            }
+        }
+    }
+}
+
+impl fmt::Display for Node {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write_infix(self, f)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::eval_number::ast::Node;
+    use crate::eval_number::parser::Parser;
+    use crate::eval_number::Number;
+    use crate::utils::{latex, mathml};
+    use num_rational::Rational64;
+
+    #[test]
+    fn test_literals() {
+        let pow = |base| {
+            Node::Pow(
+                Box::new(Node::Num(base)),
+                Box::new(Node::Variable("x".into())),
+            )
+        };
+        assert_eq!(pow(Number::Float(2.0)).to_string(), "2.0^x");
+        assert_eq!(pow(Number::Integer(-2)).to_string(), "(-2)^x");
+        assert_eq!(
+            pow(Number::Rational(Rational64::new(2, 3))).to_string(),
+            "(2/3)^x"
+        );
+        assert_eq!(pow(Number::from((1, 2))).to_string(), "(1+2i)^x");
+        assert_eq!(pow(Number::from((0, 1))).to_string(), "i^x");
+        assert_eq!(pow(Number::Float(f64::NAN)).to_string(), "(0 * 1e999)^x");
+        let infinite = Number::Complex(
+            Box::new(Number::Integer(1)),
+            Box::new(Number::Float(f64::NEG_INFINITY)),
+        );
+        assert_eq!(pow(infinite.clone()).to_string(), "(1 - 1e999i)^x");
+        assert!(Parser::new(&pow(infinite).to_string()).is_ok());
+    }
+    #[test]
+    fn test_latex_and_mathml() {
+        let number = |value| Node::Num(value);
+        assert_eq!(latex(&number(Number::Float(f64::INFINITY))), "\\infty");
+        assert_eq!(latex(&number(Number::from((1, 2)))), "1 + 2i");
+        assert_eq!(
+            mathml(&number(Number::from((0, -1)))),
+            "<math xmlns=\"http://www.w3.org/1998/Math/MathML\"><mrow><mo>&#x2212;</mo>\
+             <mi>i</mi></mrow></math>"
+        );
+    }
+    #[test]
+    fn test_round_trip() {
+        let formulas = [
+            "2.0+3*0.5-1e400+2i^2-9999999999999999999999999999999",
+            "sin(x)+cos(x)+tan(x)+sinh(x)+cosh(x)+tanh(x)+asin(x)+acos(x)+atan(x)+atan2(x,1)",
+            "arsinh(x)+arcosh(x)+artanh(x)+lambert_w(x)+ln(x)+lb(x)+exp(x)+exp2(x)+sqrt(x)",
+            "root(3,x)+ilog(x,2)+log(x,2)+min(x,1)+max(x,1)+avg(x,1)+med(x,1)+x%2+4!",
+            "abs(x)+floor(x)+ceil(x)+round(x)+trunc(x)+sign(x)",
+            "x>=1&&x!=2||!(x<=0)?1:2",
+        ];
+        for formula in formulas {
+            let ast = Parser::new(formula).unwrap().parse().unwrap();
+            let text = ast.to_string();
+            assert_eq!(
+                Parser::new(&text).unwrap().parse().unwrap(),
+                ast,
+                "{}",
+                text
+            );
+        }
+    }
+}
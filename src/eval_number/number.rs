@@ -1,8 +1,17 @@
+use num_complex::Complex64;
+use num_integer::Integer;
+use num_rational::Rational64;
+use std::cmp::Ordering;
+use std::fmt;
+
 #[derive(Debug, PartialEq, Clone)]
 pub enum Number {
     Float(f64),
     Integer(i64),
-    // Complex(Box<Number>, Box<Number>),
+    /// An exact fraction, never an integer, with a positive denominator.
+    Rational(Rational64),
+    /// A real and a non-zero imaginary part, neither of which is complex.
+    Complex(Box<Number>, Box<Number>),
 }
 
 impl From<i64> for Number {
@@ -11,11 +20,21 @@ impl From<i64> for Number {
     }
 }
 
-// impl From<(i64, i64)> for Number {
-//     fn from(value: (i64, i64)) -> Self {
-//       Number::Complex(Box::new(Number::Integer(value.0)), Box::new(Number::Integer(value.1)))
-//     }
-// }
+impl From<Rational64> for Number {
+    fn from(value: Rational64) -> Self {
+        if value.is_integer() {
+            Number::Integer(value.to_integer())
+        } else {
+            Number::Rational(value)
+        }
+    }
+}
+
+impl From<(i64, i64)> for Number {
+    fn from(value: (i64, i64)) -> Self {
+        Number::complex(Number::Integer(value.0), Number::Integer(value.1))
+    }
+}
 
 impl From<f64> for Number {
     fn from(value: f64) -> Self {
@@ -32,20 +51,138 @@ impl From<f64> for Number {
     }
 }
 
-// impl From<(f64, f64)> for Number {
-//     fn from(value: (f64, f64)) -> Self {
-//       Number::Complex(Box::new(Number::Float(value.0)), Box::new(Number::Float(value.1)))
-//     }
-// }
+impl From<(f64, f64)> for Number {
+    fn from(value: (f64, f64)) -> Self {
+        Number::complex(Number::from(value.0), Number::from(value.1))
+    }
+}
 
-// impl From<(i64, f64)> for Number {
-//     fn from(value: (i64, f64)) -> Self {
-//       Number::Complex(Box::new(Number::Integer(value.0)), Box::new(Number::Float(value.1)))
-//     }
-// }
+impl From<(i64, f64)> for Number {
+    fn from(value: (i64, f64)) -> Self {
+        Number::complex(Number::Integer(value.0), Number::from(value.1))
+    }
+}
 
-// impl From<(f64, i64)> for Number {
-//     fn from(value: (f64, i64)) -> Self {
-//       Number::Complex(Box::new(Number::Float(value.0)), Box::new(Number::Integer(value.1)))
-//     }
-// }
+impl From<(f64, i64)> for Number {
+    fn from(value: (f64, i64)) -> Self {
+        Number::complex(Number::from(value.0), Number::Integer(value.1))
+    }
+}
+
+impl From<Complex64> for Number {
+    fn from(value: Complex64) -> Self {
+        Number::from((value.re, value.im))
+    }
+}
+
+impl Number {
+    pub fn is_zero(&self) -> bool {
+        match self {
+            Number::Float(value) => *value == 0.0,
+            Number::Integer(value) => *value == 0,
+            Number::Rational(_) => false,
+            Number::Complex(re, im) => re.is_zero() && im.is_zero(),
+        }
+    }
+    /// The value as a float, which rounds a rational and is NaN for a complex number.
+    pub fn to_f64(&self) -> f64 {
+        match self {
+            Number::Float(value) => *value,
+            Number::Integer(value) => *value as f64,
+            Number::Rational(value) => *value.numer() as f64 / *value.denom() as f64,
+            Number::Complex(_, _) => f64::NAN,
+        }
+    }
+    /// The value as a complex float.
+    pub fn to_complex(&self) -> Complex64 {
+        match self {
+            Number::Complex(re, im) => Complex64::new(re.to_f64(), im.to_f64()),
+            value => Complex64::new(value.to_f64(), 0.0),
+        }
+    }
+    /// The complex number with these real and imaginary parts, which is real when the imaginary
+    /// part is zero.
+    pub(crate) fn complex(re: Number, im: Number) -> Number {
+        if im.is_zero() {
+            re
+        } else {
+            Number::Complex(Box::new(re), Box::new(im))
+        }
+    }
+    /// The real and the imaginary parts.
+    pub(crate) fn parts(&self) -> (Number, Number) {
+        match self {
+            Number::Complex(re, im) => (re.as_ref().clone(), im.as_ref().clone()),
+            value => (value.clone(), Number::Integer(0)),
+        }
+    }
+    /// The numerator and the positive denominator of an exact number, wide enough to be
+    /// multiplied together without overflowing.
+    pub(crate) fn to_fraction(&self) -> Option<(i128, i128)> {
+        match self {
+            Number::Float(_) | Number::Complex(_, _) => None,
+            Number::Integer(value) => Some((*value as i128, 1)),
+            Number::Rational(value) => Some((*value.numer() as i128, *value.denom() as i128)),
+        }
+    }
+    /// The integer or the rational equal to a fraction, if its denominator isn't zero and its
+    /// reduced form fits in i64.
+    pub(crate) fn from_fraction(numer: i128, denom: i128) -> Option<Number> {
+        if denom == 0 || numer == i128::MIN || denom == i128::MIN {
+            return None;
+        }
+        let gcd = numer.gcd(&denom);
+        let (mut numer, mut denom) = (numer / gcd, denom / gcd);
+        if denom < 0 {
+            numer = -numer;
+            denom = -denom;
+        }
+        let numer = i64::try_from(numer).ok()?;
+        let denom = i64::try_from(denom).ok()?;
+        if denom == 1 {
+            Some(Number::Integer(numer))
+        } else {
+            Some(Number::Rational(Rational64::new_raw(numer, denom)))
+        }
+    }
+    /// Compare two numbers by value, exactly unless one of them is a float.
+    pub(crate) fn compare(&self, other: &Self) -> Option<Ordering> {
+        match (self.to_fraction(), other.to_fraction()) {
+            (Some((a_numer, a_denom)), Some((b_numer, b_denom))) => {
+                Some((a_numer * b_denom).cmp(&(b_numer * a_denom)))
+            }
+            _ => self.to_f64().partial_cmp(&other.to_f64()),
+        }
+    }
+}
+
+impl fmt::Display for Number {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Number::Float(value) => write!(f, "{}", value),
+            Number::Integer(value) => write!(f, "{}", value),
+            Number::Rational(value) => write!(f, "{}", value),
+            Number::Complex(re, im) => {
+                if !re.is_zero() {
+                    write!(f, "{}", re)?;
+                }
+                let negative = im.to_f64().is_sign_negative();
+                if negative {
+                    write!(f, "-")?;
+                } else if !re.is_zero() {
+                    write!(f, "+")?;
+                }
+                match im.as_ref() {
+                    Number::Integer(value) if value.unsigned_abs() == 1 => {}
+                    Number::Integer(value) => write!(f, "{}", value.unsigned_abs())?,
+                    Number::Float(value) => write!(f, "{}", value.abs())?,
+                    Number::Rational(value) => {
+                        write!(f, "({}/{})", value.numer().unsigned_abs(), value.denom())?
+                    }
+                    im => write!(f, "({})", im)?,
+                }
+                write!(f, "i")
+            }
+        }
+    }
+}
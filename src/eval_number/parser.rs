@@ -1,60 +1,96 @@
-use std::sync::Arc;
+use std::{ops::Range, sync::Arc};
 
 use super::ast::Node;
 use super::token::{NativeFunction, Token};
 use super::tokenizer::Tokenizer;
 use super::Number;
-use crate::utils::{OperatorCategory, ParseError};
+use crate::utils::{
+    too_deep, Arity, Functions, OperatorCategory, ParseError, ParseErrorKind, MAX_DEPTH,
+};
 
 pub struct Parser<'a> {
     tokenizer: Tokenizer<'a>,
     current_token: Token,
+    current_span: Range<usize>,
     previous_token: Option<Token>,
-    placeholder: Number,
+    depth: usize,
 }
 
 impl<'a> Parser<'a> {
-    pub fn new(expr: &'a str, placeholder: Option<Number>) -> Result<Self, ParseError> {
-        let mut lexer = Tokenizer::new(expr);
+    pub fn new(expr: &'a str) -> Result<Self, ParseError> {
+        Parser::from_tokenizer(Tokenizer::new(expr))
+    }
+    pub fn with_functions(
+        expr: &'a str,
+        functions: &'a Functions<Number>,
+    ) -> Result<Self, ParseError> {
+        Parser::from_tokenizer(Tokenizer::with_functions(expr, functions))
+    }
+    fn from_tokenizer(mut lexer: Tokenizer<'a>) -> Result<Self, ParseError> {
+        lexer.skip_whitespace();
+        let start = lexer.position();
         let cur_token = match lexer.next() {
             Some(token) => token,
-            None => return Err(ParseError::InvalidOperator("Invalid character".into())),
+            None => return Err(lexer.error(start)),
         };
         Ok(Parser {
+            current_span: start..lexer.position(),
             tokenizer: lexer,
             current_token: cur_token,
             previous_token: None,
-            placeholder: placeholder.unwrap_or(Number::Integer(0)),
+            depth: 0,
         })
     }
     pub fn parse(&mut self) -> Result<Node, ParseError> {
-        let ast = self.generate_ast(OperatorCategory::DefaultZero);
-        match ast {
-            Ok(ast) => Ok(ast),
-            Err(e) => Err(e),
+        let ast = self.generate_ast(OperatorCategory::DefaultZero)?;
+        if self.current_token == Token::Eof {
+            Ok(ast)
+        } else if self.current_token.is_closing_bracket() {
+            Err(ParseError::new(
+                ParseErrorKind::UnbalancedBracket,
+                self.current_span.clone(),
+                format!("Unmatched {}", self.current_token),
+            ))
+        } else {
+            Err(self.unexpected_token(&["an operator", "end of input"]))
         }
     }
     fn get_next_token(&mut self) -> Result<(), ParseError> {
+        self.tokenizer.skip_whitespace();
+        let start = self.tokenizer.position();
         let next_token = match self.tokenizer.next() {
             Some(token) => token,
-            None => return Err(ParseError::InvalidOperator("Invalid character".into())),
+            None => return Err(self.tokenizer.error(start)),
         };
         self.previous_token = Some(self.current_token.clone());
         self.current_token = next_token;
+        self.current_span = start..self.tokenizer.position();
         Ok(())
     }
     fn generate_ast(&mut self, oper_prec: OperatorCategory) -> Result<Node, ParseError> {
+        let depth = self.depth;
+        self.nest()?;
         let mut left_expr = self.parse_number()?;
         while oper_prec < self.current_token.get_oper_prec() {
             if self.current_token == Token::Eof {
                 break;
             }
-            let right_expr = self.convert_token_to_node(left_expr.clone())?;
-            left_expr = right_expr;
+            self.nest()?;
+            left_expr = self.convert_token_to_node(left_expr)?;
         }
+        self.depth = depth;
         Ok(left_expr)
     }
-    fn function_static_arguments(&mut self, n: i32) -> Result<Vec<Node>, ParseError> {
+    fn nest(&mut self) -> Result<(), ParseError> {
+        self.depth += 1;
+        if self.depth > MAX_DEPTH {
+            return Err(too_deep(self.current_span.clone()));
+        }
+        Ok(())
+    }
+    fn function_static_arguments(&mut self, n: usize) -> Result<Vec<Node>, ParseError> {
+        let function = self.current_token.clone();
+        let function_span = self.current_span.clone();
         self.get_next_token()?;
         self.check_paren(Token::LeftParen)?;
         let mut args = Vec::new();
@@ -62,9 +98,15 @@ impl<'a> Parser<'a> {
             let arg_expr = self.generate_ast(OperatorCategory::DefaultZero)?;
             args.push(arg_expr);
             if i < n - 1 {
+                if self.current_token == Token::RightParen {
+                    return Err(wrong_arity(&function, function_span, n));
+                }
                 self.check_paren(Token::Comma)?;
             }
         }
+        if self.current_token == Token::Comma {
+            return Err(wrong_arity(&function, function_span, n));
+        }
         self.check_paren(Token::RightParen)?;
         Ok(args)
     }
@@ -97,12 +139,7 @@ impl<'a> Parser<'a> {
                 self.get_next_token()?;
                 break;
             } else {
-                return Err(ParseError::InvalidOperator(format!(
-                    "Expected either {:?} or {:?}, got {:?}",
-                    Token::Comma,
-                    end_token,
-                    self.current_token
-                )));
+                return Err(self.missing_token(&[Token::Comma, end_token]));
             }
         }
         Ok(args)
@@ -112,9 +149,36 @@ impl<'a> Parser<'a> {
         match token {
             Token::Ans => {
                 self.get_next_token()?;
-                Ok(Node::Num(self.placeholder.clone()))
+                Ok(Node::Ans)
+            }
+            Token::Identifier(name) => {
+                self.get_next_token()?;
+                self.implicit_multiply(Node::Variable(name))
+            }
+            Token::CustomFunction(function) => {
+                let function_span = self.current_span.clone();
+                let args = match function.arity() {
+                    Arity::Exactly(n) => self.function_static_arguments(n)?,
+                    Arity::AtLeast(n) => {
+                        let args = self.function_arguments()?;
+                        if args.len() < n {
+                            return Err(ParseError::new(
+                                ParseErrorKind::WrongArity,
+                                function_span,
+                                format!(
+                                    "The {} function takes at least {} arguments",
+                                    function.name(),
+                                    n
+                                ),
+                            ));
+                        }
+                        args
+                    }
+                };
+                self.implicit_multiply(Node::CustomFunction(function, Arc::new(args)))
             }
             Token::ExplicitFunction(current_function) => {
+                let function_span = self.current_span.clone();
                 let current_function = match current_function {
                     NativeFunction::Abs => {
                         Node::Abs(Box::new(self.function_static_arguments(1)?[0].clone()))
@@ -215,7 +279,9 @@ impl<'a> Parser<'a> {
                     NativeFunction::Min => {
                         let args = self.function_arguments()?;
                         if args.is_empty() {
-                            return Err(ParseError::UnableToParse(
+                            return Err(ParseError::new(
+                                ParseErrorKind::WrongArity,
+                                function_span,
                                 "There's no arguments in the min function".to_string(),
                             ));
                         }
@@ -224,7 +290,9 @@ impl<'a> Parser<'a> {
                     NativeFunction::Max => {
                         let args = self.function_arguments()?;
                         if args.is_empty() {
-                            return Err(ParseError::UnableToParse(
+                            return Err(ParseError::new(
+                                ParseErrorKind::WrongArity,
+                                function_span,
                                 "There's no arguments in the max function".to_string(),
                             ));
                         }
@@ -241,13 +309,23 @@ impl<'a> Parser<'a> {
                     NativeFunction::Med => {
                         let args = self.function_arguments()?;
                         if args.is_empty() {
-                            return Err(ParseError::UnableToParse(
+                            return Err(ParseError::new(
+                                ParseErrorKind::WrongArity,
+                                function_span,
                                 "Cannot compute the median of no arguments".to_string(),
                             ));
                         } else {
                             Node::Med(Arc::new(args))
                         }
                     }
+                    NativeFunction::If => {
+                        let args = self.function_static_arguments(3)?;
+                        Node::Conditional(
+                            Box::new(args[0].clone()),
+                            Box::new(args[1].clone()),
+                            Box::new(args[2].clone()),
+                        )
+                    }
                 };
                 self.implicit_multiply(current_function)
             }
@@ -261,17 +339,29 @@ impl<'a> Parser<'a> {
                 let expr = self.generate_ast(OperatorCategory::Negative)?;
                 Ok(expr)
             }
+            Token::ExclamationMark => {
+                self.get_next_token()?;
+                let expr = self.generate_ast(OperatorCategory::Negative)?;
+                Ok(Node::LogicalNot(Box::new(expr)))
+            }
             Token::Num(i) => {
+                let end = self.current_span.end;
                 self.get_next_token()?;
+                if let Token::Num(_) = self.current_token {
+                    if self.current_span.start > end {
+                        // Numbers separated by whitespace are not multiplied.
+                        return Err(self.unexpected_token(&["an operator"]));
+                    }
+                }
                 self.implicit_multiply(Node::Num(i))
             }
             Token::Pi => {
                 self.get_next_token()?;
-                Ok(Node::Num(Number::Float(std::f64::consts::PI)))
+                self.implicit_multiply(Node::Num(Number::Float(std::f64::consts::PI)))
             }
             Token::E => {
                 self.get_next_token()?;
-                Ok(Node::Num(Number::Float(std::f64::consts::E)))
+                self.implicit_multiply(Node::Num(Number::Float(std::f64::consts::E)))
             }
             Token::LeftParen => self.get_enclosed_elements_with_impl_mult(
                 OperatorCategory::DefaultZero,
@@ -288,9 +378,7 @@ impl<'a> Parser<'a> {
                 Token::RightCeiling,
                 |expr| Node::Ceil(Box::new(expr)),
             ),
-            _ => Err(ParseError::UnableToParse(
-                "Unknown parsing token for parsing number".to_string(),
-            )),
+            _ => Err(self.unexpected_token(&["a number", "a variable", "a function", "("])),
         }
     }
     fn implicit_multiply(&mut self, node: Node) -> Result<Node, ParseError> {
@@ -298,7 +386,11 @@ impl<'a> Parser<'a> {
             || (self.current_token == Token::LeftCeiling)
             || (self.current_token == Token::LeftFloor)
             || matches!(self.current_token, Token::ExplicitFunction(_))
+            || matches!(self.current_token, Token::CustomFunction(_))
             || matches!(self.current_token, Token::Num(_))
+            || matches!(self.current_token, Token::Identifier(_))
+            || self.current_token == Token::Pi
+            || self.current_token == Token::E
         {
             let right = self.generate_ast(OperatorCategory::Multiplicative)?;
             return Ok(Node::Multiply(Box::new(node), Box::new(right)));
@@ -316,15 +408,32 @@ impl<'a> Parser<'a> {
         self.check_paren(end_token)?;
         self.implicit_multiply(get_node(expr))
     }
+    fn unexpected_token<S: ToString>(&self, expected: &[S]) -> ParseError {
+        ParseError::new(
+            ParseErrorKind::UnexpectedToken,
+            self.current_span.clone(),
+            format!("Unexpected {}", self.current_token),
+        )
+        .with_expected(expected)
+    }
+    fn missing_token(&self, expected: &[Token]) -> ParseError {
+        let unclosed = self.current_token == Token::Eof || self.current_token.is_closing_bracket();
+        if !(unclosed && expected.iter().any(Token::is_closing_bracket)) {
+            return self.unexpected_token(expected);
+        }
+        ParseError::new(
+            ParseErrorKind::UnbalancedBracket,
+            self.current_span.clone(),
+            format!("Unexpected {}", self.current_token),
+        )
+        .with_expected(expected)
+    }
     fn check_paren(&mut self, expected: Token) -> Result<(), ParseError> {
         if expected == self.current_token {
             self.get_next_token()?;
             Ok(())
         } else {
-            Err(ParseError::InvalidOperator(format!(
-                "Expected {:?}, got {:?}",
-                expected, self.current_token
-            )))
+            Err(self.missing_token(&[expected]))
         }
     }
     fn convert_token_to_node(&mut self, left_expr: Node) -> Result<Node, ParseError> {
@@ -351,7 +460,8 @@ impl<'a> Parser<'a> {
             }
             Token::Caret => {
                 self.get_next_token()?;
-                let right_expr = self.generate_ast(OperatorCategory::Power)?;
+                // Right-associative: the exponent is itself a power, optionally negated.
+                let right_expr = self.generate_ast(OperatorCategory::Negative)?;
                 Ok(Node::Pow(Box::new(left_expr), Box::new(right_expr)))
             }
             Token::ExclamationMark => {
@@ -381,14 +491,74 @@ impl<'a> Parser<'a> {
                 let right_expr = self.generate_ast(OperatorCategory::Multiplicative)?;
                 Ok(Node::Modulo(Box::new(left_expr), Box::new(right_expr)))
             }
-            _ => Err(ParseError::InvalidOperator(format!(
-                "Please enter a valid operator {:?}",
-                self.current_token
-            ))),
+            Token::Equal => {
+                self.get_next_token()?;
+                let right_expr = self.generate_ast(OperatorCategory::Comparison)?;
+                Ok(Node::Equal(Box::new(left_expr), Box::new(right_expr)))
+            }
+            Token::NotEqual => {
+                self.get_next_token()?;
+                let right_expr = self.generate_ast(OperatorCategory::Comparison)?;
+                Ok(Node::NotEqual(Box::new(left_expr), Box::new(right_expr)))
+            }
+            Token::Less => {
+                self.get_next_token()?;
+                let right_expr = self.generate_ast(OperatorCategory::Comparison)?;
+                Ok(Node::Less(Box::new(left_expr), Box::new(right_expr)))
+            }
+            Token::LessEqual => {
+                self.get_next_token()?;
+                let right_expr = self.generate_ast(OperatorCategory::Comparison)?;
+                Ok(Node::LessEqual(Box::new(left_expr), Box::new(right_expr)))
+            }
+            Token::Greater => {
+                self.get_next_token()?;
+                let right_expr = self.generate_ast(OperatorCategory::Comparison)?;
+                Ok(Node::Greater(Box::new(left_expr), Box::new(right_expr)))
+            }
+            Token::GreaterEqual => {
+                self.get_next_token()?;
+                let right_expr = self.generate_ast(OperatorCategory::Comparison)?;
+                Ok(Node::GreaterEqual(
+                    Box::new(left_expr),
+                    Box::new(right_expr),
+                ))
+            }
+            Token::DoubleAmpersand => {
+                self.get_next_token()?;
+                let right_expr = self.generate_ast(OperatorCategory::LogicalAnd)?;
+                Ok(Node::LogicalAnd(Box::new(left_expr), Box::new(right_expr)))
+            }
+            Token::DoubleBar => {
+                self.get_next_token()?;
+                let right_expr = self.generate_ast(OperatorCategory::LogicalOr)?;
+                Ok(Node::LogicalOr(Box::new(left_expr), Box::new(right_expr)))
+            }
+            Token::QuestionMark => {
+                self.get_next_token()?;
+                let then_expr = self.generate_ast(OperatorCategory::DefaultZero)?;
+                self.check_paren(Token::Colon)?;
+                // Right-associative: the else branch can itself be a conditional.
+                let else_expr = self.generate_ast(OperatorCategory::DefaultZero)?;
+                Ok(Node::Conditional(
+                    Box::new(left_expr),
+                    Box::new(then_expr),
+                    Box::new(else_expr),
+                ))
+            }
+            _ => Err(self.unexpected_token(&["an operator"])),
         }
     }
 }
 
+fn wrong_arity(function: &Token, function_span: Range<usize>, n: usize) -> ParseError {
+    ParseError::new(
+        ParseErrorKind::WrongArity,
+        function_span,
+        format!("The {} function takes {} arguments", function, n),
+    )
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -396,25 +566,25 @@ mod tests {
 
     #[test]
     fn test_pi() {
-        let mut parser = Parser::new("pi", None).unwrap();
+        let mut parser = Parser::new("pi").unwrap();
         let expected = Num(Number::Float(std::f64::consts::PI));
         assert_eq!(parser.parse().unwrap(), expected);
     }
     #[test]
     fn test_e() {
-        let mut parser = Parser::new("e", None).unwrap();
+        let mut parser = Parser::new("e").unwrap();
         let expected = Num(Number::Float(std::f64::consts::E));
         assert_eq!(parser.parse().unwrap(), expected);
     }
     #[test]
     fn test_negative() {
-        let mut parser = Parser::new("-1", None).unwrap();
+        let mut parser = Parser::new("-1").unwrap();
         let expected = Negative(Box::new(Num(Number::Integer(1))));
         assert_eq!(parser.parse().unwrap(), expected);
     }
     #[test]
     fn test_add() {
-        let mut parser = Parser::new("1+2", None).unwrap();
+        let mut parser = Parser::new("1+2").unwrap();
         let expected = Add(
             Box::new(Num(Number::Integer(1))),
             Box::new(Num(Number::Integer(2))),
@@ -423,7 +593,7 @@ mod tests {
     }
     #[test]
     fn test_subtract() {
-        let mut parser = Parser::new("1-2", None).unwrap();
+        let mut parser = Parser::new("1-2").unwrap();
         let expected = Subtract(
             Box::new(Num(Number::Integer(1))),
             Box::new(Num(Number::Integer(2))),
@@ -432,7 +602,7 @@ mod tests {
     }
     #[test]
     fn test_multiply() {
-        let mut parser = Parser::new("1*2", None).unwrap();
+        let mut parser = Parser::new("1*2").unwrap();
         let expected = Multiply(
             Box::new(Num(Number::Integer(1))),
             Box::new(Num(Number::Integer(2))),
@@ -441,7 +611,7 @@ mod tests {
     }
     #[test]
     fn test_divide() {
-        let mut parser = Parser::new("1/2", None).unwrap();
+        let mut parser = Parser::new("1/2").unwrap();
         let expected = Divide(
             Box::new(Num(Number::Integer(1))),
             Box::new(Num(Number::Integer(2))),
@@ -450,7 +620,7 @@ mod tests {
     }
     #[test]
     fn test_modulo() {
-        let mut parser = Parser::new("1%2", None).unwrap();
+        let mut parser = Parser::new("1%2").unwrap();
         let expected = Modulo(
             Box::new(Num(Number::Integer(1))),
             Box::new(Num(Number::Integer(2))),
@@ -459,7 +629,7 @@ mod tests {
     }
     #[test]
     fn test_caret() {
-        let mut parser = Parser::new("1^2", None).unwrap();
+        let mut parser = Parser::new("1^2").unwrap();
         let expected = Pow(
             Box::new(Num(Number::Integer(1))),
             Box::new(Num(Number::Integer(2))),
@@ -467,14 +637,51 @@ mod tests {
         assert_eq!(parser.parse().unwrap(), expected);
     }
     #[test]
+    fn test_caret_right_associative() {
+        let mut parser = Parser::new("2^3^2").unwrap();
+        let expected = Pow(
+            Box::new(Num(Number::Integer(2))),
+            Box::new(Pow(
+                Box::new(Num(Number::Integer(3))),
+                Box::new(Num(Number::Integer(2))),
+            )),
+        );
+        assert_eq!(parser.parse().unwrap(), expected);
+        let mut parser = Parser::new("2^3²").unwrap();
+        assert_eq!(parser.parse().unwrap(), expected);
+    }
+    #[test]
+    fn test_negative_power() {
+        let mut parser = Parser::new("-2^2").unwrap();
+        let expected = Negative(Box::new(Pow(
+            Box::new(Num(Number::Integer(2))),
+            Box::new(Num(Number::Integer(2))),
+        )));
+        assert_eq!(parser.parse().unwrap(), expected);
+        let mut parser = Parser::new("-2²").unwrap();
+        assert_eq!(parser.parse().unwrap(), expected);
+        let mut parser = Parser::new("2^-2").unwrap();
+        let expected = Pow(
+            Box::new(Num(Number::Integer(2))),
+            Box::new(Negative(Box::new(Num(Number::Integer(2))))),
+        );
+        assert_eq!(parser.parse().unwrap(), expected);
+        let mut parser = Parser::new("-2*3").unwrap();
+        let expected = Multiply(
+            Box::new(Negative(Box::new(Num(Number::Integer(2))))),
+            Box::new(Num(Number::Integer(3))),
+        );
+        assert_eq!(parser.parse().unwrap(), expected);
+    }
+    #[test]
     fn test_exclamation_mark() {
-        let mut parser = Parser::new("1!", None).unwrap();
+        let mut parser = Parser::new("1!").unwrap();
         let expected = Factorial(Box::new(Num(Number::Integer(1))));
         assert_eq!(parser.parse().unwrap(), expected);
     }
     #[test]
     fn test_deg_to_rad() {
-        let mut parser = Parser::new("1°", None).unwrap();
+        let mut parser = Parser::new("1°").unwrap();
         let expected = Multiply(
             Box::new(Num(Number::Integer(1))),
             Box::new(Num(Number::Float(0.017453292519943295))),
@@ -483,7 +690,7 @@ mod tests {
     }
     #[test]
     fn test_rad_to_deg() {
-        let mut parser = Parser::new("1rad", None).unwrap();
+        let mut parser = Parser::new("1rad").unwrap();
         let expected = Multiply(
             Box::new(Num(Number::Integer(1))),
             Box::new(Num(Number::Float(57.2957795131))),
@@ -492,19 +699,19 @@ mod tests {
     }
     #[test]
     fn test_floor() {
-        let mut parser = Parser::new("⌊5.25⌋", None).unwrap();
+        let mut parser = Parser::new("⌊5.25⌋").unwrap();
         let expected = Floor(Box::new(Num(Number::Float(5.25))));
         assert_eq!(parser.parse().unwrap(), expected);
     }
     #[test]
     fn test_ceil() {
-        let mut parser = Parser::new("⌈5.25⌉", None).unwrap();
+        let mut parser = Parser::new("⌈5.25⌉").unwrap();
         let expected = Ceil(Box::new(Num(Number::Float(5.25))));
         assert_eq!(parser.parse().unwrap(), expected);
     }
     #[test]
     fn test_modulo_function() {
-        let mut parser = Parser::new("mod(3,2)", None).unwrap();
+        let mut parser = Parser::new("mod(3,2)").unwrap();
         let expected = Modulo(
             Box::new(Num(Number::Integer(3))),
             Box::new(Num(Number::Integer(2))),
@@ -513,7 +720,7 @@ mod tests {
     }
     #[test]
     fn test_pow_function() {
-        let mut parser = Parser::new("pow(3,2)", None).unwrap();
+        let mut parser = Parser::new("pow(3,2)").unwrap();
         let expected = Pow(
             Box::new(Num(Number::Integer(3))),
             Box::new(Num(Number::Integer(2))),
@@ -522,7 +729,7 @@ mod tests {
     }
     #[test]
     fn test_log_function() {
-        let mut parser = Parser::new("log(3,2)", None).unwrap();
+        let mut parser = Parser::new("log(3,2)").unwrap();
         let expected = Log(
             Box::new(Num(Number::Integer(3))),
             Box::new(Num(Number::Integer(2))),
@@ -531,67 +738,67 @@ mod tests {
     }
     #[test]
     fn test_abs_function() {
-        let mut parser = Parser::new("abs(5.25)", None).unwrap();
+        let mut parser = Parser::new("abs(5.25)").unwrap();
         let expected = Abs(Box::new(Num(Number::Float(5.25))));
         assert_eq!(parser.parse().unwrap(), expected);
     }
     #[test]
     fn test_floor_function() {
-        let mut parser = Parser::new("floor(5.25)", None).unwrap();
+        let mut parser = Parser::new("floor(5.25)").unwrap();
         let expected = Floor(Box::new(Num(Number::Float(5.25))));
         assert_eq!(parser.parse().unwrap(), expected);
     }
     #[test]
     fn test_ceil_function() {
-        let mut parser = Parser::new("ceil(5.25)", None).unwrap();
+        let mut parser = Parser::new("ceil(5.25)").unwrap();
         let expected = Ceil(Box::new(Num(Number::Float(5.25))));
         assert_eq!(parser.parse().unwrap(), expected);
     }
     #[test]
     fn test_round_function() {
-        let mut parser = Parser::new("round(5.25)", None).unwrap();
+        let mut parser = Parser::new("round(5.25)").unwrap();
         let expected = Round(Box::new(Num(Number::Float(5.25))));
         assert_eq!(parser.parse().unwrap(), expected);
     }
     #[test]
     fn test_truncate_function() {
-        let mut parser = Parser::new("truncate(5.25)", None).unwrap();
+        let mut parser = Parser::new("truncate(5.25)").unwrap();
         let expected = Truncate(Box::new(Num(Number::Float(5.25))));
         assert_eq!(parser.parse().unwrap(), expected);
     }
     #[test]
     fn test_sqrt_function() {
-        let mut parser = Parser::new("sqrt(5.25)", None).unwrap();
+        let mut parser = Parser::new("sqrt(5.25)").unwrap();
         let expected = Sqrt(Box::new(Num(Number::Float(5.25))));
         assert_eq!(parser.parse().unwrap(), expected);
     }
     #[test]
     fn test_exp_function() {
-        let mut parser = Parser::new("exp(5.25)", None).unwrap();
+        let mut parser = Parser::new("exp(5.25)").unwrap();
         let expected = Exp(Box::new(Num(Number::Float(5.25))));
         assert_eq!(parser.parse().unwrap(), expected);
     }
     #[test]
     fn test_exp2_function() {
-        let mut parser = Parser::new("exp2(5.25)", None).unwrap();
+        let mut parser = Parser::new("exp2(5.25)").unwrap();
         let expected = Exp2(Box::new(Num(Number::Float(5.25))));
         assert_eq!(parser.parse().unwrap(), expected);
     }
     #[test]
     fn test_ln_function() {
-        let mut parser = Parser::new("ln(5.25)", None).unwrap();
+        let mut parser = Parser::new("ln(5.25)").unwrap();
         let expected = Ln(Box::new(Num(Number::Float(5.25))));
         assert_eq!(parser.parse().unwrap(), expected);
     }
     #[test]
     fn test_lb_function() {
-        let mut parser = Parser::new("lb(5.25)", None).unwrap();
+        let mut parser = Parser::new("lb(5.25)").unwrap();
         let expected = Lb(Box::new(Num(Number::Float(5.25))));
         assert_eq!(parser.parse().unwrap(), expected);
     }
     #[test]
     fn test_ilog_function() {
-        let mut parser = Parser::new("ilog(.14159,e)", None).unwrap();
+        let mut parser = Parser::new("ilog(.14159,e)").unwrap();
         let expected = ILog(
             Box::new(Num(Number::Float(0.14159))),
             Box::new(Num(Number::Float(std::f64::consts::E))),
@@ -600,73 +807,73 @@ mod tests {
     }
     #[test]
     fn test_lambert_w_function() {
-        let mut parser = Parser::new("w(5.25)", None).unwrap();
+        let mut parser = Parser::new("w(5.25)").unwrap();
         let expected = LambertW(Box::new(Num(Number::Float(5.25))));
         assert_eq!(parser.parse().unwrap(), expected);
     }
     #[test]
     fn test_sign_function() {
-        let mut parser = Parser::new("sign(5.25)", None).unwrap();
+        let mut parser = Parser::new("sign(5.25)").unwrap();
         let expected = Sign(Box::new(Num(Number::Float(5.25))));
         assert_eq!(parser.parse().unwrap(), expected);
     }
     #[test]
     fn test_sin_function() {
-        let mut parser = Parser::new("sin(5.25)", None).unwrap();
+        let mut parser = Parser::new("sin(5.25)").unwrap();
         let expected = Sin(Box::new(Num(Number::Float(5.25))));
         assert_eq!(parser.parse().unwrap(), expected);
     }
     #[test]
     fn test_cos_function() {
-        let mut parser = Parser::new("cos(5.25)", None).unwrap();
+        let mut parser = Parser::new("cos(5.25)").unwrap();
         let expected = Cos(Box::new(Num(Number::Float(5.25))));
         assert_eq!(parser.parse().unwrap(), expected);
     }
     #[test]
     fn test_tan_function() {
-        let mut parser = Parser::new("tan(5.25)", None).unwrap();
+        let mut parser = Parser::new("tan(5.25)").unwrap();
         let expected = Tan(Box::new(Num(Number::Float(5.25))));
         assert_eq!(parser.parse().unwrap(), expected);
     }
     #[test]
     fn test_sinh_function() {
-        let mut parser = Parser::new("sinh(5.25)", None).unwrap();
+        let mut parser = Parser::new("sinh(5.25)").unwrap();
         let expected = Sinh(Box::new(Num(Number::Float(5.25))));
         assert_eq!(parser.parse().unwrap(), expected);
     }
     #[test]
     fn test_cosh_function() {
-        let mut parser = Parser::new("cosh(5.25)", None).unwrap();
+        let mut parser = Parser::new("cosh(5.25)").unwrap();
         let expected = Cosh(Box::new(Num(Number::Float(5.25))));
         assert_eq!(parser.parse().unwrap(), expected);
     }
     #[test]
     fn test_tanh_function() {
-        let mut parser = Parser::new("tanh(5.25)", None).unwrap();
+        let mut parser = Parser::new("tanh(5.25)").unwrap();
         let expected = Tanh(Box::new(Num(Number::Float(5.25))));
         assert_eq!(parser.parse().unwrap(), expected);
     }
     #[test]
     fn test_asin_function() {
-        let mut parser = Parser::new("asin(5.25)", None).unwrap();
+        let mut parser = Parser::new("asin(5.25)").unwrap();
         let expected = Asin(Box::new(Num(Number::Float(5.25))));
         assert_eq!(parser.parse().unwrap(), expected);
     }
     #[test]
     fn test_acos_function() {
-        let mut parser = Parser::new("acos(5.25)", None).unwrap();
+        let mut parser = Parser::new("acos(5.25)").unwrap();
         let expected = Acos(Box::new(Num(Number::Float(5.25))));
         assert_eq!(parser.parse().unwrap(), expected);
     }
     #[test]
     fn test_atan_function() {
-        let mut parser = Parser::new("atan(5.25)", None).unwrap();
+        let mut parser = Parser::new("atan(5.25)").unwrap();
         let expected = Atan(Box::new(Num(Number::Float(5.25))));
         assert_eq!(parser.parse().unwrap(), expected);
     }
     #[test]
     fn test_atan2_function() {
-        let mut parser = Parser::new("atan2(5.25,7.8)", None).unwrap();
+        let mut parser = Parser::new("atan2(5.25,7.8)").unwrap();
         let expected = Atan2(
             Box::new(Num(Number::Float(5.25))),
             Box::new(Num(Number::Float(7.8))),
@@ -675,31 +882,31 @@ mod tests {
     }
     #[test]
     fn test_arsinh_function() {
-        let mut parser = Parser::new("arsinh(5.25)", None).unwrap();
+        let mut parser = Parser::new("arsinh(5.25)").unwrap();
         let expected = Arsinh(Box::new(Num(Number::Float(5.25))));
         assert_eq!(parser.parse().unwrap(), expected);
     }
     #[test]
     fn test_arcosh_function() {
-        let mut parser = Parser::new("arcosh(5.25)", None).unwrap();
+        let mut parser = Parser::new("arcosh(5.25)").unwrap();
         let expected = Arcosh(Box::new(Num(Number::Float(5.25))));
         assert_eq!(parser.parse().unwrap(), expected);
     }
     #[test]
     fn test_artanh_function() {
-        let mut parser = Parser::new("artanh(5.25)", None).unwrap();
+        let mut parser = Parser::new("artanh(5.25)").unwrap();
         let expected = Artanh(Box::new(Num(Number::Float(5.25))));
         assert_eq!(parser.parse().unwrap(), expected);
     }
     #[test]
     fn test_min_function() {
-        let mut parser = Parser::new("min(3)", None).unwrap();
+        let mut parser = Parser::new("min(3)").unwrap();
         let expected = Min(Arc::new(vec![Num(Number::Integer(3))]));
         assert_eq!(parser.parse().unwrap(), expected);
     }
     #[test]
     fn test_min_function2() {
-        let mut parser = Parser::new("min(2,3,5)", None).unwrap();
+        let mut parser = Parser::new("min(2,3,5)").unwrap();
         let expected = Min(Arc::new(vec![
             Num(Number::Integer(2)),
             Num(Number::Integer(3)),
@@ -709,13 +916,13 @@ mod tests {
     }
     #[test]
     fn test_max_function() {
-        let mut parser = Parser::new("max(3)", None).unwrap();
+        let mut parser = Parser::new("max(3)").unwrap();
         let expected = Max(Arc::new(vec![Num(Number::Integer(3))]));
         assert_eq!(parser.parse().unwrap(), expected);
     }
     #[test]
     fn test_max_function2() {
-        let mut parser = Parser::new("max(2,3,5)", None).unwrap();
+        let mut parser = Parser::new("max(2,3,5)").unwrap();
         let expected = Max(Arc::new(vec![
             Num(Number::Integer(2)),
             Num(Number::Integer(3)),
@@ -725,13 +932,13 @@ mod tests {
     }
     #[test]
     fn test_avg_function() {
-        let mut parser = Parser::new("avg(3)", None).unwrap();
+        let mut parser = Parser::new("avg(3)").unwrap();
         let expected = Avg(Arc::new(vec![Num(Number::Integer(3))]));
         assert_eq!(parser.parse().unwrap(), expected);
     }
     #[test]
     fn test_avg_function2() {
-        let mut parser = Parser::new("avg(2,3,5)", None).unwrap();
+        let mut parser = Parser::new("avg(2,3,5)").unwrap();
         let expected = Avg(Arc::new(vec![
             Num(Number::Integer(2)),
             Num(Number::Integer(3)),
@@ -741,13 +948,13 @@ mod tests {
     }
     #[test]
     fn test_med_function() {
-        let mut parser = Parser::new("med(3)", None).unwrap();
+        let mut parser = Parser::new("med(3)").unwrap();
         let expected = Med(Arc::new(vec![Num(Number::Integer(3))]));
         assert_eq!(parser.parse().unwrap(), expected);
     }
     #[test]
     fn test_med_function2() {
-        let mut parser = Parser::new("med(2,3,5)", None).unwrap();
+        let mut parser = Parser::new("med(2,3,5)").unwrap();
         let expected = Med(Arc::new(vec![
             Num(Number::Integer(2)),
             Num(Number::Integer(3)),
@@ -757,7 +964,7 @@ mod tests {
     }
     #[test]
     fn test_implicit_mul_prts() {
-        let mut parser = Parser::new("(2)(3)", None).unwrap();
+        let mut parser = Parser::new("(2)(3)").unwrap();
         let expected = Multiply(
             Box::new(Num(Number::Integer(2))),
             Box::new(Num(Number::Integer(3))),
@@ -766,7 +973,7 @@ mod tests {
     }
     #[test]
     fn test_implicit_mul_prts_floor() {
-        let mut parser = Parser::new("(2)⌊3⌋", None).unwrap();
+        let mut parser = Parser::new("(2)⌊3⌋").unwrap();
         let expected = Multiply(
             Box::new(Num(Number::Integer(2))),
             Box::new(Floor(Box::new(Num(Number::Integer(3))))),
@@ -775,7 +982,7 @@ mod tests {
     }
     #[test]
     fn test_implicit_mul_floor_prts() {
-        let mut parser = Parser::new("⌊2⌋(3)", None).unwrap();
+        let mut parser = Parser::new("⌊2⌋(3)").unwrap();
         let expected = Multiply(
             Box::new(Floor(Box::new(Num(Number::Integer(2))))),
             Box::new(Num(Number::Integer(3))),
@@ -784,7 +991,7 @@ mod tests {
     }
     #[test]
     fn test_implicit_mul_prts_ceil() {
-        let mut parser = Parser::new("(2)⌈3⌉", None).unwrap();
+        let mut parser = Parser::new("(2)⌈3⌉").unwrap();
         let expected = Multiply(
             Box::new(Num(Number::Integer(2))),
             Box::new(Ceil(Box::new(Num(Number::Integer(3))))),
@@ -793,7 +1000,7 @@ mod tests {
     }
     #[test]
     fn test_implicit_mul_ceil_prts() {
-        let mut parser = Parser::new("⌈2⌉(3)", None).unwrap();
+        let mut parser = Parser::new("⌈2⌉(3)").unwrap();
         let expected = Multiply(
             Box::new(Ceil(Box::new(Num(Number::Integer(2))))),
             Box::new(Num(Number::Integer(3))),
@@ -802,7 +1009,7 @@ mod tests {
     }
     #[test]
     fn test_implicit_mul_floors() {
-        let mut parser = Parser::new("⌊2⌋⌊3⌋", None).unwrap();
+        let mut parser = Parser::new("⌊2⌋⌊3⌋").unwrap();
         let expected = Multiply(
             Box::new(Floor(Box::new(Num(Number::Integer(2))))),
             Box::new(Floor(Box::new(Num(Number::Integer(3))))),
@@ -811,7 +1018,7 @@ mod tests {
     }
     #[test]
     fn test_implicit_mul_ceils() {
-        let mut parser = Parser::new("⌈2⌉⌈3⌉", None).unwrap();
+        let mut parser = Parser::new("⌈2⌉⌈3⌉").unwrap();
         let expected = Multiply(
             Box::new(Ceil(Box::new(Num(Number::Integer(2))))),
             Box::new(Ceil(Box::new(Num(Number::Integer(3))))),
@@ -820,7 +1027,7 @@ mod tests {
     }
     #[test]
     fn test_implicit_mul_floor_ceil() {
-        let mut parser = Parser::new("⌊2⌋⌈3⌉", None).unwrap();
+        let mut parser = Parser::new("⌊2⌋⌈3⌉").unwrap();
         let expected = Multiply(
             Box::new(Floor(Box::new(Num(Number::Integer(2))))),
             Box::new(Ceil(Box::new(Num(Number::Integer(3))))),
@@ -829,11 +1036,86 @@ mod tests {
     }
     #[test]
     fn test_implicit_mul_ceil_floor() {
-        let mut parser = Parser::new("⌈2⌉⌊3⌋", None).unwrap();
+        let mut parser = Parser::new("⌈2⌉⌊3⌋").unwrap();
         let expected = Multiply(
             Box::new(Ceil(Box::new(Num(Number::Integer(2))))),
             Box::new(Floor(Box::new(Num(Number::Integer(3))))),
         );
         assert_eq!(parser.parse().unwrap(), expected);
     }
+    #[test]
+    fn test_variable() {
+        let mut parser = Parser::new("x").unwrap();
+        let expected = Variable("x".to_string());
+        assert_eq!(parser.parse().unwrap(), expected);
+    }
+    #[test]
+    fn test_implicit_mul_variable() {
+        let mut parser = Parser::new("2x").unwrap();
+        let expected = Multiply(
+            Box::new(Num(Number::Integer(2))),
+            Box::new(Variable("x".to_string())),
+        );
+        assert_eq!(parser.parse().unwrap(), expected);
+    }
+    #[test]
+    fn test_conditional() {
+        let mut parser = Parser::new("1+1<3&&!0?4:5").unwrap();
+        let condition = LogicalAnd(
+            Box::new(Less(
+                Box::new(Add(
+                    Box::new(Num(Number::Integer(1))),
+                    Box::new(Num(Number::Integer(1))),
+                )),
+                Box::new(Num(Number::Integer(3))),
+            )),
+            Box::new(LogicalNot(Box::new(Num(Number::Integer(0))))),
+        );
+        let expected = Conditional(
+            Box::new(condition),
+            Box::new(Num(Number::Integer(4))),
+            Box::new(Num(Number::Integer(5))),
+        );
+        assert_eq!(parser.parse().unwrap(), expected);
+        let mut parser = Parser::new("1?2:3?4:5").unwrap();
+        let expected = Conditional(
+            Box::new(Num(Number::Integer(1))),
+            Box::new(Num(Number::Integer(2))),
+            Box::new(Conditional(
+                Box::new(Num(Number::Integer(3))),
+                Box::new(Num(Number::Integer(4))),
+                Box::new(Num(Number::Integer(5))),
+            )),
+        );
+        assert_eq!(parser.parse().unwrap(), expected);
+        let mut parser = Parser::new("if(0||1,2,3)").unwrap();
+        let expected = Conditional(
+            Box::new(LogicalOr(
+                Box::new(Num(Number::Integer(0))),
+                Box::new(Num(Number::Integer(1))),
+            )),
+            Box::new(Num(Number::Integer(2))),
+            Box::new(Num(Number::Integer(3))),
+        );
+        assert_eq!(parser.parse().unwrap(), expected);
+        let error = Parser::new("1?2").unwrap().parse().unwrap_err();
+        assert_eq!(error.kind(), ParseErrorKind::UnexpectedToken);
+        assert_eq!(error.expected(), [":"]);
+    }
+    #[test]
+    fn test_whitespace() {
+        let mut parser = Parser::new("2i").unwrap();
+        let expected = Multiply(
+            Box::new(Num(Number::from(2))),
+            Box::new(Num(Number::from((0, 1)))),
+        );
+        assert_eq!(parser.parse().unwrap(), expected);
+        let error = Parser::new("2 i").unwrap().parse().unwrap_err();
+        assert_eq!(error.kind(), ParseErrorKind::UnexpectedToken);
+        assert_eq!(error.span(), 2..3);
+        assert_eq!(
+            Parser::new("sqrt (4)").unwrap().parse().unwrap(),
+            Parser::new("sqrt(4)").unwrap().parse().unwrap()
+        );
+    }
 }
@@ -0,0 +1,303 @@
+use super::ast::{eval, Node};
+use super::Number;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+fn zero() -> Node {
+    Node::Num(Number::Integer(0))
+}
+
+fn one() -> Node {
+    Node::Num(Number::Integer(1))
+}
+
+fn is_literal(expr: &Node) -> bool {
+    matches!(expr, Node::Num(_))
+}
+
+/// Evaluate a formula without variables into a literal, or `None` if its evaluation fails, in
+/// which case the error is left to be raised when evaluating the whole formula, or if its value
+/// isn't finite, which no literal can be written for.
+fn fold(expr: &Node) -> Option<Node> {
+    eval(expr, &Number::Integer(0), &HashMap::<String, Number>::new())
+        .ok()
+        .filter(|value| value.to_complex().is_finite())
+        .map(Node::Num)
+}
+
+/// Whether a literal condition holds.
+fn holds(condition: &Node) -> bool {
+    let test = Node::Conditional(
+        Box::new(condition.clone()),
+        Box::new(one()),
+        Box::new(zero()),
+    );
+    fold(&test) == Some(one())
+}
+
+/// The opposite of a literal coefficient if it is negative.
+fn opposite_if_negative(coefficient: &Node) -> Option<Node> {
+    let test = Node::Less(Box::new(coefficient.clone()), Box::new(zero()));
+    if fold(&test) == Some(one()) {
+        fold(&Node::Negative(Box::new(coefficient.clone())))
+    } else {
+        None
+    }
+}
+
+/// Rebuild a node with each of its operands replaced.
+fn map(expr: &Node, f: &mut dyn FnMut(&Node) -> Node) -> Node {
+    use self::Node::*;
+    match expr {
+        Add(expr1, expr2) => Add(Box::new(f(expr1)), Box::new(f(expr2))),
+        Subtract(expr1, expr2) => Subtract(Box::new(f(expr1)), Box::new(f(expr2))),
+        Multiply(expr1, expr2) => Multiply(Box::new(f(expr1)), Box::new(f(expr2))),
+        Divide(expr1, expr2) => Divide(Box::new(f(expr1)), Box::new(f(expr2))),
+        Modulo(expr1, expr2) => Modulo(Box::new(f(expr1)), Box::new(f(expr2))),
+        Atan2(expr1, expr2) => Atan2(Box::new(f(expr1)), Box::new(f(expr2))),
+        Root(expr1, expr2) => Root(Box::new(f(expr1)), Box::new(f(expr2))),
+        Pow(expr1, expr2) => Pow(Box::new(f(expr1)), Box::new(f(expr2))),
+        Log(expr1, expr2) => Log(Box::new(f(expr1)), Box::new(f(expr2))),
+        ILog(expr1, expr2) => ILog(Box::new(f(expr1)), Box::new(f(expr2))),
+        Negative(sub_expr) => Negative(Box::new(f(sub_expr))),
+        Factorial(sub_expr) => Factorial(Box::new(f(sub_expr))),
+        Abs(sub_expr) => Abs(Box::new(f(sub_expr))),
+        Floor(sub_expr) => Floor(Box::new(f(sub_expr))),
+        Ceil(sub_expr) => Ceil(Box::new(f(sub_expr))),
+        Round(sub_expr) => Round(Box::new(f(sub_expr))),
+        Sin(sub_expr) => Sin(Box::new(f(sub_expr))),
+        Cos(sub_expr) => Cos(Box::new(f(sub_expr))),
+        Tan(sub_expr) => Tan(Box::new(f(sub_expr))),
+        Sinh(sub_expr) => Sinh(Box::new(f(sub_expr))),
+        Cosh(sub_expr) => Cosh(Box::new(f(sub_expr))),
+        Tanh(sub_expr) => Tanh(Box::new(f(sub_expr))),
+        Arsinh(sub_expr) => Arsinh(Box::new(f(sub_expr))),
+        Arcosh(sub_expr) => Arcosh(Box::new(f(sub_expr))),
+        Artanh(sub_expr) => Artanh(Box::new(f(sub_expr))),
+        Asin(sub_expr) => Asin(Box::new(f(sub_expr))),
+        Acos(sub_expr) => Acos(Box::new(f(sub_expr))),
+        Atan(sub_expr) => Atan(Box::new(f(sub_expr))),
+        Sqrt(sub_expr) => Sqrt(Box::new(f(sub_expr))),
+        Ln(sub_expr) => Ln(Box::new(f(sub_expr))),
+        Lb(sub_expr) => Lb(Box::new(f(sub_expr))),
+        Exp(sub_expr) => Exp(Box::new(f(sub_expr))),
+        Exp2(sub_expr) => Exp2(Box::new(f(sub_expr))),
+        Truncate(sub_expr) => Truncate(Box::new(f(sub_expr))),
+        Sign(sub_expr) => Sign(Box::new(f(sub_expr))),
+        LambertW(sub_expr) => LambertW(Box::new(f(sub_expr))),
+        Min(args) => Min(Arc::new(args.iter().map(&mut *f).collect())),
+        Max(args) => Max(Arc::new(args.iter().map(&mut *f).collect())),
+        Avg(args) => Avg(Arc::new(args.iter().map(&mut *f).collect())),
+        Med(args) => Med(Arc::new(args.iter().map(&mut *f).collect())),
+        CustomFunction(function, args) => CustomFunction(
+            function.clone(),
+            Arc::new(args.iter().map(&mut *f).collect()),
+        ),
+        Equal(expr1, expr2) => Equal(Box::new(f(expr1)), Box::new(f(expr2))),
+        NotEqual(expr1, expr2) => NotEqual(Box::new(f(expr1)), Box::new(f(expr2))),
+        Less(expr1, expr2) => Less(Box::new(f(expr1)), Box::new(f(expr2))),
+        LessEqual(expr1, expr2) => LessEqual(Box::new(f(expr1)), Box::new(f(expr2))),
+        Greater(expr1, expr2) => Greater(Box::new(f(expr1)), Box::new(f(expr2))),
+        GreaterEqual(expr1, expr2) => GreaterEqual(Box::new(f(expr1)), Box::new(f(expr2))),
+        LogicalAnd(expr1, expr2) => LogicalAnd(Box::new(f(expr1)), Box::new(f(expr2))),
+        LogicalOr(expr1, expr2) => LogicalOr(Box::new(f(expr1)), Box::new(f(expr2))),
+        LogicalNot(sub_expr) => LogicalNot(Box::new(f(sub_expr))),
+        Conditional(condition, expr1, expr2) => Conditional(
+            Box::new(f(condition)),
+            Box::new(f(expr1)),
+            Box::new(f(expr2)),
+        ),
+        Num(_) | Variable(_) | Ans => expr.clone(),
+    }
+}
+
+/// Split a sum into its terms, each one a literal coefficient and the rest of the term, which is
+/// one for the constant term. Returns `false` if a coefficient can't be computed, such as a
+/// negative one for an unsigned integer.
+fn split_sum(expr: &Node, coefficient: Node, terms: &mut Vec<(Node, Node)>) -> bool {
+    use self::Node::*;
+    match expr {
+        Add(expr1, expr2) => {
+            split_sum(expr1, coefficient.clone(), terms) && split_sum(expr2, coefficient, terms)
+        }
+        Subtract(expr1, expr2) => {
+            split_sum(expr1, coefficient.clone(), terms)
+                && match fold(&Negative(Box::new(coefficient))) {
+                    Some(opposite) => split_sum(expr2, opposite, terms),
+                    None => false,
+                }
+        }
+        Negative(sub_expr) => match fold(&Negative(Box::new(coefficient))) {
+            Some(opposite) => split_sum(sub_expr, opposite, terms),
+            None => false,
+        },
+        Multiply(expr1, expr2) if is_literal(expr1) => {
+            match fold(&Multiply(Box::new(coefficient), expr1.clone())) {
+                Some(coefficient) => {
+                    terms.push((coefficient, *expr2.clone()));
+                    true
+                }
+                None => false,
+            }
+        }
+        Num(_) => match fold(&Multiply(Box::new(coefficient), Box::new(expr.clone()))) {
+            Some(coefficient) => {
+                terms.push((coefficient, one()));
+                true
+            }
+            None => false,
+        },
+        _ => {
+            terms.push((coefficient, expr.clone()));
+            true
+        }
+    }
+}
+
+/// Add up the coefficients of the terms which only differ by them, and rebuild the sum with its
+/// terms in a canonical order and the constant last.
+fn sum(expr: Node) -> Node {
+    use self::Node::*;
+    let mut terms: Vec<(Node, Node)> = Vec::new();
+    let mut split = Vec::new();
+    if !split_sum(&expr, one(), &mut split) {
+        return expr;
+    }
+    for (coefficient, term) in split {
+        match terms.iter_mut().find(|(_, other)| *other == term) {
+            Some((total, _)) => match fold(&Add(Box::new(total.clone()), Box::new(coefficient))) {
+                Some(coefficient) => *total = coefficient,
+                None => return expr,
+            },
+            None => terms.push((coefficient, term)),
+        }
+    }
+    // The debug representation is arbitrary but it gives the same order to the same terms.
+    terms.sort_by_cached_key(|(_, term)| (*term == one(), format!("{:?}", term)));
+    let mut result: Option<Node> = None;
+    for (coefficient, term) in terms {
+        if term == one() && coefficient == zero() {
+            continue;
+        }
+        let (negative, coefficient) = match opposite_if_negative(&coefficient) {
+            Some(opposite) => (true, opposite),
+            None => (false, coefficient),
+        };
+        let term = if term == one() {
+            coefficient
+        } else if coefficient == one() {
+            term
+        } else {
+            Multiply(Box::new(coefficient), Box::new(term))
+        };
+        result = Some(match result {
+            Some(sum) if negative => Subtract(Box::new(sum), Box::new(term)),
+            Some(sum) => Add(Box::new(sum), Box::new(term)),
+            None if negative => Negative(Box::new(term)),
+            None => term,
+        });
+    }
+    result.unwrap_or_else(zero)
+}
+
+/// Split a product into its factors, multiplying the literal ones into the coefficient.
+fn split_product(expr: &Node, coefficient: &mut Node, factors: &mut Vec<Node>) -> bool {
+    match expr {
+        Node::Multiply(expr1, expr2) => {
+            split_product(expr1, coefficient, factors) && split_product(expr2, coefficient, factors)
+        }
+        Node::Num(_) => {
+            let product = Node::Multiply(Box::new(coefficient.clone()), Box::new(expr.clone()));
+            match fold(&product) {
+                Some(product) => {
+                    *coefficient = product;
+                    true
+                }
+                None => false,
+            }
+        }
+        _ => {
+            factors.push(expr.clone());
+            true
+        }
+    }
+}
+
+/// Rebuild a product with its literal coefficient first and its other factors in a canonical
+/// order.
+fn product(expr: Node) -> Node {
+    let mut coefficient = one();
+    let mut factors = Vec::new();
+    if !split_product(&expr, &mut coefficient, &mut factors) {
+        return expr;
+    }
+    factors.sort_by_cached_key(|factor| format!("{:?}", factor));
+    let factors = factors
+        .into_iter()
+        .reduce(|product, factor| Node::Multiply(Box::new(product), Box::new(factor)));
+    match factors {
+        Some(factors) if coefficient == one() => factors,
+        Some(factors) => Node::Multiply(Box::new(coefficient), Box::new(factors)),
+        None => coefficient,
+    }
+}
+
+/// Simplify a formula: fold its constant parts, remove the operations without effect, such as
+/// `x*1`, `x+0`, `x^1` and `--x`, combine the like terms of sums, and order the terms of sums and
+/// the factors of products canonically. User-defined functions are never folded.
+pub fn simplify(expr: &Node) -> Node {
+    use self::Node::*;
+    let mut literal = true;
+    let expr = map(expr, &mut |operand| {
+        let operand = simplify(operand);
+        literal &= is_literal(&operand);
+        operand
+    });
+    match expr {
+        Num(_) | Variable(_) | Ans | CustomFunction(_, _) => expr,
+        _ if literal => fold(&expr).unwrap_or(expr),
+        Conditional(condition, expr1, expr2) if is_literal(&condition) => {
+            if holds(&condition) {
+                *expr1
+            } else {
+                *expr2
+            }
+        }
+        Negative(sub_expr) => match *sub_expr {
+            Negative(sub_expr) => *sub_expr,
+            sub_expr => Negative(Box::new(sub_expr)),
+        },
+        Add(_, _) | Subtract(_, _) => sum(expr),
+        Multiply(_, _) => product(expr),
+        Divide(expr1, expr2) | Pow(expr1, expr2) if *expr2 == one() => *expr1,
+        expr => expr,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::eval_number::parser::Parser;
+    use num_rational::Rational64;
+
+    fn parsed(formula: &str) -> Node {
+        Parser::new(formula).unwrap().parse().unwrap()
+    }
+
+    #[test]
+    fn test_simplify() {
+        assert_eq!(
+            simplify(&parsed("x/3+1/3+1/3")),
+            Node::Add(
+                Box::new(parsed("x/3")),
+                Box::new(Node::Num(Number::Rational(Rational64::new(2, 3))))
+            )
+        );
+        assert_eq!(simplify(&parsed("2x-3x")), parsed("-x"));
+        assert_eq!(simplify(&parsed("sqrt(-4)*x")), simplify(&parsed("2i*x")));
+        for formula in ["atan(i)", "0^i", "x*lb(0)"] {
+            let text = simplify(&parsed(formula)).to_string();
+            assert_eq!(parsed(&text), simplify(&parsed(formula)), "{}", text);
+        }
+    }
+}
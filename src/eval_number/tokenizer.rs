@@ -1,25 +1,44 @@
 use super::token::{NativeFunction, Token};
 use super::Number;
-use crate::utils::deserialize_superscript_number;
-use std::iter::Peekable;
-use std::str::Chars;
+use crate::utils::{
+    deserialize_number, deserialize_superscript_number, peek_identifier, unrecognized_token,
+    Cursor, Functions, ParseError,
+};
+
+/// The names read as constants or units rather than variables.
+pub const RESERVED_WORDS: [&str; 4] = ["e", "pi", "rad", "i"];
 
 pub struct Tokenizer<'a> {
-    expr: Peekable<Chars<'a>>,
+    expr: Cursor<'a>,
+    functions: Option<&'a Functions<Number>>,
 }
 
 impl<'a> Tokenizer<'a> {
     pub fn new(new_expr: &'a str) -> Self {
         Tokenizer {
-            expr: new_expr.chars().peekable(),
+            expr: Cursor::new(new_expr),
+            functions: None,
         }
     }
-}
-
-impl<'a> Iterator for Tokenizer<'a> {
-    type Item = Token;
-
-    fn next(&mut self) -> Option<Token> {
+    pub fn with_functions(new_expr: &'a str, functions: &'a Functions<Number>) -> Self {
+        Tokenizer {
+            expr: Cursor::new(new_expr),
+            functions: Some(functions),
+        }
+    }
+    /// The byte offset of the next token.
+    pub fn position(&self) -> usize {
+        self.expr.position()
+    }
+    /// Consume the whitespaces before the next token, which only separate tokens.
+    pub fn skip_whitespace(&mut self) {
+        self.expr.skip_whitespace()
+    }
+    /// The error explaining why no token could be read at the given byte offset.
+    pub fn error(&self, start: usize) -> ParseError {
+        unrecognized_token(self.expr.source(), start)
+    }
+    fn native_token(&mut self) -> Option<Token> {
         let current_char = self.expr.next();
 
         match current_char {
@@ -31,7 +50,56 @@ impl<'a> Iterator for Tokenizer<'a> {
             Some('^') => Some(Token::Caret),
             Some('(') => Some(Token::LeftParen),
             Some(')') => Some(Token::RightParen),
-            Some('!') => Some(Token::ExclamationMark),
+            Some('!') => {
+                if let Some('=') = self.expr.peek() {
+                    self.expr.next();
+                    Some(Token::NotEqual)
+                } else {
+                    Some(Token::ExclamationMark)
+                }
+            }
+            Some('=') => {
+                if let Some('=') = self.expr.peek() {
+                    self.expr.next();
+                    Some(Token::Equal)
+                } else {
+                    None
+                }
+            }
+            Some('<') => {
+                if let Some('=') = self.expr.peek() {
+                    self.expr.next();
+                    Some(Token::LessEqual)
+                } else {
+                    Some(Token::Less)
+                }
+            }
+            Some('>') => {
+                if let Some('=') = self.expr.peek() {
+                    self.expr.next();
+                    Some(Token::GreaterEqual)
+                } else {
+                    Some(Token::Greater)
+                }
+            }
+            Some('&') => {
+                if let Some('&') = self.expr.peek() {
+                    self.expr.next();
+                    Some(Token::DoubleAmpersand)
+                } else {
+                    None
+                }
+            }
+            Some('|') => {
+                if let Some('|') = self.expr.peek() {
+                    self.expr.next();
+                    Some(Token::DoubleBar)
+                } else {
+                    None
+                }
+            }
+            Some('?') => Some(Token::QuestionMark),
+            Some(':') => Some(Token::Colon),
             Some(',') => Some(Token::Comma),
             Some('%') => Some(Token::Modulo),
             Some('π') => Some(Token::Pi),
@@ -43,16 +111,9 @@ impl<'a> Iterator for Tokenizer<'a> {
             Some('.') => {
                 let next_char = self.expr.peek()?;
                 if next_char.is_ascii_digit() {
-                    let mut number = "0".to_string();
-                    number.push(current_char?);
-                    while let Some(next_char) = self.expr.peek() {
-                        if next_char.is_ascii_digit() {
-                            number.push(self.expr.next()?);
-                        } else {
-                            break;
-                        }
-                    }
-                    Some(Token::Num(Number::Float(number.parse::<f64>().unwrap())))
+                    let (mut number, exponent) = deserialize_number('.', &mut self.expr)?;
+                    number.push_str(&exponent);
+                    Some(Token::Num(Number::Float(number.parse::<f64>().ok()?)))
                 } else {
                     None
                 }
@@ -60,72 +121,64 @@ impl<'a> Iterator for Tokenizer<'a> {
             Some('⁰') => Some(Token::Superscript(Number::Integer(
                 deserialize_superscript_number(&current_char?, &mut self.expr)
                     .parse::<i64>()
-                    .unwrap(),
+                    .ok()?,
             ))),
             Some('¹') => Some(Token::Superscript(Number::Integer(
                 deserialize_superscript_number(&current_char?, &mut self.expr)
                     .parse::<i64>()
-                    .unwrap(),
+                    .ok()?,
             ))),
             Some('²') => Some(Token::Superscript(Number::Integer(
                 deserialize_superscript_number(&current_char?, &mut self.expr)
                     .parse::<i64>()
-                    .unwrap(),
+                    .ok()?,
             ))),
             Some('³') => Some(Token::Superscript(Number::Integer(
                 deserialize_superscript_number(&current_char?, &mut self.expr)
                     .parse::<i64>()
-                    .unwrap(),
+                    .ok()?,
             ))),
             Some('⁴') => Some(Token::Superscript(Number::Integer(
                 deserialize_superscript_number(&current_char?, &mut self.expr)
                     .parse::<i64>()
-                    .unwrap(),
+                    .ok()?,
             ))),
             Some('⁵') => Some(Token::Superscript(Number::Integer(
                 deserialize_superscript_number(&current_char?, &mut self.expr)
                     .parse::<i64>()
-                    .unwrap(),
+                    .ok()?,
             ))),
             Some('⁶') => Some(Token::Superscript(Number::Integer(
                 deserialize_superscript_number(&current_char?, &mut self.expr)
                     .parse::<i64>()
-                    .unwrap(),
+                    .ok()?,
             ))),
             Some('⁷') => Some(Token::Superscript(Number::Integer(
                 deserialize_superscript_number(&current_char?, &mut self.expr)
                     .parse::<i64>()
-                    .unwrap(),
+                    .ok()?,
             ))),
             Some('⁸') => Some(Token::Superscript(Number::Integer(
                 deserialize_superscript_number(&current_char?, &mut self.expr)
                     .parse::<i64>()
-                    .unwrap(),
+                    .ok()?,
             ))),
             Some('⁹') => Some(Token::Superscript(Number::Integer(
                 deserialize_superscript_number(&current_char?, &mut self.expr)
                     .parse::<i64>()
-                    .unwrap(),
+                    .ok()?,
             ))),
             Some('0'..='9') => {
-                let mut floatting = false;
-                let mut number = current_char?.to_string();
-                while let Some(next_char) = self.expr.peek() {
-                    if floatting && next_char == &'.' {
-                        break;
-                    } else if next_char.is_ascii_digit() || next_char == &'.' {
-                        if next_char == &'.' {
-                            floatting = true;
-                        }
-                        number.push(self.expr.next()?);
-                    } else {
-                        break;
-                    }
-                }
-                if floatting {
-                    Some(Token::Num(Number::Float(number.parse::<f64>().unwrap())))
+                let (mut number, exponent) = deserialize_number(current_char?, &mut self.expr)?;
+                if number.contains('.') || !exponent.is_empty() {
+                    number.push_str(&exponent);
+                    Some(Token::Num(Number::Float(number.parse::<f64>().ok()?)))
                 } else {
-                    Some(Token::Num(Number::Integer(number.parse::<i64>().unwrap())))
+                    // Integers too large for i64 fall back to a float, like the arithmetic does.
+                    match number.parse::<i64>() {
+                        Ok(integer) => Some(Token::Num(Number::Integer(integer))),
+                        Err(_) => Some(Token::Num(Number::Float(number.parse::<f64>().ok()?))),
+                    }
                 }
             }
             Some('a') => match self.expr.clone().take(6).collect::<String>().as_str() {
@@ -225,8 +278,11 @@ impl<'a> Iterator for Tokenizer<'a> {
                 if self.expr.clone().take(4).collect::<String>() == "log(" {
                     self.expr.by_ref().take(3).for_each(drop);
                     Some(Token::ExplicitFunction(NativeFunction::ILog))
+                } else if self.expr.clone().take(2).collect::<String>() == "f(" {
+                    self.expr.by_ref().take(1).for_each(drop);
+                    Some(Token::ExplicitFunction(NativeFunction::If))
                 } else {
-                    None
+                    Some(Token::Num(Number::from((0, 1))))
                 }
             }
             Some('l') => {
@@ -361,6 +417,44 @@ impl<'a> Iterator for Tokenizer<'a> {
     }
 }
 
+impl<'a> Iterator for Tokenizer<'a> {
+    type Item = Token;
+
+    fn next(&mut self) -> Option<Token> {
+        self.expr.skip_whitespace();
+        let (identifier, next_char) = peek_identifier(&self.expr);
+        if !identifier.is_empty() {
+            if next_char == Some('(') {
+                if let Some(function) = self
+                    .functions
+                    .and_then(|functions| functions.get(&identifier))
+                {
+                    let function = function.clone();
+                    self.expr.by_ref().take(identifier.len()).for_each(drop);
+                    return Some(Token::CustomFunction(function));
+                }
+            } else if !RESERVED_WORDS.contains(&identifier.as_str()) {
+                self.expr.by_ref().take(identifier.len()).for_each(drop);
+                return Some(Token::Identifier(identifier));
+            }
+        }
+        if next_char == Some('(') && !identifier.is_empty() {
+            // The native functions are matched along with their `(`, which whitespace may set
+            // apart from the name.
+            let call = format!("{}(", identifier);
+            let mut native = Tokenizer::new(&call);
+            let token = native.native_token()?;
+            if native.position() != identifier.len() {
+                // The word before the arguments is not a native function either.
+                return None;
+            }
+            self.expr.by_ref().take(identifier.len()).for_each(drop);
+            return Some(token);
+        }
+        self.native_token()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -389,11 +483,40 @@ mod tests {
         assert_eq!(tokenizer.next().unwrap(), Token::Num(Number::Float(0.5)))
     }
     #[test]
+    fn test_scientific_number() {
+        let mut tokenizer = Tokenizer::new("1.5e-3");
+        assert_eq!(tokenizer.next().unwrap(), Token::Num(Number::Float(0.0015)));
+        let mut tokenizer = Tokenizer::new("6.022E23");
+        assert_eq!(
+            tokenizer.next().unwrap(),
+            Token::Num(Number::Float(6.022e23))
+        );
+        let mut tokenizer = Tokenizer::new("2e+2");
+        assert_eq!(tokenizer.next().unwrap(), Token::Num(Number::Float(200.0)));
+    }
+    #[test]
+    fn test_e_const_after_number() {
+        let mut tokenizer = Tokenizer::new("2e-x");
+        assert_eq!(tokenizer.next().unwrap(), Token::Num(Number::Integer(2)));
+        assert_eq!(tokenizer.next().unwrap(), Token::E);
+        assert_eq!(tokenizer.next().unwrap(), Token::Subtract);
+    }
+    #[test]
     fn test_e_const() {
         let mut tokenizer = Tokenizer::new("e");
         assert_eq!(tokenizer.next().unwrap(), Token::E)
     }
     #[test]
+    fn test_imaginary_unit() {
+        let mut tokenizer = Tokenizer::new("i");
+        assert_eq!(tokenizer.next().unwrap(), Token::Num(Number::from((0, 1))));
+        let mut tokenizer = Tokenizer::new("ix");
+        assert_eq!(
+            tokenizer.next().unwrap(),
+            Token::Identifier("ix".to_string())
+        );
+    }
+    #[test]
     fn test_pi_const() {
         let mut tokenizer = Tokenizer::new("π");
         assert_eq!(tokenizer.next().unwrap(), Token::Pi)
@@ -824,4 +947,47 @@ mod tests {
             Token::ExplicitFunction(NativeFunction::Med)
         )
     }
+    #[test]
+    fn test_identifier() {
+        let mut tokenizer = Tokenizer::new("price_2*x");
+        assert_eq!(
+            tokenizer.next().unwrap(),
+            Token::Identifier("price_2".to_string())
+        );
+        assert_eq!(tokenizer.next().unwrap(), Token::Multiply);
+        assert_eq!(
+            tokenizer.next().unwrap(),
+            Token::Identifier("x".to_string())
+        );
+    }
+    #[test]
+    fn test_identifier_starting_with_reserved_word() {
+        let mut tokenizer = Tokenizer::new("pixel");
+        assert_eq!(
+            tokenizer.next().unwrap(),
+            Token::Identifier("pixel".to_string())
+        )
+    }
+    #[test]
+    fn test_logical_operators() {
+        let mut tokenizer = Tokenizer::new("==!=<=<>=>&&||!?:if(i");
+        for token in [
+            Token::Equal,
+            Token::NotEqual,
+            Token::LessEqual,
+            Token::Less,
+            Token::GreaterEqual,
+            Token::Greater,
+            Token::DoubleAmpersand,
+            Token::DoubleBar,
+            Token::ExclamationMark,
+            Token::QuestionMark,
+            Token::Colon,
+            Token::ExplicitFunction(NativeFunction::If),
+        ] {
+            assert_eq!(tokenizer.next().unwrap(), token);
+        }
+        assert_eq!(tokenizer.next().unwrap(), Token::LeftParen);
+        assert_eq!(tokenizer.next().unwrap(), Token::Num(Number::from((0, 1))));
+    }
 }
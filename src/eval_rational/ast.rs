@@ -0,0 +1,474 @@
+use num_bigint::BigInt;
+use num_integer::Integer;
+use num_rational::BigRational;
+use num_traits::{One, Signed, ToPrimitive, Zero};
+use std::sync::Arc;
+
+use crate::utils::{Context, EvalError, Function};
+
+/// The largest size in bits of the numerator or the denominator of a result, larger ones being
+/// reported as an overflow so that a formula such as `9^9^9` fails instead of exhausting the memory.
+/// The gcd reducing each fraction takes a time quadratic in this size, which keeps it to a few
+/// milliseconds.
+pub const MAX_BITS: u64 = 1 << 16;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Node {
+    Add(Box<Node>, Box<Node>),
+    Subtract(Box<Node>, Box<Node>),
+    Multiply(Box<Node>, Box<Node>),
+    Divide(Box<Node>, Box<Node>),
+    Modulo(Box<Node>, Box<Node>),
+    Root(Box<Node>, Box<Node>),
+    Pow(Box<Node>, Box<Node>),
+    Negative(Box<Node>),
+    Factorial(Box<Node>),
+    Abs(Box<Node>),
+    Floor(Box<Node>),
+    Ceil(Box<Node>),
+    Round(Box<Node>),
+    Sqrt(Box<Node>),
+    Exp2(Box<Node>),
+    Truncate(Box<Node>),
+    Sign(Box<Node>),
+    Min(Arc<Vec<Node>>),
+    Max(Arc<Vec<Node>>),
+    Avg(Arc<Vec<Node>>),
+    Med(Arc<Vec<Node>>),
+    Gcd(Arc<Vec<Node>>),
+    Lcm(Arc<Vec<Node>>),
+    Num(BigRational),
+    CustomFunction(Function<BigRational>, Arc<Vec<Node>>),
+    Equal(Box<Node>, Box<Node>),
+    NotEqual(Box<Node>, Box<Node>),
+    Less(Box<Node>, Box<Node>),
+    LessEqual(Box<Node>, Box<Node>),
+    Greater(Box<Node>, Box<Node>),
+    GreaterEqual(Box<Node>, Box<Node>),
+    LogicalAnd(Box<Node>, Box<Node>),
+    LogicalOr(Box<Node>, Box<Node>),
+    LogicalNot(Box<Node>),
+    Conditional(Box<Node>, Box<Node>, Box<Node>),
+    Variable(String),
+    Ans,
+}
+
+fn bounded(value: BigRational) -> Result<BigRational, EvalError> {
+    if value.numer().bits() > MAX_BITS || value.denom().bits() > MAX_BITS {
+        Err(EvalError::Overflow)
+    } else {
+        Ok(value)
+    }
+}
+fn integer(function: &str, value: &BigRational) -> Result<BigInt, EvalError> {
+    if value.is_integer() {
+        Ok(value.to_integer())
+    } else {
+        Err(EvalError::Domain(function.to_string()))
+    }
+}
+/// The q-th root of an integer, if it is an integer too.
+fn exact_root(value: &BigInt, q: u32) -> Option<BigInt> {
+    let root = value.nth_root(q);
+    if num_traits::pow(root.clone(), q as usize) == *value {
+        Some(root)
+    } else {
+        None
+    }
+}
+/// Raise to a rational power, the result having to be rational as well.
+fn pow(
+    function: &str,
+    base: &BigRational,
+    exponent: &BigRational,
+) -> Result<BigRational, EvalError> {
+    if base.is_zero() {
+        return if exponent.is_negative() {
+            Err(EvalError::DivisionByZero)
+        } else if exponent.is_zero() {
+            Ok(BigRational::one())
+        } else {
+            Ok(BigRational::zero())
+        };
+    }
+    if base.is_one() {
+        return Ok(BigRational::one());
+    }
+    let even_root = exponent.denom().is_even();
+    if base.is_negative() && even_root {
+        return Err(EvalError::Domain(function.to_string()));
+    }
+    if -base == BigRational::one() {
+        return Ok(if exponent.numer().is_even() {
+            BigRational::one()
+        } else {
+            -BigRational::one()
+        });
+    }
+    // Only 0 and ±1 have exact roots of a higher degree than their number of bits.
+    let irrational = || EvalError::Irrational(function.to_string());
+    let q = exponent.denom().to_u32().ok_or_else(irrational)?;
+    let root = BigRational::new(
+        exact_root(base.numer(), q).ok_or_else(irrational)?,
+        exact_root(base.denom(), q).ok_or_else(irrational)?,
+    );
+    // The root is neither 0 nor ±1, so each power takes at least one more bit.
+    let bits = root.numer().bits().max(root.denom().bits()) - 1;
+    let p = exponent
+        .numer()
+        .abs()
+        .to_u64()
+        .filter(|p| p.saturating_mul(bits) <= MAX_BITS)
+        .ok_or(EvalError::Overflow)?;
+    // Each power can take more than one bit, so the result is bounded again.
+    let power = bounded(root.pow(p as i32))?;
+    if exponent.is_negative() {
+        Ok(power.recip())
+    } else {
+        Ok(power)
+    }
+}
+fn factorial(value: &BigRational) -> Result<BigRational, EvalError> {
+    let n = integer("factorial", value)?;
+    if n.is_negative() {
+        return Err(EvalError::NegativeFactorial);
+    }
+    // n! has about n * (log2(n) - 1.44) bits.
+    let n = n
+        .to_u64()
+        .filter(|n| n.saturating_mul(BigInt::from(*n).bits().saturating_sub(2)) <= MAX_BITS)
+        .ok_or(EvalError::Overflow)?;
+    let mut result = BigInt::one();
+    for i in 2..=n {
+        result *= i;
+    }
+    Ok(BigRational::from_integer(result))
+}
+
+/// 1 when the condition holds and 0 otherwise.
+fn truth(condition: bool) -> BigRational {
+    if condition {
+        BigRational::one()
+    } else {
+        BigRational::zero()
+    }
+}
+
+pub fn eval(
+    expr: &Node,
+    placeholder: &BigRational,
+    context: &dyn Context<BigRational>,
+) -> Result<BigRational, EvalError> {
+    use self::Node::*;
+    match expr {
+        Num(value) => Ok(value.clone()),
+        Ans => Ok(placeholder.clone()),
+        CustomFunction(function, args) => {
+            let mut values = vec![];
+            for arg in args.iter() {
+                values.push(eval(arg, placeholder, context)?);
+            }
+            function.call(&values)
+        }
+        Variable(name) => match context.get(name) {
+            Some(value) => Ok(value),
+            None => Err(EvalError::UndefinedVariable(name.clone())),
+        },
+        Add(expr1, expr2) => {
+            bounded(eval(expr1, placeholder, context)? + eval(expr2, placeholder, context)?)
+        }
+        Subtract(expr1, expr2) => {
+            bounded(eval(expr1, placeholder, context)? - eval(expr2, placeholder, context)?)
+        }
+        Multiply(expr1, expr2) => {
+            bounded(eval(expr1, placeholder, context)? * eval(expr2, placeholder, context)?)
+        }
+        Divide(expr1, expr2) => {
+            let dividend = eval(expr1, placeholder, context)?;
+            let divisor = eval(expr2, placeholder, context)?;
+            if divisor.is_zero() {
+                return Err(EvalError::DivisionByZero);
+            }
+            bounded(dividend / divisor)
+        }
+        Modulo(expr1, expr2) => {
+            let dividend = eval(expr1, placeholder, context)?;
+            let divisor = eval(expr2, placeholder, context)?;
+            if divisor.is_zero() {
+                return Err(EvalError::DivisionByZero);
+            }
+            bounded(dividend % divisor)
+        }
+        Equal(expr1, expr2) => Ok(truth(
+            eval(expr1, placeholder, context)? == eval(expr2, placeholder, context)?,
+        )),
+        NotEqual(expr1, expr2) => Ok(truth(
+            eval(expr1, placeholder, context)? != eval(expr2, placeholder, context)?,
+        )),
+        Less(expr1, expr2) => Ok(truth(
+            eval(expr1, placeholder, context)? < eval(expr2, placeholder, context)?,
+        )),
+        LessEqual(expr1, expr2) => Ok(truth(
+            eval(expr1, placeholder, context)? <= eval(expr2, placeholder, context)?,
+        )),
+        Greater(expr1, expr2) => Ok(truth(
+            eval(expr1, placeholder, context)? > eval(expr2, placeholder, context)?,
+        )),
+        GreaterEqual(expr1, expr2) => Ok(truth(
+            eval(expr1, placeholder, context)? >= eval(expr2, placeholder, context)?,
+        )),
+        LogicalAnd(expr1, expr2) => Ok(truth(
+            !eval(expr1, placeholder, context)?.is_zero()
+                && !eval(expr2, placeholder, context)?.is_zero(),
+        )),
+        LogicalOr(expr1, expr2) => Ok(truth(
+            !eval(expr1, placeholder, context)?.is_zero()
+                || !eval(expr2, placeholder, context)?.is_zero(),
+        )),
+        LogicalNot(expr1) => Ok(truth(eval(expr1, placeholder, context)?.is_zero())),
+        Conditional(condition, expr1, expr2) => {
+            if !eval(condition, placeholder, context)?.is_zero() {
+                eval(expr1, placeholder, context)
+            } else {
+                eval(expr2, placeholder, context)
+            }
+        }
+        Negative(expr1) => Ok(-eval(expr1, placeholder, context)?),
+        Pow(expr1, expr2) => pow(
+            "pow",
+            &eval(expr1, placeholder, context)?,
+            &eval(expr2, placeholder, context)?,
+        ),
+        Root(n_th_expr, x_expr) => {
+            let n_th = eval(n_th_expr, placeholder, context)?;
+            let x = eval(x_expr, placeholder, context)?;
+            if n_th.is_zero() {
+                return Err(EvalError::Domain("root".to_string()));
+            }
+            pow("root", &x, &n_th.recip())
+        }
+        Sqrt(sub_expr) => pow(
+            "sqrt",
+            &eval(sub_expr, placeholder, context)?,
+            &BigRational::new(1.into(), 2.into()),
+        ),
+        Exp2(sub_expr) => pow(
+            "exp2",
+            &BigRational::from_integer(2.into()),
+            &eval(sub_expr, placeholder, context)?,
+        ),
+        Factorial(sub_expr) => factorial(&eval(sub_expr, placeholder, context)?),
+        Abs(sub_expr) => Ok(eval(sub_expr, placeholder, context)?.abs()),
+        Floor(sub_expr) => Ok(eval(sub_expr, placeholder, context)?.floor()),
+        Ceil(sub_expr) => Ok(eval(sub_expr, placeholder, context)?.ceil()),
+        Round(sub_expr) => Ok(eval(sub_expr, placeholder, context)?.round()),
+        Truncate(sub_expr) => Ok(eval(sub_expr, placeholder, context)?.trunc()),
+        Sign(sub_expr) => Ok(eval(sub_expr, placeholder, context)?.signum()),
+        Min(args) => {
+            let mut result: Option<BigRational> = None;
+            for arg in args.iter() {
+                let value = eval(arg, placeholder, context)?;
+                result = Some(match result {
+                    Some(result) => result.min(value),
+                    None => value,
+                });
+            }
+            Ok(result.unwrap_or_else(BigRational::zero))
+        }
+        Max(args) => {
+            let mut result: Option<BigRational> = None;
+            for arg in args.iter() {
+                let value = eval(arg, placeholder, context)?;
+                result = Some(match result {
+                    Some(result) => result.max(value),
+                    None => value,
+                });
+            }
+            Ok(result.unwrap_or_else(BigRational::zero))
+        }
+        Avg(args) => {
+            let mut result = BigRational::zero();
+            for arg in args.iter() {
+                result = bounded(result + eval(arg, placeholder, context)?)?;
+            }
+            Ok(result / BigInt::from(args.len()))
+        }
+        Med(args) => {
+            let mut results = vec![];
+            for arg in args.iter() {
+                results.push(eval(arg, placeholder, context)?);
+            }
+            results.sort();
+            let len = results.len();
+            if len % 2 == 0 {
+                bounded((&results[len >> 1] + &results[(len >> 1) - 1]) / BigInt::from(2))
+            } else {
+                Ok(results[len >> 1].clone())
+            }
+        }
+        Gcd(args) => {
+            let mut result = BigInt::zero();
+            for arg in args.iter() {
+                result = result.gcd(&integer("gcd", &eval(arg, placeholder, context)?)?);
+            }
+            Ok(BigRational::from_integer(result))
+        }
+        Lcm(args) => {
+            let mut result = BigInt::one();
+            for arg in args.iter() {
+                result = result.lcm(&integer("lcm", &eval(arg, placeholder, context)?)?);
+                if result.bits() > MAX_BITS {
+                    return Err(EvalError::Overflow);
+                }
+            }
+            Ok(BigRational::from_integer(result))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::eval_rational::parser::Parser;
+    use std::collections::HashMap;
+
+    fn ratio(numer: i64, denom: i64) -> BigRational {
+        BigRational::new(numer.into(), denom.into())
+    }
+    fn eval_formula(formula: &str) -> Result<BigRational, EvalError> {
+        let ast = Parser::new(formula).unwrap().parse().unwrap();
+        eval(&ast, &BigRational::zero(), &HashMap::new())
+    }
+
+    #[test]
+    fn test_fractions() {
+        assert_eq!(eval_formula("1/3+1/6"), Ok(ratio(1, 2)));
+        assert_eq!(eval_formula("1/3*3"), Ok(ratio(1, 1)));
+        assert_eq!(eval_formula("0.1+0.2"), Ok(ratio(3, 10)));
+        assert_eq!(eval_formula("-7/2%2"), Ok(ratio(-3, 2)));
+    }
+    #[test]
+    fn test_factorial() {
+        let expected = (2..=100).fold(BigInt::one(), |acc, i| acc * i);
+        assert_eq!(
+            eval_formula("100!"),
+            Ok(BigRational::from_integer(expected))
+        );
+        assert_eq!(eval_formula("0!"), Ok(ratio(1, 1)));
+        assert_eq!(eval_formula("(-1)!"), Err(EvalError::NegativeFactorial));
+        assert_eq!(
+            eval_formula("(1/2)!"),
+            Err(EvalError::Domain("factorial".to_string()))
+        );
+        assert_eq!(eval_formula("1000000!"), Err(EvalError::Overflow));
+    }
+    #[test]
+    fn test_pow() {
+        assert_eq!(
+            eval_formula("2^100"),
+            Ok(BigRational::from_integer(BigInt::one() << 100))
+        );
+        assert_eq!(eval_formula("(2/3)^-2"), Ok(ratio(9, 4)));
+        assert_eq!(eval_formula("(8/27)^(2/3)"), Ok(ratio(4, 9)));
+        assert_eq!(eval_formula("(-8)^(1/3)"), Ok(ratio(-2, 1)));
+        assert_eq!(
+            eval_formula("(-1)^(1/2)"),
+            Err(EvalError::Domain("pow".to_string()))
+        );
+        assert_eq!(
+            eval_formula("2^(1/2)"),
+            Err(EvalError::Irrational("pow".to_string()))
+        );
+        assert_eq!(eval_formula("0^-1"), Err(EvalError::DivisionByZero));
+        assert_eq!(eval_formula("9^9^9"), Err(EvalError::Overflow));
+        assert_eq!(eval_formula("1^(9^9^9)"), Err(EvalError::Overflow));
+        assert_eq!(eval_formula("(-1)^(10^100)"), Ok(ratio(1, 1)));
+        // Dividing by a power larger than the bound would spend minutes in the gcd.
+        assert_eq!(eval_formula("1/3^1000000"), Err(EvalError::Overflow));
+        assert_eq!(eval_formula("1/3^50000"), Err(EvalError::Overflow));
+        assert_eq!(
+            eval_formula("1/3^40000"),
+            Ok(BigRational::new(
+                BigInt::one(),
+                num_traits::pow(BigInt::from(3), 40000)
+            ))
+        );
+    }
+    #[test]
+    fn test_roots() {
+        assert_eq!(eval_formula("sqrt(9/4)"), Ok(ratio(3, 2)));
+        assert_eq!(eval_formula("root(3,-27)"), Ok(ratio(-3, 1)));
+        assert_eq!(
+            eval_formula("sqrt(2)"),
+            Err(EvalError::Irrational("sqrt".to_string()))
+        );
+        assert_eq!(
+            eval_formula("sqrt(-4)"),
+            Err(EvalError::Domain("sqrt".to_string()))
+        );
+        assert_eq!(
+            eval_formula("root(0,4)"),
+            Err(EvalError::Domain("root".to_string()))
+        );
+        assert_eq!(eval_formula("exp2(-3)"), Ok(ratio(1, 8)));
+    }
+    #[test]
+    fn test_rounding() {
+        assert_eq!(eval_formula("floor(-7/2)"), Ok(ratio(-4, 1)));
+        assert_eq!(eval_formula("⌈7/2⌉"), Ok(ratio(4, 1)));
+        assert_eq!(eval_formula("round(5/2)"), Ok(ratio(3, 1)));
+        assert_eq!(eval_formula("trunc(-7/2)"), Ok(ratio(-3, 1)));
+        assert_eq!(eval_formula("sign(-7/2)*abs(-7/2)"), Ok(ratio(-7, 2)));
+    }
+    #[test]
+    fn test_statistics() {
+        assert_eq!(eval_formula("min(1/2,1/3,3/4)"), Ok(ratio(1, 3)));
+        assert_eq!(eval_formula("max(1/2,1/3,3/4)"), Ok(ratio(3, 4)));
+        assert_eq!(eval_formula("avg(1,2)"), Ok(ratio(3, 2)));
+        assert_eq!(eval_formula("med(1,2,4,3)"), Ok(ratio(5, 2)));
+        assert_eq!(eval_formula("gcd(12,18,-8)"), Ok(ratio(2, 1)));
+        assert_eq!(eval_formula("lcm(4,6)"), Ok(ratio(12, 1)));
+        assert_eq!(
+            eval_formula("gcd(1/2,2)"),
+            Err(EvalError::Domain("gcd".to_string()))
+        );
+    }
+    #[test]
+    fn test_eval_errors() {
+        assert_eq!(eval_formula("1/0"), Err(EvalError::DivisionByZero));
+        assert_eq!(eval_formula("1%0"), Err(EvalError::DivisionByZero));
+        assert_eq!(
+            eval_formula("x"),
+            Err(EvalError::UndefinedVariable("x".to_string()))
+        );
+    }
+    #[test]
+    fn test_logic() {
+        for (formula, expected) in [
+            ("2<3", 1),
+            ("3<=2", 0),
+            ("2==2", 1),
+            ("2!=2", 0),
+            ("3>2", 1),
+            ("2>=3", 0),
+            ("1&&0", 0),
+            ("0||2", 1),
+            ("!0", 1),
+            ("!5", 0),
+            ("1<2?10:20", 10),
+            ("if(0,10,20)", 20),
+            ("0?1:0?2:3", 3),
+            ("0&&x", 0),
+            ("1||x", 1),
+            ("1?2:x", 2),
+            ("if(0,x,3)", 3),
+            ("1/3+1/6==1/2", 1),
+        ] {
+            assert_eq!(eval_formula(formula), Ok(ratio(expected, 1)), "{}", formula);
+        }
+        assert_eq!(
+            eval_formula("1?x:2"),
+            Err(EvalError::UndefinedVariable("x".to_string()))
+        );
+    }
+}
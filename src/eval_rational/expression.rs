@@ -0,0 +1,157 @@
+use super::ast::{eval, Node};
+use super::parser::Parser;
+use super::simplify::simplify;
+use super::tokenizer::RESERVED_WORDS;
+use crate::utils::{latex, mathml, Context, EvalError, Formula, Functions, ParseError};
+use num_rational::BigRational;
+use std::{collections::HashMap, fmt};
+
+/// A formula parsed once into a reusable AST and evaluated into an exact BigRational as many times as needed.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExpressionRational {
+    ast: Node,
+}
+
+impl ExpressionRational {
+    /// Parse a formula without evaluating it.
+    pub fn new(expr: String) -> Result<Self, ParseError> {
+        let mut math_parser = Parser::new(&expr)?;
+        let ast = math_parser.parse()?;
+        Ok(ExpressionRational { ast })
+    }
+    /// Parse a formula that can call the given user-defined functions.
+    pub fn with_functions(
+        expr: String,
+        functions: &Functions<BigRational>,
+    ) -> Result<Self, ParseError> {
+        let mut math_parser = Parser::with_functions(&expr, functions)?;
+        let ast = math_parser.parse()?;
+        Ok(ExpressionRational { ast })
+    }
+    /// Evaluate the parsed formula, `@` being replaced by the placeholder.
+    pub fn eval(&self, placeholder: BigRational) -> Result<BigRational, EvalError> {
+        self.eval_with_context(placeholder, &HashMap::<String, BigRational>::new())
+    }
+    /// Evaluate the parsed formula, resolving its named variables with the context.
+    pub fn eval_with_context(
+        &self,
+        placeholder: BigRational,
+        context: &dyn Context<BigRational>,
+    ) -> Result<BigRational, EvalError> {
+        eval(&self.ast, &placeholder, context)
+    }
+    /// Simplify the parsed formula, folding its constant parts and combining its like terms, so
+    /// that it evaluates faster.
+    pub fn simplify(&self) -> Self {
+        ExpressionRational {
+            ast: simplify(&self.ast),
+        }
+    }
+    /// Write the parsed formula as LaTeX math, without the surrounding `$`.
+    pub fn to_latex(&self) -> String {
+        latex(&self.ast)
+    }
+    /// Write the parsed formula as a MathML `math` element.
+    pub fn to_mathml(&self) -> String {
+        mathml(&self.ast)
+    }
+}
+
+/// Writes the parsed formula back with as few parentheses as possible, as text that parses into
+/// the same formula.
+impl fmt::Display for ExpressionRational {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.ast)
+    }
+}
+
+impl Formula for ExpressionRational {
+    type Value = BigRational;
+
+    fn parse(expr: String, functions: Option<&Functions<BigRational>>) -> Result<Self, ParseError> {
+        match functions {
+            Some(functions) => ExpressionRational::with_functions(expr, functions),
+            None => ExpressionRational::new(expr),
+        }
+    }
+    fn evaluate(
+        &self,
+        placeholder: BigRational,
+        context: &dyn Context<BigRational>,
+    ) -> Result<BigRational, EvalError> {
+        self.eval_with_context(placeholder, context)
+    }
+    fn is_reserved(name: &str) -> bool {
+        RESERVED_WORDS.contains(&name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::Arity;
+    use crate::utils::ParseErrorKind;
+    use num_traits::Zero;
+
+    fn ratio(numer: i64, denom: i64) -> BigRational {
+        BigRational::new(numer.into(), denom.into())
+    }
+
+    #[test]
+    fn test_reuse() {
+        let expr = ExpressionRational::new("@ / 3".to_string()).unwrap();
+        assert_eq!(expr.eval(ratio(1, 1)).unwrap(), ratio(1, 3));
+        assert_eq!(expr.eval(ratio(6, 1)).unwrap(), ratio(2, 1));
+    }
+    #[test]
+    fn test_display() {
+        let expr = ExpressionRational::new("1/3 + 1/6".to_string()).unwrap();
+        assert_eq!(expr.eval(BigRational::zero()).unwrap().to_string(), "1/2");
+        let expr = ExpressionRational::new("30!".to_string()).unwrap();
+        assert_eq!(
+            expr.eval(BigRational::zero()).unwrap().to_string(),
+            "265252859812191058636308480000000"
+        );
+    }
+    #[test]
+    fn test_error_span() {
+        let error = ExpressionRational::new("1 + sin(2)".to_string()).unwrap_err();
+        assert_eq!(error.kind(), ParseErrorKind::UnknownFunction);
+        assert_eq!(error.span(), 4..7);
+        let error = ExpressionRational::new("2 + 1e3.5".to_string()).unwrap_err();
+        assert_eq!(error.kind(), ParseErrorKind::InvalidNumber);
+        assert_eq!(error.span(), 4..9);
+        let error = ExpressionRational::new("1.2.3".to_string()).unwrap_err();
+        assert_eq!(error.kind(), ParseErrorKind::InvalidNumber);
+        assert_eq!(error.span(), 0..5);
+        let error = ExpressionRational::new("x + 2..5".to_string()).unwrap_err();
+        assert_eq!(error.kind(), ParseErrorKind::InvalidNumber);
+        assert_eq!(error.span(), 4..8);
+    }
+    #[test]
+    fn test_context() {
+        let expr = ExpressionRational::new("price * rate".to_string()).unwrap();
+        let mut context = HashMap::new();
+        context.insert("price".to_string(), ratio(1999, 100));
+        context.insert("rate".to_string(), ratio(3, 10));
+        assert_eq!(
+            expr.eval_with_context(BigRational::zero(), &context)
+                .unwrap(),
+            ratio(5997, 1000)
+        );
+    }
+    #[test]
+    fn test_custom_functions() {
+        let mut functions = Functions::new();
+        functions.register("inv", Arity::Exactly(1), |args: &[BigRational]| {
+            if args[0].is_zero() {
+                return Err(EvalError::DivisionByZero);
+            }
+            Ok(args[0].recip())
+        });
+        let expr =
+            ExpressionRational::with_functions("inv(@) + 1".to_string(), &functions).unwrap();
+        assert_eq!(expr.eval(ratio(4, 1)).unwrap(), ratio(5, 4));
+        assert_eq!(expr.eval(ratio(0, 1)), Err(EvalError::DivisionByZero));
+    }
+}
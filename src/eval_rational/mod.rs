@@ -0,0 +1,16 @@
+mod ast;
+mod expression;
+mod notation;
+mod parser;
+mod simplify;
+mod token;
+mod tokenizer;
+
+use crate::utils::Error;
+pub use expression::ExpressionRational;
+use num_rational::BigRational;
+
+/// Evaluate a formula inside a string and compute it exactly into a BigRational.
+pub fn eval_rational(expr: String, placeholder: BigRational) -> Result<BigRational, Error> {
+    Ok(ExpressionRational::new(expr)?.eval(placeholder)?)
+}
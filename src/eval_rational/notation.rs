@@ -0,0 +1,131 @@
+use super::ast::Node;
+use crate::utils::{write_infix, Notation, Operator, OperatorCategory, Term, Unary};
+use num_bigint::BigInt;
+use num_integer::Integer;
+use num_rational::BigRational;
+use num_traits::{One, Signed};
+use std::fmt;
+
+/// Write a number as the decimal the tokenizer reads into it, or as a fraction when it has no
+/// finite decimal expansion.
+fn literal<'a>(value: &BigRational) -> Term<'a, Node> {
+    let mut denominator = value.denom().clone();
+    for factor in [2, 5] {
+        let factor = BigInt::from(factor);
+        while denominator.is_multiple_of(&factor) {
+            denominator /= &factor;
+        }
+    }
+    if !denominator.is_one() {
+        return Term::Literal(value.to_string(), OperatorCategory::Multiplicative);
+    }
+    let mut scaled = value.abs();
+    let mut decimals = 0;
+    while !scaled.is_integer() {
+        scaled *= BigInt::from(10);
+        decimals += 1;
+    }
+    let mut digits = scaled.to_integer().to_string();
+    if decimals > 0 {
+        if digits.len() <= decimals {
+            digits = format!("{}{}", "0".repeat(decimals + 1 - digits.len()), digits);
+        }
+        digits.insert(digits.len() - decimals, '.');
+    }
+    if value.is_negative() {
+        digits.insert(0, '-');
+    }
+    Term::number(digits)
+}
+
+impl Notation for Node {
+    fn term(&self) -> Term<'_, Self> {
+        use self::Node::*;
+        match self {
+            Num(value) => literal(value),
+            Variable(name) => Term::Variable(name),
+            Ans => Term::Ans,
+            Add(left, right) => Term::Binary(Operator::Add, left, right),
+            Subtract(left, right) => Term::Binary(Operator::Subtract, left, right),
+            Multiply(left, right) => Term::Binary(Operator::Multiply, left, right),
+            Divide(left, right) => Term::Binary(Operator::Divide, left, right),
+            Modulo(left, right) => Term::Binary(Operator::Modulo, left, right),
+            Root(left, right) => Term::Function("root", vec![left, right]),
+            Pow(left, right) => Term::Binary(Operator::Pow, left, right),
+            Negative(expr) => Term::Unary(Unary::Negative, expr),
+            Abs(expr) => Term::Function("abs", vec![expr]),
+            Floor(expr) => Term::Function("floor", vec![expr]),
+            Ceil(expr) => Term::Function("ceil", vec![expr]),
+            Round(expr) => Term::Function("round", vec![expr]),
+            Sqrt(expr) => Term::Function("sqrt", vec![expr]),
+            Exp2(expr) => Term::Function("exp2", vec![expr]),
+            Truncate(expr) => Term::Function("trunc", vec![expr]),
+            Sign(expr) => Term::Function("sign", vec![expr]),
+            Min(args) => Term::Function("min", args.iter().collect()),
+            Max(args) => Term::Function("max", args.iter().collect()),
+            Avg(args) => Term::Function("avg", args.iter().collect()),
+            Med(args) => Term::Function("med", args.iter().collect()),
+            Gcd(args) => Term::Function("gcd", args.iter().collect()),
+            Lcm(args) => Term::Function("lcm", args.iter().collect()),
+            Equal(left, right) => Term::Binary(Operator::Equal, left, right),
+            NotEqual(left, right) => Term::Binary(Operator::NotEqual, left, right),
+            Less(left, right) => Term::Binary(Operator::Less, left, right),
+            LessEqual(left, right) => Term::Binary(Operator::LessEqual, left, right),
+            Greater(left, right) => Term::Binary(Operator::Greater, left, right),
+            GreaterEqual(left, right) => Term::Binary(Operator::GreaterEqual, left, right),
+            LogicalAnd(left, right) => Term::Binary(Operator::LogicalAnd, left, right),
+            LogicalOr(left, right) => Term::Binary(Operator::LogicalOr, left, right),
+            LogicalNot(expr) => Term::Unary(Unary::LogicalNot, expr),
+            Factorial(expr) => Term::Factorial(expr),
+            Conditional(condition, then, otherwise) => {
+                Term::Conditional(condition, then, otherwise)
+            }
+            CustomFunction(function, args) => {
+                Term::Function(function.name(), args.iter().collect())
+            }
+        }
+    }
+}
+
+impl fmt::Display for Node {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write_infix(self, f)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::eval_rational::ast::Node;
+    use crate::eval_rational::parser::Parser;
+    use num_rational::BigRational;
+
+    #[test]
+    fn test_literals() {
+        let number = |numer: i32, denom: i32| {
+            Node::Num(BigRational::new(numer.into(), denom.into())).to_string()
+        };
+        assert_eq!(number(3, 2000), "0.0015");
+        assert_eq!(number(-25, 2), "-12.5");
+        assert_eq!(number(1, 3), "1/3");
+        assert_eq!(number(200, 1), "200");
+    }
+    #[test]
+    fn test_round_trip() {
+        let formulas = [
+            "0.1+1.5e-3*x-2^-3/(4-x)",
+            "abs(x)+⌊x⌋+⌈x⌉+round(x)+trunc(x)+sign(x)+sqrt(x)+root(3,x)+exp2(x)+x%2+3!",
+            "min(x,1)+max(x,1)+avg(x,1)+med(x,1)+gcd(x,2)+lcm(x,3)",
+            "x>=1&&x!=2||!(x<=0)?1:2",
+        ];
+        for formula in formulas {
+            let ast = Parser::new(formula).unwrap().parse().unwrap();
+            let text = ast.to_string();
+            assert_eq!(
+                Parser::new(&text).unwrap().parse().unwrap(),
+                ast,
+                "{}",
+                text
+            );
+        }
+    }
+}
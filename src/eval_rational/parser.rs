@@ -0,0 +1,614 @@
+use std::{ops::Range, sync::Arc};
+
+use super::ast::Node;
+use super::token::{NativeFunction, Token};
+use super::tokenizer::Tokenizer;
+use crate::utils::{
+    too_deep, Arity, Functions, OperatorCategory, ParseError, ParseErrorKind, MAX_DEPTH,
+};
+use num_rational::BigRational;
+use num_traits::Zero;
+
+pub struct Parser<'a> {
+    tokenizer: Tokenizer<'a>,
+    current_token: Token,
+    current_span: Range<usize>,
+    previous_token: Option<Token>,
+    depth: usize,
+}
+
+impl<'a> Parser<'a> {
+    pub fn new(expr: &'a str) -> Result<Self, ParseError> {
+        Parser::from_tokenizer(Tokenizer::new(expr))
+    }
+    pub fn with_functions(
+        expr: &'a str,
+        functions: &'a Functions<BigRational>,
+    ) -> Result<Self, ParseError> {
+        Parser::from_tokenizer(Tokenizer::with_functions(expr, functions))
+    }
+    fn from_tokenizer(mut lexer: Tokenizer<'a>) -> Result<Self, ParseError> {
+        lexer.skip_whitespace();
+        let start = lexer.position();
+        let cur_token = match lexer.next() {
+            Some(token) => token,
+            None => return Err(lexer.error(start)),
+        };
+        Ok(Parser {
+            current_span: start..lexer.position(),
+            tokenizer: lexer,
+            current_token: cur_token,
+            previous_token: None,
+            depth: 0,
+        })
+    }
+    pub fn parse(&mut self) -> Result<Node, ParseError> {
+        let ast = self.generate_ast(OperatorCategory::DefaultZero)?;
+        if self.current_token == Token::Eof {
+            Ok(ast)
+        } else if self.current_token.is_closing_bracket() {
+            Err(ParseError::new(
+                ParseErrorKind::UnbalancedBracket,
+                self.current_span.clone(),
+                format!("Unmatched {}", self.current_token),
+            ))
+        } else {
+            Err(self.unexpected_token(&["an operator", "end of input"]))
+        }
+    }
+    fn get_next_token(&mut self) -> Result<(), ParseError> {
+        self.tokenizer.skip_whitespace();
+        let start = self.tokenizer.position();
+        let next_token = match self.tokenizer.next() {
+            Some(token) => token,
+            None => return Err(self.tokenizer.error(start)),
+        };
+        self.previous_token = Some(self.current_token.clone());
+        self.current_token = next_token;
+        self.current_span = start..self.tokenizer.position();
+        Ok(())
+    }
+    fn generate_ast(&mut self, oper_prec: OperatorCategory) -> Result<Node, ParseError> {
+        let depth = self.depth;
+        self.nest()?;
+        let mut left_expr = self.parse_number()?;
+        while oper_prec < self.current_token.get_oper_prec() {
+            if self.current_token == Token::Eof {
+                break;
+            }
+            self.nest()?;
+            left_expr = self.convert_token_to_node(left_expr)?;
+        }
+        self.depth = depth;
+        Ok(left_expr)
+    }
+    fn nest(&mut self) -> Result<(), ParseError> {
+        self.depth += 1;
+        if self.depth > MAX_DEPTH {
+            return Err(too_deep(self.current_span.clone()));
+        }
+        Ok(())
+    }
+    fn function_static_arguments(&mut self, n: usize) -> Result<Vec<Node>, ParseError> {
+        let function = self.current_token.clone();
+        let function_span = self.current_span.clone();
+        self.get_next_token()?;
+        self.check_paren(Token::LeftParen)?;
+        let mut args = Vec::new();
+        for i in 0..n {
+            let arg_expr = self.generate_ast(OperatorCategory::DefaultZero)?;
+            args.push(arg_expr);
+            if i < n - 1 {
+                if self.current_token == Token::RightParen {
+                    return Err(wrong_arity(&function, function_span, n));
+                }
+                self.check_paren(Token::Comma)?;
+            }
+        }
+        if self.current_token == Token::Comma {
+            return Err(wrong_arity(&function, function_span, n));
+        }
+        self.check_paren(Token::RightParen)?;
+        Ok(args)
+    }
+    fn function_arguments(&mut self) -> Result<Vec<Node>, ParseError> {
+        self.find_item_list(
+            Token::LeftParen,
+            Token::RightParen,
+            OperatorCategory::DefaultZero,
+        )
+    }
+    fn find_item_list(
+        &mut self,
+        start_token: Token,
+        end_token: Token,
+        oper_prec: OperatorCategory,
+    ) -> Result<Vec<Node>, ParseError> {
+        self.get_next_token()?;
+        self.check_paren(start_token)?;
+        let mut args = Vec::new();
+        loop {
+            if args.is_empty() && (end_token == self.current_token) {
+                self.get_next_token()?;
+                break;
+            }
+            let arg_expr = self.generate_ast(oper_prec.clone())?;
+            args.push(arg_expr);
+            if Token::Comma == self.current_token {
+                self.get_next_token()?;
+            } else if end_token == self.current_token {
+                self.get_next_token()?;
+                break;
+            } else {
+                return Err(self.missing_token(&[Token::Comma, end_token]));
+            }
+        }
+        Ok(args)
+    }
+    fn parse_number(&mut self) -> Result<Node, ParseError> {
+        let token = self.current_token.clone();
+        match token {
+            Token::Ans => {
+                self.get_next_token()?;
+                Ok(Node::Ans)
+            }
+            Token::Identifier(name) => {
+                self.get_next_token()?;
+                self.implicit_multiply(Node::Variable(name))
+            }
+            Token::CustomFunction(function) => {
+                let function_span = self.current_span.clone();
+                let args = match function.arity() {
+                    Arity::Exactly(n) => self.function_static_arguments(n)?,
+                    Arity::AtLeast(n) => {
+                        let args = self.function_arguments()?;
+                        if args.len() < n {
+                            return Err(ParseError::new(
+                                ParseErrorKind::WrongArity,
+                                function_span,
+                                format!(
+                                    "The {} function takes at least {} arguments",
+                                    function.name(),
+                                    n
+                                ),
+                            ));
+                        }
+                        args
+                    }
+                };
+                self.implicit_multiply(Node::CustomFunction(function, Arc::new(args)))
+            }
+            Token::ExplicitFunction(current_function) => {
+                let function_span = self.current_span.clone();
+                let current_function = match current_function {
+                    NativeFunction::Abs => {
+                        Node::Abs(Box::new(self.function_static_arguments(1)?[0].clone()))
+                    }
+                    NativeFunction::Floor => {
+                        Node::Floor(Box::new(self.function_static_arguments(1)?[0].clone()))
+                    }
+                    NativeFunction::Ceil => {
+                        Node::Ceil(Box::new(self.function_static_arguments(1)?[0].clone()))
+                    }
+                    NativeFunction::Round => {
+                        Node::Round(Box::new(self.function_static_arguments(1)?[0].clone()))
+                    }
+                    NativeFunction::Sqrt => {
+                        Node::Sqrt(Box::new(self.function_static_arguments(1)?[0].clone()))
+                    }
+                    NativeFunction::Exp2 => {
+                        Node::Exp2(Box::new(self.function_static_arguments(1)?[0].clone()))
+                    }
+                    NativeFunction::Sign => {
+                        Node::Sign(Box::new(self.function_static_arguments(1)?[0].clone()))
+                    }
+                    NativeFunction::Truncate => {
+                        Node::Truncate(Box::new(self.function_static_arguments(1)?[0].clone()))
+                    }
+                    NativeFunction::Mod => {
+                        let args = self.function_static_arguments(2)?;
+                        Node::Modulo(Box::new(args[0].clone()), Box::new(args[1].clone()))
+                    }
+                    NativeFunction::Pow => {
+                        let args = self.function_static_arguments(2)?;
+                        Node::Pow(Box::new(args[0].clone()), Box::new(args[1].clone()))
+                    }
+                    NativeFunction::Root => {
+                        let args = self.function_static_arguments(2)?;
+                        Node::Root(Box::new(args[0].clone()), Box::new(args[1].clone()))
+                    }
+                    NativeFunction::Gcd => {
+                        let args = self.function_arguments()?;
+                        if args.is_empty() {
+                            return Err(ParseError::new(
+                                ParseErrorKind::WrongArity,
+                                function_span,
+                                "There's no arguments in the gcd function".to_string(),
+                            ));
+                        }
+                        Node::Gcd(Arc::new(args))
+                    }
+                    NativeFunction::Lcm => {
+                        let args = self.function_arguments()?;
+                        if args.is_empty() {
+                            return Err(ParseError::new(
+                                ParseErrorKind::WrongArity,
+                                function_span,
+                                "There's no arguments in the lcm function".to_string(),
+                            ));
+                        }
+                        Node::Lcm(Arc::new(args))
+                    }
+                    NativeFunction::Min => {
+                        let args = self.function_arguments()?;
+                        if args.is_empty() {
+                            return Err(ParseError::new(
+                                ParseErrorKind::WrongArity,
+                                function_span,
+                                "There's no arguments in the min function".to_string(),
+                            ));
+                        }
+                        Node::Min(Arc::new(args))
+                    }
+                    NativeFunction::Max => {
+                        let args = self.function_arguments()?;
+                        if args.is_empty() {
+                            return Err(ParseError::new(
+                                ParseErrorKind::WrongArity,
+                                function_span,
+                                "There's no arguments in the max function".to_string(),
+                            ));
+                        }
+                        Node::Max(Arc::new(args))
+                    }
+                    NativeFunction::Avg => {
+                        let args = self.function_arguments()?;
+                        if args.is_empty() {
+                            Node::Num(BigRational::zero())
+                        } else {
+                            Node::Avg(Arc::new(args))
+                        }
+                    }
+                    NativeFunction::Med => {
+                        let args = self.function_arguments()?;
+                        if args.is_empty() {
+                            return Err(ParseError::new(
+                                ParseErrorKind::WrongArity,
+                                function_span,
+                                "Cannot compute the median of no arguments".to_string(),
+                            ));
+                        } else {
+                            Node::Med(Arc::new(args))
+                        }
+                    }
+                    NativeFunction::If => {
+                        let args = self.function_static_arguments(3)?;
+                        Node::Conditional(
+                            Box::new(args[0].clone()),
+                            Box::new(args[1].clone()),
+                            Box::new(args[2].clone()),
+                        )
+                    }
+                };
+                self.implicit_multiply(current_function)
+            }
+            Token::Subtract => {
+                self.get_next_token()?;
+                let expr = self.generate_ast(OperatorCategory::Negative)?;
+                Ok(Node::Negative(Box::new(expr)))
+            }
+            Token::Add => {
+                self.get_next_token()?;
+                let expr = self.generate_ast(OperatorCategory::Negative)?;
+                Ok(expr)
+            }
+            Token::ExclamationMark => {
+                self.get_next_token()?;
+                let expr = self.generate_ast(OperatorCategory::Negative)?;
+                Ok(Node::LogicalNot(Box::new(expr)))
+            }
+            Token::Num(i) => {
+                let end = self.current_span.end;
+                self.get_next_token()?;
+                if let Token::Num(_) = self.current_token {
+                    if self.current_span.start > end {
+                        // Numbers separated by whitespace are not multiplied.
+                        return Err(self.unexpected_token(&["an operator"]));
+                    }
+                }
+                self.implicit_multiply(Node::Num(i))
+            }
+            Token::LeftParen => self.get_enclosed_elements_with_impl_mult(
+                OperatorCategory::DefaultZero,
+                Token::RightParen,
+                |expr| expr,
+            ),
+            Token::LeftFloor => self.get_enclosed_elements_with_impl_mult(
+                OperatorCategory::DefaultZero,
+                Token::RightFloor,
+                |expr| Node::Floor(Box::new(expr)),
+            ),
+            Token::LeftCeiling => self.get_enclosed_elements_with_impl_mult(
+                OperatorCategory::DefaultZero,
+                Token::RightCeiling,
+                |expr| Node::Ceil(Box::new(expr)),
+            ),
+            _ => Err(self.unexpected_token(&["a number", "a variable", "a function", "("])),
+        }
+    }
+    fn implicit_multiply(&mut self, node: Node) -> Result<Node, ParseError> {
+        if (self.current_token == Token::LeftParen)
+            || (self.current_token == Token::LeftCeiling)
+            || (self.current_token == Token::LeftFloor)
+            || matches!(self.current_token, Token::ExplicitFunction(_))
+            || matches!(self.current_token, Token::CustomFunction(_))
+            || matches!(self.current_token, Token::Num(_))
+            || matches!(self.current_token, Token::Identifier(_))
+        {
+            let right = self.generate_ast(OperatorCategory::Multiplicative)?;
+            return Ok(Node::Multiply(Box::new(node), Box::new(right)));
+        }
+        Ok(node)
+    }
+    fn get_enclosed_elements_with_impl_mult(
+        &mut self,
+        oper_prec: OperatorCategory,
+        end_token: Token,
+        get_node: fn(Node) -> Node,
+    ) -> Result<Node, ParseError> {
+        self.get_next_token()?;
+        let expr = self.generate_ast(oper_prec)?;
+        self.check_paren(end_token)?;
+        self.implicit_multiply(get_node(expr))
+    }
+    fn unexpected_token<S: ToString>(&self, expected: &[S]) -> ParseError {
+        ParseError::new(
+            ParseErrorKind::UnexpectedToken,
+            self.current_span.clone(),
+            format!("Unexpected {}", self.current_token),
+        )
+        .with_expected(expected)
+    }
+    fn missing_token(&self, expected: &[Token]) -> ParseError {
+        let unclosed = self.current_token == Token::Eof || self.current_token.is_closing_bracket();
+        if !(unclosed && expected.iter().any(Token::is_closing_bracket)) {
+            return self.unexpected_token(expected);
+        }
+        ParseError::new(
+            ParseErrorKind::UnbalancedBracket,
+            self.current_span.clone(),
+            format!("Unexpected {}", self.current_token),
+        )
+        .with_expected(expected)
+    }
+    fn check_paren(&mut self, expected: Token) -> Result<(), ParseError> {
+        if expected == self.current_token {
+            self.get_next_token()?;
+            Ok(())
+        } else {
+            Err(self.missing_token(&[expected]))
+        }
+    }
+    fn convert_token_to_node(&mut self, left_expr: Node) -> Result<Node, ParseError> {
+        match self.current_token.clone() {
+            Token::Add => {
+                self.get_next_token()?;
+                let right_expr = self.generate_ast(OperatorCategory::Additive)?;
+                Ok(Node::Add(Box::new(left_expr), Box::new(right_expr)))
+            }
+            Token::Subtract => {
+                self.get_next_token()?;
+                let right_expr = self.generate_ast(OperatorCategory::Additive)?;
+                Ok(Node::Subtract(Box::new(left_expr), Box::new(right_expr)))
+            }
+            Token::Multiply => {
+                self.get_next_token()?;
+                let right_expr = self.generate_ast(OperatorCategory::Multiplicative)?;
+                Ok(Node::Multiply(Box::new(left_expr), Box::new(right_expr)))
+            }
+            Token::Divide => {
+                self.get_next_token()?;
+                let right_expr = self.generate_ast(OperatorCategory::Multiplicative)?;
+                Ok(Node::Divide(Box::new(left_expr), Box::new(right_expr)))
+            }
+            Token::Caret => {
+                self.get_next_token()?;
+                // Right-associative: the exponent is itself a power, optionally negated.
+                let right_expr = self.generate_ast(OperatorCategory::Negative)?;
+                Ok(Node::Pow(Box::new(left_expr), Box::new(right_expr)))
+            }
+            Token::ExclamationMark => {
+                self.get_next_token()?;
+                self.implicit_multiply(Node::Factorial(Box::new(left_expr)))
+            }
+            Token::Superscript(script) => {
+                self.get_next_token()?;
+                Ok(Node::Pow(Box::new(left_expr), Box::new(Node::Num(script))))
+            }
+            Token::Modulo => {
+                self.get_next_token()?;
+                let right_expr = self.generate_ast(OperatorCategory::Multiplicative)?;
+                Ok(Node::Modulo(Box::new(left_expr), Box::new(right_expr)))
+            }
+            Token::Equal => {
+                self.get_next_token()?;
+                let right_expr = self.generate_ast(OperatorCategory::Comparison)?;
+                Ok(Node::Equal(Box::new(left_expr), Box::new(right_expr)))
+            }
+            Token::NotEqual => {
+                self.get_next_token()?;
+                let right_expr = self.generate_ast(OperatorCategory::Comparison)?;
+                Ok(Node::NotEqual(Box::new(left_expr), Box::new(right_expr)))
+            }
+            Token::Less => {
+                self.get_next_token()?;
+                let right_expr = self.generate_ast(OperatorCategory::Comparison)?;
+                Ok(Node::Less(Box::new(left_expr), Box::new(right_expr)))
+            }
+            Token::LessEqual => {
+                self.get_next_token()?;
+                let right_expr = self.generate_ast(OperatorCategory::Comparison)?;
+                Ok(Node::LessEqual(Box::new(left_expr), Box::new(right_expr)))
+            }
+            Token::Greater => {
+                self.get_next_token()?;
+                let right_expr = self.generate_ast(OperatorCategory::Comparison)?;
+                Ok(Node::Greater(Box::new(left_expr), Box::new(right_expr)))
+            }
+            Token::GreaterEqual => {
+                self.get_next_token()?;
+                let right_expr = self.generate_ast(OperatorCategory::Comparison)?;
+                Ok(Node::GreaterEqual(
+                    Box::new(left_expr),
+                    Box::new(right_expr),
+                ))
+            }
+            Token::DoubleAmpersand => {
+                self.get_next_token()?;
+                let right_expr = self.generate_ast(OperatorCategory::LogicalAnd)?;
+                Ok(Node::LogicalAnd(Box::new(left_expr), Box::new(right_expr)))
+            }
+            Token::DoubleBar => {
+                self.get_next_token()?;
+                let right_expr = self.generate_ast(OperatorCategory::LogicalOr)?;
+                Ok(Node::LogicalOr(Box::new(left_expr), Box::new(right_expr)))
+            }
+            Token::QuestionMark => {
+                self.get_next_token()?;
+                let then_expr = self.generate_ast(OperatorCategory::DefaultZero)?;
+                self.check_paren(Token::Colon)?;
+                // Right-associative: the else branch can itself be a conditional.
+                let else_expr = self.generate_ast(OperatorCategory::DefaultZero)?;
+                Ok(Node::Conditional(
+                    Box::new(left_expr),
+                    Box::new(then_expr),
+                    Box::new(else_expr),
+                ))
+            }
+            _ => Err(self.unexpected_token(&["an operator"])),
+        }
+    }
+}
+
+fn wrong_arity(function: &Token, function_span: Range<usize>, n: usize) -> ParseError {
+    ParseError::new(
+        ParseErrorKind::WrongArity,
+        function_span,
+        format!("The {} function takes {} arguments", function, n),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::eval_rational::ast::Node::*;
+
+    fn num(n: i64) -> Box<Node> {
+        Box::new(Num(BigRational::from_integer(n.into())))
+    }
+
+    #[test]
+    fn test_negative() {
+        let mut parser = Parser::new("-1").unwrap();
+        let expected = Negative(num(1));
+        assert_eq!(parser.parse().unwrap(), expected);
+    }
+    #[test]
+    fn test_add() {
+        let mut parser = Parser::new("1+2").unwrap();
+        let expected = Add(num(1), num(2));
+        assert_eq!(parser.parse().unwrap(), expected);
+    }
+    #[test]
+    fn test_divide() {
+        let mut parser = Parser::new("1/3").unwrap();
+        let expected = Divide(num(1), num(3));
+        assert_eq!(parser.parse().unwrap(), expected);
+    }
+    #[test]
+    fn test_decimal() {
+        let mut parser = Parser::new("0.25").unwrap();
+        let expected = Num(BigRational::new(1.into(), 4.into()));
+        assert_eq!(parser.parse().unwrap(), expected);
+    }
+    #[test]
+    fn test_caret_right_associative() {
+        let mut parser = Parser::new("2^3^2").unwrap();
+        let expected = Pow(num(2), Box::new(Pow(num(3), num(2))));
+        assert_eq!(parser.parse().unwrap(), expected);
+        let mut parser = Parser::new("2^3²").unwrap();
+        assert_eq!(parser.parse().unwrap(), expected);
+    }
+    #[test]
+    fn test_exclamation_mark() {
+        let mut parser = Parser::new("5!").unwrap();
+        let expected = Factorial(num(5));
+        assert_eq!(parser.parse().unwrap(), expected);
+    }
+    #[test]
+    fn test_floor() {
+        let mut parser = Parser::new("⌊7/2⌋").unwrap();
+        let expected = Floor(Box::new(Divide(num(7), num(2))));
+        assert_eq!(parser.parse().unwrap(), expected);
+    }
+    #[test]
+    fn test_ceil() {
+        let mut parser = Parser::new("⌈7/2⌉").unwrap();
+        let expected = Ceil(Box::new(Divide(num(7), num(2))));
+        assert_eq!(parser.parse().unwrap(), expected);
+    }
+    #[test]
+    fn test_root_function() {
+        let mut parser = Parser::new("root(3,8)").unwrap();
+        let expected = Root(num(3), num(8));
+        assert_eq!(parser.parse().unwrap(), expected);
+    }
+    #[test]
+    fn test_gcd_function() {
+        let mut parser = Parser::new("gcd(12,18)").unwrap();
+        let expected = Gcd(Arc::new(vec![*num(12), *num(18)]));
+        assert_eq!(parser.parse().unwrap(), expected);
+        let error = Parser::new("gcd()").unwrap().parse().unwrap_err();
+        assert_eq!(error.kind(), ParseErrorKind::WrongArity);
+    }
+    #[test]
+    fn test_avg_function() {
+        let mut parser = Parser::new("avg()").unwrap();
+        let expected = Num(BigRational::zero());
+        assert_eq!(parser.parse().unwrap(), expected);
+    }
+    #[test]
+    fn test_transcendental_function() {
+        let error = Parser::new("sin(1)").err().unwrap();
+        assert_eq!(error.kind(), ParseErrorKind::UnknownFunction);
+    }
+    #[test]
+    fn test_implicit_mul_variable() {
+        let mut parser = Parser::new("2x").unwrap();
+        let expected = Multiply(num(2), Box::new(Variable("x".to_string())));
+        assert_eq!(parser.parse().unwrap(), expected);
+    }
+    #[test]
+    fn test_conditional() {
+        let mut parser = Parser::new("1+1<3&&!0?4:5").unwrap();
+        let condition = LogicalAnd(
+            Box::new(Less(Box::new(Add(num(1), num(1))), num(3))),
+            Box::new(LogicalNot(num(0))),
+        );
+        let expected = Conditional(Box::new(condition), num(4), num(5));
+        assert_eq!(parser.parse().unwrap(), expected);
+        let mut parser = Parser::new("1?2:3?4:5").unwrap();
+        let expected = Conditional(
+            num(1),
+            num(2),
+            Box::new(Conditional(num(3), num(4), num(5))),
+        );
+        assert_eq!(parser.parse().unwrap(), expected);
+        let mut parser = Parser::new("if(0||1,2,3)").unwrap();
+        let expected = Conditional(Box::new(LogicalOr(num(0), num(1))), num(2), num(3));
+        assert_eq!(parser.parse().unwrap(), expected);
+        let error = Parser::new("1?2").unwrap().parse().unwrap_err();
+        assert_eq!(error.kind(), ParseErrorKind::UnexpectedToken);
+        assert_eq!(error.expected(), [":"]);
+    }
+}
@@ -0,0 +1,126 @@
+use crate::utils::{Function, OperatorCategory};
+use num_rational::BigRational;
+use std::fmt;
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum NativeFunction {
+    Gcd,
+    Lcm,
+    Pow,
+    Sqrt,
+    Root,
+    Exp2,
+    Abs,
+    Sign,
+    Truncate,
+    Floor,
+    Ceil,
+    Round,
+    Min,
+    Max,
+    Avg,
+    Med,
+    Mod,
+    If,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum Token {
+    Add,
+    Subtract,
+    Multiply,
+    Divide,
+    Caret,
+    ExclamationMark,
+    Modulo,
+    Equal,
+    NotEqual,
+    Less,
+    LessEqual,
+    Greater,
+    GreaterEqual,
+    DoubleAmpersand,
+    DoubleBar,
+    QuestionMark,
+    Colon,
+    LeftParen,
+    RightParen,
+    LeftFloor,
+    RightFloor,
+    LeftCeiling,
+    RightCeiling,
+    Comma,
+    ExplicitFunction(NativeFunction),
+    CustomFunction(Function<BigRational>),
+    Superscript(BigRational),
+    Num(BigRational),
+    Identifier(String),
+    Ans,
+    Eof,
+}
+
+impl Token {
+    pub fn get_oper_prec(&self) -> OperatorCategory {
+        use self::Token::*;
+        match *self {
+            QuestionMark => OperatorCategory::Conditional,
+            DoubleBar => OperatorCategory::LogicalOr,
+            DoubleAmpersand => OperatorCategory::LogicalAnd,
+            Equal | NotEqual | Less | LessEqual | Greater | GreaterEqual => {
+                OperatorCategory::Comparison
+            }
+            Add | Subtract => OperatorCategory::Additive,
+            Multiply | Divide | Modulo => OperatorCategory::Multiplicative,
+            Caret | Superscript(_) => OperatorCategory::Power,
+            ExclamationMark | ExplicitFunction(_) | CustomFunction(_) => {
+                OperatorCategory::Functional
+            }
+            _ => OperatorCategory::DefaultZero,
+        }
+    }
+    pub fn is_closing_bracket(&self) -> bool {
+        use self::Token::*;
+        matches!(self, RightParen | RightFloor | RightCeiling)
+    }
+}
+
+impl fmt::Display for Token {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        use self::Token::*;
+        match self {
+            Add => write!(f, "+"),
+            Subtract => write!(f, "-"),
+            Multiply => write!(f, "*"),
+            Divide => write!(f, "/"),
+            Caret => write!(f, "^"),
+            ExclamationMark => write!(f, "!"),
+            Modulo => write!(f, "%"),
+            Equal => write!(f, "=="),
+            NotEqual => write!(f, "!="),
+            Less => write!(f, "<"),
+            LessEqual => write!(f, "<="),
+            Greater => write!(f, ">"),
+            GreaterEqual => write!(f, ">="),
+            DoubleAmpersand => write!(f, "&&"),
+            DoubleBar => write!(f, "||"),
+            QuestionMark => write!(f, "?"),
+            Colon => write!(f, ":"),
+            LeftParen => write!(f, "("),
+            RightParen => write!(f, ")"),
+            LeftFloor => write!(f, "⌊"),
+            RightFloor => write!(f, "⌋"),
+            LeftCeiling => write!(f, "⌈"),
+            RightCeiling => write!(f, "⌉"),
+            Comma => write!(f, ","),
+            ExplicitFunction(function) => {
+                write!(f, "{}", format!("{:?}", function).to_lowercase())
+            }
+            CustomFunction(function) => write!(f, "{}", function.name()),
+            Superscript(value) => write!(f, "^{}", value),
+            Num(value) => write!(f, "{}", value),
+            Identifier(name) => write!(f, "{}", name),
+            Ans => write!(f, "@"),
+            Eof => write!(f, "end of input"),
+        }
+    }
+}
@@ -0,0 +1,507 @@
+use super::ast::MAX_BITS;
+use super::token::{NativeFunction, Token};
+use crate::utils::{
+    deserialize_number, deserialize_superscript_number, peek_identifier, unrecognized_token,
+    Cursor, Functions, ParseError,
+};
+use num_bigint::BigInt;
+use num_rational::BigRational;
+use num_traits::{One, Pow, Zero};
+
+/// The names read as constants or units rather than variables.
+pub const RESERVED_WORDS: [&str; 0] = [];
+
+/// Read a decimal literal exactly, `0.1` being one tenth, scaled by its `e` exponent if any.
+fn parse_rational(number: &str, exponent: &str) -> Option<BigRational> {
+    let (integer, fraction) = match number.split_once('.') {
+        Some((integer, fraction)) => (integer, fraction),
+        None => (number, ""),
+    };
+    let numer = format!("{}{}", integer, fraction).parse::<BigInt>().ok()?;
+    let mut scale = -i64::try_from(fraction.len()).ok()?;
+    if !exponent.is_empty() {
+        scale = scale.checked_add(exponent[1..].parse::<i64>().ok()?)?;
+    }
+    // A power of ten takes less than four bits per digit.
+    if scale.unsigned_abs() > MAX_BITS / 4 {
+        return None;
+    }
+    let digits = scale.unsigned_abs();
+    if numer.is_zero() {
+        return Some(BigRational::zero());
+    }
+    if scale >= 0 {
+        return Some(BigRational::from_integer(
+            numer * BigInt::from(10).pow(digits),
+        ));
+    }
+    // Only the factors 2 and 5 of the power of ten can divide the digits, so they are cancelled
+    // directly instead of through a gcd, which is slow with a denominator that long.
+    let twos = numer.trailing_zeros()?.min(digits);
+    let mut numer = numer >> twos;
+    let mut fives = 0;
+    while fives < digits && (&numer % 5u32).is_zero() {
+        numer /= 5u32;
+        fives += 1;
+    }
+    let denom = (BigInt::one() << (digits - twos)) * BigInt::from(5).pow(digits - fives);
+    Some(BigRational::new_raw(numer, denom))
+}
+
+pub struct Tokenizer<'a> {
+    expr: Cursor<'a>,
+    functions: Option<&'a Functions<BigRational>>,
+}
+
+impl<'a> Tokenizer<'a> {
+    pub fn new(new_expr: &'a str) -> Self {
+        Tokenizer {
+            expr: Cursor::new(new_expr),
+            functions: None,
+        }
+    }
+    pub fn with_functions(new_expr: &'a str, functions: &'a Functions<BigRational>) -> Self {
+        Tokenizer {
+            expr: Cursor::new(new_expr),
+            functions: Some(functions),
+        }
+    }
+    /// The byte offset of the next token.
+    pub fn position(&self) -> usize {
+        self.expr.position()
+    }
+    /// Consume the whitespaces before the next token, which only separate tokens.
+    pub fn skip_whitespace(&mut self) {
+        self.expr.skip_whitespace()
+    }
+    /// The error explaining why no token could be read at the given byte offset.
+    pub fn error(&self, start: usize) -> ParseError {
+        unrecognized_token(self.expr.source(), start)
+    }
+    fn superscript(&mut self, first_char: char) -> Option<Token> {
+        let digits = deserialize_superscript_number(&first_char, &mut self.expr);
+        Some(Token::Superscript(BigRational::from_integer(
+            digits.parse::<BigInt>().ok()?,
+        )))
+    }
+    fn native_token(&mut self) -> Option<Token> {
+        let current_char = self.expr.next();
+
+        match current_char {
+            Some('@') => Some(Token::Ans),
+            Some('+') => Some(Token::Add),
+            Some('-') => Some(Token::Subtract),
+            Some('*') => Some(Token::Multiply),
+            Some('/') => Some(Token::Divide),
+            Some('^') => Some(Token::Caret),
+            Some('(') => Some(Token::LeftParen),
+            Some(')') => Some(Token::RightParen),
+            Some('!') => {
+                if let Some('=') = self.expr.peek() {
+                    self.expr.next();
+                    Some(Token::NotEqual)
+                } else {
+                    Some(Token::ExclamationMark)
+                }
+            }
+            Some('=') => {
+                if let Some('=') = self.expr.peek() {
+                    self.expr.next();
+                    Some(Token::Equal)
+                } else {
+                    None
+                }
+            }
+            Some('<') => {
+                if let Some('=') = self.expr.peek() {
+                    self.expr.next();
+                    Some(Token::LessEqual)
+                } else {
+                    Some(Token::Less)
+                }
+            }
+            Some('>') => {
+                if let Some('=') = self.expr.peek() {
+                    self.expr.next();
+                    Some(Token::GreaterEqual)
+                } else {
+                    Some(Token::Greater)
+                }
+            }
+            Some('&') => {
+                if let Some('&') = self.expr.peek() {
+                    self.expr.next();
+                    Some(Token::DoubleAmpersand)
+                } else {
+                    None
+                }
+            }
+            Some('|') => {
+                if let Some('|') = self.expr.peek() {
+                    self.expr.next();
+                    Some(Token::DoubleBar)
+                } else {
+                    None
+                }
+            }
+            Some('?') => Some(Token::QuestionMark),
+            Some(':') => Some(Token::Colon),
+            Some(',') => Some(Token::Comma),
+            Some('%') => Some(Token::Modulo),
+            Some('⌊') => Some(Token::LeftFloor),
+            Some('⌋') => Some(Token::RightFloor),
+            Some('⌈') => Some(Token::LeftCeiling),
+            Some('⌉') => Some(Token::RightCeiling),
+            Some('.') => {
+                let next_char = self.expr.peek()?;
+                if next_char.is_ascii_digit() {
+                    let (number, exponent) = deserialize_number('.', &mut self.expr)?;
+                    Some(Token::Num(parse_rational(&number, &exponent)?))
+                } else {
+                    None
+                }
+            }
+            Some(first_char @ ('⁰' | '¹' | '²' | '³' | '⁴' | '⁵' | '⁶' | '⁷' | '⁸' | '⁹')) => {
+                self.superscript(first_char)
+            }
+            Some('0'..='9') => {
+                let (number, exponent) = deserialize_number(current_char?, &mut self.expr)?;
+                Some(Token::Num(parse_rational(&number, &exponent)?))
+            }
+            Some('a') => match self.expr.clone().take(3).collect::<String>().as_str() {
+                "bs(" => {
+                    self.expr.by_ref().take(2).for_each(drop);
+                    Some(Token::ExplicitFunction(NativeFunction::Abs))
+                }
+                "vg(" => {
+                    self.expr.by_ref().take(2).for_each(drop);
+                    Some(Token::ExplicitFunction(NativeFunction::Avg))
+                }
+                _ => None,
+            },
+            Some('c') => {
+                if self.expr.clone().take(4).collect::<String>() == "eil(" {
+                    self.expr.by_ref().take(3).for_each(drop);
+                    Some(Token::ExplicitFunction(NativeFunction::Ceil))
+                } else {
+                    None
+                }
+            }
+            Some('e') => {
+                if self.expr.clone().take(4).collect::<String>() == "xp2(" {
+                    self.expr.by_ref().take(3).for_each(drop);
+                    Some(Token::ExplicitFunction(NativeFunction::Exp2))
+                } else {
+                    None
+                }
+            }
+            Some('f') => {
+                if self.expr.clone().take(5).collect::<String>() == "loor(" {
+                    self.expr.by_ref().take(4).for_each(drop);
+                    Some(Token::ExplicitFunction(NativeFunction::Floor))
+                } else {
+                    None
+                }
+            }
+            Some('g') => {
+                if self.expr.clone().take(3).collect::<String>() == "cd(" {
+                    self.expr.by_ref().take(2).for_each(drop);
+                    Some(Token::ExplicitFunction(NativeFunction::Gcd))
+                } else {
+                    None
+                }
+            }
+            Some('i') => {
+                if self.expr.clone().take(2).collect::<String>() == "f(" {
+                    self.expr.by_ref().take(1).for_each(drop);
+                    Some(Token::ExplicitFunction(NativeFunction::If))
+                } else {
+                    None
+                }
+            }
+            Some('l') => {
+                if self.expr.clone().take(3).collect::<String>() == "cm(" {
+                    self.expr.by_ref().take(2).for_each(drop);
+                    Some(Token::ExplicitFunction(NativeFunction::Lcm))
+                } else {
+                    None
+                }
+            }
+            Some('m') => match self.expr.clone().take(6).collect::<String>().as_str() {
+                "edian(" => {
+                    self.expr.by_ref().take(5).for_each(drop);
+                    Some(Token::ExplicitFunction(NativeFunction::Med))
+                }
+                _ => match self.expr.clone().take(3).collect::<String>().as_str() {
+                    "in(" => {
+                        self.expr.by_ref().take(2).for_each(drop);
+                        Some(Token::ExplicitFunction(NativeFunction::Min))
+                    }
+                    "ax(" => {
+                        self.expr.by_ref().take(2).for_each(drop);
+                        Some(Token::ExplicitFunction(NativeFunction::Max))
+                    }
+                    "od(" => {
+                        self.expr.by_ref().take(2).for_each(drop);
+                        Some(Token::ExplicitFunction(NativeFunction::Mod))
+                    }
+                    "ed(" => {
+                        self.expr.by_ref().take(2).for_each(drop);
+                        Some(Token::ExplicitFunction(NativeFunction::Med))
+                    }
+                    _ => None,
+                },
+            },
+            Some('p') => {
+                if self.expr.clone().take(3).collect::<String>() == "ow(" {
+                    self.expr.by_ref().take(2).for_each(drop);
+                    Some(Token::ExplicitFunction(NativeFunction::Pow))
+                } else {
+                    None
+                }
+            }
+            Some('r') => {
+                if self.expr.clone().take(5).collect::<String>() == "ound(" {
+                    self.expr.by_ref().take(4).for_each(drop);
+                    Some(Token::ExplicitFunction(NativeFunction::Round))
+                } else if self.expr.clone().take(4).collect::<String>() == "oot(" {
+                    self.expr.by_ref().take(3).for_each(drop);
+                    Some(Token::ExplicitFunction(NativeFunction::Root))
+                } else {
+                    None
+                }
+            }
+            Some('s') => match self.expr.clone().take(6).collect::<String>().as_str() {
+                "ignum(" => {
+                    self.expr.by_ref().take(5).for_each(drop);
+                    Some(Token::ExplicitFunction(NativeFunction::Sign))
+                }
+                _ => match self.expr.clone().take(4).collect::<String>().as_str() {
+                    "qrt(" => {
+                        self.expr.by_ref().take(3).for_each(drop);
+                        Some(Token::ExplicitFunction(NativeFunction::Sqrt))
+                    }
+                    "ign(" => {
+                        self.expr.by_ref().take(3).for_each(drop);
+                        Some(Token::ExplicitFunction(NativeFunction::Sign))
+                    }
+                    _ => match self.expr.clone().take(3).collect::<String>().as_str() {
+                        "gn(" => {
+                            self.expr.by_ref().take(2).for_each(drop);
+                            Some(Token::ExplicitFunction(NativeFunction::Sign))
+                        }
+                        _ => None,
+                    },
+                },
+            },
+            Some('t') => {
+                if self.expr.clone().take(8).collect::<String>() == "runcate(" {
+                    self.expr.by_ref().take(7).for_each(drop);
+                    Some(Token::ExplicitFunction(NativeFunction::Truncate))
+                } else if self.expr.clone().take(5).collect::<String>() == "runc(" {
+                    self.expr.by_ref().take(4).for_each(drop);
+                    Some(Token::ExplicitFunction(NativeFunction::Truncate))
+                } else {
+                    None
+                }
+            }
+            None => Some(Token::Eof),
+            Some(_) => None,
+        }
+    }
+}
+
+impl<'a> Iterator for Tokenizer<'a> {
+    type Item = Token;
+
+    fn next(&mut self) -> Option<Token> {
+        self.expr.skip_whitespace();
+        let (identifier, next_char) = peek_identifier(&self.expr);
+        if !identifier.is_empty() {
+            if next_char == Some('(') {
+                if let Some(function) = self
+                    .functions
+                    .and_then(|functions| functions.get(&identifier))
+                {
+                    let function = function.clone();
+                    self.expr.by_ref().take(identifier.len()).for_each(drop);
+                    return Some(Token::CustomFunction(function));
+                }
+            } else if !RESERVED_WORDS.contains(&identifier.as_str()) {
+                self.expr.by_ref().take(identifier.len()).for_each(drop);
+                return Some(Token::Identifier(identifier));
+            }
+        }
+        if next_char == Some('(') && !identifier.is_empty() {
+            // The native functions are matched along with their `(`, which whitespace may set
+            // apart from the name.
+            let call = format!("{}(", identifier);
+            let mut native = Tokenizer::new(&call);
+            let token = native.native_token()?;
+            if native.position() != identifier.len() {
+                // The word before the arguments is not a native function either.
+                return None;
+            }
+            self.expr.by_ref().take(identifier.len()).for_each(drop);
+            return Some(token);
+        }
+        self.native_token()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ratio(numer: i64, denom: i64) -> BigRational {
+        BigRational::new(numer.into(), denom.into())
+    }
+
+    #[test]
+    fn test_integer_number() {
+        let mut tokenizer = Tokenizer::new("34");
+        assert_eq!(tokenizer.next().unwrap(), Token::Num(ratio(34, 1)))
+    }
+    #[test]
+    fn test_big_integer_number() {
+        let mut tokenizer = Tokenizer::new("123456789012345678901234567890");
+        assert_eq!(
+            tokenizer.next().unwrap(),
+            Token::Num(BigRational::from_integer(
+                "123456789012345678901234567890".parse().unwrap()
+            ))
+        )
+    }
+    #[test]
+    fn test_decimal_number() {
+        let mut tokenizer = Tokenizer::new("0.1+.25");
+        assert_eq!(tokenizer.next().unwrap(), Token::Num(ratio(1, 10)));
+        assert_eq!(tokenizer.next().unwrap(), Token::Add);
+        assert_eq!(tokenizer.next().unwrap(), Token::Num(ratio(1, 4)));
+    }
+    #[test]
+    fn test_scientific_number() {
+        let mut tokenizer = Tokenizer::new("1.5e-3*2E2");
+        assert_eq!(tokenizer.next().unwrap(), Token::Num(ratio(3, 2000)));
+        assert_eq!(tokenizer.next().unwrap(), Token::Multiply);
+        assert_eq!(tokenizer.next().unwrap(), Token::Num(ratio(200, 1)));
+        let mut tokenizer = Tokenizer::new("1e999999999");
+        assert_eq!(tokenizer.next(), None);
+        let mut tokenizer = Tokenizer::new("2.50e-2+0.0e-5+250e-3");
+        assert_eq!(tokenizer.next().unwrap(), Token::Num(ratio(1, 40)));
+        assert_eq!(tokenizer.next().unwrap(), Token::Add);
+        assert_eq!(tokenizer.next().unwrap(), Token::Num(ratio(0, 1)));
+        assert_eq!(tokenizer.next().unwrap(), Token::Add);
+        assert_eq!(tokenizer.next().unwrap(), Token::Num(ratio(1, 4)));
+        // The literal is reduced without a gcd.
+        let mut tokenizer = Tokenizer::new("3e-16000");
+        let expected = BigRational::new_raw(BigInt::from(3), BigInt::from(10).pow(16000u32));
+        assert_eq!(tokenizer.next().unwrap(), Token::Num(expected));
+        let mut tokenizer = Tokenizer::new("1e-201000");
+        assert_eq!(tokenizer.next(), None);
+    }
+    #[test]
+    fn test_superscript_number() {
+        let mut tokenizer = Tokenizer::new("⁰¹²³⁴⁵⁶⁷⁸⁹");
+        assert_eq!(
+            tokenizer.next().unwrap(),
+            Token::Superscript(ratio(123456789, 1))
+        )
+    }
+    #[test]
+    fn test_floor_ceiling() {
+        let mut tokenizer = Tokenizer::new("⌊⌋⌈⌉");
+        assert_eq!(tokenizer.next().unwrap(), Token::LeftFloor);
+        assert_eq!(tokenizer.next().unwrap(), Token::RightFloor);
+        assert_eq!(tokenizer.next().unwrap(), Token::LeftCeiling);
+        assert_eq!(tokenizer.next().unwrap(), Token::RightCeiling);
+    }
+    #[test]
+    fn test_operators() {
+        let mut tokenizer = Tokenizer::new("+-*/^!%(),@");
+        for token in [
+            Token::Add,
+            Token::Subtract,
+            Token::Multiply,
+            Token::Divide,
+            Token::Caret,
+            Token::ExclamationMark,
+            Token::Modulo,
+            Token::LeftParen,
+            Token::RightParen,
+            Token::Comma,
+            Token::Ans,
+            Token::Eof,
+        ] {
+            assert_eq!(tokenizer.next().unwrap(), token);
+        }
+    }
+    #[test]
+    fn test_functions() {
+        let functions = [
+            ("gcd(", NativeFunction::Gcd),
+            ("lcm(", NativeFunction::Lcm),
+            ("pow(", NativeFunction::Pow),
+            ("sqrt(", NativeFunction::Sqrt),
+            ("root(", NativeFunction::Root),
+            ("exp2(", NativeFunction::Exp2),
+            ("abs(", NativeFunction::Abs),
+            ("sign(", NativeFunction::Sign),
+            ("sgn(", NativeFunction::Sign),
+            ("signum(", NativeFunction::Sign),
+            ("trunc(", NativeFunction::Truncate),
+            ("truncate(", NativeFunction::Truncate),
+            ("floor(", NativeFunction::Floor),
+            ("ceil(", NativeFunction::Ceil),
+            ("round(", NativeFunction::Round),
+            ("min(", NativeFunction::Min),
+            ("max(", NativeFunction::Max),
+            ("avg(", NativeFunction::Avg),
+            ("med(", NativeFunction::Med),
+            ("median(", NativeFunction::Med),
+            ("mod(", NativeFunction::Mod),
+        ];
+        for (formula, function) in functions {
+            let mut tokenizer = Tokenizer::new(formula);
+            assert_eq!(tokenizer.next().unwrap(), Token::ExplicitFunction(function));
+        }
+    }
+    #[test]
+    fn test_irrational_functions() {
+        let mut tokenizer = Tokenizer::new("sin(1)");
+        assert_eq!(tokenizer.next(), None);
+    }
+    #[test]
+    fn test_identifier() {
+        let mut tokenizer = Tokenizer::new("price_2*e");
+        assert_eq!(
+            tokenizer.next().unwrap(),
+            Token::Identifier("price_2".to_string())
+        );
+        assert_eq!(tokenizer.next().unwrap(), Token::Multiply);
+        assert_eq!(
+            tokenizer.next().unwrap(),
+            Token::Identifier("e".to_string())
+        );
+    }
+    #[test]
+    fn test_logical_operators() {
+        let mut tokenizer = Tokenizer::new("==!=<=<>=>&&||!?:if(");
+        for token in [
+            Token::Equal,
+            Token::NotEqual,
+            Token::LessEqual,
+            Token::Less,
+            Token::GreaterEqual,
+            Token::Greater,
+            Token::DoubleAmpersand,
+            Token::DoubleBar,
+            Token::ExclamationMark,
+            Token::QuestionMark,
+            Token::Colon,
+            Token::ExplicitFunction(NativeFunction::If),
+        ] {
+            assert_eq!(tokenizer.next().unwrap(), token);
+        }
+    }
+}
@@ -0,0 +1,438 @@
+//! Property tests feeding random formulas to every backend, none of which may panic.
+
+use crate::{Error, ParseErrorKind};
+use std::panic::{catch_unwind, AssertUnwindSafe};
+
+const FRAGMENTS: &[&str] = &[
+    "0",
+    "1",
+    "2",
+    "7",
+    "42",
+    "0.5",
+    ".5",
+    "1.2.3",
+    "3.",
+    "9999999999999999999999999999999",
+    "170",
+    "1000",
+    "0x",
+    "0b",
+    "0o7",
+    "FF",
+    "_",
+    "-",
+    "+",
+    "*",
+    "/",
+    "^",
+    "!",
+    "%",
+    "&",
+    "|",
+    "<<",
+    ">>",
+    ">>>",
+    "⊕",
+    "~",
+    "<",
+    ">",
+    "<=",
+    ">=",
+    "==",
+    "!=",
+    "=",
+    "&&",
+    "||",
+    "?",
+    ":",
+    "(",
+    ")",
+    "(",
+    ")",
+    ",",
+    "⌊",
+    "⌋",
+    "⌈",
+    "⌉",
+    "@",
+    "π",
+    "pi",
+    "e",
+    "E",
+    "e-",
+    "e+",
+    "e400",
+    "(1e400-1e400)",
+    "i",
+    "x",
+    "rad",
+    "°",
+    "²",
+    "⁰",
+    "¹",
+    "⁹⁹⁹⁹⁹⁹⁹⁹⁹⁹⁹⁹⁹⁹⁹⁹⁹⁹⁹⁹",
+    " ",
+    "  ",
+    "\t",
+    "if(",
+    "sin(",
+    "cos(",
+    "tan(",
+    "asin(",
+    "acos(",
+    "atan(",
+    "atan2(",
+    "sinh(",
+    "cosh(",
+    "tanh(",
+    "arsinh(",
+    "arcosh(",
+    "artanh(",
+    "sqrt(",
+    "root(",
+    "ln(",
+    "lb(",
+    "log(",
+    "ilog(",
+    "exp(",
+    "exp2(",
+    "pow(",
+    "abs(",
+    "sign(",
+    "floor(",
+    "ceil(",
+    "round(",
+    "trunc(",
+    "mod(",
+    "min(",
+    "max(",
+    "avg(",
+    "med(",
+    "gcd(",
+    "lcm(",
+    "popcnt(",
+    "clz(",
+    "ctz(",
+    "rotl(",
+    "rotr(",
+    "W(",
+    "lambert_w(",
+    "re(",
+    "im(",
+    "arg(",
+    "conj(",
+    "norm(",
+    "polar(",
+    "cis(",
+    "∠",
+    "foo(",
+    "$",
+    "é",
+    "\u{0}",
+    "ⁱ",
+];
+
+/// A small xorshift generator, so that every run checks the same formulas.
+struct Random(u64);
+
+impl Random {
+    fn next(&mut self) -> usize {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0 as usize
+    }
+    fn formula(&mut self) -> String {
+        let length = self.next() % 16;
+        (0..length)
+            .map(|_| FRAGMENTS[self.next() % FRAGMENTS.len()])
+            .collect()
+    }
+}
+
+fn check_formulas(eval: impl Fn(String)) {
+    let mut random = Random(0x2545_f491_4f6c_dd1d);
+    for _ in 0..20_000 {
+        let formula = random.formula();
+        if catch_unwind(AssertUnwindSafe(|| eval(formula.clone()))).is_err() {
+            panic!("Evaluating {:?} panicked", formula);
+        }
+    }
+}
+
+/// Debug builds use far larger stack frames than release ones, so the deeply nested
+/// formulas are evaluated on a thread with the usual main thread stack size.
+fn check_nesting(eval: impl Fn(String) -> Result<(), Error> + Send + 'static) {
+    let formulas = [
+        "(".repeat(100_000),
+        "(".repeat(100_000) + "1" + &")".repeat(100_000),
+        "-".repeat(100_000) + "1",
+        "1+".repeat(100_000) + "1",
+        "2^".repeat(100_000) + "2",
+        "sqrt(".repeat(100_000) + "1",
+        "(1)".repeat(100_000),
+    ];
+    std::thread::Builder::new()
+        .stack_size(8 << 20)
+        .spawn(move || {
+            for formula in formulas {
+                match eval(formula) {
+                    Err(Error::Parse(error)) => assert_eq!(error.kind(), ParseErrorKind::TooDeep),
+                    result => panic!("Expected a nesting error, got {:?}", result),
+                }
+            }
+        })
+        .unwrap()
+        .join()
+        .unwrap();
+}
+
+#[cfg(feature = "eval_f64")]
+#[test]
+fn test_eval_f64() {
+    for placeholder in [0.0, -1.0, f64::NAN, f64::INFINITY, f64::MAX] {
+        check_formulas(|formula| drop(crate::eval_f64(formula, placeholder)));
+    }
+}
+
+#[cfg(feature = "eval_f64")]
+#[test]
+fn test_eval_f64_nesting() {
+    check_nesting(|formula| crate::eval_f64(formula, 0.0).map(drop));
+}
+
+#[cfg(feature = "eval_i64")]
+#[test]
+fn test_eval_i64() {
+    use crate::{ExpressionI64, OverflowMode};
+    use std::collections::HashMap;
+    for placeholder in [0, -1, i64::MIN, i64::MAX] {
+        check_formulas(|formula| drop(crate::eval_i64(formula, placeholder)));
+    }
+    for mode in [OverflowMode::Wrapping, OverflowMode::Saturating] {
+        check_formulas(|formula| {
+            if let Ok(expr) = ExpressionI64::new(formula) {
+                drop(expr.eval_with_mode(i64::MAX, &HashMap::new(), mode));
+            }
+        });
+    }
+}
+
+#[cfg(feature = "eval_i64")]
+#[test]
+fn test_eval_i64_nesting() {
+    check_nesting(|formula| crate::eval_i64(formula, 0).map(drop));
+}
+
+/// Evaluate the formulas into another integer type at its bounds, in every overflow mode.
+#[cfg(feature = "eval_integer")]
+fn check_integer<T: crate::Integer>() {
+    use crate::{ExpressionInteger, OverflowMode};
+    use std::collections::HashMap;
+    for mode in [
+        OverflowMode::Checked,
+        OverflowMode::Wrapping,
+        OverflowMode::Saturating,
+    ] {
+        check_formulas(|formula| {
+            if let Ok(expr) = ExpressionInteger::<T>::new(formula) {
+                for placeholder in [T::ZERO, T::MIN, T::MAX] {
+                    drop(expr.eval_with_mode(placeholder, &HashMap::new(), mode));
+                }
+            }
+        });
+    }
+}
+
+#[cfg(feature = "eval_integer")]
+#[test]
+fn test_eval_integer() {
+    check_integer::<u8>();
+    check_integer::<i8>();
+    check_integer::<u128>();
+    check_integer::<i128>();
+}
+
+#[cfg(feature = "eval_integer")]
+#[test]
+fn test_eval_integer_nesting() {
+    check_nesting(|formula| {
+        crate::ExpressionInteger::<u8>::new(formula)
+            .map(drop)
+            .map_err(Error::Parse)
+    });
+}
+
+#[cfg(feature = "eval_decimal")]
+#[test]
+fn test_eval_decimal() {
+    use rust_decimal::Decimal;
+    for placeholder in [
+        Decimal::ZERO,
+        Decimal::NEGATIVE_ONE,
+        Decimal::MIN,
+        Decimal::MAX,
+    ] {
+        check_formulas(|formula| drop(crate::eval_decimal(formula, placeholder)));
+    }
+}
+
+#[cfg(feature = "eval_decimal")]
+#[test]
+fn test_eval_decimal_nesting() {
+    check_nesting(|formula| crate::eval_decimal(formula, rust_decimal::Decimal::ZERO).map(drop));
+}
+
+#[cfg(feature = "eval_complex")]
+#[test]
+fn test_eval_complex() {
+    use num_complex::Complex;
+    for placeholder in [
+        Complex::new(0.0, 0.0),
+        Complex::new(-1.0, 1.0),
+        Complex::new(f64::NAN, f64::INFINITY),
+    ] {
+        check_formulas(|formula| drop(crate::eval_complex(formula, placeholder)));
+    }
+}
+
+#[cfg(feature = "eval_complex")]
+#[test]
+fn test_eval_complex_nesting() {
+    check_nesting(|formula| {
+        crate::eval_complex(formula, num_complex::Complex::new(0.0, 0.0)).map(drop)
+    });
+}
+
+#[cfg(feature = "eval_number")]
+#[test]
+fn test_eval_number() {
+    use crate::Number;
+    for placeholder in [
+        Number::Integer(0),
+        Number::Integer(i64::MIN),
+        Number::Float(f64::NAN),
+        Number::Float(-0.5),
+        Number::Rational(num_rational::Rational64::new(i64::MAX, 2)),
+        Number::from((i64::MIN, -1)),
+    ] {
+        check_formulas(|formula| drop(crate::eval_number(formula, placeholder.clone())));
+    }
+}
+
+#[cfg(feature = "eval_number")]
+#[test]
+fn test_eval_number_nesting() {
+    check_nesting(|formula| crate::eval_number(formula, crate::Number::Integer(0)).map(drop));
+}
+
+#[cfg(feature = "eval_rational")]
+#[test]
+fn test_eval_rational() {
+    use num_rational::BigRational;
+    for placeholder in [
+        BigRational::from_integer(0.into()),
+        BigRational::new((-1).into(), 3.into()),
+        BigRational::from_integer(i64::MAX.into()),
+    ] {
+        check_formulas(|formula| drop(crate::eval_rational(formula, placeholder.clone())));
+    }
+}
+
+#[cfg(feature = "eval_rational")]
+#[test]
+fn test_eval_rational_nesting() {
+    check_nesting(|formula| {
+        crate::eval_rational(formula, num_rational::BigRational::from_integer(0.into())).map(drop)
+    });
+}
+
+/// Simplifying folds the constant parts of the formulas by evaluating them, which may not panic
+/// either.
+#[test]
+fn test_simplify() {
+    #[cfg(feature = "eval_f64")]
+    check_formulas(|formula| {
+        if let Ok(expr) = crate::ExpressionF64::new(formula) {
+            drop(expr.simplify().eval(-1.0));
+        }
+    });
+    #[cfg(feature = "eval_integer")]
+    check_formulas(|formula| {
+        if let Ok(expr) = crate::ExpressionInteger::<i8>::new(formula.clone()) {
+            drop(expr.simplify().eval(i8::MIN));
+        }
+        if let Ok(expr) = crate::ExpressionInteger::<u8>::new(formula) {
+            drop(expr.simplify().eval(u8::MAX));
+        }
+    });
+    #[cfg(feature = "eval_decimal")]
+    check_formulas(|formula| {
+        if let Ok(expr) = crate::ExpressionDecimal::new(formula) {
+            drop(expr.simplify().eval(rust_decimal::Decimal::MAX));
+        }
+    });
+    #[cfg(feature = "eval_complex")]
+    check_formulas(|formula| {
+        if let Ok(expr) = crate::ExpressionComplex::new(formula) {
+            drop(expr.simplify().eval(num_complex::Complex::new(0.0, 1.0)));
+        }
+    });
+    #[cfg(feature = "eval_number")]
+    check_formulas(|formula| {
+        if let Ok(expr) = crate::ExpressionNumber::new(formula) {
+            drop(expr.simplify().eval(crate::Number::Integer(i64::MIN)));
+        }
+    });
+    #[cfg(feature = "eval_rational")]
+    check_formulas(|formula| {
+        if let Ok(expr) = crate::ExpressionRational::new(formula) {
+            drop(
+                expr.simplify()
+                    .eval(num_rational::BigRational::new(1.into(), 3.into())),
+            );
+        }
+    });
+}
+
+/// The formulas written back as text parse into the same formulas.
+#[test]
+fn test_display() {
+    #[cfg(feature = "eval_f64")]
+    check_formulas(|formula| {
+        if let Ok(expr) = crate::ExpressionF64::new(formula) {
+            assert_eq!(crate::ExpressionF64::new(expr.to_string()), Ok(expr));
+        }
+    });
+    #[cfg(feature = "eval_integer")]
+    check_formulas(|formula| {
+        if let Ok(expr) = crate::ExpressionInteger::<i64>::new(formula) {
+            assert_eq!(crate::ExpressionInteger::new(expr.to_string()), Ok(expr));
+        }
+    });
+    #[cfg(feature = "eval_decimal")]
+    check_formulas(|formula| {
+        if let Ok(expr) = crate::ExpressionDecimal::new(formula) {
+            assert_eq!(crate::ExpressionDecimal::new(expr.to_string()), Ok(expr));
+        }
+    });
+    #[cfg(feature = "eval_complex")]
+    check_formulas(|formula| {
+        if let Ok(expr) = crate::ExpressionComplex::new(formula) {
+            assert_eq!(crate::ExpressionComplex::new(expr.to_string()), Ok(expr));
+        }
+    });
+    #[cfg(feature = "eval_number")]
+    check_formulas(|formula| {
+        if let Ok(expr) = crate::ExpressionNumber::new(formula) {
+            assert_eq!(crate::ExpressionNumber::new(expr.to_string()), Ok(expr));
+        }
+    });
+    #[cfg(feature = "eval_rational")]
+    check_formulas(|formula| {
+        if let Ok(expr) = crate::ExpressionRational::new(formula) {
+            assert_eq!(crate::ExpressionRational::new(expr.to_string()), Ok(expr));
+        }
+    });
+}
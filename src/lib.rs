@@ -4,34 +4,90 @@ mod eval_complex;
 mod eval_decimal;
 #[cfg(feature = "eval_f64")]
 mod eval_f64;
-#[cfg(feature = "eval_i64")]
-mod eval_i64;
+#[cfg(feature = "eval_integer")]
+mod eval_integer;
 #[cfg(feature = "eval_number")]
 mod eval_number;
+#[cfg(feature = "eval_rational")]
+mod eval_rational;
 #[cfg(any(
     feature = "eval_decimal",
     feature = "eval_f64",
-    feature = "eval_i64",
+    feature = "eval_integer",
     feature = "eval_complex",
-    feature = "eval_number"
+    feature = "eval_number",
+    feature = "eval_rational"
 ))]
 mod utils;
 
 #[cfg(feature = "eval_complex")]
-pub use eval_complex::eval_complex;
+pub use eval_complex::{eval_complex, ExpressionComplex};
 #[cfg(feature = "eval_decimal")]
-pub use eval_decimal::eval_decimal;
+pub use eval_decimal::{eval_decimal, ExpressionDecimal, Rounding};
 #[cfg(feature = "eval_f64")]
-pub use eval_f64::eval_f64;
+pub use eval_f64::{eval_f64, ExpressionF64};
+#[cfg(feature = "eval_i128")]
+pub use eval_integer::{eval_i128, ExpressionI128};
+#[cfg(feature = "eval_i16")]
+pub use eval_integer::{eval_i16, ExpressionI16};
+#[cfg(feature = "eval_i32")]
+pub use eval_integer::{eval_i32, ExpressionI32};
 #[cfg(feature = "eval_i64")]
-pub use eval_i64::eval_i64;
+pub use eval_integer::{eval_i64, ExpressionI64};
+#[cfg(feature = "eval_i8")]
+pub use eval_integer::{eval_i8, ExpressionI8};
+#[cfg(feature = "eval_u128")]
+pub use eval_integer::{eval_u128, ExpressionU128};
+#[cfg(feature = "eval_u16")]
+pub use eval_integer::{eval_u16, ExpressionU16};
+#[cfg(feature = "eval_u32")]
+pub use eval_integer::{eval_u32, ExpressionU32};
+#[cfg(feature = "eval_u64")]
+pub use eval_integer::{eval_u64, ExpressionU64};
+#[cfg(feature = "eval_u8")]
+pub use eval_integer::{eval_u8, ExpressionU8};
+#[cfg(feature = "eval_integer")]
+pub use eval_integer::{ExpressionInteger, Integer};
 #[cfg(feature = "eval_number")]
-pub use eval_number::{eval_number, Number};
+pub use eval_number::{eval_number, ExpressionNumber, Number};
+#[cfg(feature = "eval_rational")]
+pub use eval_rational::{eval_rational, ExpressionRational};
 #[cfg(any(
     feature = "eval_decimal",
     feature = "eval_f64",
-    feature = "eval_i64",
+    feature = "eval_integer",
     feature = "eval_complex",
-    feature = "eval_number"
+    feature = "eval_number",
+    feature = "eval_rational"
+))]
+pub use utils::{
+    Arity, Context, Error, EvalError, Formula, Function, Functions, ParseError, ParseErrorKind,
+    Script, MAX_DEPTH,
+};
+#[cfg(feature = "eval_integer")]
+pub use utils::{Base, OverflowMode};
+
+#[cfg(all(
+    test,
+    any(
+        feature = "eval_decimal",
+        feature = "eval_f64",
+        feature = "eval_integer",
+        feature = "eval_complex",
+        feature = "eval_number",
+        feature = "eval_rational"
+    )
+))]
+mod fuzz;
+#[cfg(all(
+    test,
+    any(
+        feature = "eval_decimal",
+        feature = "eval_f64",
+        feature = "eval_integer",
+        feature = "eval_complex",
+        feature = "eval_number",
+        feature = "eval_rational"
+    )
 ))]
-pub use utils::ParseError;
+mod parity;
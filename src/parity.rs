@@ -0,0 +1,130 @@
+//! The operators and functions shared by every backend, so that a formula can move between them.
+
+use crate::Error;
+
+/// Formulas using one shared operator or function each, with their value when `@` is 2.
+const SHARED: &[(&str, f64)] = &[
+    ("1+2", 3.0),
+    ("3-5", -2.0),
+    ("2*3", 6.0),
+    ("6/3", 2.0),
+    ("7%3", 1.0),
+    ("2^3", 8.0),
+    ("-2^2", -4.0),
+    ("+2", 2.0),
+    ("3!", 6.0),
+    ("(1+2)*3", 9.0),
+    ("2(3)", 6.0),
+    ("⌊7/2⌋", 3.0),
+    ("⌈7/2⌉", 4.0),
+    ("2⌊3⌋⌈4⌉", 24.0),
+    ("@*@", 4.0),
+    ("abs(-2)", 2.0),
+    ("mod(7,3)", 1.0),
+    ("min(3,1,2)", 1.0),
+    ("max(3,1,2)", 3.0),
+    ("avg(1,3)", 2.0),
+    ("med(3,1,2)", 2.0),
+    ("median(3,1,2)", 2.0),
+    ("floor(3)", 3.0),
+    ("ceil(3)", 3.0),
+    ("round(3)", 3.0),
+    ("trunc(3)", 3.0),
+    ("truncate(3)", 3.0),
+    ("sqrt(9)", 3.0),
+    ("root(3,8)", 2.0),
+    ("pow(2,3)", 8.0),
+    ("exp2(3)", 8.0),
+    ("1<2", 1.0),
+    ("2<=1", 0.0),
+    ("3>@", 1.0),
+    ("@>=2", 1.0),
+    ("@==2", 1.0),
+    ("@!=2", 0.0),
+    ("1&&0", 0.0),
+    ("0||3", 1.0),
+    ("!0", 1.0),
+    ("1+1==2&&!(3<2)", 1.0),
+    ("@>1?4:5", 4.0),
+    ("0?1:0?2:3", 3.0),
+    ("if(@,6,7)", 6.0),
+    ("0&&1/0", 0.0),
+    ("1||1/0", 1.0),
+    ("1?2:1/0", 2.0),
+    ("if(0,1/0,3)", 3.0),
+];
+
+fn check_shared(eval: impl Fn(&str) -> Result<f64, Error>) {
+    for (formula, expected) in SHARED {
+        match eval(formula) {
+            Ok(value) => assert!(
+                (value - expected).abs() < 1e-9,
+                "{} is {} instead of {}",
+                formula,
+                value,
+                expected
+            ),
+            Err(error) => panic!("{} failed: {}", formula, error),
+        }
+    }
+}
+
+#[cfg(feature = "eval_f64")]
+#[test]
+fn test_eval_f64() {
+    check_shared(|formula| crate::eval_f64(formula.to_string(), 2.0));
+}
+
+#[cfg(feature = "eval_integer")]
+#[test]
+fn test_eval_integer() {
+    fn check<T: crate::Integer>() {
+        check_shared(|formula| {
+            let expression = crate::ExpressionInteger::<T>::new(formula.to_string())?;
+            Ok(expression.eval(T::from_u32(2))?.to_f64())
+        });
+    }
+    check::<i8>();
+    check::<i64>();
+    check::<i128>();
+}
+
+#[cfg(feature = "eval_decimal")]
+#[test]
+fn test_eval_decimal() {
+    use rust_decimal::prelude::ToPrimitive;
+    check_shared(|formula| {
+        let value = crate::eval_decimal(formula.to_string(), rust_decimal::Decimal::TWO)?;
+        Ok(value.to_f64().unwrap())
+    });
+}
+
+#[cfg(feature = "eval_complex")]
+#[test]
+fn test_eval_complex() {
+    check_shared(|formula| {
+        let value = crate::eval_complex(formula.to_string(), num_complex::Complex::new(2.0, 0.0))?;
+        assert_eq!(value.im, 0.0, "{} is not real", formula);
+        Ok(value.re)
+    });
+}
+
+#[cfg(feature = "eval_number")]
+#[test]
+fn test_eval_number() {
+    check_shared(|formula| {
+        Ok(crate::eval_number(formula.to_string(), crate::Number::from(2))?.to_f64())
+    });
+}
+
+#[cfg(feature = "eval_rational")]
+#[test]
+fn test_eval_rational() {
+    use num_traits::ToPrimitive;
+    check_shared(|formula| {
+        let placeholder = num_rational::BigRational::from_integer(2.into());
+        Ok(crate::eval_rational(formula.to_string(), placeholder)?
+            .to_f64()
+            .unwrap())
+    });
+}
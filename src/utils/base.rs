@@ -0,0 +1,40 @@
+use std::fmt;
+
+/// The base used to format an integer result as a literal that can be parsed back.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Base {
+    Binary,
+    Octal,
+    Decimal,
+    Hexadecimal,
+}
+
+impl Base {
+    /// Format the integer with the `0b`, `0o` or `0x` prefix of the base, negative integers being
+    /// written as their two's complement outside of the decimal base.
+    pub fn format<T>(&self, value: T) -> String
+    where
+        T: fmt::Binary + fmt::Octal + fmt::Display + fmt::LowerHex,
+    {
+        match self {
+            Base::Binary => format!("{:#b}", value),
+            Base::Octal => format!("{:#o}", value),
+            Base::Decimal => format!("{}", value),
+            Base::Hexadecimal => format!("{:#x}", value),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format() {
+        assert_eq!(Base::Binary.format(10), "0b1010");
+        assert_eq!(Base::Octal.format(8), "0o10");
+        assert_eq!(Base::Decimal.format(-42), "-42");
+        assert_eq!(Base::Hexadecimal.format(255), "0xff");
+        assert_eq!(Base::Hexadecimal.format(-1i64), "0xffffffffffffffff");
+    }
+}
@@ -0,0 +1,18 @@
+use std::collections::{BTreeMap, HashMap};
+
+/// Resolve the value of the named variables of a formula at evaluation time.
+pub trait Context<T> {
+    fn get(&self, name: &str) -> Option<T>;
+}
+
+impl<T: Clone> Context<T> for HashMap<String, T> {
+    fn get(&self, name: &str) -> Option<T> {
+        HashMap::get(self, name).cloned()
+    }
+}
+
+impl<T: Clone> Context<T> for BTreeMap<String, T> {
+    fn get(&self, name: &str) -> Option<T> {
+        BTreeMap::get(self, name).cloned()
+    }
+}
@@ -0,0 +1,70 @@
+/// An iterator over the characters of a formula keeping track of its byte position.
+#[derive(Debug, Clone)]
+pub struct Cursor<'a> {
+    source: &'a str,
+    position: usize,
+}
+
+impl<'a> Cursor<'a> {
+    pub fn new(source: &'a str) -> Self {
+        Cursor {
+            source,
+            position: 0,
+        }
+    }
+    /// The whole formula being read.
+    pub fn source(&self) -> &'a str {
+        self.source
+    }
+    /// The byte offset of the next character.
+    pub fn position(&self) -> usize {
+        self.position
+    }
+    /// Look at the next character without consuming it.
+    pub fn peek(&self) -> Option<char> {
+        self.source[self.position..].chars().next()
+    }
+    /// Consume the whitespaces separating the next token from the previous one.
+    pub fn skip_whitespace(&mut self) {
+        let rest = &self.source[self.position..];
+        self.position += rest.len() - rest.trim_start().len();
+    }
+}
+
+impl<'a> Iterator for Cursor<'a> {
+    type Item = char;
+
+    fn next(&mut self) -> Option<char> {
+        let current_char = self.source[self.position..].chars().next()?;
+        self.position += current_char.len_utf8();
+        Some(current_char)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_position() {
+        let mut cursor = Cursor::new("2π+1");
+        assert_eq!(cursor.next(), Some('2'));
+        assert_eq!(cursor.peek(), Some('π'));
+        assert_eq!(cursor.position(), 1);
+        assert_eq!(cursor.next(), Some('π'));
+        assert_eq!(cursor.position(), 3);
+        assert_eq!(cursor.by_ref().collect::<String>(), "+1");
+        assert_eq!(cursor.position(), 5);
+        assert_eq!(cursor.peek(), None);
+    }
+    #[test]
+    fn test_skip_whitespace() {
+        let mut cursor = Cursor::new(" \t2 \n");
+        cursor.skip_whitespace();
+        assert_eq!(cursor.position(), 2);
+        assert_eq!(cursor.next(), Some('2'));
+        cursor.skip_whitespace();
+        assert_eq!(cursor.position(), 5);
+        assert_eq!(cursor.next(), None);
+    }
+}
@@ -0,0 +1,50 @@
+use super::Cursor;
+
+/// Read the exponent of a number written in scientific notation, such as `e-3` in `1.5e-3`.
+///
+/// Nothing is consumed unless the `e` is followed by digits, so that it can still be read as the constant.
+pub fn deserialize_exponent(expr: &mut Cursor) -> String {
+    let mut lookahead = expr.clone();
+    let mut exponent = String::new();
+    match lookahead.next() {
+        Some(current_char @ ('e' | 'E')) => exponent.push(current_char),
+        _ => return String::new(),
+    }
+    if let Some(sign @ ('+' | '-')) = lookahead.peek() {
+        lookahead.next();
+        exponent.push(sign);
+    }
+    let digits = exponent.len();
+    while let Some(next_char) = lookahead.peek() {
+        if next_char.is_ascii_digit() {
+            exponent.push(next_char);
+            lookahead.next();
+        } else {
+            break;
+        }
+    }
+    if exponent.len() == digits {
+        return String::new();
+    }
+    *expr = lookahead;
+    exponent
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_deserialize_exponent() {
+        let mut expr = Cursor::new("e-3+1");
+        assert_eq!(deserialize_exponent(&mut expr), "e-3");
+        assert_eq!(expr.position(), 3);
+        let mut expr = Cursor::new("E23");
+        assert_eq!(deserialize_exponent(&mut expr), "E23");
+        let mut expr = Cursor::new("e+x");
+        assert_eq!(deserialize_exponent(&mut expr), "");
+        assert_eq!(expr.position(), 0);
+        let mut expr = Cursor::new("e");
+        assert_eq!(deserialize_exponent(&mut expr), "");
+    }
+}
@@ -0,0 +1,54 @@
+use super::{deserialize_exponent, Cursor};
+
+/// Read a decimal number literal starting with the given digit or `.`, into its mantissa and its
+/// exponent, such as `1.5` and `e-3` in `1.5e-3`. A leading `.` is read as `0.`.
+///
+/// Returns `None` if the mantissa has several `.` or if a `.` or a digit directly follows the
+/// literal, as in `1e3.5`, so that the whole run is an invalid number rather than a product.
+pub fn deserialize_number(first_char: char, expr: &mut Cursor) -> Option<(String, String)> {
+    let mut mantissa = match first_char {
+        '.' => "0.".to_string(),
+        _ => first_char.to_string(),
+    };
+    while let Some(next_char) = expr.peek() {
+        if next_char.is_ascii_digit() || next_char == '.' {
+            mantissa.push(next_char);
+            expr.next();
+        } else {
+            break;
+        }
+    }
+    let exponent = deserialize_exponent(expr);
+    match expr.peek() {
+        Some(next_char) if next_char.is_ascii_digit() || next_char == '.' => None,
+        _ if mantissa.matches('.').count() > 1 => None,
+        _ => Some((mantissa, exponent)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_deserialize_number() {
+        let mut expr = Cursor::new("1.5e-3+1");
+        expr.next();
+        assert_eq!(
+            deserialize_number('1', &mut expr),
+            Some(("1.5".to_string(), "e-3".to_string()))
+        );
+        assert_eq!(expr.position(), 6);
+        let mut expr = Cursor::new("5x");
+        assert_eq!(
+            deserialize_number('.', &mut expr),
+            Some(("0.5".to_string(), String::new()))
+        );
+        assert_eq!(deserialize_number('1', &mut Cursor::new("e3.5")), None);
+        assert_eq!(deserialize_number('1', &mut Cursor::new(".5e2.5")), None);
+        assert_eq!(
+            deserialize_number('1', &mut Cursor::new("e38")),
+            Some(("1".to_string(), "e38".to_string()))
+        );
+    }
+}
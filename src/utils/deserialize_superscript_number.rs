@@ -1,13 +1,11 @@
-use std::{iter::Peekable, str::Chars};
+use super::{superscript_digit_to_digit, Cursor};
 
-use super::superscript_digit_to_digit;
-
-pub fn deserialize_superscript_number(current_char: &char, expr: &mut Peekable<Chars>) -> String {
+pub fn deserialize_superscript_number(current_char: &char, expr: &mut Cursor) -> String {
     let mut number = superscript_digit_to_digit(current_char)
         .map(|c| c.to_string())
         .unwrap_or_default();
     while let Some(next_char) = expr.peek() {
-        if let Some(next_char) = superscript_digit_to_digit(next_char) {
+        if let Some(next_char) = superscript_digit_to_digit(&next_char) {
             expr.next();
             number.push(next_char);
         } else {
@@ -0,0 +1,32 @@
+use super::{EvalError, ParseError};
+use std::fmt;
+
+/// Any error raised while parsing or evaluating a formula.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Error {
+    Parse(ParseError),
+    Eval(EvalError),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::Parse(error) => write!(f, "{}", error),
+            Error::Eval(error) => write!(f, "{}", error),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl From<ParseError> for Error {
+    fn from(error: ParseError) -> Self {
+        Error::Parse(error)
+    }
+}
+
+impl From<EvalError> for Error {
+    fn from(error: EvalError) -> Self {
+        Error::Eval(error)
+    }
+}
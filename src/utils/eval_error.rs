@@ -0,0 +1,57 @@
+use std::fmt;
+
+/// An error raised while evaluating a well-formed formula.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EvalError {
+    /// A division or a modulo by zero.
+    DivisionByZero,
+    /// A result that does not fit in the number type.
+    Overflow,
+    /// A function evaluated outside of its domain, such as the square root of a negative number.
+    Domain(String),
+    /// A function whose result is irrational, such as `sqrt(2)`, in a backend computing exactly.
+    Irrational(String),
+    /// The factorial of a negative integer.
+    NegativeFactorial,
+    /// A shift by a negative amount or by at least the width of the integer, whose amount is kept
+    /// as text since it can be of any integer type.
+    InvalidShift(String),
+    /// A variable that the context does not define.
+    UndefinedVariable(String),
+    /// A function without a symbolic derivative, such as a user-defined function, applied to the
+    /// variable a formula is differentiated with respect to.
+    NotDifferentiable(String),
+}
+
+impl fmt::Display for EvalError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            EvalError::DivisionByZero => write!(f, "Division by zero"),
+            EvalError::Overflow => write!(f, "The result overflows"),
+            EvalError::Domain(function) => {
+                write!(
+                    f,
+                    "The {} function is not defined for this argument",
+                    function
+                )
+            }
+            EvalError::Irrational(function) => {
+                write!(
+                    f,
+                    "The {} function has no exact result for this argument",
+                    function
+                )
+            }
+            EvalError::NegativeFactorial => {
+                write!(f, "The factorial is not defined for negative integers")
+            }
+            EvalError::InvalidShift(shift) => write!(f, "Cannot shift by {}", shift),
+            EvalError::UndefinedVariable(name) => write!(f, "The variable {} is not defined", name),
+            EvalError::NotDifferentiable(function) => {
+                write!(f, "The {} function cannot be differentiated", function)
+            }
+        }
+    }
+}
+
+impl std::error::Error for EvalError {}
@@ -0,0 +1,114 @@
+use super::EvalError;
+use std::{collections::HashMap, fmt, sync::Arc};
+
+/// The number of arguments a user-defined function accepts.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Arity {
+    Exactly(usize),
+    AtLeast(usize),
+}
+
+type Callback<T> = Arc<dyn Fn(&[T]) -> Result<T, EvalError> + Send + Sync>;
+
+/// A function defined in Rust that can be called inside a formula, returning an [`EvalError`] for
+/// the arguments it has no result for.
+pub struct Function<T> {
+    name: String,
+    arity: Arity,
+    callback: Callback<T>,
+}
+
+impl<T> Function<T> {
+    pub fn new<F>(name: &str, arity: Arity, callback: F) -> Self
+    where
+        F: Fn(&[T]) -> Result<T, EvalError> + Send + Sync + 'static,
+    {
+        Function {
+            name: name.to_string(),
+            arity,
+            callback: Arc::new(callback),
+        }
+    }
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+    pub fn arity(&self) -> Arity {
+        self.arity
+    }
+    /// Call the function, whose errors fail the evaluation of the formula.
+    pub fn call(&self, args: &[T]) -> Result<T, EvalError> {
+        (self.callback)(args)
+    }
+}
+
+impl<T> Clone for Function<T> {
+    fn clone(&self) -> Self {
+        Function {
+            name: self.name.clone(),
+            arity: self.arity,
+            callback: self.callback.clone(),
+        }
+    }
+}
+
+impl<T> fmt::Debug for Function<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Function")
+            .field("name", &self.name)
+            .field("arity", &self.arity)
+            .finish()
+    }
+}
+
+impl<T> PartialEq for Function<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.name == other.name
+            && self.arity == other.arity
+            && Arc::ptr_eq(&self.callback, &other.callback)
+    }
+}
+
+/// The set of user-defined functions available while parsing a formula.
+pub struct Functions<T> {
+    functions: HashMap<String, Function<T>>,
+}
+
+impl<T> Functions<T> {
+    pub fn new() -> Self {
+        Functions {
+            functions: HashMap::new(),
+        }
+    }
+    /// Register a function, replacing any function previously registered with the same name.
+    pub fn register<F>(&mut self, name: &str, arity: Arity, callback: F) -> &mut Self
+    where
+        F: Fn(&[T]) -> Result<T, EvalError> + Send + Sync + 'static,
+    {
+        self.functions
+            .insert(name.to_string(), Function::new(name, arity, callback));
+        self
+    }
+    pub fn get(&self, name: &str) -> Option<&Function<T>> {
+        self.functions.get(name)
+    }
+}
+
+impl<T> Default for Functions<T> {
+    fn default() -> Self {
+        Functions::new()
+    }
+}
+
+impl<T> Clone for Functions<T> {
+    fn clone(&self) -> Self {
+        Functions {
+            functions: self.functions.clone(),
+        }
+    }
+}
+
+impl<T> fmt::Debug for Functions<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_set().entries(self.functions.values()).finish()
+    }
+}
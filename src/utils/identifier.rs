@@ -0,0 +1,30 @@
+use super::Cursor;
+
+fn is_identifier_start(current_char: &char) -> bool {
+    current_char.is_ascii_alphabetic() || current_char == &'_'
+}
+
+fn is_identifier_char(current_char: &char) -> bool {
+    current_char.is_ascii_alphanumeric() || current_char == &'_'
+}
+
+/// Read the identifier at the start of the expression without consuming it, along with the character following it past
+/// any whitespace, so that `sin (x)` still calls `sin`.
+pub fn peek_identifier(expr: &Cursor) -> (String, Option<char>) {
+    let mut chars = expr.clone();
+    let mut identifier = String::new();
+    match chars.peek() {
+        Some(current_char) if is_identifier_start(&current_char) => {}
+        next_char => return (identifier, next_char),
+    }
+    while let Some(next_char) = chars.peek() {
+        if is_identifier_char(&next_char) {
+            identifier.push(next_char);
+            chars.next();
+        } else {
+            break;
+        }
+    }
+    chars.skip_whitespace();
+    (identifier, chars.next())
+}
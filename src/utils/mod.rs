@@ -1,9 +1,57 @@
+#[cfg(feature = "eval_integer")]
+mod base;
+mod context;
+mod cursor;
+mod deserialize_exponent;
+#[cfg(any(
+    feature = "eval_decimal",
+    feature = "eval_f64",
+    feature = "eval_complex",
+    feature = "eval_number",
+    feature = "eval_rational"
+))]
+mod deserialize_number;
 mod deserialize_superscript_number;
+mod error;
+mod eval_error;
+mod function;
+mod identifier;
+mod notation;
 mod operator_category;
+#[cfg(feature = "eval_integer")]
+mod overflow_mode;
 mod parse_error;
+mod script;
 mod superscript;
 
+#[cfg(feature = "eval_integer")]
+pub use base::Base;
+pub use context::Context;
+pub use cursor::Cursor;
+pub use deserialize_exponent::deserialize_exponent;
+#[cfg(any(
+    feature = "eval_decimal",
+    feature = "eval_f64",
+    feature = "eval_complex",
+    feature = "eval_number",
+    feature = "eval_rational"
+))]
+pub use deserialize_number::deserialize_number;
 pub use deserialize_superscript_number::deserialize_superscript_number;
+pub use error::Error;
+pub use eval_error::EvalError;
+pub use function::{Arity, Function, Functions};
+pub use identifier::peek_identifier;
+#[cfg(any(
+    feature = "eval_f64",
+    feature = "eval_complex",
+    feature = "eval_number"
+))]
+pub use notation::real_text;
+pub use notation::{latex, mathml, write_infix, Notation, Operator, Term, Unary};
 pub use operator_category::OperatorCategory;
-pub use parse_error::ParseError;
+#[cfg(feature = "eval_integer")]
+pub use overflow_mode::OverflowMode;
+pub use parse_error::{too_deep, unrecognized_token, ParseError, ParseErrorKind, MAX_DEPTH};
+pub use script::{Formula, Script};
 pub use superscript::superscript_digit_to_digit;
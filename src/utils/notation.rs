@@ -0,0 +1,632 @@
+//! Rendering of the parsed formulas as infix text, LaTeX and MathML.
+
+use super::OperatorCategory;
+use std::fmt::{self, Write};
+
+/// An operator written between its two operands.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Operator {
+    Add,
+    Subtract,
+    Multiply,
+    Divide,
+    Modulo,
+    Pow,
+    Equal,
+    NotEqual,
+    Less,
+    LessEqual,
+    Greater,
+    GreaterEqual,
+    LogicalAnd,
+    LogicalOr,
+    #[cfg(feature = "eval_integer")]
+    BitwiseAnd,
+    #[cfg(feature = "eval_integer")]
+    BitwiseOr,
+    #[cfg(feature = "eval_integer")]
+    BitwiseXor,
+    #[cfg(feature = "eval_integer")]
+    LeftShift,
+    #[cfg(feature = "eval_integer")]
+    RightShift,
+    #[cfg(feature = "eval_integer")]
+    UnsignedRightShift,
+}
+
+/// An operator written before its operand.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Unary {
+    Negative,
+    LogicalNot,
+    #[cfg(feature = "eval_integer")]
+    Complement,
+}
+
+/// How a node of the syntax tree is written, its children being written recursively.
+pub enum Term<'a, N> {
+    /// A number with the precedence of the operators its text contains, such as `-2` or `2/3`.
+    Literal(String, OperatorCategory),
+    Variable(&'a str),
+    Ans,
+    Binary(Operator, &'a N, &'a N),
+    Unary(Unary, &'a N),
+    Factorial(&'a N),
+    /// A function called with the name the parser reads.
+    Function(&'a str, Vec<&'a N>),
+    Conditional(&'a N, &'a N, &'a N),
+}
+
+/// The literal that the tokenizers read as an infinity, being out of range.
+pub const INFINITY: &str = "1e999";
+
+/// The product that NaN is written as, there being no literal for it.
+const NAN: &str = "0 * 1e999";
+
+/// Write a real number as text that the tokenizers read back, including the infinities and NaN.
+#[cfg(any(
+    feature = "eval_f64",
+    feature = "eval_complex",
+    feature = "eval_number"
+))]
+pub fn real_text(value: f64) -> String {
+    if value.is_nan() {
+        NAN.to_string()
+    } else if value.is_infinite() {
+        format!("{}{}", if value < 0.0 { "-" } else { "" }, INFINITY)
+    } else {
+        value.to_string()
+    }
+}
+
+impl<'a, N> Term<'a, N> {
+    /// A number written as a single token, or as a negation if it is negative.
+    pub fn number(text: String) -> Self {
+        let category = if text.starts_with('-') {
+            OperatorCategory::Negative
+        } else if text.contains(NAN) {
+            OperatorCategory::Multiplicative
+        } else {
+            OperatorCategory::Functional
+        };
+        Term::Literal(text, category)
+    }
+    fn category(&self) -> OperatorCategory {
+        match self {
+            Term::Literal(_, category) => category.clone(),
+            Term::Binary(operator, _, _) => operator.category(),
+            Term::Unary(_, _) => OperatorCategory::Negative,
+            Term::Conditional(_, _, _) => OperatorCategory::Conditional,
+            Term::Variable(_) | Term::Ans | Term::Factorial(_) | Term::Function(_, _) => {
+                OperatorCategory::Functional
+            }
+        }
+    }
+}
+
+/// A syntax tree that can be written in the notations of this module.
+pub trait Notation: Sized {
+    fn term(&self) -> Term<'_, Self>;
+}
+
+impl Operator {
+    fn category(self) -> OperatorCategory {
+        use self::Operator::*;
+        match self {
+            Add | Subtract => OperatorCategory::Additive,
+            Multiply | Divide | Modulo => OperatorCategory::Multiplicative,
+            Pow => OperatorCategory::Power,
+            Equal | NotEqual | Less | LessEqual | Greater | GreaterEqual => {
+                OperatorCategory::Comparison
+            }
+            LogicalAnd => OperatorCategory::LogicalAnd,
+            LogicalOr => OperatorCategory::LogicalOr,
+            #[cfg(feature = "eval_integer")]
+            BitwiseAnd => OperatorCategory::BitwiseAnd,
+            #[cfg(feature = "eval_integer")]
+            BitwiseOr => OperatorCategory::BitwiseOr,
+            #[cfg(feature = "eval_integer")]
+            BitwiseXor => OperatorCategory::BitwiseXor,
+            #[cfg(feature = "eval_integer")]
+            LeftShift | RightShift | UnsignedRightShift => OperatorCategory::Shift,
+        }
+    }
+    /// The symbols in infix text, LaTeX and MathML.
+    fn symbols(self) -> (&'static str, &'static str, &'static str) {
+        use self::Operator::*;
+        match self {
+            Add => ("+", "+", "+"),
+            Subtract => ("-", "-", "&#x2212;"),
+            Multiply => ("*", "\\cdot", "&#x22C5;"),
+            Divide => ("/", "/", "/"),
+            Modulo => ("%", "\\bmod", "mod"),
+            Pow => ("^", "^", "^"),
+            Equal => ("==", "=", "="),
+            NotEqual => ("!=", "\\neq", "&#x2260;"),
+            Less => ("<", "<", "&lt;"),
+            LessEqual => ("<=", "\\leq", "&#x2264;"),
+            Greater => (">", ">", "&gt;"),
+            GreaterEqual => (">=", "\\geq", "&#x2265;"),
+            LogicalAnd => ("&&", "\\land", "&#x2227;"),
+            LogicalOr => ("||", "\\lor", "&#x2228;"),
+            #[cfg(feature = "eval_integer")]
+            BitwiseAnd => ("&", "\\mathbin{\\&}", "&amp;"),
+            #[cfg(feature = "eval_integer")]
+            BitwiseOr => ("|", "\\mathbin{|}", "|"),
+            #[cfg(feature = "eval_integer")]
+            BitwiseXor => ("⊕", "\\oplus", "&#x2295;"),
+            #[cfg(feature = "eval_integer")]
+            LeftShift => ("<<", "\\ll", "&#x226A;"),
+            #[cfg(feature = "eval_integer")]
+            RightShift => (">>", "\\gg", "&#x226B;"),
+            #[cfg(feature = "eval_integer")]
+            UnsignedRightShift => (">>>", "\\ggg", "&#x22D9;"),
+        }
+    }
+}
+
+impl Unary {
+    /// The symbols in infix text, LaTeX and MathML.
+    fn symbols(self) -> (&'static str, &'static str, &'static str) {
+        match self {
+            Unary::Negative => ("-", "-", "&#x2212;"),
+            Unary::LogicalNot => ("!", "\\neg ", "&#xAC;"),
+            #[cfg(feature = "eval_integer")]
+            Unary::Complement => ("~", "{\\sim}", "~"),
+        }
+    }
+}
+
+/// Whether the operands of a binary operator need parentheses, the operators of the same
+/// precedence being left-associative but for the power.
+fn operands_grouped(
+    operator: Operator,
+    left: &OperatorCategory,
+    right: &OperatorCategory,
+) -> (bool, bool) {
+    let category = operator.category();
+    if operator == Operator::Pow {
+        (
+            *left < OperatorCategory::Functional,
+            *right < OperatorCategory::Negative,
+        )
+    } else {
+        (*left < category, *right <= category)
+    }
+}
+
+/// Whether the operand of a prefix operator needs parentheses, which also keep a negative number
+/// from reading as a double negation.
+fn unary_operand_grouped<N>(operand: &Term<N>, category: OperatorCategory) -> bool {
+    category < OperatorCategory::Negative
+        || matches!(operand, Term::Literal(text, _) if text.starts_with('-'))
+}
+
+/// Write the node as infix text with as few parentheses as possible, which parses back into the
+/// same node.
+pub fn write_infix<N: Notation>(node: &N, out: &mut impl Write) -> fmt::Result {
+    match node.term() {
+        Term::Literal(text, _) => out.write_str(&text),
+        Term::Variable(name) => out.write_str(name),
+        Term::Ans => out.write_char('@'),
+        Term::Binary(operator, left, right) => {
+            let (left_grouped, right_grouped) =
+                operands_grouped(operator, &left.term().category(), &right.term().category());
+            write_infix_operand(left, left_grouped, out)?;
+            match operator {
+                Operator::Pow => out.write_char('^')?,
+                operator => write!(out, " {} ", operator.symbols().0)?,
+            }
+            write_infix_operand(right, right_grouped, out)
+        }
+        Term::Unary(unary, operand) => {
+            out.write_str(unary.symbols().0)?;
+            let term = operand.term();
+            let grouped = unary_operand_grouped(&term, term.category());
+            write_infix_operand(operand, grouped, out)
+        }
+        Term::Factorial(operand) => {
+            let grouped = operand.term().category() < OperatorCategory::Functional;
+            write_infix_operand(operand, grouped, out)?;
+            out.write_char('!')
+        }
+        Term::Function(name, args) => {
+            write!(out, "{}(", name)?;
+            for (i, arg) in args.into_iter().enumerate() {
+                if i > 0 {
+                    out.write_str(", ")?;
+                }
+                write_infix(arg, out)?;
+            }
+            out.write_char(')')
+        }
+        Term::Conditional(condition, then, otherwise) => {
+            let grouped = condition.term().category() <= OperatorCategory::Conditional;
+            write_infix_operand(condition, grouped, out)?;
+            out.write_str(" ? ")?;
+            write_infix(then, out)?;
+            out.write_str(" : ")?;
+            write_infix(otherwise, out)
+        }
+    }
+}
+
+fn write_infix_operand<N: Notation>(node: &N, grouped: bool, out: &mut impl Write) -> fmt::Result {
+    if grouped {
+        out.write_char('(')?;
+        write_infix(node, out)?;
+        out.write_char(')')
+    } else {
+        write_infix(node, out)
+    }
+}
+
+/// The precedence of a node in LaTeX, where a fraction is grouped by its bar.
+fn latex_category<N: Notation>(term: &Term<N>) -> OperatorCategory {
+    match term {
+        Term::Binary(Operator::Divide, _, _) => OperatorCategory::Negative,
+        Term::Conditional(_, _, _) => OperatorCategory::Functional,
+        term => term.category(),
+    }
+}
+
+/// The LaTeX commands of the functions typeset upright by LaTeX itself.
+fn latex_function(name: &str) -> Option<&'static str> {
+    Some(match name {
+        "sin" => "\\sin",
+        "cos" => "\\cos",
+        "tan" => "\\tan",
+        "sinh" => "\\sinh",
+        "cosh" => "\\cosh",
+        "tanh" => "\\tanh",
+        "asin" => "\\arcsin",
+        "acos" => "\\arccos",
+        "atan" => "\\arctan",
+        "ln" => "\\ln",
+        "exp" => "\\exp",
+        "min" => "\\min",
+        "max" => "\\max",
+        "gcd" => "\\gcd",
+        "arg" => "\\arg",
+        _ => return None,
+    })
+}
+
+/// A part of a literal that LaTeX and MathML write apart from the digits.
+enum Piece<'a> {
+    Digits(&'a str),
+    Sign(char),
+    Infinity,
+    NaN,
+    /// The imaginary unit.
+    Unit,
+}
+
+/// Whether the literal holds an imaginary unit, an infinity or NaN, rather than just digits.
+fn marked_up(text: &str) -> bool {
+    text.contains('i') || text.contains(INFINITY)
+}
+
+/// Split the text of a literal into its digits, signs, units and the numbers out of range.
+fn pieces(text: &str) -> Vec<Piece<'_>> {
+    let mut pieces = Vec::new();
+    let mut rest = text.trim_start();
+    while let Some(c) = rest.chars().next() {
+        let (piece, len) = if rest.starts_with(NAN) {
+            (Piece::NaN, NAN.len())
+        } else if rest.starts_with(INFINITY) {
+            (Piece::Infinity, INFINITY.len())
+        } else if c == 'i' {
+            (Piece::Unit, 1)
+        } else if c == '+' || c == '-' {
+            (Piece::Sign(c), 1)
+        } else {
+            let len = rest.find(&[' ', '+', '-', 'i'][..]).unwrap_or(rest.len());
+            (Piece::Digits(&rest[..len]), len)
+        };
+        pieces.push(piece);
+        rest = rest[len..].trim_start();
+    }
+    pieces
+}
+
+/// Write the node as LaTeX math, without the surrounding `$`.
+pub fn latex<N: Notation>(node: &N) -> String {
+    let mut out = String::new();
+    write_latex(node, &mut out);
+    out
+}
+
+fn write_latex<N: Notation>(node: &N, out: &mut String) {
+    match node.term() {
+        Term::Literal(text, _) if !marked_up(&text) => out.push_str(&text),
+        Term::Literal(text, _) => {
+            for (i, piece) in pieces(&text).into_iter().enumerate() {
+                match piece {
+                    Piece::Digits(digits) => out.push_str(digits),
+                    Piece::Sign(sign) if i == 0 => out.push(sign),
+                    Piece::Sign(sign) => {
+                        out.push(' ');
+                        out.push(sign);
+                        out.push(' ');
+                    }
+                    Piece::Infinity => out.push_str("\\infty"),
+                    Piece::NaN => out.push_str("\\mathrm{NaN}"),
+                    // A space keeps the unit from running into the name of `\infty`.
+                    Piece::Unit if out.ends_with("\\infty") => out.push_str(" i"),
+                    Piece::Unit => out.push('i'),
+                }
+            }
+        }
+        Term::Variable(name) if name.chars().count() == 1 => out.push_str(name),
+        Term::Variable(name) => {
+            out.push_str("\\mathrm{");
+            out.push_str(&name.replace('_', "\\_"));
+            out.push('}');
+        }
+        Term::Ans => out.push('@'),
+        Term::Binary(Operator::Divide, left, right) => {
+            out.push_str("\\frac{");
+            write_latex(left, out);
+            out.push_str("}{");
+            write_latex(right, out);
+            out.push('}');
+        }
+        Term::Binary(Operator::Pow, left, right) => {
+            let grouped = latex_category(&left.term()) < OperatorCategory::Functional;
+            write_latex_operand(left, grouped, out);
+            out.push_str("^{");
+            write_latex(right, out);
+            out.push('}');
+        }
+        Term::Binary(operator, left, right) => {
+            let (left_grouped, right_grouped) = operands_grouped(
+                operator,
+                &latex_category(&left.term()),
+                &latex_category(&right.term()),
+            );
+            write_latex_operand(left, left_grouped, out);
+            out.push(' ');
+            out.push_str(operator.symbols().1);
+            out.push(' ');
+            write_latex_operand(right, right_grouped, out);
+        }
+        Term::Unary(unary, operand) => {
+            out.push_str(unary.symbols().1);
+            let term = operand.term();
+            let grouped = unary_operand_grouped(&term, latex_category(&term));
+            write_latex_operand(operand, grouped, out);
+        }
+        Term::Factorial(operand) => {
+            let grouped = latex_category(&operand.term()) < OperatorCategory::Functional;
+            write_latex_operand(operand, grouped, out);
+            out.push('!');
+        }
+        Term::Function(name, args) => write_latex_function(name, &args, out),
+        Term::Conditional(condition, then, otherwise) => {
+            out.push_str("\\begin{cases} ");
+            write_latex(then, out);
+            out.push_str(" & \\text{if } ");
+            write_latex(condition, out);
+            out.push_str(" \\\\ ");
+            write_latex(otherwise, out);
+            out.push_str(" & \\text{otherwise} \\end{cases}");
+        }
+    }
+}
+
+fn write_latex_function<N: Notation>(name: &str, args: &[&N], out: &mut String) {
+    let (open, close) = match (name, args.len()) {
+        ("sqrt", 1) => ("\\sqrt{", "}"),
+        ("root", 2) => {
+            out.push_str("\\sqrt[");
+            write_latex(args[0], out);
+            out.push_str("]{");
+            write_latex(args[1], out);
+            out.push('}');
+            return;
+        }
+        ("log", 2) => {
+            out.push_str("\\log_{");
+            write_latex(args[1], out);
+            out.push_str("}\\left(");
+            write_latex(args[0], out);
+            out.push_str("\\right)");
+            return;
+        }
+        ("abs", 1) => ("\\left|", "\\right|"),
+        ("floor", 1) => ("\\left\\lfloor ", "\\right\\rfloor"),
+        ("ceil", 1) => ("\\left\\lceil ", "\\right\\rceil"),
+        _ => {
+            match latex_function(name) {
+                Some(command) => out.push_str(command),
+                None => {
+                    out.push_str("\\operatorname{");
+                    out.push_str(&name.replace('_', "\\_"));
+                    out.push('}');
+                }
+            }
+            ("\\left(", "\\right)")
+        }
+    };
+    out.push_str(open);
+    for (i, arg) in args.iter().enumerate() {
+        if i > 0 {
+            out.push_str(", ");
+        }
+        write_latex(*arg, out);
+    }
+    out.push_str(close);
+}
+
+fn write_latex_operand<N: Notation>(node: &N, grouped: bool, out: &mut String) {
+    if grouped {
+        out.push_str("\\left(");
+        write_latex(node, out);
+        out.push_str("\\right)");
+    } else {
+        write_latex(node, out);
+    }
+}
+
+/// Write the node as a MathML `math` element.
+pub fn mathml<N: Notation>(node: &N) -> String {
+    let mut out = String::from("<math xmlns=\"http://www.w3.org/1998/Math/MathML\">");
+    write_mathml(node, &mut out);
+    out.push_str("</math>");
+    out
+}
+
+/// Write the node as a single MathML element.
+fn write_mathml<N: Notation>(node: &N, out: &mut String) {
+    match node.term() {
+        Term::Literal(text, _) if !marked_up(&text) => {
+            out.push_str("<mn>");
+            out.push_str(&text);
+            out.push_str("</mn>");
+        }
+        Term::Literal(text, _) => {
+            let pieces = pieces(&text);
+            if pieces.len() > 1 {
+                out.push_str("<mrow>");
+            }
+            for piece in &pieces {
+                match piece {
+                    Piece::Digits(digits) => {
+                        out.push_str("<mn>");
+                        out.push_str(digits);
+                        out.push_str("</mn>");
+                    }
+                    Piece::Sign('-') => write_mo("&#x2212;", out),
+                    Piece::Sign(_) => write_mo("+", out),
+                    Piece::Infinity => out.push_str("<mi>&#x221E;</mi>"),
+                    Piece::NaN => out.push_str("<mi>NaN</mi>"),
+                    Piece::Unit => out.push_str("<mi>i</mi>"),
+                }
+            }
+            if pieces.len() > 1 {
+                out.push_str("</mrow>");
+            }
+        }
+        Term::Variable(name) => {
+            out.push_str("<mi>");
+            out.push_str(name);
+            out.push_str("</mi>");
+        }
+        Term::Ans => out.push_str("<mi>@</mi>"),
+        Term::Binary(Operator::Divide, left, right) => {
+            out.push_str("<mfrac>");
+            write_mathml(left, out);
+            write_mathml(right, out);
+            out.push_str("</mfrac>");
+        }
+        Term::Binary(Operator::Pow, left, right) => {
+            let grouped = latex_category(&left.term()) < OperatorCategory::Functional;
+            out.push_str("<msup>");
+            write_mathml_operand(left, grouped, out);
+            write_mathml(right, out);
+            out.push_str("</msup>");
+        }
+        Term::Binary(operator, left, right) => {
+            let (left_grouped, right_grouped) = operands_grouped(
+                operator,
+                &latex_category(&left.term()),
+                &latex_category(&right.term()),
+            );
+            out.push_str("<mrow>");
+            write_mathml_operand(left, left_grouped, out);
+            write_mo(operator.symbols().2, out);
+            write_mathml_operand(right, right_grouped, out);
+            out.push_str("</mrow>");
+        }
+        Term::Unary(unary, operand) => {
+            let term = operand.term();
+            let grouped = unary_operand_grouped(&term, latex_category(&term));
+            out.push_str("<mrow>");
+            write_mo(unary.symbols().2, out);
+            write_mathml_operand(operand, grouped, out);
+            out.push_str("</mrow>");
+        }
+        Term::Factorial(operand) => {
+            let grouped = latex_category(&operand.term()) < OperatorCategory::Functional;
+            out.push_str("<mrow>");
+            write_mathml_operand(operand, grouped, out);
+            write_mo("!", out);
+            out.push_str("</mrow>");
+        }
+        Term::Function(name, args) => write_mathml_function(name, &args, out),
+        Term::Conditional(condition, then, otherwise) => {
+            out.push_str("<mrow><mo>{</mo><mtable><mtr><mtd>");
+            write_mathml(then, out);
+            out.push_str("</mtd><mtd><mtext>if&#xA0;</mtext>");
+            write_mathml(condition, out);
+            out.push_str("</mtd></mtr><mtr><mtd>");
+            write_mathml(otherwise, out);
+            out.push_str("</mtd><mtd><mtext>otherwise</mtext></mtd></mtr></mtable></mrow>");
+        }
+    }
+}
+
+fn write_mathml_function<N: Notation>(name: &str, args: &[&N], out: &mut String) {
+    let (open, close, named) = match (name, args.len()) {
+        ("sqrt", 1) => {
+            out.push_str("<msqrt>");
+            write_mathml(args[0], out);
+            out.push_str("</msqrt>");
+            return;
+        }
+        ("root", 2) => {
+            out.push_str("<mroot>");
+            write_mathml(args[1], out);
+            write_mathml(args[0], out);
+            out.push_str("</mroot>");
+            return;
+        }
+        ("log", 2) => {
+            out.push_str("<mrow><msub><mi>log</mi>");
+            write_mathml(args[1], out);
+            out.push_str("</msub><mo>&#x2061;</mo><mrow><mo>(</mo>");
+            write_mathml(args[0], out);
+            out.push_str("<mo>)</mo></mrow></mrow>");
+            return;
+        }
+        ("abs", 1) => ("|", "|", false),
+        ("floor", 1) => ("&#x230A;", "&#x230B;", false),
+        ("ceil", 1) => ("&#x2308;", "&#x2309;", false),
+        _ => {
+            out.push_str("<mrow><mi>");
+            out.push_str(name);
+            out.push_str("</mi><mo>&#x2061;</mo>");
+            ("(", ")", true)
+        }
+    };
+    out.push_str("<mrow>");
+    write_mo(open, out);
+    for (i, arg) in args.iter().enumerate() {
+        if i > 0 {
+            write_mo(",", out);
+        }
+        write_mathml(*arg, out);
+    }
+    write_mo(close, out);
+    out.push_str("</mrow>");
+    if named {
+        out.push_str("</mrow>");
+    }
+}
+
+fn write_mathml_operand<N: Notation>(node: &N, grouped: bool, out: &mut String) {
+    if grouped {
+        out.push_str("<mrow><mo>(</mo>");
+        write_mathml(node, out);
+        out.push_str("<mo>)</mo></mrow>");
+    } else {
+        write_mathml(node, out);
+    }
+}
+
+fn write_mo(symbol: &str, out: &mut String) {
+    out.push_str("<mo>");
+    out.push_str(symbol);
+    out.push_str("</mo>");
+}
@@ -1,15 +1,21 @@
 #[derive(Debug, PartialEq, PartialOrd, Clone)]
 pub enum OperatorCategory {
     DefaultZero,
-    #[cfg(feature = "eval_i64")]
+    Conditional,
+    LogicalOr,
+    LogicalAnd,
+    Comparison,
+    #[cfg(feature = "eval_integer")]
     BitwiseOr,
-    #[cfg(feature = "eval_i64")]
+    #[cfg(feature = "eval_integer")]
+    BitwiseXor,
+    #[cfg(feature = "eval_integer")]
     BitwiseAnd,
-    #[cfg(feature = "eval_i64")]
+    #[cfg(feature = "eval_integer")]
     Shift,
     Additive,
     Multiplicative,
-    Power,
     Negative,
+    Power,
     Functional,
 }
@@ -0,0 +1,16 @@
+/// How integer operations behave when their result doesn't fit in the number type.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OverflowMode {
+    /// Return `EvalError::Overflow`.
+    Checked,
+    /// Wrap around at the bounds of the type, as two's complement arithmetic does.
+    Wrapping,
+    /// Clamp to the bounds of the type.
+    Saturating,
+}
+
+impl Default for OverflowMode {
+    fn default() -> Self {
+        OverflowMode::Checked
+    }
+}
@@ -1,23 +1,203 @@
-use std::fmt;
+use super::{deserialize_exponent, peek_identifier, superscript_digit_to_digit, Cursor};
+use std::{fmt, ops::Range};
 
-#[derive(Debug)]
-pub enum ParseError {
-    UnableToParse(String),
-    InvalidOperator(String),
+/// The kind of mistake found in a formula.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ParseErrorKind {
+    /// A token that cannot appear at this position.
+    UnexpectedToken,
+    /// A name followed by arguments that is neither a native nor a registered function.
+    UnknownFunction,
+    /// A function called with the wrong number of arguments.
+    WrongArity,
+    /// A bracket that is never closed or that closes nothing.
+    UnbalancedBracket,
+    /// A malformed number literal.
+    InvalidNumber,
+    /// A character that is not part of the syntax.
+    InvalidCharacter,
+    /// A constant or a unit assigned by a script.
+    ReservedName,
+    /// A formula nested deeper than [`MAX_DEPTH`] levels.
+    TooDeep,
+}
+
+/// The deepest nesting of operators and brackets a formula may have.
+pub const MAX_DEPTH: usize = 256;
+
+/// An error locating the part of the formula that could not be understood.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParseError {
+    kind: ParseErrorKind,
+    span: Range<usize>,
+    expected: Vec<String>,
+    message: String,
+}
+
+impl ParseError {
+    pub fn new(kind: ParseErrorKind, span: Range<usize>, message: String) -> Self {
+        ParseError {
+            kind,
+            span,
+            expected: Vec::new(),
+            message,
+        }
+    }
+    /// Attach the tokens that would have been accepted instead.
+    pub fn with_expected<S: ToString>(mut self, expected: &[S]) -> Self {
+        self.expected = expected.iter().map(|token| token.to_string()).collect();
+        self
+    }
+    /// What went wrong.
+    pub fn kind(&self) -> ParseErrorKind {
+        self.kind
+    }
+    /// The byte range of the offending part of the formula.
+    pub fn span(&self) -> Range<usize> {
+        self.span.clone()
+    }
+    /// The tokens that would have been accepted at this position.
+    pub fn expected(&self) -> &[String] {
+        &self.expected
+    }
+    /// A description of the error, without its position.
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+    /// Move the span from a part of a script onto the whole script, the part starting at the given
+    /// byte offset.
+    pub(crate) fn shift(mut self, offset: usize) -> Self {
+        self.span = self.span.start + offset..self.span.end + offset;
+        self
+    }
+}
+
+/// Build the error for a formula nested deeper than [`MAX_DEPTH`] at the given span.
+pub fn too_deep(span: Range<usize>) -> ParseError {
+    ParseError::new(
+        ParseErrorKind::TooDeep,
+        span,
+        "The formula is nested too deeply".to_string(),
+    )
+}
+
+/// Build the error for a formula that no token can be read from at the given byte offset.
+pub fn unrecognized_token(source: &str, start: usize) -> ParseError {
+    let rest = &source[start..];
+    let (identifier, next_char) = peek_identifier(&Cursor::new(rest));
+    if !identifier.is_empty() && next_char == Some('(') {
+        return ParseError::new(
+            ParseErrorKind::UnknownFunction,
+            start..start + identifier.len(),
+            format!("Unknown function {}", identifier),
+        );
+    }
+    match rest.chars().next() {
+        Some(current_char) if current_char.is_ascii_digit() || current_char == '.' => {
+            let radix_prefixed = ["0x", "0o", "0b", "0X", "0O", "0B"]
+                .iter()
+                .any(|prefix| rest.starts_with(prefix));
+            // The run goes on through the exponents, so that `1e3.5` is reported as a whole.
+            let mut length = 0;
+            loop {
+                length += rest[length..]
+                    .find(|next_char: char| {
+                        if radix_prefixed {
+                            !next_char.is_ascii_alphanumeric() && next_char != '_'
+                        } else {
+                            !next_char.is_ascii_digit() && next_char != '.' && next_char != '_'
+                        }
+                    })
+                    .unwrap_or(rest.len() - length);
+                match deserialize_exponent(&mut Cursor::new(&rest[length..])).len() {
+                    0 => break,
+                    exponent => length += exponent,
+                }
+            }
+            ParseError::new(
+                ParseErrorKind::InvalidNumber,
+                start..start + length,
+                format!("Invalid number {}", &rest[..length]),
+            )
+        }
+        Some(current_char) if superscript_digit_to_digit(&current_char).is_some() => {
+            let length = rest
+                .find(|next_char: char| superscript_digit_to_digit(&next_char).is_none())
+                .unwrap_or(rest.len());
+            ParseError::new(
+                ParseErrorKind::InvalidNumber,
+                start..start + length,
+                format!("Invalid number {}", &rest[..length]),
+            )
+        }
+        Some(current_char) => ParseError::new(
+            ParseErrorKind::InvalidCharacter,
+            start..start + current_char.len_utf8(),
+            format!("Invalid character {}", current_char),
+        ),
+        None => ParseError::new(
+            ParseErrorKind::UnexpectedToken,
+            start..start,
+            "Unexpected end of input".to_string(),
+        ),
+    }
 }
 
 impl fmt::Display for ParseError {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        let message = match &self {
-            self::ParseError::UnableToParse(e) => e.clone(),
-            self::ParseError::InvalidOperator(e) => e.clone(),
-        };
-        write!(f, "Error in evaluating {}", message)
+        write!(
+            f,
+            "{} at {}..{}",
+            self.message, self.span.start, self.span.end
+        )?;
+        if let Some((last, others)) = self.expected.split_last() {
+            if others.is_empty() {
+                write!(f, ", expected {}", last)?;
+            } else {
+                write!(f, ", expected {} or {}", others.join(", "), last)?;
+            }
+        }
+        Ok(())
     }
 }
 
-impl std::convert::From<std::boxed::Box<dyn std::error::Error>> for ParseError {
-    fn from(_evalerr: std::boxed::Box<dyn std::error::Error>) -> Self {
-        ParseError::UnableToParse("Unable to parse".into())
+impl std::error::Error for ParseError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_unrecognized_token() {
+        let error = unrecognized_token("2+foo(3)", 2);
+        assert_eq!(error.kind(), ParseErrorKind::UnknownFunction);
+        assert_eq!(error.span(), 2..5);
+        let error = unrecognized_token("2+1.2.3", 2);
+        assert_eq!(error.kind(), ParseErrorKind::InvalidNumber);
+        assert_eq!(error.span(), 2..7);
+        let error = unrecognized_token("0xFFFF_FFFF_FFFF_FFFF_F&1", 0);
+        assert_eq!(error.kind(), ParseErrorKind::InvalidNumber);
+        assert_eq!(error.span(), 0..23);
+        let error = unrecognized_token("1e99+1", 0);
+        assert_eq!(error.kind(), ParseErrorKind::InvalidNumber);
+        assert_eq!(error.span(), 0..4);
+        let error = unrecognized_token("1.5e2.5e1*2", 0);
+        assert_eq!(error.kind(), ParseErrorKind::InvalidNumber);
+        assert_eq!(error.span(), 0..9);
+        let error = unrecognized_token("2⁹⁹⁹⁹⁹⁹⁹⁹⁹⁹⁹⁹⁹⁹⁹⁹⁹⁹⁹⁹+1", 1);
+        assert_eq!(error.kind(), ParseErrorKind::InvalidNumber);
+        assert_eq!(error.span(), 1..61);
+        let error = unrecognized_token("2+$", 2);
+        assert_eq!(error.kind(), ParseErrorKind::InvalidCharacter);
+        assert_eq!(error.span(), 2..3);
+    }
+    #[test]
+    fn test_display() {
+        let error = ParseError::new(ParseErrorKind::UnexpectedToken, 3..4, "Unexpected )".into())
+            .with_expected(&["a number", "("]);
+        assert_eq!(
+            error.to_string(),
+            "Unexpected ) at 3..4, expected a number or ("
+        );
     }
 }
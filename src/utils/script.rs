@@ -0,0 +1,221 @@
+use super::{peek_identifier, Context, Cursor, EvalError, Functions, ParseError, ParseErrorKind};
+use std::collections::HashMap;
+
+/// A formula parsed once and evaluated with a context, which the statements of a [`Script`] are
+/// made of.
+pub trait Formula: Sized {
+    type Value: Clone;
+
+    /// Parse a formula, with the given user-defined functions if any.
+    fn parse(expr: String, functions: Option<&Functions<Self::Value>>) -> Result<Self, ParseError>;
+    /// Evaluate the parsed formula, resolving its named variables with the context.
+    fn evaluate(
+        &self,
+        placeholder: Self::Value,
+        context: &dyn Context<Self::Value>,
+    ) -> Result<Self::Value, EvalError>;
+    /// Whether the formulas read a name as a constant or a unit, which a script can't assign.
+    fn is_reserved(name: &str) -> bool;
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Statement<E> {
+    name: Option<String>,
+    formula: E,
+}
+
+/// Statements separated by `;`, each one either a formula or the assignment of a formula to a name
+/// with `=`, evaluated in order into the value of the last one.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Script<E> {
+    statements: Vec<Statement<E>>,
+}
+
+/// The names assigned so far by a script, hiding the variables of the context.
+struct Scope<'a, T> {
+    assigned: &'a HashMap<String, T>,
+    context: &'a dyn Context<T>,
+}
+
+impl<'a, T: Clone> Context<T> for Scope<'a, T> {
+    fn get(&self, name: &str) -> Option<T> {
+        match self.assigned.get(name) {
+            Some(value) => Some(value.clone()),
+            None => self.context.get(name),
+        }
+    }
+}
+
+/// Split an assignment into the name and the formula with its byte offset in the statement, or
+/// return `None` if the statement is a formula.
+fn split_assignment(statement: &str) -> Option<(String, &str, usize)> {
+    let start = statement.len() - statement.trim_start().len();
+    let (name, _) = peek_identifier(&Cursor::new(&statement[start..]));
+    if name.is_empty() {
+        return None;
+    }
+    let rest = statement[start + name.len()..].trim_start();
+    if !rest.starts_with('=') || rest.starts_with("==") {
+        return None;
+    }
+    let offset = statement.len() - rest.len() + 1;
+    Some((name, &statement[offset..], offset))
+}
+
+impl<E: Formula> Script<E> {
+    /// Parse a script without evaluating it.
+    pub fn new(script: String) -> Result<Self, ParseError> {
+        Script::compile(script, None)
+    }
+    /// Parse a script whose formulas can call the given user-defined functions.
+    pub fn with_functions(
+        script: String,
+        functions: &Functions<E::Value>,
+    ) -> Result<Self, ParseError> {
+        Script::compile(script, Some(functions))
+    }
+    fn compile(
+        script: String,
+        functions: Option<&Functions<E::Value>>,
+    ) -> Result<Self, ParseError> {
+        let mut statements = Vec::new();
+        let mut start = 0;
+        for statement in script.split(';') {
+            let offset = start;
+            start += statement.len() + 1;
+            if statement.trim().is_empty() {
+                continue;
+            }
+            let (name, expr, expr_offset) = match split_assignment(statement) {
+                Some((name, expr, expr_offset)) => (Some(name), expr, expr_offset),
+                None => (None, statement, 0),
+            };
+            if let Some(name) = name.as_ref().filter(|name| E::is_reserved(name)) {
+                let start = offset + statement.len() - statement.trim_start().len();
+                return Err(ParseError::new(
+                    ParseErrorKind::ReservedName,
+                    start..start + name.len(),
+                    format!("Cannot assign {}", name),
+                ));
+            }
+            let formula = E::parse(expr.to_string(), functions)
+                .map_err(|error| error.shift(offset + expr_offset))?;
+            statements.push(Statement { name, formula });
+        }
+        if statements.is_empty() {
+            // Report the same error as an empty formula.
+            E::parse(String::new(), functions)?;
+        }
+        Ok(Script { statements })
+    }
+    /// Evaluate the statements, `@` being replaced by the placeholder.
+    pub fn eval(&self, placeholder: E::Value) -> Result<E::Value, EvalError> {
+        self.eval_with_context(placeholder, &HashMap::<String, E::Value>::new())
+    }
+    /// Evaluate the statements, resolving the names they don't assign with the context.
+    pub fn eval_with_context(
+        &self,
+        placeholder: E::Value,
+        context: &dyn Context<E::Value>,
+    ) -> Result<E::Value, EvalError> {
+        self.run(placeholder, &mut HashMap::new(), context)
+    }
+    /// Evaluate the statements with the names assigned by previous evaluations, and keep the
+    /// names they assign in the environment, which is left as it was if a statement fails.
+    pub fn eval_with_environment(
+        &self,
+        placeholder: E::Value,
+        environment: &mut HashMap<String, E::Value>,
+    ) -> Result<E::Value, EvalError> {
+        let previous = Scope {
+            assigned: environment,
+            context: &HashMap::<String, E::Value>::new(),
+        };
+        let mut assigned = HashMap::new();
+        let value = self.run(placeholder, &mut assigned, &previous)?;
+        environment.extend(assigned);
+        Ok(value)
+    }
+    /// Evaluate the statements into the value of the last one, keeping the names they assign.
+    fn run(
+        &self,
+        placeholder: E::Value,
+        assigned: &mut HashMap<String, E::Value>,
+        context: &dyn Context<E::Value>,
+    ) -> Result<E::Value, EvalError> {
+        let mut value = placeholder.clone();
+        for statement in &self.statements {
+            let scope = Scope { assigned, context };
+            let result = statement.formula.evaluate(placeholder.clone(), &scope)?;
+            if let Some(name) = &statement.name {
+                assigned.insert(name.clone(), result.clone());
+            }
+            value = result;
+        }
+        Ok(value)
+    }
+}
+
+#[cfg(all(test, feature = "eval_f64"))]
+mod tests {
+    use super::*;
+    use crate::utils::ParseErrorKind;
+    use crate::ExpressionF64;
+
+    type ScriptF64 = Script<ExpressionF64>;
+
+    #[test]
+    fn test_assignments() {
+        let script = ScriptF64::new("r = 3; area = π r²; area * 2".to_string()).unwrap();
+        assert_eq!(script.eval(0.0), Ok(std::f64::consts::PI * 18.0));
+        let script = ScriptF64::new("x = @ + 1; x = x * x;".to_string()).unwrap();
+        assert_eq!(script.eval(2.0), Ok(9.0));
+    }
+    #[test]
+    fn test_context() {
+        let script = ScriptF64::new("y = x + 1; x = 10; x + y".to_string()).unwrap();
+        let mut context = HashMap::new();
+        context.insert("x".to_string(), 1.0);
+        assert_eq!(script.eval_with_context(0.0, &context), Ok(12.0));
+        assert_eq!(
+            script.eval(0.0),
+            Err(EvalError::UndefinedVariable("x".to_string()))
+        );
+        let script = ScriptF64::new("x == 1".to_string()).unwrap();
+        assert_eq!(script.eval_with_context(0.0, &context), Ok(1.0));
+    }
+    #[test]
+    fn test_environment() {
+        let mut environment = HashMap::new();
+        let init = ScriptF64::new("total = 5".to_string()).unwrap();
+        let step = ScriptF64::new("total = total + @".to_string()).unwrap();
+        init.eval_with_environment(0.0, &mut environment).unwrap();
+        assert_eq!(step.eval_with_environment(2.0, &mut environment), Ok(7.0));
+        assert_eq!(step.eval_with_environment(3.0, &mut environment), Ok(10.0));
+        assert_eq!(environment.get("total"), Some(&10.0));
+        let failing = ScriptF64::new("total = total + @; missing".to_string()).unwrap();
+        assert_eq!(
+            failing.eval_with_environment(1.0, &mut environment),
+            Err(EvalError::UndefinedVariable("missing".to_string()))
+        );
+        assert_eq!(environment.get("total"), Some(&10.0));
+    }
+    #[test]
+    fn test_parse_errors() {
+        let error = ScriptF64::new("a = 1; b = (2".to_string()).unwrap_err();
+        assert_eq!(error.kind(), ParseErrorKind::UnbalancedBracket);
+        assert_eq!(error.span(), 13..13);
+        let error = ScriptF64::new("a = 1; 2 = a".to_string()).unwrap_err();
+        assert_eq!(error.kind(), ParseErrorKind::InvalidCharacter);
+        assert_eq!(error.span(), 9..10);
+        assert!(ScriptF64::new(" ; ".to_string()).is_err());
+        let error = ScriptF64::new("a = 1;  pi = a".to_string()).unwrap_err();
+        assert_eq!(error.kind(), ParseErrorKind::ReservedName);
+        assert_eq!(error.span(), 8..10);
+        let error = ScriptF64::new("e=3; e".to_string()).unwrap_err();
+        assert_eq!(error.kind(), ParseErrorKind::ReservedName);
+        assert_eq!(error.span(), 0..1);
+        let error = ScriptF64::new("π = 3".to_string()).unwrap_err();
+        assert_eq!(error.kind(), ParseErrorKind::InvalidCharacter);
+    }
+}